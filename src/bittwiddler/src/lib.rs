@@ -725,7 +725,7 @@ pub fn bittwiddler(input: TokenStream) -> TokenStream {
                 impl #input_ident {
                     #ispub_token fn #encode_fn_ident<T>(&self,
                         fuses: &mut T, start_coord: (#(#usize_idents),*), #(#mirror_idents),*)
-                        where T: ::std::ops::IndexMut<(#(#usize_idents2),*), Output=bool> + ?Sized
+                        where T: ::core::ops::IndexMut<(#(#usize_idents2),*), Output=bool> + ?Sized
                     {
                         #encode_field_tokens
                     }
@@ -735,7 +735,7 @@ pub fn bittwiddler(input: TokenStream) -> TokenStream {
             quote!{
                 impl #input_ident {
                     #ispub_token fn #encode_fn_ident<T>(&self, fuses: &mut T)
-                        where T: ::std::ops::IndexMut<(#(#usize_idents2),*), Output=bool> + ?Sized
+                        where T: ::core::ops::IndexMut<(#(#usize_idents2),*), Output=bool> + ?Sized
                     {
                         #encode_field_tokens
                     }
@@ -794,7 +794,7 @@ pub fn bittwiddler(input: TokenStream) -> TokenStream {
                     #ispub_token fn #decode_fn_ident<T>(
                         fuses: &T, start_coord: (#(#usize_idents3),*), #(#mirror_idents2),*)
                         -> #decode_output_tokens
-                        where T: ::std::ops::Index<(#(#usize_idents4),*), Output=bool> + ?Sized
+                        where T: ::core::ops::Index<(#(#usize_idents4),*), Output=bool> + ?Sized
                     {
                         #decode_field_tokens
                         
@@ -806,7 +806,7 @@ pub fn bittwiddler(input: TokenStream) -> TokenStream {
             quote!{
                 impl #input_ident {
                     #ispub_token fn #decode_fn_ident<T>(fuses: &T) -> #decode_output_tokens
-                        where T: ::std::ops::Index<(#(#usize_idents4),*), Output=bool> + ?Sized
+                        where T: ::core::ops::Index<(#(#usize_idents4),*), Output=bool> + ?Sized
                     {
                         #decode_field_tokens
                         
@@ -24,14 +24,94 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 */
 
 //! JEDEC programming file format parser and writer
-
+//!
+//! The `std` feature is enabled by default. Without it, this crate only needs `alloc`, and output is written to
+//! anything implementing the minimal `ByteWrite` trait instead of `std::io::Write`.
+
+#![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
+
+extern crate alloc;
+
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::num;
+use core::num::Wrapping;
+use core::str;
+#[cfg(feature = "std")]
 use std::error;
-use std::fmt;
+#[cfg(feature = "std")]
 use std::io;
-use std::io::Write;
-use std::num;
-use std::num::Wrapping;
-use std::str;
+
+/// Minimal writer trait that output is written to, so that files can be written without `std`. With the `std`
+/// feature, it is implemented for everything that implements `std::io::Write`. Without it, it is implemented for
+/// `Vec<u8>` and mutable references to other writers.
+pub trait ByteWrite {
+    /// The error returned when writing fails
+    type Error;
+
+    /// Writes all of the given bytes
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error>;
+
+    /// Writes formatted text. This allows the `write!` macro to be used with any writer.
+    fn write_fmt(&mut self, args: fmt::Arguments) -> Result<(), Self::Error> {
+        struct Adapter<'a, W: ByteWrite + ?Sized> {
+            inner: &'a mut W,
+            error: Option<W::Error>,
+        }
+
+        impl<W: ByteWrite + ?Sized> fmt::Write for Adapter<'_, W> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                match self.inner.write_all(s.as_bytes()) {
+                    Ok(()) => Ok(()),
+                    Err(err) => {
+                        self.error = Some(err);
+                        Err(fmt::Error)
+                    }
+                }
+            }
+        }
+
+        let mut adapter = Adapter {inner: self, error: None};
+        match fmt::write(&mut adapter, args) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(adapter.error.expect("a formatting trait implementation returned an error")),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: io::Write + ?Sized> ByteWrite for W {
+    type Error = io::Error;
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), io::Error> {
+        io::Write::write_all(self, buf)
+    }
+
+    fn write_fmt(&mut self, args: fmt::Arguments) -> Result<(), io::Error> {
+        io::Write::write_fmt(self, args)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl ByteWrite for Vec<u8> {
+    type Error = core::convert::Infallible;
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.extend_from_slice(buf);
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<W: ByteWrite + ?Sized> ByteWrite for &mut W {
+    type Error = W::Error;
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        (**self).write_all(buf)
+    }
+}
 
 /// Errors that can occur when parsing a .jed file
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
@@ -60,6 +140,7 @@ pub enum JedParserError {
     UnrecognizedField,
 }
 
+#[cfg(feature = "std")]
 impl error::Error for JedParserError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
@@ -138,7 +219,7 @@ impl JEDECFile {
         let mut device = None;
         let mut jed_stx: usize = 0;
         let mut jed_etx: usize;
-        let mut fuses_ternary = vec![];
+        let mut fuses_ternary = Vec::new();
         let mut default_fuse = Ternary::Undef;
 
         // Find STX
@@ -307,10 +388,10 @@ impl JEDECFile {
         })
     }
 
-    /// Writes the contents to a JEDEC file. Note that a `&mut Write` can also be passed as a writer. Line breaks are
-    /// inserted _before_ the given fuse numbers in the iterator.
-    pub fn write_custom_linebreaks<W, I>(&self, mut writer: W, linebreaks: I) -> Result<(), io::Error>
-        where W: Write, I: Iterator<Item = usize> {
+    /// Writes the contents to a JEDEC file. Note that a mutable reference to a writer can also be passed. Line breaks
+    /// are inserted _before_ the given fuse numbers in the iterator.
+    pub fn write_custom_linebreaks<W, I>(&self, mut writer: W, linebreaks: I) -> Result<(), W::Error>
+        where W: ByteWrite, I: Iterator<Item = usize> {
 
         // FIXME: Un-hardcode the number of 0s in the fuse index
 
@@ -352,15 +433,17 @@ impl JEDECFile {
         Ok(())
     }
 
-    /// Writes the contents to a JEDEC file. Note that a `&mut Write` can also be passed as a writer. Line breaks
+    /// Writes the contents to a JEDEC file. Note that a mutable reference to a writer can also be passed. Line breaks
     /// happen every `break_inverval` fuses.
-    pub fn write_with_linebreaks<W>(&self, writer: W, break_inverval: usize) -> Result<(), io::Error> where W: Write {
+    pub fn write_with_linebreaks<W>(&self, writer: W, break_inverval: usize) -> Result<(), W::Error>
+        where W: ByteWrite {
+
         self.write_custom_linebreaks(writer, (0..self.f.len()).step_by(break_inverval).skip(1))
     }
 
-    /// Writes the contents to a JEDEC file. Note that a `&mut Write` can also be passed as a writer. Line breaks
+    /// Writes the contents to a JEDEC file. Note that a mutable reference to a writer can also be passed. Line breaks
     /// default to once every 16 fuses.
-    pub fn write<W>(&self, writer: W) -> Result<(), io::Error> where W: Write {
+    pub fn write<W>(&self, writer: W) -> Result<(), W::Error> where W: ByteWrite {
         self.write_with_linebreaks(writer, 16)
     }
 
@@ -491,6 +574,19 @@ mod tests {
         assert_eq!(ret, Err(JedParserError::BadFuseChecksum));
     }
 
+    #[test]
+    fn write_two_fuses() {
+        let jed = JEDECFile {
+            f: vec![true, false],
+            dev_name_str: Some(String::from("asdf")),
+        };
+        let mut out = Vec::new();
+        jed.write(&mut out).unwrap();
+
+        assert_eq!(out, b"\x02QF2*\nN DEVICE asdf*\n\nL000000 10*\n\x030000\n");
+        assert_eq!(JEDECFile::from_bytes(&out), Ok(jed));
+    }
+
     #[test]
     fn read_two_fuses_space() {
         let ret = JEDECFile::from_bytes(b"\x02F0*QF2*L0 0 1*\x030000");
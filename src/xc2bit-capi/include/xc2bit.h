@@ -0,0 +1,224 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+#ifndef XC2BIT_H
+#define XC2BIT_H
+
+/* Generated from src/lib.rs by cbindgen. Do not edit. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+/* Opaque handle to a bitstream */
+typedef struct xc2bit_bitstream xc2bit_bitstream;
+
+// Success
+#define XC2BIT_OK 0
+
+// A pointer was NULL, or an index or value was out of range
+#define XC2BIT_ERROR_INVALID_ARGUMENT 1
+
+// The input could not be parsed
+#define XC2BIT_ERROR_PARSE 2
+
+// The input was parsed but is not a valid bitstream
+#define XC2BIT_ERROR_BITSTREAM 3
+
+// The part or setting is not supported
+#define XC2BIT_ERROR_UNSUPPORTED 4
+
+// The output buffer is too small
+#define XC2BIT_ERROR_BUFFER_TOO_SMALL 5
+
+// A bug in the library
+#define XC2BIT_ERROR_INTERNAL 6
+
+// Macrocell setting: GCK0, GCK1, GCK2, PTC, CTC
+#define XC2BIT_MC_CLK_SRC 0
+
+// Macrocell setting: 0 or 1
+#define XC2BIT_MC_CLK_INVERT 1
+
+// Macrocell setting: 0 or 1
+#define XC2BIT_MC_IS_DDR 2
+
+// Macrocell setting: disabled, PTA, GSR, CTR
+#define XC2BIT_MC_RESET_SRC 3
+
+// Macrocell setting: disabled, PTA, GSR, CTS
+#define XC2BIT_MC_SET_SRC 4
+
+// Macrocell setting: 0 or 1
+#define XC2BIT_MC_INIT_STATE 5
+
+// Macrocell setting: DFF, latch, TFF, DFF with clock enable
+#define XC2BIT_MC_REG_MODE 6
+
+// Macrocell setting: disabled, XOR gate, register
+#define XC2BIT_MC_FB_MODE 7
+
+// Macrocell setting: 0 or 1
+#define XC2BIT_MC_FF_IN_IBUF 8
+
+// Macrocell setting: 0, 1, PTC, ~PTC
+#define XC2BIT_MC_XOR_MODE 9
+
+// IOB setting: disabled, pin, register
+#define XC2BIT_IOB_ZIA_MODE 0
+
+// IOB setting: 0 or 1; XC2C32(A) and XC2C64(A) only
+#define XC2BIT_IOB_SCHMITT_TRIGGER 1
+
+// IOB setting: 0 or 1
+#define XC2BIT_IOB_OBUF_USES_FF 2
+
+// IOB setting: disabled, push-pull, open-drain, GTS0, GTS1, GTS2, GTS3, PTB, CTE, CGND
+#define XC2BIT_IOB_OBUF_MODE 3
+
+// IOB setting: 0 or 1
+#define XC2BIT_IOB_TERMINATION_ENABLED 4
+
+// IOB setting: 0 or 1
+#define XC2BIT_IOB_SLEW_IS_FAST 5
+
+// IOB setting: no VREF, no VREF with Schmitt trigger, uses VREF, is VREF; XC2C128 and larger only
+#define XC2BIT_IOB_IBUF_MODE 6
+
+// IOB setting: 0 or 1; XC2C128 and larger only
+#define XC2BIT_IOB_USES_DATA_GATE 7
+
+// ZIA input: constant 0
+#define XC2BIT_ZIA_ZERO 0
+
+// ZIA input: constant 1
+#define XC2BIT_ZIA_ONE 1
+
+// ZIA input: a macrocell; a = function block, b = macrocell
+#define XC2BIT_ZIA_MACROCELL 2
+
+// ZIA input: an input buffer; a = IOB
+#define XC2BIT_ZIA_IBUF 3
+
+// ZIA input: the dedicated input pin
+#define XC2BIT_ZIA_DEDICATED_INPUT 4
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Reads a bitstream from the contents of a .jed file
+int32_t xc2bit_bitstream_from_jed(const uint8_t *data, size_t len, xc2bit_bitstream **out);
+
+// Reads a bitstream from the contents of a .crbit file
+int32_t xc2bit_bitstream_from_crbit(const uint8_t *data, size_t len, xc2bit_bitstream **out);
+
+// Creates a blank bitstream for the given part, such as "XC2C32A-4-VQ44"
+int32_t xc2bit_bitstream_new_blank(const char *part, xc2bit_bitstream **out);
+
+// Frees a bitstream. Passing NULL does nothing.
+void xc2bit_bitstream_free(xc2bit_bitstream *bitstream);
+
+// Writes the part name of the bitstream, such as "XC2C32A-4-VQ44", as a NUL-terminated string
+int32_t xc2bit_bitstream_get_part(const xc2bit_bitstream *bitstream, char *buf, size_t buf_len);
+
+// Returns the number of function blocks of the device of the bitstream
+int32_t xc2bit_bitstream_num_fbs(const xc2bit_bitstream *bitstream, uint32_t *out);
+
+// Returns the number of IOBs (not counting the input-only pin) of the device of the bitstream
+int32_t xc2bit_bitstream_num_iobs(const xc2bit_bitstream *bitstream, uint32_t *out);
+
+// Reads a setting of a macrocell
+int32_t xc2bit_mc_get(const xc2bit_bitstream *bitstream, uint32_t fb, uint32_t mc, uint32_t setting, uint32_t *out);
+
+// Changes a setting of a macrocell
+int32_t xc2bit_mc_set(xc2bit_bitstream *bitstream, uint32_t fb, uint32_t mc, uint32_t setting, uint32_t value);
+
+// Reads a setting of an IOB
+int32_t xc2bit_iob_get(const xc2bit_bitstream *bitstream, uint32_t iob, uint32_t setting, uint32_t *out);
+
+// Changes a setting of an IOB
+int32_t xc2bit_iob_set(xc2bit_bitstream *bitstream, uint32_t iob, uint32_t setting, uint32_t value);
+
+// Looks up a part, such as "XC2C32A-4-VQ44". The canonical name of the part is written as a NUL-terminated string, and
+// the numbers of function blocks and IOBs of the device are returned.
+int32_t xc2bit_part_info(const char *part, char *buf, size_t buf_len, uint32_t *num_fbs, uint32_t *num_iobs);
+
+// Reads whether a ZIA row is connected to the true and complement inputs of an AND term
+int32_t xc2bit_andterm_get(const xc2bit_bitstream *bitstream,
+                           uint32_t fb,
+                           uint32_t term,
+                           uint32_t row,
+                           uint32_t *out_true,
+                           uint32_t *out_comp);
+
+// Changes whether a ZIA row is connected to the true and complement inputs of an AND term
+int32_t xc2bit_andterm_set(xc2bit_bitstream *bitstream,
+                           uint32_t fb,
+                           uint32_t term,
+                           uint32_t row,
+                           uint32_t value_true,
+                           uint32_t value_comp);
+
+// Reads whether an AND term is connected to the OR term of a macrocell
+int32_t xc2bit_orterm_get(const xc2bit_bitstream *bitstream, uint32_t fb, uint32_t mc, uint32_t term, uint32_t *out);
+
+// Changes whether an AND term is connected to the OR term of a macrocell
+int32_t xc2bit_orterm_set(xc2bit_bitstream *bitstream, uint32_t fb, uint32_t mc, uint32_t term, uint32_t value);
+
+// Reads the signal selected by a ZIA row. The kind of signal is returned in `out_kind`, and `out_a` and `out_b` are
+// set to its indices (FB and macrocell for macrocells, IOB and 0 for IOBs, and 0 and 0 otherwise).
+int32_t xc2bit_zia_get(const xc2bit_bitstream *bitstream,
+                       uint32_t fb,
+                       uint32_t row,
+                       uint32_t *out_kind,
+                       uint32_t *out_a,
+                       uint32_t *out_b);
+
+// Changes the signal selected by a ZIA row. The arguments are the same as the results of `xc2bit_zia_get`. Signals
+// that the row cannot select are rejected.
+int32_t xc2bit_zia_set(xc2bit_bitstream *bitstream, uint32_t fb, uint32_t row, uint32_t kind, uint32_t a, uint32_t b);
+
+// Writes the same human-readable description of the bitstream as `xc2 dump` into a newly allocated buffer, which
+// must be freed with `xc2bit_buffer_free`
+int32_t xc2bit_bitstream_dump(const xc2bit_bitstream *bitstream, uint8_t **out_data, size_t *out_len);
+
+// Writes the bitstream as a .jed file into a newly allocated buffer, which must be freed with `xc2bit_buffer_free`
+int32_t xc2bit_bitstream_to_jed(const xc2bit_bitstream *bitstream, uint8_t **out_data, size_t *out_len);
+
+// Writes the bitstream as a .crbit file into a newly allocated buffer, which must be freed with `xc2bit_buffer_free`
+int32_t xc2bit_bitstream_to_crbit(const xc2bit_bitstream *bitstream, uint8_t **out_data, size_t *out_len);
+
+// Frees a buffer returned by this library. Passing NULL does nothing.
+void xc2bit_buffer_free(uint8_t *data, size_t len);
+
+// Returns a description of the most recent error on the calling thread. The string stays valid until the next call
+// into this library on the same thread.
+const char *xc2bit_last_error_message(void);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* XC2BIT_H */
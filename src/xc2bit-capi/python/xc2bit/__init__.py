@@ -0,0 +1,489 @@
+# Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+# All rights reserved.
+#
+# Redistribution and use in source and binary forms, with or without
+# modification, are permitted provided that the following conditions are met:
+#
+# 1. Redistributions of source code must retain the above copyright notice,
+#    this list of conditions and the following disclaimer.
+# 2. Redistributions in binary form must reproduce the above copyright notice,
+#    this list of conditions and the following disclaimer in the documentation
+#    and/or other materials provided with the distribution.
+#
+# THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+# ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+# WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+# DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+# FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+# DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+# SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+# CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+# OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+# OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+"""Python bindings for xc2bit, built on its C interface (include/xc2bit.h) using ctypes.
+
+Install the package with `pip install src/xc2bit-capi`, which builds the shared library and puts it into the package.
+The shared library is found using the XC2BIT_LIBRARY environment variable, then in the package, then in the cargo
+target directory of src/xc2bit-capi (so the package can be used from the source tree after `cargo build`), and then on
+the system library path.
+
+    >>> import xc2bit
+    >>> bitstream = xc2bit.Bitstream.load("design.jed")
+    >>> bitstream.part
+    'XC2C32A-6-VQ44'
+    >>> bitstream.fbs[0].mcs[3].reg_mode
+    'DFF'
+    >>> bitstream.fbs[0].zia[5] = xc2bit.ZIAInput.IBuf(3)
+    >>> bitstream.save("modified.jed")
+
+The objects mirror the structure of `XC2Bitstream`, and settings with a Rust enum type are represented by the names of
+the enum variants.
+"""
+
+import ctypes
+import ctypes.util
+import os
+import sys
+
+ANDTERMS_PER_FB = 56
+MCS_PER_FB = 16
+INPUTS_PER_ANDTERM = 40
+
+XC2BIT_OK = 0
+XC2BIT_ERROR_INVALID_ARGUMENT = 1
+XC2BIT_ERROR_PARSE = 2
+XC2BIT_ERROR_BITSTREAM = 3
+XC2BIT_ERROR_UNSUPPORTED = 4
+XC2BIT_ERROR_BUFFER_TOO_SMALL = 5
+XC2BIT_ERROR_INTERNAL = 6
+
+
+def _library_name(name):
+    if sys.platform == "darwin":
+        return "lib{}.dylib".format(name)
+    elif sys.platform == "win32":
+        return "{}.dll".format(name)
+    return "lib{}.so".format(name)
+
+
+def load_library(name, env_var):
+    """Finds and loads one of the shared libraries of the project. Also used by the xc2par module."""
+    path = os.environ.get(env_var)
+    if path:
+        return ctypes.CDLL(path)
+    here = os.path.dirname(os.path.abspath(__file__))
+    path = os.path.join(here, _library_name(name))
+    if os.path.exists(path):
+        return ctypes.CDLL(path)
+    for target_dir in [os.path.join(here, "..", "..", "target"), os.path.join(here, "..", "..", "..", "..", "target")]:
+        for profile in ["release", "debug"]:
+            path = os.path.join(target_dir, profile, _library_name(name))
+            if os.path.exists(path):
+                return ctypes.CDLL(path)
+    path = ctypes.util.find_library(name)
+    if path:
+        return ctypes.CDLL(path)
+    raise OSError("could not find the {} library; set {} to its path".format(name, env_var))
+
+
+_lib = load_library("xc2bit_capi", "XC2BIT_LIBRARY")
+
+_u32 = ctypes.c_uint32
+_pu32 = ctypes.POINTER(ctypes.c_uint32)
+_handle = ctypes.c_void_p
+_buffer = ctypes.POINTER(ctypes.c_uint8)
+_out_buffer = [ctypes.POINTER(_buffer), ctypes.POINTER(ctypes.c_size_t)]
+
+for _name, _args in [
+    ("xc2bit_part_info", [ctypes.c_char_p, ctypes.c_char_p, ctypes.c_size_t, _pu32, _pu32]),
+    ("xc2bit_bitstream_from_jed", [ctypes.c_char_p, ctypes.c_size_t, ctypes.POINTER(_handle)]),
+    ("xc2bit_bitstream_from_crbit", [ctypes.c_char_p, ctypes.c_size_t, ctypes.POINTER(_handle)]),
+    ("xc2bit_bitstream_new_blank", [ctypes.c_char_p, ctypes.POINTER(_handle)]),
+    ("xc2bit_bitstream_get_part", [_handle, ctypes.c_char_p, ctypes.c_size_t]),
+    ("xc2bit_bitstream_num_fbs", [_handle, _pu32]),
+    ("xc2bit_bitstream_num_iobs", [_handle, _pu32]),
+    ("xc2bit_mc_get", [_handle, _u32, _u32, _u32, _pu32]),
+    ("xc2bit_mc_set", [_handle, _u32, _u32, _u32, _u32]),
+    ("xc2bit_iob_get", [_handle, _u32, _u32, _pu32]),
+    ("xc2bit_iob_set", [_handle, _u32, _u32, _u32]),
+    ("xc2bit_andterm_get", [_handle, _u32, _u32, _u32, _pu32, _pu32]),
+    ("xc2bit_andterm_set", [_handle, _u32, _u32, _u32, _u32, _u32]),
+    ("xc2bit_orterm_get", [_handle, _u32, _u32, _u32, _pu32]),
+    ("xc2bit_orterm_set", [_handle, _u32, _u32, _u32, _u32]),
+    ("xc2bit_zia_get", [_handle, _u32, _u32, _pu32, _pu32, _pu32]),
+    ("xc2bit_zia_set", [_handle, _u32, _u32, _u32, _u32, _u32]),
+    ("xc2bit_bitstream_dump", [_handle] + _out_buffer),
+    ("xc2bit_bitstream_to_jed", [_handle] + _out_buffer),
+    ("xc2bit_bitstream_to_crbit", [_handle] + _out_buffer),
+]:
+    getattr(_lib, _name).argtypes = _args
+    getattr(_lib, _name).restype = ctypes.c_int
+_lib.xc2bit_bitstream_free.argtypes = [_handle]
+_lib.xc2bit_bitstream_free.restype = None
+_lib.xc2bit_buffer_free.argtypes = [_buffer, ctypes.c_size_t]
+_lib.xc2bit_buffer_free.restype = None
+_lib.xc2bit_last_error_message.argtypes = []
+_lib.xc2bit_last_error_message.restype = ctypes.c_char_p
+
+
+class XC2BitError(Exception):
+    """An error reported by xc2bit. `code` is one of the XC2BIT_ERROR_* values."""
+
+    def __init__(self, code, message):
+        super(XC2BitError, self).__init__(message)
+        self.code = code
+
+
+def _check(code):
+    if code != XC2BIT_OK:
+        raise XC2BitError(code, _lib.xc2bit_last_error_message().decode("utf-8", "replace"))
+
+
+def _take_buffer(func, *args):
+    data = _buffer()
+    length = ctypes.c_size_t()
+    _check(func(*(args + (ctypes.byref(data), ctypes.byref(length)))))
+    try:
+        return ctypes.string_at(data, length.value)
+    finally:
+        _lib.xc2bit_buffer_free(data, length)
+
+
+class PartInfo(object):
+    """The result of looking up a part with `part_info`"""
+
+    def __init__(self, name, num_fbs, num_iobs):
+        self.name = name
+        self.num_fbs = num_fbs
+        self.num_iobs = num_iobs
+
+    def __repr__(self):
+        return "PartInfo({!r}, num_fbs={}, num_iobs={})".format(self.name, self.num_fbs, self.num_iobs)
+
+
+def part_info(part):
+    """Looks up a part name such as "XC2C32A-4-VQ44" in the same way as `XC2DeviceSpeedPackage::from_str`"""
+    buf = ctypes.create_string_buffer(64)
+    num_fbs = _u32()
+    num_iobs = _u32()
+    _check(_lib.xc2bit_part_info(part.encode("ascii"), buf, len(buf), ctypes.byref(num_fbs), ctypes.byref(num_iobs)))
+    return PartInfo(buf.value.decode("ascii"), num_fbs.value, num_iobs.value)
+
+
+class ZIAInput(object):
+    """The signal selected by a ZIA row, mirroring `XC2ZIAInput`. Use the `Macrocell` and `IBuf` constructors and the
+    `Zero`, `One` and `DedicatedInput` constants."""
+
+    _KIND_NAMES = ["Zero", "One", "Macrocell", "IBuf", "DedicatedInput"]
+
+    def __init__(self, kind, a=0, b=0):
+        self._kind = kind
+        self._a = a
+        self._b = b
+
+    @classmethod
+    def Macrocell(cls, fb, mc):
+        return cls(2, fb, mc)
+
+    @classmethod
+    def IBuf(cls, ibuf):
+        return cls(3, ibuf)
+
+    @property
+    def kind(self):
+        """The name of the `XC2ZIAInput` variant"""
+        return self._KIND_NAMES[self._kind]
+
+    @property
+    def fb(self):
+        if self._kind != 2:
+            raise AttributeError("only Macrocell inputs have a function block")
+        return self._a
+
+    @property
+    def mc(self):
+        if self._kind != 2:
+            raise AttributeError("only Macrocell inputs have a macrocell")
+        return self._b
+
+    @property
+    def ibuf(self):
+        if self._kind != 3:
+            raise AttributeError("only IBuf inputs have an IOB")
+        return self._a
+
+    def __eq__(self, other):
+        return isinstance(other, ZIAInput) and (self._kind, self._a, self._b) == (other._kind, other._a, other._b)
+
+    def __ne__(self, other):
+        return not self == other
+
+    def __hash__(self):
+        return hash((self._kind, self._a, self._b))
+
+    def __repr__(self):
+        if self._kind == 2:
+            return "ZIAInput.Macrocell(fb={}, mc={})".format(self._a, self._b)
+        elif self._kind == 3:
+            return "ZIAInput.IBuf(ibuf={})".format(self._a)
+        return "ZIAInput.{}".format(self.kind)
+
+
+ZIAInput.Zero = ZIAInput(0)
+ZIAInput.One = ZIAInput(1)
+ZIAInput.DedicatedInput = ZIAInput(4)
+
+
+def _setting(index, values, doc):
+    # Creates a property for a macrocell or IOB setting. values lists the names of the enum variants in the order used
+    # by the C interface, or is None for booleans.
+
+    def getter(self):
+        value = self._get(index)
+        return bool(value) if values is None else values[value]
+
+    def setter(self, value):
+        if values is None:
+            value = int(bool(value))
+        elif value in values:
+            value = values.index(value)
+        else:
+            raise ValueError("{!r} is not one of {}".format(value, ", ".join(values)))
+        self._set(index, value)
+
+    return property(getter, setter, doc=doc)
+
+
+class Macrocell(object):
+    """A macrocell, mirroring `XC2Macrocell`"""
+
+    def __init__(self, bitstream, fb, mc):
+        self._bitstream = bitstream
+        self.fb = fb
+        self.mc = mc
+
+    def _get(self, setting):
+        out = _u32()
+        _check(_lib.xc2bit_mc_get(self._bitstream._handle, self.fb, self.mc, setting, ctypes.byref(out)))
+        return out.value
+
+    def _set(self, setting, value):
+        _check(_lib.xc2bit_mc_set(self._bitstream._handle, self.fb, self.mc, setting, value))
+
+    clk_src = _setting(0, ["GCK0", "GCK1", "GCK2", "PTC", "CTC"], "Clock source for the register")
+    clk_invert_pol = _setting(1, None, "Whether the clock is inverted")
+    is_ddr = _setting(2, None, "Whether the register triggers on both clock edges")
+    r_src = _setting(3, ["Disabled", "PTA", "GSR", "CTR"], "Reset source for the register")
+    s_src = _setting(4, ["Disabled", "PTA", "GSR", "CTS"], "Set source for the register")
+    init_state = _setting(5, None, "Power-up state of the register")
+    reg_mode = _setting(6, ["DFF", "LATCH", "TFF", "DFFCE"], "Register mode")
+    fb_mode = _setting(7, ["Disabled", "COMB", "REG"], "Source of the feedback into the ZIA")
+    ff_in_ibuf = _setting(8, None, "Whether the register input comes from the input pin")
+    xor_mode = _setting(9, ["ZERO", "ONE", "PTC", "PTCB"], "Mode of the XOR gate")
+
+    def __repr__(self):
+        return "<xc2bit.Macrocell FB{}_{}>".format(self.fb + 1, self.mc + 1)
+
+
+class IOB(object):
+    """An I/O block, mirroring `XC2MCSmallIOB` or `XC2MCLargeIOB` depending on the device. Accessing a setting that
+    the device does not have raises an XC2BitError with the code XC2BIT_ERROR_UNSUPPORTED."""
+
+    def __init__(self, bitstream, iob):
+        self._bitstream = bitstream
+        self.iob = iob
+
+    def _get(self, setting):
+        out = _u32()
+        _check(_lib.xc2bit_iob_get(self._bitstream._handle, self.iob, setting, ctypes.byref(out)))
+        return out.value
+
+    def _set(self, setting, value):
+        _check(_lib.xc2bit_iob_set(self._bitstream._handle, self.iob, setting, value))
+
+    zia_mode = _setting(0, ["Disabled", "PAD", "REG"], "What the IOB sends into the ZIA")
+    schmitt_trigger = _setting(1, None, "Whether the input has a Schmitt trigger (XC2C32(A) and XC2C64(A) only)")
+    obuf_uses_ff = _setting(2, None, "Whether the output comes from the register")
+    obuf_mode = _setting(3, ["Disabled", "PushPull", "OpenDrain", "TriStateGTS0", "TriStateGTS1", "TriStateGTS2",
+        "TriStateGTS3", "TriStatePTB", "TriStateCTE", "CGND"], "Output buffer mode")
+    termination_enabled = _setting(4, None, "Whether the bus hold/pull-up is enabled")
+    slew_is_fast = _setting(5, None, "Whether the output uses the fast slew rate")
+    ibuf_mode = _setting(6, ["NoVrefNoSt", "NoVrefSt", "UsesVref", "IsVref"],
+        "Input buffer mode (XC2C128 and larger only)")
+    uses_data_gate = _setting(7, None, "Whether the input goes through the DataGate (XC2C128 and larger only)")
+
+    def __repr__(self):
+        return "<xc2bit.IOB {}>".format(self.iob)
+
+
+class AndTerm(object):
+    """An AND term, mirroring `XC2PLAAndTerm`. Input `i` of the term corresponds to ZIA row `i`."""
+
+    def __init__(self, bitstream, fb, term):
+        self._bitstream = bitstream
+        self.fb = fb
+        self.term = term
+
+    def _get(self, row):
+        out_true = _u32()
+        out_comp = _u32()
+        _check(_lib.xc2bit_andterm_get(self._bitstream._handle, self.fb, self.term, row, ctypes.byref(out_true),
+            ctypes.byref(out_comp)))
+        return bool(out_true.value), bool(out_comp.value)
+
+    def _set(self, row, value_true, value_comp):
+        _check(_lib.xc2bit_andterm_set(self._bitstream._handle, self.fb, self.term, row, int(bool(value_true)),
+            int(bool(value_comp))))
+
+    def get(self, row):
+        """Returns whether the true value of ZIA row `row` is used"""
+        return self._get(row)[0]
+
+    def get_b(self, row):
+        """Returns whether the complement of ZIA row `row` is used"""
+        return self._get(row)[1]
+
+    def set(self, row, value):
+        self._set(row, value, self.get_b(row))
+
+    def set_b(self, row, value):
+        self._set(row, self.get(row), value)
+
+    def __repr__(self):
+        return "<xc2bit.AndTerm FB{} term {}>".format(self.fb + 1, self.term)
+
+
+class OrTerm(object):
+    """The OR term of a macrocell, mirroring `XC2PLAOrTerm`"""
+
+    def __init__(self, bitstream, fb, mc):
+        self._bitstream = bitstream
+        self.fb = fb
+        self.mc = mc
+
+    def get(self, term):
+        """Returns whether AND term `term` is used"""
+        out = _u32()
+        _check(_lib.xc2bit_orterm_get(self._bitstream._handle, self.fb, self.mc, term, ctypes.byref(out)))
+        return bool(out.value)
+
+    def set(self, term, value):
+        _check(_lib.xc2bit_orterm_set(self._bitstream._handle, self.fb, self.mc, term, int(bool(value))))
+
+    def __repr__(self):
+        return "<xc2bit.OrTerm FB{}_{}>".format(self.fb + 1, self.mc + 1)
+
+
+class ZIARows(object):
+    """The ZIA selections of a function block, indexed by row and holding `ZIAInput` values"""
+
+    def __init__(self, bitstream, fb):
+        self._bitstream = bitstream
+        self.fb = fb
+
+    def __len__(self):
+        return INPUTS_PER_ANDTERM
+
+    def __getitem__(self, row):
+        if not 0 <= row < INPUTS_PER_ANDTERM:
+            raise IndexError("ZIA row index out of range")
+        kind = _u32()
+        a = _u32()
+        b = _u32()
+        _check(_lib.xc2bit_zia_get(self._bitstream._handle, self.fb, row, ctypes.byref(kind), ctypes.byref(a),
+            ctypes.byref(b)))
+        return ZIAInput(kind.value, a.value, b.value)
+
+    def __setitem__(self, row, value):
+        if not 0 <= row < INPUTS_PER_ANDTERM:
+            raise IndexError("ZIA row index out of range")
+        _check(_lib.xc2bit_zia_set(self._bitstream._handle, self.fb, row, value._kind, value._a, value._b))
+
+
+class FunctionBlock(object):
+    """A function block, mirroring `XC2BitstreamFB`"""
+
+    def __init__(self, bitstream, fb):
+        self.fb = fb
+        self.mcs = [Macrocell(bitstream, fb, i) for i in range(MCS_PER_FB)]
+        self.and_terms = [AndTerm(bitstream, fb, i) for i in range(ANDTERMS_PER_FB)]
+        self.or_terms = [OrTerm(bitstream, fb, i) for i in range(MCS_PER_FB)]
+        self.zia = ZIARows(bitstream, fb)
+
+    def __repr__(self):
+        return "<xc2bit.FunctionBlock FB{}>".format(self.fb + 1)
+
+
+class Bitstream(object):
+    """A bitstream, mirroring `XC2Bitstream`. Use one of the class methods to create one."""
+
+    def __init__(self, handle):
+        self._handle = handle
+        num_fbs = _u32()
+        num_iobs = _u32()
+        _check(_lib.xc2bit_bitstream_num_fbs(handle, ctypes.byref(num_fbs)))
+        _check(_lib.xc2bit_bitstream_num_iobs(handle, ctypes.byref(num_iobs)))
+        self.fbs = [FunctionBlock(self, i) for i in range(num_fbs.value)]
+        self.iobs = [IOB(self, i) for i in range(num_iobs.value)]
+
+    def __del__(self):
+        if getattr(self, "_handle", None):
+            _lib.xc2bit_bitstream_free(self._handle)
+            self._handle = None
+
+    @classmethod
+    def _create(cls, func, *args):
+        handle = _handle()
+        _check(func(*(args + (ctypes.byref(handle),))))
+        return cls(handle)
+
+    @classmethod
+    def from_jed(cls, data):
+        """Parses the contents of a .jed file"""
+        return cls._create(_lib.xc2bit_bitstream_from_jed, data, len(data))
+
+    @classmethod
+    def from_crbit(cls, data):
+        """Parses the contents of a .crbit file"""
+        return cls._create(_lib.xc2bit_bitstream_from_crbit, data, len(data))
+
+    @classmethod
+    def blank(cls, part):
+        """Creates a blank bitstream for a part such as "XC2C32A-4-VQ44\""""
+        return cls._create(_lib.xc2bit_bitstream_new_blank, part.encode("ascii"))
+
+    @classmethod
+    def load(cls, path):
+        """Reads a .crbit file if the name ends in .crbit and a .jed file otherwise"""
+        with open(path, "rb") as f:
+            data = f.read()
+        if path.endswith(".crbit"):
+            return cls.from_crbit(data)
+        return cls.from_jed(data)
+
+    def to_jed(self):
+        return _take_buffer(_lib.xc2bit_bitstream_to_jed, self._handle)
+
+    def to_crbit(self):
+        return _take_buffer(_lib.xc2bit_bitstream_to_crbit, self._handle)
+
+    def save(self, path):
+        """Writes a .crbit file if the name ends in .crbit and a .jed file otherwise"""
+        data = self.to_crbit() if path.endswith(".crbit") else self.to_jed()
+        with open(path, "wb") as f:
+            f.write(data)
+
+    def dump(self):
+        """Returns the same human-readable description as `xc2 dump`"""
+        return _take_buffer(_lib.xc2bit_bitstream_dump, self._handle).decode("utf-8")
+
+    @property
+    def part(self):
+        """The full part name, such as "XC2C32A-4-VQ44\""""
+        buf = ctypes.create_string_buffer(64)
+        _check(_lib.xc2bit_bitstream_get_part(self._handle, buf, len(buf)))
+        return buf.value.decode("ascii")
+
+    def __repr__(self):
+        return "<xc2bit.Bitstream {}>".format(self.part)
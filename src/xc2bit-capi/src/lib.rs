@@ -0,0 +1,669 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! # xc2bit-capi: C interface to xc2bit
+//!
+//! These functions are declared in `include/xc2bit.h`, which is generated from this file by cbindgen. Bitstreams are
+//! passed to C as opaque pointers. Every function that can fail returns one of the `XC2BIT_*` error codes, and a
+//! description of the most recent error on the calling thread can be retrieved with `xc2bit_last_error_message`.
+//! Settings of macrocells and IOBs are accessed by number; the numbering of the settings and of their values is listed
+//! in the header.
+//!
+//! Pointers passed to these functions must either be NULL or point to valid memory of the given length, and
+//! bitstreams must have been returned by this library and not yet freed.
+
+// The safety requirements are the same for every function and are listed above
+#![allow(clippy::missing_safety_doc)]
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic;
+use std::ptr;
+use std::slice;
+
+use jedec::*;
+
+use xc2bit::*;
+
+/// Success
+pub const XC2BIT_OK: i32 = 0;
+/// A pointer was NULL, or an index or value was out of range
+pub const XC2BIT_ERROR_INVALID_ARGUMENT: i32 = 1;
+/// The input could not be parsed
+pub const XC2BIT_ERROR_PARSE: i32 = 2;
+/// The input was parsed but is not a valid bitstream
+pub const XC2BIT_ERROR_BITSTREAM: i32 = 3;
+/// The part or setting is not supported
+pub const XC2BIT_ERROR_UNSUPPORTED: i32 = 4;
+/// The output buffer is too small
+pub const XC2BIT_ERROR_BUFFER_TOO_SMALL: i32 = 5;
+/// A bug in the library
+pub const XC2BIT_ERROR_INTERNAL: i32 = 6;
+
+/// Macrocell setting: GCK0, GCK1, GCK2, PTC, CTC
+pub const XC2BIT_MC_CLK_SRC: u32 = 0;
+/// Macrocell setting: 0 or 1
+pub const XC2BIT_MC_CLK_INVERT: u32 = 1;
+/// Macrocell setting: 0 or 1
+pub const XC2BIT_MC_IS_DDR: u32 = 2;
+/// Macrocell setting: disabled, PTA, GSR, CTR
+pub const XC2BIT_MC_RESET_SRC: u32 = 3;
+/// Macrocell setting: disabled, PTA, GSR, CTS
+pub const XC2BIT_MC_SET_SRC: u32 = 4;
+/// Macrocell setting: 0 or 1
+pub const XC2BIT_MC_INIT_STATE: u32 = 5;
+/// Macrocell setting: DFF, latch, TFF, DFF with clock enable
+pub const XC2BIT_MC_REG_MODE: u32 = 6;
+/// Macrocell setting: disabled, XOR gate, register
+pub const XC2BIT_MC_FB_MODE: u32 = 7;
+/// Macrocell setting: 0 or 1
+pub const XC2BIT_MC_FF_IN_IBUF: u32 = 8;
+/// Macrocell setting: 0, 1, PTC, ~PTC
+pub const XC2BIT_MC_XOR_MODE: u32 = 9;
+
+/// IOB setting: disabled, pin, register
+pub const XC2BIT_IOB_ZIA_MODE: u32 = 0;
+/// IOB setting: 0 or 1; XC2C32(A) and XC2C64(A) only
+pub const XC2BIT_IOB_SCHMITT_TRIGGER: u32 = 1;
+/// IOB setting: 0 or 1
+pub const XC2BIT_IOB_OBUF_USES_FF: u32 = 2;
+/// IOB setting: disabled, push-pull, open-drain, GTS0, GTS1, GTS2, GTS3, PTB, CTE, CGND
+pub const XC2BIT_IOB_OBUF_MODE: u32 = 3;
+/// IOB setting: 0 or 1
+pub const XC2BIT_IOB_TERMINATION_ENABLED: u32 = 4;
+/// IOB setting: 0 or 1
+pub const XC2BIT_IOB_SLEW_IS_FAST: u32 = 5;
+/// IOB setting: no VREF, no VREF with Schmitt trigger, uses VREF, is VREF; XC2C128 and larger only
+pub const XC2BIT_IOB_IBUF_MODE: u32 = 6;
+/// IOB setting: 0 or 1; XC2C128 and larger only
+pub const XC2BIT_IOB_USES_DATA_GATE: u32 = 7;
+
+/// ZIA input: constant 0
+pub const XC2BIT_ZIA_ZERO: u32 = 0;
+/// ZIA input: constant 1
+pub const XC2BIT_ZIA_ONE: u32 = 1;
+/// ZIA input: a macrocell; a = function block, b = macrocell
+pub const XC2BIT_ZIA_MACROCELL: u32 = 2;
+/// ZIA input: an input buffer; a = IOB
+pub const XC2BIT_ZIA_IBUF: u32 = 3;
+/// ZIA input: the dedicated input pin
+pub const XC2BIT_ZIA_DEDICATED_INPUT: u32 = 4;
+
+/// An error code together with its description
+struct CError(i32, String);
+
+fn invalid(msg: &str) -> CError {
+    CError(XC2BIT_ERROR_INVALID_ARGUMENT, msg.to_owned())
+}
+
+fn unsupported(msg: &str) -> CError {
+    CError(XC2BIT_ERROR_UNSUPPORTED, msg.to_owned())
+}
+
+impl From<XC2BitError> for CError {
+    fn from(err: XC2BitError) -> Self {
+        let code = match err {
+            XC2BitError::JedParseError(_) | XC2BitError::CrbitParseError(_) => XC2BIT_ERROR_PARSE,
+            _ => XC2BIT_ERROR_BITSTREAM,
+        };
+        CError(code, format!("{}", err))
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+/// Runs the body of an exported function, turning errors and panics into error codes
+fn guard<F>(f: F) -> i32 where F: FnOnce() -> Result<(), CError> {
+    let CError(code, msg) = match panic::catch_unwind(panic::AssertUnwindSafe(f)) {
+        Ok(Ok(())) => return XC2BIT_OK,
+        Ok(Err(err)) => err,
+        Err(_) => CError(XC2BIT_ERROR_INTERNAL, String::from("internal error")),
+    };
+    let msg = CString::new(msg.replace('\0', " ")).unwrap();
+    LAST_ERROR.with(|x| *x.borrow_mut() = msg);
+    code
+}
+
+unsafe fn input_bytes<'a>(data: *const u8, len: usize) -> Result<&'a [u8], CError> {
+    if len == 0 {
+        Ok(&[])
+    } else if data.is_null() {
+        Err(invalid("data is NULL"))
+    } else {
+        Ok(slice::from_raw_parts(data, len))
+    }
+}
+
+unsafe fn bitstream_ref<'a>(bitstream: *const XC2Bitstream) -> Result<&'a XC2Bitstream, CError> {
+    bitstream.as_ref().ok_or_else(|| invalid("bitstream is NULL"))
+}
+
+unsafe fn bitstream_mut<'a>(bitstream: *mut XC2Bitstream) -> Result<&'a mut XC2Bitstream, CError> {
+    bitstream.as_mut().ok_or_else(|| invalid("bitstream is NULL"))
+}
+
+unsafe fn output<'a, T>(out: *mut T) -> Result<&'a mut T, CError> {
+    out.as_mut().ok_or_else(|| invalid("output pointer is NULL"))
+}
+
+unsafe fn output_buffer(data: Vec<u8>, out_data: *mut *mut u8, out_len: *mut usize) -> Result<(), CError> {
+    let out_data = output(out_data)?;
+    let out_len = output(out_len)?;
+    let data = data.into_boxed_slice();
+    *out_len = data.len();
+    *out_data = Box::into_raw(data) as *mut u8;
+    Ok(())
+}
+
+unsafe fn write_string(s: &str, buf: *mut c_char, buf_len: usize) -> Result<(), CError> {
+    if s.len() + 1 > buf_len {
+        return Err(CError(XC2BIT_ERROR_BUFFER_TOO_SMALL, format!("a buffer of {} bytes is needed", s.len() + 1)));
+    }
+    ptr::copy_nonoverlapping(s.as_ptr() as *const c_char, buf, s.len());
+    *buf.add(s.len()) = 0;
+    Ok(())
+}
+
+// The numbering of the values of each setting, in the same order as in the docs of the settings
+const CLK_SRCS: [XC2MCRegClkSrc; 5] = [XC2MCRegClkSrc::GCK0, XC2MCRegClkSrc::GCK1, XC2MCRegClkSrc::GCK2,
+    XC2MCRegClkSrc::PTC, XC2MCRegClkSrc::CTC];
+const RESET_SRCS: [XC2MCRegResetSrc; 4] = [XC2MCRegResetSrc::Disabled, XC2MCRegResetSrc::PTA,
+    XC2MCRegResetSrc::GSR, XC2MCRegResetSrc::CTR];
+const SET_SRCS: [XC2MCRegSetSrc; 4] = [XC2MCRegSetSrc::Disabled, XC2MCRegSetSrc::PTA, XC2MCRegSetSrc::GSR,
+    XC2MCRegSetSrc::CTS];
+const REG_MODES: [XC2MCRegMode; 4] = [XC2MCRegMode::DFF, XC2MCRegMode::LATCH, XC2MCRegMode::TFF,
+    XC2MCRegMode::DFFCE];
+const FB_MODES: [XC2MCFeedbackMode; 3] = [XC2MCFeedbackMode::Disabled, XC2MCFeedbackMode::COMB,
+    XC2MCFeedbackMode::REG];
+const XOR_MODES: [XC2MCXorMode; 4] = [XC2MCXorMode::ZERO, XC2MCXorMode::ONE, XC2MCXorMode::PTC,
+    XC2MCXorMode::PTCB];
+const ZIA_MODES: [XC2IOBZIAMode; 3] = [XC2IOBZIAMode::Disabled, XC2IOBZIAMode::PAD, XC2IOBZIAMode::REG];
+const OBUF_MODES: [XC2IOBOBufMode; 10] = [XC2IOBOBufMode::Disabled, XC2IOBOBufMode::PushPull,
+    XC2IOBOBufMode::OpenDrain, XC2IOBOBufMode::TriStateGTS0, XC2IOBOBufMode::TriStateGTS1,
+    XC2IOBOBufMode::TriStateGTS2, XC2IOBOBufMode::TriStateGTS3, XC2IOBOBufMode::TriStatePTB,
+    XC2IOBOBufMode::TriStateCTE, XC2IOBOBufMode::CGND];
+const IBUF_MODES: [XC2IOBIbufMode; 4] = [XC2IOBIbufMode::NoVrefNoSt, XC2IOBIbufMode::NoVrefSt,
+    XC2IOBIbufMode::UsesVref, XC2IOBIbufMode::IsVref];
+
+fn to_u32<T: PartialEq>(table: &[T], val: &T) -> u32 {
+    table.iter().position(|x| x == val).unwrap() as u32
+}
+
+fn from_u32<T: Copy>(table: &[T], val: u32) -> Result<T, CError> {
+    table.get(val as usize).cloned().ok_or_else(|| invalid("value out of range"))
+}
+
+fn from_bool(val: u32) -> Result<bool, CError> {
+    match val {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(invalid("value out of range")),
+    }
+}
+
+/// Reads a bitstream from the contents of a .jed file
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_bitstream_from_jed(data: *const u8, len: usize, out: *mut *mut XC2Bitstream) -> i32 {
+    guard(|| {
+        let out = output(out)?;
+        let jed = JEDECFile::from_bytes(input_bytes(data, len)?).map_err(XC2BitError::from)?;
+        *out = Box::into_raw(Box::new(XC2Bitstream::from_jed(&jed)?));
+        Ok(())
+    })
+}
+
+/// Reads a bitstream from the contents of a .crbit file
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_bitstream_from_crbit(data: *const u8, len: usize, out: *mut *mut XC2Bitstream)
+    -> i32 {
+
+    guard(|| {
+        let out = output(out)?;
+        let fuse_array = FuseArray::from_file_contents(input_bytes(data, len)?).map_err(XC2BitError::from)?;
+        *out = Box::into_raw(Box::new(XC2Bitstream::from_crbit(&fuse_array)?));
+        Ok(())
+    })
+}
+
+/// Creates a blank bitstream for the given part, such as "XC2C32A-4-VQ44"
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_bitstream_new_blank(part: *const c_char, out: *mut *mut XC2Bitstream) -> i32 {
+    guard(|| {
+        let out = output(out)?;
+        if part.is_null() {
+            return Err(invalid("part is NULL"));
+        }
+        let part = CStr::from_ptr(part).to_str().map_err(|_| invalid("part is not valid UTF-8"))?;
+        let part = XC2DeviceSpeedPackage::from_str(part)
+            .ok_or_else(|| CError(XC2BIT_ERROR_UNSUPPORTED, format!("part \"{}\" is invalid/unsupported", part)))?;
+        *out = Box::into_raw(Box::new(XC2Bitstream::blank_bitstream(part)));
+        Ok(())
+    })
+}
+
+/// Frees a bitstream. Passing NULL does nothing.
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_bitstream_free(bitstream: *mut XC2Bitstream) {
+    if !bitstream.is_null() {
+        drop(Box::from_raw(bitstream));
+    }
+}
+
+/// Writes the part name of the bitstream, such as "XC2C32A-4-VQ44", as a NUL-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_bitstream_get_part(bitstream: *const XC2Bitstream, buf: *mut c_char, buf_len: usize)
+    -> i32 {
+
+    guard(|| {
+        let bitstream = bitstream_ref(bitstream)?;
+        if buf.is_null() {
+            return Err(invalid("buf is NULL"));
+        }
+        write_string(&format!("{}-{}-{}", bitstream.bits.device_type(), bitstream.speed_grade, bitstream.package),
+            buf, buf_len)
+    })
+}
+
+/// Returns the number of function blocks of the device of the bitstream
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_bitstream_num_fbs(bitstream: *const XC2Bitstream, out: *mut u32) -> i32 {
+    guard(|| {
+        *output(out)? = bitstream_ref(bitstream)?.bits.device_type().num_fbs() as u32;
+        Ok(())
+    })
+}
+
+/// Returns the number of IOBs (not counting the input-only pin) of the device of the bitstream
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_bitstream_num_iobs(bitstream: *const XC2Bitstream, out: *mut u32) -> i32 {
+    guard(|| {
+        *output(out)? = bitstream_ref(bitstream)?.bits.device_type().num_iobs() as u32;
+        Ok(())
+    })
+}
+
+fn get_mc(bitstream: &XC2Bitstream, fb: u32, mc: u32) -> Result<&XC2Macrocell, CError> {
+    if mc as usize >= MCS_PER_FB {
+        return Err(invalid("macrocell index out of range"));
+    }
+    bitstream.bits.get_fb().get(fb as usize).map(|x| &x.mcs[mc as usize])
+        .ok_or_else(|| invalid("function block index out of range"))
+}
+
+/// Reads a setting of a macrocell
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_mc_get(bitstream: *const XC2Bitstream, fb: u32, mc: u32, setting: u32,
+    out: *mut u32) -> i32 {
+
+    guard(|| {
+        let out = output(out)?;
+        let mc = get_mc(bitstream_ref(bitstream)?, fb, mc)?;
+        *out = match setting {
+            XC2BIT_MC_CLK_SRC => to_u32(&CLK_SRCS, &mc.clk_src),
+            XC2BIT_MC_CLK_INVERT => mc.clk_invert_pol as u32,
+            XC2BIT_MC_IS_DDR => mc.is_ddr as u32,
+            XC2BIT_MC_RESET_SRC => to_u32(&RESET_SRCS, &mc.r_src),
+            XC2BIT_MC_SET_SRC => to_u32(&SET_SRCS, &mc.s_src),
+            XC2BIT_MC_INIT_STATE => mc.init_state as u32,
+            XC2BIT_MC_REG_MODE => to_u32(&REG_MODES, &mc.reg_mode),
+            XC2BIT_MC_FB_MODE => to_u32(&FB_MODES, &mc.fb_mode),
+            XC2BIT_MC_FF_IN_IBUF => mc.ff_in_ibuf as u32,
+            XC2BIT_MC_XOR_MODE => to_u32(&XOR_MODES, &mc.xor_mode),
+            _ => return Err(invalid("unknown macrocell setting")),
+        };
+        Ok(())
+    })
+}
+
+/// Changes a setting of a macrocell
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_mc_set(bitstream: *mut XC2Bitstream, fb: u32, mc: u32, setting: u32, value: u32)
+    -> i32 {
+
+    guard(|| {
+        let bitstream = bitstream_mut(bitstream)?;
+        get_mc(bitstream, fb, mc)?;
+        let mc = &mut bitstream.bits.get_fb_mut()[fb as usize].mcs[mc as usize];
+        match setting {
+            XC2BIT_MC_CLK_SRC => mc.clk_src = from_u32(&CLK_SRCS, value)?,
+            XC2BIT_MC_CLK_INVERT => mc.clk_invert_pol = from_bool(value)?,
+            XC2BIT_MC_IS_DDR => mc.is_ddr = from_bool(value)?,
+            XC2BIT_MC_RESET_SRC => mc.r_src = from_u32(&RESET_SRCS, value)?,
+            XC2BIT_MC_SET_SRC => mc.s_src = from_u32(&SET_SRCS, value)?,
+            XC2BIT_MC_INIT_STATE => mc.init_state = from_bool(value)?,
+            XC2BIT_MC_REG_MODE => mc.reg_mode = from_u32(&REG_MODES, value)?,
+            XC2BIT_MC_FB_MODE => mc.fb_mode = from_u32(&FB_MODES, value)?,
+            XC2BIT_MC_FF_IN_IBUF => mc.ff_in_ibuf = from_bool(value)?,
+            XC2BIT_MC_XOR_MODE => mc.xor_mode = from_u32(&XOR_MODES, value)?,
+            _ => return Err(invalid("unknown macrocell setting")),
+        }
+        Ok(())
+    })
+}
+
+fn check_iob(bitstream: &XC2Bitstream, iob: u32) -> Result<(), CError> {
+    if iob as usize >= bitstream.bits.device_type().num_iobs() {
+        return Err(invalid("IOB index out of range"));
+    }
+    Ok(())
+}
+
+/// Reads a setting of an IOB
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_iob_get(bitstream: *const XC2Bitstream, iob: u32, setting: u32, out: *mut u32)
+    -> i32 {
+
+    guard(|| {
+        let out = output(out)?;
+        let bitstream = bitstream_ref(bitstream)?;
+        check_iob(bitstream, iob)?;
+        if let Some(iob) = bitstream.bits.get_small_iob(iob as usize) {
+            *out = match setting {
+                XC2BIT_IOB_ZIA_MODE => to_u32(&ZIA_MODES, &iob.zia_mode),
+                XC2BIT_IOB_SCHMITT_TRIGGER => iob.schmitt_trigger as u32,
+                XC2BIT_IOB_OBUF_USES_FF => iob.obuf_uses_ff as u32,
+                XC2BIT_IOB_OBUF_MODE => to_u32(&OBUF_MODES, &iob.obuf_mode),
+                XC2BIT_IOB_TERMINATION_ENABLED => iob.termination_enabled as u32,
+                XC2BIT_IOB_SLEW_IS_FAST => iob.slew_is_fast as u32,
+                XC2BIT_IOB_IBUF_MODE | XC2BIT_IOB_USES_DATA_GATE =>
+                    return Err(unsupported("setting only exists on XC2C128 and larger devices")),
+                _ => return Err(invalid("unknown IOB setting")),
+            };
+        } else if let Some(iob) = bitstream.bits.get_large_iob(iob as usize) {
+            *out = match setting {
+                XC2BIT_IOB_ZIA_MODE => to_u32(&ZIA_MODES, &iob.zia_mode),
+                XC2BIT_IOB_SCHMITT_TRIGGER =>
+                    return Err(unsupported("setting only exists on XC2C32(A) and XC2C64(A) devices")),
+                XC2BIT_IOB_OBUF_USES_FF => iob.obuf_uses_ff as u32,
+                XC2BIT_IOB_OBUF_MODE => to_u32(&OBUF_MODES, &iob.obuf_mode),
+                XC2BIT_IOB_TERMINATION_ENABLED => iob.termination_enabled as u32,
+                XC2BIT_IOB_SLEW_IS_FAST => iob.slew_is_fast as u32,
+                XC2BIT_IOB_IBUF_MODE => to_u32(&IBUF_MODES, &iob.ibuf_mode),
+                XC2BIT_IOB_USES_DATA_GATE => iob.uses_data_gate as u32,
+                _ => return Err(invalid("unknown IOB setting")),
+            };
+        } else {
+            unreachable!();
+        }
+        Ok(())
+    })
+}
+
+/// Changes a setting of an IOB
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_iob_set(bitstream: *mut XC2Bitstream, iob: u32, setting: u32, value: u32) -> i32 {
+    guard(|| {
+        let bitstream = bitstream_mut(bitstream)?;
+        check_iob(bitstream, iob)?;
+        if let Some(iob) = bitstream.bits.get_mut_small_iob(iob as usize) {
+            match setting {
+                XC2BIT_IOB_ZIA_MODE => iob.zia_mode = from_u32(&ZIA_MODES, value)?,
+                XC2BIT_IOB_SCHMITT_TRIGGER => iob.schmitt_trigger = from_bool(value)?,
+                XC2BIT_IOB_OBUF_USES_FF => iob.obuf_uses_ff = from_bool(value)?,
+                XC2BIT_IOB_OBUF_MODE => iob.obuf_mode = from_u32(&OBUF_MODES, value)?,
+                XC2BIT_IOB_TERMINATION_ENABLED => iob.termination_enabled = from_bool(value)?,
+                XC2BIT_IOB_SLEW_IS_FAST => iob.slew_is_fast = from_bool(value)?,
+                XC2BIT_IOB_IBUF_MODE | XC2BIT_IOB_USES_DATA_GATE =>
+                    return Err(unsupported("setting only exists on XC2C128 and larger devices")),
+                _ => return Err(invalid("unknown IOB setting")),
+            }
+        } else if let Some(iob) = bitstream.bits.get_mut_large_iob(iob as usize) {
+            match setting {
+                XC2BIT_IOB_ZIA_MODE => iob.zia_mode = from_u32(&ZIA_MODES, value)?,
+                XC2BIT_IOB_SCHMITT_TRIGGER =>
+                    return Err(unsupported("setting only exists on XC2C32(A) and XC2C64(A) devices")),
+                XC2BIT_IOB_OBUF_USES_FF => iob.obuf_uses_ff = from_bool(value)?,
+                XC2BIT_IOB_OBUF_MODE => iob.obuf_mode = from_u32(&OBUF_MODES, value)?,
+                XC2BIT_IOB_TERMINATION_ENABLED => iob.termination_enabled = from_bool(value)?,
+                XC2BIT_IOB_SLEW_IS_FAST => iob.slew_is_fast = from_bool(value)?,
+                XC2BIT_IOB_IBUF_MODE => iob.ibuf_mode = from_u32(&IBUF_MODES, value)?,
+                XC2BIT_IOB_USES_DATA_GATE => iob.uses_data_gate = from_bool(value)?,
+                _ => return Err(invalid("unknown IOB setting")),
+            }
+        } else {
+            unreachable!();
+        }
+        Ok(())
+    })
+}
+
+/// Looks up a part, such as "XC2C32A-4-VQ44". The canonical name of the part is written as a NUL-terminated string, and
+/// the numbers of function blocks and IOBs of the device are returned.
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_part_info(part: *const c_char, buf: *mut c_char, buf_len: usize, num_fbs: *mut u32,
+    num_iobs: *mut u32) -> i32 {
+
+    guard(|| {
+        if part.is_null() || buf.is_null() {
+            return Err(invalid("NULL pointer passed"));
+        }
+        let num_fbs = output(num_fbs)?;
+        let num_iobs = output(num_iobs)?;
+        let part = CStr::from_ptr(part).to_str().map_err(|_| invalid("part is not valid UTF-8"))?;
+        let part = XC2DeviceSpeedPackage::from_str(part)
+            .ok_or_else(|| unsupported(&format!("part \"{}\" is invalid/unsupported", part)))?;
+        write_string(&format!("{}", part), buf, buf_len)?;
+        *num_fbs = part.dev.num_fbs() as u32;
+        *num_iobs = part.dev.num_iobs() as u32;
+        Ok(())
+    })
+}
+
+fn check_fb(bitstream: &XC2Bitstream, fb: u32) -> Result<(), CError> {
+    if fb as usize >= bitstream.bits.device_type().num_fbs() {
+        return Err(invalid("function block index out of range"));
+    }
+    Ok(())
+}
+
+/// Reads whether a ZIA row is connected to the true and complement inputs of an AND term
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_andterm_get(bitstream: *const XC2Bitstream, fb: u32, term: u32, row: u32,
+    out_true: *mut u32, out_comp: *mut u32) -> i32 {
+
+    guard(|| {
+        let bitstream = bitstream_ref(bitstream)?;
+        let out_true = output(out_true)?;
+        let out_comp = output(out_comp)?;
+        check_fb(bitstream, fb)?;
+        if term as usize >= ANDTERMS_PER_FB || row as usize >= INPUTS_PER_ANDTERM {
+            return Err(invalid("AND term or ZIA row index out of range"));
+        }
+        let and_term = bitstream.bits.get_fb()[fb as usize].get_andterm(term as usize);
+        *out_true = and_term.get(row as usize) as u32;
+        *out_comp = and_term.get_b(row as usize) as u32;
+        Ok(())
+    })
+}
+
+/// Changes whether a ZIA row is connected to the true and complement inputs of an AND term
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_andterm_set(bitstream: *mut XC2Bitstream, fb: u32, term: u32, row: u32,
+    value_true: u32, value_comp: u32) -> i32 {
+
+    guard(|| {
+        let bitstream = bitstream_mut(bitstream)?;
+        check_fb(bitstream, fb)?;
+        if term as usize >= ANDTERMS_PER_FB || row as usize >= INPUTS_PER_ANDTERM {
+            return Err(invalid("AND term or ZIA row index out of range"));
+        }
+        let value_true = from_bool(value_true)?;
+        let value_comp = from_bool(value_comp)?;
+        let and_term = bitstream.bits.get_fb_mut()[fb as usize].get_mut_andterm(term as usize);
+        and_term.set(row as usize, value_true);
+        and_term.set_b(row as usize, value_comp);
+        Ok(())
+    })
+}
+
+/// Reads whether an AND term is connected to the OR term of a macrocell
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_orterm_get(bitstream: *const XC2Bitstream, fb: u32, mc: u32, term: u32,
+    out: *mut u32) -> i32 {
+
+    guard(|| {
+        let bitstream = bitstream_ref(bitstream)?;
+        let out = output(out)?;
+        check_fb(bitstream, fb)?;
+        if mc as usize >= MCS_PER_FB || term as usize >= ANDTERMS_PER_FB {
+            return Err(invalid("macrocell or AND term index out of range"));
+        }
+        *out = bitstream.bits.get_fb()[fb as usize].or_terms[mc as usize].get(term as usize) as u32;
+        Ok(())
+    })
+}
+
+/// Changes whether an AND term is connected to the OR term of a macrocell
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_orterm_set(bitstream: *mut XC2Bitstream, fb: u32, mc: u32, term: u32, value: u32)
+    -> i32 {
+
+    guard(|| {
+        let bitstream = bitstream_mut(bitstream)?;
+        check_fb(bitstream, fb)?;
+        if mc as usize >= MCS_PER_FB || term as usize >= ANDTERMS_PER_FB {
+            return Err(invalid("macrocell or AND term index out of range"));
+        }
+        let value = from_bool(value)?;
+        bitstream.bits.get_fb_mut()[fb as usize].or_terms[mc as usize].set(term as usize, value);
+        Ok(())
+    })
+}
+
+/// Reads the signal selected by a ZIA row. The kind of signal is returned in `out_kind`, and `out_a` and `out_b` are
+/// set to its indices (FB and macrocell for macrocells, IOB and 0 for IOBs, and 0 and 0 otherwise).
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_zia_get(bitstream: *const XC2Bitstream, fb: u32, row: u32, out_kind: *mut u32,
+    out_a: *mut u32, out_b: *mut u32) -> i32 {
+
+    guard(|| {
+        let bitstream = bitstream_ref(bitstream)?;
+        let out_kind = output(out_kind)?;
+        let out_a = output(out_a)?;
+        let out_b = output(out_b)?;
+        check_fb(bitstream, fb)?;
+        if row as usize >= INPUTS_PER_ANDTERM {
+            return Err(invalid("ZIA row index out of range"));
+        }
+        let (kind, a, b) = match *bitstream.bits.get_fb()[fb as usize].get_zia(row as usize) {
+            XC2ZIAInput::Zero => (XC2BIT_ZIA_ZERO, 0, 0),
+            XC2ZIAInput::One => (XC2BIT_ZIA_ONE, 0, 0),
+            XC2ZIAInput::Macrocell{fb, mc} => (XC2BIT_ZIA_MACROCELL, fb as u32, mc as u32),
+            XC2ZIAInput::IBuf{ibuf} => (XC2BIT_ZIA_IBUF, ibuf as u32, 0),
+            XC2ZIAInput::DedicatedInput => (XC2BIT_ZIA_DEDICATED_INPUT, 0, 0),
+        };
+        *out_kind = kind;
+        *out_a = a;
+        *out_b = b;
+        Ok(())
+    })
+}
+
+/// Changes the signal selected by a ZIA row. The arguments are the same as the results of `xc2bit_zia_get`. Signals
+/// that the row cannot select are rejected.
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_zia_set(bitstream: *mut XC2Bitstream, fb: u32, row: u32, kind: u32, a: u32, b: u32)
+    -> i32 {
+
+    guard(|| {
+        let bitstream = bitstream_mut(bitstream)?;
+        check_fb(bitstream, fb)?;
+        if row as usize >= INPUTS_PER_ANDTERM {
+            return Err(invalid("ZIA row index out of range"));
+        }
+        let device = bitstream.bits.device_type();
+        let input = match kind {
+            XC2BIT_ZIA_ZERO => XC2ZIAInput::Zero,
+            XC2BIT_ZIA_ONE => XC2ZIAInput::One,
+            XC2BIT_ZIA_MACROCELL if a as usize >= device.num_fbs() || b as usize >= MCS_PER_FB =>
+                return Err(invalid("macrocell index out of range")),
+            XC2BIT_ZIA_MACROCELL => XC2ZIAInput::Macrocell{fb: a as u8, mc: b as u8},
+            XC2BIT_ZIA_IBUF if a as usize >= device.num_iobs() => return Err(invalid("IOB index out of range")),
+            XC2BIT_ZIA_IBUF => XC2ZIAInput::IBuf{ibuf: a as u16},
+            XC2BIT_ZIA_DEDICATED_INPUT => XC2ZIAInput::DedicatedInput,
+            _ => return Err(invalid("unknown ZIA input kind")),
+        };
+        if input != XC2ZIAInput::Zero && input != XC2ZIAInput::One && zia_encode_choice(device, row, input).is_none() {
+            return Err(invalid("ZIA row cannot select this input"));
+        }
+        *bitstream.bits.get_fb_mut()[fb as usize].get_mut_zia(row as usize) = input;
+        Ok(())
+    })
+}
+
+/// Writes the same human-readable description of the bitstream as `xc2 dump` into a newly allocated buffer, which
+/// must be freed with `xc2bit_buffer_free`
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_bitstream_dump(bitstream: *const XC2Bitstream, out_data: *mut *mut u8,
+    out_len: *mut usize) -> i32 {
+
+    guard(|| {
+        let mut data = Vec::new();
+        bitstream_ref(bitstream)?.dump_human_readable(&mut data).unwrap();
+        output_buffer(data, out_data, out_len)
+    })
+}
+
+/// Writes the bitstream as a .jed file into a newly allocated buffer, which must be freed with `xc2bit_buffer_free`
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_bitstream_to_jed(bitstream: *const XC2Bitstream, out_data: *mut *mut u8,
+    out_len: *mut usize) -> i32 {
+
+    guard(|| {
+        let mut data = Vec::new();
+        bitstream_ref(bitstream)?.to_jed(&mut data).unwrap();
+        output_buffer(data, out_data, out_len)
+    })
+}
+
+/// Writes the bitstream as a .crbit file into a newly allocated buffer, which must be freed with `xc2bit_buffer_free`
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_bitstream_to_crbit(bitstream: *const XC2Bitstream, out_data: *mut *mut u8,
+    out_len: *mut usize) -> i32 {
+
+    guard(|| {
+        let mut data = Vec::new();
+        bitstream_ref(bitstream)?.to_crbit().write_to_writer(&mut data).unwrap();
+        output_buffer(data, out_data, out_len)
+    })
+}
+
+/// Frees a buffer returned by this library. Passing NULL does nothing.
+#[no_mangle]
+pub unsafe extern "C" fn xc2bit_buffer_free(data: *mut u8, len: usize) {
+    if !data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(data, len)));
+    }
+}
+
+/// Returns a description of the most recent error on the calling thread. The string stays valid until the next call
+/// into this library on the same thread.
+#[no_mangle]
+pub extern "C" fn xc2bit_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|x| x.borrow().as_ptr())
+}
@@ -0,0 +1,83 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Builds and runs the C test program against the static library, and checks that the header is the one generated from
+//! src/lib.rs
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn capi_header_matches() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(dir.join("cbindgen.toml")).unwrap();
+    let mut generated = Vec::new();
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(dir.join("src/lib.rs"))
+        .generate().expect("failed to generate the header")
+        .write(&mut generated);
+    let generated = String::from_utf8(generated).unwrap();
+
+    let header = fs::read_to_string(dir.join("include/xc2bit.h")).unwrap();
+    if header != generated {
+        let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("xc2bit.h");
+        fs::write(&path, &generated).unwrap();
+        panic!("include/xc2bit.h is out of date; the generated header is in {}", path.display());
+    }
+}
+
+#[test]
+fn capi_c_test() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // The static library built for this test is next to it, possibly along with older builds
+    let deps_dir = std::env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let lib = fs::read_dir(&deps_dir).unwrap()
+        .map(|x| x.unwrap().path())
+        .filter(|x| {
+            let name = x.file_name().unwrap().to_str().unwrap();
+            name.starts_with("libxc2bit_capi") && name.ends_with(".a")
+        })
+        .max_by_key(|x| fs::metadata(x).unwrap().modified().unwrap())
+        .expect("static library not found");
+    let exe = Path::new(env!("CARGO_TARGET_TMPDIR")).join("xc2bit_capi_test");
+
+    let cc = std::env::var("CC").unwrap_or_else(|_| String::from("cc"));
+    let status = Command::new(&cc)
+        .arg("-Wall").arg("-Werror").arg("-std=c99")
+        .arg("-I").arg(dir.join("include"))
+        .arg(dir.join("tests/capi/test.c"))
+        .arg(&lib)
+        .args(["-lpthread", "-ldl", "-lm"])
+        .arg("-o").arg(&exe)
+        .status().expect("failed to run the C compiler");
+    assert!(status.success(), "failed to compile the C test program");
+
+    let status = Command::new(&exe)
+        .arg(dir.join("../../tests/xc2bit/reftests/32-fb.jed"))
+        .status().expect("failed to run the C test program");
+    assert!(status.success(), "C test program failed");
+}
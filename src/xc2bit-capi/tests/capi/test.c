@@ -0,0 +1,133 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+/* Test program for the C interface, run by tests/capi.rs */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "xc2bit.h"
+
+#define CHECK(x) do { \
+        if (!(x)) { \
+            fprintf(stderr, "%s:%d: check failed: %s (last error: %s)\n", __FILE__, __LINE__, #x, \
+                xc2bit_last_error_message()); \
+            exit(1); \
+        } \
+    } while (0)
+
+static uint8_t *read_file(const char *path, size_t *len) {
+    FILE *f = fopen(path, "rb");
+    CHECK(f != NULL);
+    CHECK(fseek(f, 0, SEEK_END) == 0);
+    long size = ftell(f);
+    CHECK(size >= 0);
+    CHECK(fseek(f, 0, SEEK_SET) == 0);
+    uint8_t *data = malloc(size);
+    CHECK(data != NULL);
+    CHECK(fread(data, 1, size, f) == (size_t)size);
+    fclose(f);
+    *len = size;
+    return data;
+}
+
+int main(int argc, char **argv) {
+    CHECK(argc == 2);
+
+    size_t jed_len;
+    uint8_t *jed = read_file(argv[1], &jed_len);
+    xc2bit_bitstream *bitstream = NULL;
+    CHECK(xc2bit_bitstream_from_jed(jed, jed_len, &bitstream) == XC2BIT_OK);
+
+    char part[32];
+    CHECK(xc2bit_bitstream_get_part(bitstream, part, sizeof(part)) == XC2BIT_OK);
+    CHECK(strcmp(part, "XC2C32A-4-VQ44") == 0);
+    CHECK(xc2bit_bitstream_get_part(bitstream, part, 4) == XC2BIT_ERROR_BUFFER_TOO_SMALL);
+
+    uint32_t num_fbs, num_iobs;
+    CHECK(xc2bit_bitstream_num_fbs(bitstream, &num_fbs) == XC2BIT_OK);
+    CHECK(xc2bit_bitstream_num_iobs(bitstream, &num_iobs) == XC2BIT_OK);
+    CHECK(num_fbs == 2 && num_iobs == 32);
+
+    /* Writing a bitstream that was read gives back the same file */
+    uint8_t *out;
+    size_t out_len;
+    CHECK(xc2bit_bitstream_to_jed(bitstream, &out, &out_len) == XC2BIT_OK);
+    CHECK(out_len == jed_len && memcmp(out, jed, jed_len) == 0);
+    xc2bit_buffer_free(out, out_len);
+
+    /* Changed settings survive a trip through a .crbit file */
+    uint32_t value;
+    CHECK(xc2bit_mc_set(bitstream, 1, 3, XC2BIT_MC_REG_MODE, 2) == XC2BIT_OK);
+    CHECK(xc2bit_mc_set(bitstream, 1, 3, XC2BIT_MC_CLK_SRC, 4) == XC2BIT_OK);
+    CHECK(xc2bit_iob_set(bitstream, 5, XC2BIT_IOB_OBUF_MODE, 2) == XC2BIT_OK);
+    CHECK(xc2bit_iob_set(bitstream, 5, XC2BIT_IOB_SLEW_IS_FAST, 0) == XC2BIT_OK);
+    CHECK(xc2bit_bitstream_to_crbit(bitstream, &out, &out_len) == XC2BIT_OK);
+    xc2bit_bitstream *bitstream2 = NULL;
+    CHECK(xc2bit_bitstream_from_crbit(out, out_len, &bitstream2) == XC2BIT_OK);
+    xc2bit_buffer_free(out, out_len);
+    CHECK(xc2bit_mc_get(bitstream2, 1, 3, XC2BIT_MC_REG_MODE, &value) == XC2BIT_OK && value == 2);
+    CHECK(xc2bit_mc_get(bitstream2, 1, 3, XC2BIT_MC_CLK_SRC, &value) == XC2BIT_OK && value == 4);
+    CHECK(xc2bit_iob_get(bitstream2, 5, XC2BIT_IOB_OBUF_MODE, &value) == XC2BIT_OK && value == 2);
+    CHECK(xc2bit_iob_get(bitstream2, 5, XC2BIT_IOB_SLEW_IS_FAST, &value) == XC2BIT_OK && value == 0);
+    xc2bit_bitstream_free(bitstream2);
+
+    /* Product terms and the ZIA */
+    uint32_t value_b, kind, a, b;
+    CHECK(xc2bit_andterm_set(bitstream, 0, 20, 7, 0, 1) == XC2BIT_OK);
+    CHECK(xc2bit_andterm_get(bitstream, 0, 20, 7, &value, &value_b) == XC2BIT_OK && value == 0 && value_b == 1);
+    CHECK(xc2bit_orterm_set(bitstream, 0, 15, 20, 1) == XC2BIT_OK);
+    CHECK(xc2bit_orterm_get(bitstream, 0, 15, 20, &value) == XC2BIT_OK && value == 1);
+    CHECK(xc2bit_zia_set(bitstream, 1, 0, XC2BIT_ZIA_ONE, 0, 0) == XC2BIT_OK);
+    CHECK(xc2bit_zia_get(bitstream, 1, 0, &kind, &a, &b) == XC2BIT_OK && kind == XC2BIT_ZIA_ONE);
+    CHECK(xc2bit_zia_set(bitstream, 1, 0, XC2BIT_ZIA_IBUF, 0, 0) == XC2BIT_OK);
+    CHECK(xc2bit_zia_get(bitstream, 1, 0, &kind, &a, &b) == XC2BIT_OK && kind == XC2BIT_ZIA_IBUF && a == 0);
+    CHECK(xc2bit_zia_set(bitstream, 1, 0, XC2BIT_ZIA_IBUF, 1, 0) == XC2BIT_ERROR_INVALID_ARGUMENT);
+    CHECK(xc2bit_bitstream_dump(bitstream, &out, &out_len) == XC2BIT_OK && out_len > 0);
+    xc2bit_buffer_free(out, out_len);
+
+    CHECK(xc2bit_part_info("xc2c64a-5-vq100", part, sizeof(part), &num_fbs, &num_iobs) == XC2BIT_OK);
+    CHECK(strcmp(part, "XC2C64A-5-VQ100") == 0 && num_fbs == 4 && num_iobs == 64);
+
+    /* Errors */
+    CHECK(xc2bit_mc_set(bitstream, 2, 0, XC2BIT_MC_REG_MODE, 0) == XC2BIT_ERROR_INVALID_ARGUMENT);
+    CHECK(xc2bit_mc_set(bitstream, 0, 0, XC2BIT_MC_REG_MODE, 4) == XC2BIT_ERROR_INVALID_ARGUMENT);
+    CHECK(xc2bit_iob_get(bitstream, 0, XC2BIT_IOB_IBUF_MODE, &value) == XC2BIT_ERROR_UNSUPPORTED);
+    CHECK(xc2bit_iob_get(bitstream, 32, XC2BIT_IOB_ZIA_MODE, &value) == XC2BIT_ERROR_INVALID_ARGUMENT);
+    CHECK(strcmp(xc2bit_last_error_message(), "IOB index out of range") == 0);
+    CHECK(xc2bit_bitstream_from_jed((const uint8_t *)"garbage", 7, &bitstream2) == XC2BIT_ERROR_PARSE);
+    CHECK(xc2bit_bitstream_new_blank("XC2C33", &bitstream2) == XC2BIT_ERROR_UNSUPPORTED);
+    CHECK(xc2bit_bitstream_num_fbs(NULL, &num_fbs) == XC2BIT_ERROR_INVALID_ARGUMENT);
+
+    CHECK(xc2bit_bitstream_new_blank("XC2C256-7-TQ144", &bitstream2) == XC2BIT_OK);
+    CHECK(xc2bit_iob_set(bitstream2, 10, XC2BIT_IOB_IBUF_MODE, 2) == XC2BIT_OK);
+    CHECK(xc2bit_iob_get(bitstream2, 10, XC2BIT_IOB_IBUF_MODE, &value) == XC2BIT_OK && value == 2);
+    xc2bit_bitstream_free(bitstream2);
+
+    xc2bit_bitstream_free(bitstream);
+    free(jed);
+    return 0;
+}
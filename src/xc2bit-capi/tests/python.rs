@@ -23,28 +23,33 @@ OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 */
 
-//! Testing tool that prints out a "blank" but valid bitstream for the given part.
-
-use xc2bit::*;
-
-fn main() {
-    let args = ::std::env::args().collect::<Vec<_>>();
-
-    if args.len() != 2 {
-        println!("Usage: {} <device>-<speed>-<package>", args[0]);
-
-        println!("{:?}", ::std::mem::size_of::<XC2Bitstream>());
-        println!("{:?}", ::std::mem::size_of::<XC2BitstreamFB>());
-        println!("{:?}", ::std::mem::size_of::<XC2Macrocell>());
-        println!("{:?}", ::std::mem::size_of::<XC2ZIAInput>());
-        println!("{:?}", ::std::mem::size_of::<XC2MCSmallIOB>());
-        println!("{:?}", ::std::mem::size_of::<XC2MCLargeIOB>());
-
-        ::std::process::exit(1);
-    }
-
-    let device_combination = XC2DeviceSpeedPackage::from_str(&args[1]).expect("invalid device name");
-    let bitstream = XC2Bitstream::blank_bitstream(device_combination);
-
-    bitstream.to_jed(&mut ::std::io::stdout()).expect("failed to write jed");
+//! Runs the tests of the Python bindings in python/ against the shared library
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn python_bindings() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // The shared library built for this test is next to it
+    let deps_dir = std::env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let lib = fs::read_dir(&deps_dir).unwrap()
+        .map(|x| x.unwrap().path())
+        .filter(|x| {
+            let name = x.file_name().unwrap().to_str().unwrap();
+            name.starts_with("libxc2bit_capi") && (name.ends_with(".so") || name.ends_with(".dylib"))
+        })
+        .max_by_key(|x| fs::metadata(x).unwrap().modified().unwrap())
+        .expect("shared library not found");
+
+    let python = std::env::var("PYTHON").unwrap_or_else(|_| String::from("python3"));
+    let status = Command::new(&python)
+        .arg(dir.join("tests/python/test_xc2bit.py"))
+        .arg(dir.join("../../tests/xc2bit/reftests/32-fb.jed"))
+        .env("PYTHONPATH", dir.join("python"))
+        .env("PYTHONDONTWRITEBYTECODE", "1")
+        .env("XC2BIT_LIBRARY", &lib)
+        .status().expect("failed to run Python");
+    assert!(status.success(), "Python tests failed");
 }
@@ -0,0 +1,118 @@
+# Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+# All rights reserved.
+#
+# Redistribution and use in source and binary forms, with or without
+# modification, are permitted provided that the following conditions are met:
+#
+# 1. Redistributions of source code must retain the above copyright notice,
+#    this list of conditions and the following disclaimer.
+# 2. Redistributions in binary form must reproduce the above copyright notice,
+#    this list of conditions and the following disclaimer in the documentation
+#    and/or other materials provided with the distribution.
+#
+# THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+# ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+# WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+# DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+# FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+# DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+# SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+# CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+# OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+# OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+"""Tests for the Python bindings, run by tests/python.rs. The argument is the path of a .jed file for an XC2C32A."""
+
+import os
+import sys
+import tempfile
+import unittest
+
+import xc2bit
+
+JED_PATH = None
+
+
+class BitstreamTests(unittest.TestCase):
+    def setUp(self):
+        with open(JED_PATH, "rb") as f:
+            self.jed = f.read()
+        self.bitstream = xc2bit.Bitstream.from_jed(self.jed)
+
+    def test_structure(self):
+        self.assertEqual(self.bitstream.part, "XC2C32A-4-VQ44")
+        self.assertEqual(len(self.bitstream.fbs), 2)
+        self.assertEqual(len(self.bitstream.iobs), 32)
+        fb = self.bitstream.fbs[1]
+        self.assertEqual(len(fb.mcs), xc2bit.MCS_PER_FB)
+        self.assertEqual(len(fb.and_terms), xc2bit.ANDTERMS_PER_FB)
+        self.assertEqual(len(fb.or_terms), xc2bit.MCS_PER_FB)
+        self.assertEqual(len(fb.zia), xc2bit.INPUTS_PER_ANDTERM)
+        self.assertIn("FB2", self.bitstream.dump())
+
+    def test_jed_round_trip(self):
+        self.assertEqual(self.bitstream.to_jed(), self.jed)
+
+    def test_settings(self):
+        mc = self.bitstream.fbs[1].mcs[3]
+        mc.reg_mode = "TFF"
+        mc.clk_src = "CTC"
+        mc.init_state = False
+        iob = self.bitstream.iobs[5]
+        iob.obuf_mode = "OpenDrain"
+        with self.assertRaises(ValueError):
+            mc.reg_mode = "JKFF"
+        with self.assertRaises(xc2bit.XC2BitError) as cm:
+            iob.ibuf_mode
+        self.assertEqual(cm.exception.code, xc2bit.XC2BIT_ERROR_UNSUPPORTED)
+
+        with tempfile.TemporaryDirectory() as tmp:
+            path = os.path.join(tmp, "test.crbit")
+            self.bitstream.save(path)
+            bitstream = xc2bit.Bitstream.load(path)
+        mc = bitstream.fbs[1].mcs[3]
+        self.assertEqual(mc.reg_mode, "TFF")
+        self.assertEqual(mc.clk_src, "CTC")
+        self.assertIs(mc.init_state, False)
+        self.assertEqual(bitstream.iobs[5].obuf_mode, "OpenDrain")
+
+    def test_terms_and_zia(self):
+        fb = self.bitstream.fbs[0]
+        fb.and_terms[20].set(7, True)
+        fb.and_terms[20].set_b(7, False)
+        fb.or_terms[15].set(20, True)
+        fb.zia[0] = xc2bit.ZIAInput.IBuf(0)
+        fb.zia[1] = xc2bit.ZIAInput.One
+
+        bitstream = xc2bit.Bitstream.from_jed(self.bitstream.to_jed())
+        fb = bitstream.fbs[0]
+        self.assertTrue(fb.and_terms[20].get(7))
+        self.assertFalse(fb.and_terms[20].get_b(7))
+        self.assertTrue(fb.or_terms[15].get(20))
+        self.assertEqual(fb.zia[0], xc2bit.ZIAInput.IBuf(0))
+        self.assertEqual(fb.zia[0].ibuf, 0)
+        self.assertEqual(fb.zia[1], xc2bit.ZIAInput.One)
+
+        with self.assertRaises(xc2bit.XC2BitError):
+            fb.zia[0] = xc2bit.ZIAInput.IBuf(1)
+        with self.assertRaises(IndexError):
+            fb.zia[40]
+
+    def test_blank_and_parts(self):
+        bitstream = xc2bit.Bitstream.blank("XC2C256-7-TQ144")
+        bitstream.iobs[10].ibuf_mode = "UsesVref"
+        self.assertEqual(bitstream.iobs[10].ibuf_mode, "UsesVref")
+
+        info = xc2bit.part_info("xc2c64a-5-vq100")
+        self.assertEqual(info.name, "XC2C64A-5-VQ100")
+        self.assertEqual((info.num_fbs, info.num_iobs), (4, 64))
+        with self.assertRaises(xc2bit.XC2BitError):
+            xc2bit.part_info("XC2C33")
+        with self.assertRaises(xc2bit.XC2BitError) as cm:
+            xc2bit.Bitstream.from_jed(b"garbage")
+        self.assertEqual(cm.exception.code, xc2bit.XC2BIT_ERROR_PARSE)
+
+
+if __name__ == "__main__":
+    JED_PATH = sys.argv.pop(1)
+    unittest.main()
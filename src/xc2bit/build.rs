@@ -29,4 +29,97 @@ fn main() {
             }
         }
     }
+
+    // Vendor BSDL files that the built-in JTAG model is checked against
+    let destination = std::path::Path::new(&out_dir).join("bsdltests.rs");
+    let mut f = std::fs::File::create(&destination).unwrap();
+
+    let bsdl_dir = std::path::Path::new(&root_dir).join("../../tests/xc2bit/bsdl");
+    let files = std::fs::read_dir(bsdl_dir);
+
+    if let Ok(files) = files {
+        for file in files {
+            let path = file.expect("failed to get path").path();
+            if path.extension().map(|x| x == "bsd") == Some(true) {
+                let path = path.canonicalize().unwrap();
+
+                let id_string = path.file_name().unwrap().to_str().unwrap().chars().map(|x| match x {
+                    'A'..='Z' | 'a'..='z' | '0'..='9' => x,
+                    _ => '_',
+                }).collect::<String>();
+
+                write!(f, r#"
+                    #[test]
+                    fn bsdltest_{}() {{
+                        run_one_bsdltest("{}");
+                    }}
+                    "#, id_string, path.to_str().unwrap()).unwrap();
+            }
+        }
+    }
+
+    write_zia_inverse(&root_dir, &out_dir);
+}
+
+// Parses one of the ZIA_MAP_* tables in src/zia.rs into rows of (kind, a, b), where kind 0 is `IBuf{ibuf: a}`, 1 is
+// `Macrocell{fb: a, mc: b}` and 2 is `DedicatedInput`
+fn parse_zia_map(src: &str, name: &str) -> Vec<Vec<(u32, u32, u32)>> {
+    let start = src.find(&format!("pub static {}:", name)).expect("ZIA map not found");
+    let body = &src[start..];
+    let body = &body[body.find("= [").unwrap() + 3..body.find("\n];").unwrap()];
+    let body = body.lines().filter(|l| !l.trim_start().starts_with("//")).collect::<Vec<_>>().join("\n");
+
+    let mut rows = Vec::new();
+    for row in body.split('[').skip(1) {
+        let row = &row[..row.find(']').unwrap()];
+        let mut entries = Vec::new();
+        for entry in row.split("XC2ZIAInput::").skip(1) {
+            let nums = entry.split(|c: char| !c.is_ascii_digit()).filter(|x| x.len() > 0)
+                .map(|x| x.parse::<u32>().unwrap()).collect::<Vec<_>>();
+            if entry.starts_with("IBuf") {
+                entries.push((0, nums[0], 0));
+            } else if entry.starts_with("Macrocell") {
+                entries.push((1, nums[0], nums[1]));
+            } else if entry.starts_with("DedicatedInput") {
+                entries.push((2, 0, 0));
+            } else {
+                panic!("bad ZIA map entry {}", entry);
+            }
+        }
+        rows.push(entries);
+    }
+    rows
+}
+
+// Writes the rows of the ZIA that can select each input, for the parts where the ZIA map is known
+fn write_zia_inverse(root_dir: &str, out_dir: &str) {
+    let src = std::fs::read_to_string(std::path::Path::new(root_dir).join("src/zia.rs")).unwrap();
+    let destination = std::path::Path::new(out_dir).join("zia_inverse.rs");
+    let mut f = std::fs::File::create(&destination).unwrap();
+
+    for &size in &[32, 64] {
+        let rows = parse_zia_map(&src, &format!("ZIA_MAP_{}", size));
+        assert_eq!(rows.len(), 40);
+
+        let mut inverse = std::collections::BTreeMap::new();
+        for (row_i, row) in rows.iter().enumerate() {
+            for &input in row {
+                let input_rows = inverse.entry(input).or_insert_with(Vec::new);
+                if input_rows.last() != Some(&row_i) {
+                    input_rows.push(row_i);
+                }
+            }
+        }
+
+        write!(f, "static ZIA_INVERSE_{}: [(XC2ZIAInput, &[u32]); {}] = [\n", size, inverse.len()).unwrap();
+        for (&(kind, a, b), input_rows) in &inverse {
+            let input = match kind {
+                0 => format!("XC2ZIAInput::IBuf{{ibuf: {}}}", a),
+                1 => format!("XC2ZIAInput::Macrocell{{fb: {}, mc: {}}}", a, b),
+                _ => "XC2ZIAInput::DedicatedInput".to_owned(),
+            };
+            write!(f, "    ({}, &{:?}),\n", input, input_rows).unwrap();
+        }
+        write!(f, "];\n").unwrap();
+    }
 }
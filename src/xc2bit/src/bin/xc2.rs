@@ -0,0 +1,647 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Command-line tool for working with Coolrunner-II bitstreams
+//!
+//! Bitstreams can be read in either .jed or crbit format, which is detected automatically. A file name of "-" (the
+//! default) reads from standard input or writes to standard output. `coverage` and `solve` work on the fuses of .jed
+//! files directly, so they only accept .jed files.
+
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use jedec::*;
+use serde_json::json;
+use xc2bit::*;
+use yosys_netlist_json::Netlist;
+
+/// File formats that a bitstream can be stored in
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum BitstreamFormat {
+    Jed,
+    Crbit,
+}
+
+impl BitstreamFormat {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "crbit" => BitstreamFormat::Crbit,
+            _ => BitstreamFormat::Jed,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            &BitstreamFormat::Jed => "jed",
+            &BitstreamFormat::Crbit => "crbit",
+        }
+    }
+}
+
+fn display_name(file_name: &str) -> &str {
+    if file_name == "-" {"<stdin>"} else {file_name}
+}
+
+/// Reads the entire input file
+fn read_input(file_name: &str) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    let result = if file_name == "-" {
+        io::stdin().read_to_end(&mut data)
+    } else {
+        File::open(file_name).and_then(|mut f| f.read_to_end(&mut data))
+    };
+    result.map_err(|err| format!("{}: {}", display_name(file_name), err))?;
+
+    Ok(data)
+}
+
+/// Reads a bitstream in either .jed or crbit format. A .jed file always contains an STX character (which can never
+/// appear in a crbit file), so this is used to tell the two formats apart.
+fn read_bitstream(file_name: &str) -> Result<(XC2Bitstream, BitstreamFormat), String> {
+    let data = read_input(file_name)?;
+    let name = display_name(file_name);
+
+    if data.contains(&0x02) {
+        let jed = JEDECFile::from_bytes(&data).map_err(|err| format!("{}: invalid .jed file: {}", name, err))?;
+        let bitstream = XC2Bitstream::from_jed(&jed).map_err(|err| format!("{}: {}", name, err))?;
+        Ok((bitstream, BitstreamFormat::Jed))
+    } else {
+        let fuse_array = FuseArray::from_file_contents(&data)
+            .map_err(|err| format!("{}: not a .jed file and not a valid crbit file: {}", name, err))?;
+        let bitstream = XC2Bitstream::from_crbit(&fuse_array).map_err(|err| format!("{}: {}", name, err))?;
+        Ok((bitstream, BitstreamFormat::Crbit))
+    }
+}
+
+fn parse_part(part_name: &str) -> Result<XC2DeviceSpeedPackage, String> {
+    XC2DeviceSpeedPackage::from_str(part_name).ok_or_else(||
+        format!("invalid part name {:?} (expected <device>-<speed>-<package>, e.g. xc2c32a-6-vq44)", part_name))
+}
+
+/// Runs `f` on the output file and makes sure that everything was written
+fn write_output<F>(file_name: &str, f: F) -> Result<(), String>
+    where F: FnOnce(&mut dyn Write) -> Result<(), String> {
+
+    if file_name == "-" {
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        f(&mut out)?;
+        out.flush().map_err(write_error)
+    } else {
+        let file = File::create(file_name).map_err(|err| format!("{}: {}", file_name, err))?;
+        let mut out = io::BufWriter::new(file);
+        f(&mut out)?;
+        out.flush().map_err(|err| format!("{}: {}", file_name, err))
+    }
+}
+
+fn write_error<E: Into<io::Error>>(err: E) -> String {
+    let err = err.into();
+    if err.kind() == io::ErrorKind::BrokenPipe {
+        // Whatever was reading the output (e.g. `head`) does not want any more of it, which is not an error
+        process::exit(0);
+    }
+    format!("failed to write output: {}", err)
+}
+
+fn write_json(out: &mut dyn Write, value: &serde_json::Value) -> Result<(), String> {
+    serde_json::to_writer_pretty(&mut *out, value).map_err(write_error)?;
+    write!(out, "\n").map_err(write_error)
+}
+
+fn write_bitstream(out: &mut dyn Write, bitstream: &XC2Bitstream, format: BitstreamFormat) -> Result<(), String> {
+    match format {
+        BitstreamFormat::Jed => bitstream.to_jed(out),
+        BitstreamFormat::Crbit => bitstream.to_crbit().write_to_writer(out),
+    }.map_err(write_error)
+}
+
+fn cmd_convert(matches: &ArgMatches) -> Result<(), String> {
+    let (bitstream, in_format) = read_bitstream(matches.value_of("INPUT").unwrap())?;
+
+    // Without an explicit format, convert to the other one
+    let out_format = match matches.value_of("format") {
+        Some(x) => BitstreamFormat::from_name(x),
+        None => if in_format == BitstreamFormat::Jed {BitstreamFormat::Crbit} else {BitstreamFormat::Jed},
+    };
+
+    write_output(matches.value_of("output").unwrap(), |out| write_bitstream(out, &bitstream, out_format))
+}
+
+fn cmd_dump(matches: &ArgMatches) -> Result<(), String> {
+    let (bitstream, _) = read_bitstream(matches.value_of("INPUT").unwrap())?;
+
+    write_output(matches.value_of("output").unwrap(), |out| {
+        if matches.value_of("format") == Some("json") {
+            write_json(out, &serde_json::to_value(&bitstream).map_err(write_error)?)
+        } else {
+            bitstream.dump_human_readable(out).map_err(write_error)
+        }
+    })
+}
+
+fn cmd_blank(matches: &ArgMatches) -> Result<(), String> {
+    let part = parse_part(matches.value_of("PART").unwrap())?;
+    let bitstream = XC2Bitstream::blank_bitstream(part);
+    let format = BitstreamFormat::from_name(matches.value_of("format").unwrap());
+
+    write_output(matches.value_of("output").unwrap(), |out| write_bitstream(out, &bitstream, format))
+}
+
+fn cmd_netlist(matches: &ArgMatches) -> Result<(), String> {
+    let (bitstream, _) = read_bitstream(matches.value_of("INPUT").unwrap())?;
+
+    write_output(matches.value_of("output").unwrap(), |out| {
+        bitstream.to_netlist().to_writer(&mut *out).map_err(write_error)?;
+        write!(out, "\n").map_err(write_error)
+    })
+}
+
+fn cmd_structure(matches: &ArgMatches) -> Result<(), String> {
+    let part = parse_part(matches.value_of("PART").unwrap())?;
+    let graph = XC2RoutingGraph::new(part.dev);
+    if zia_inverse_table(part.dev).is_none() {
+        eprintln!("xc2: warning: the ZIA map of {} is not known, so the ZIA muxes have no inputs", part.dev);
+    }
+
+    write_output(matches.value_of("output").unwrap(), |out| {
+        match matches.value_of("format").unwrap() {
+            "json" => write_json(out, &serde_json::to_value(&graph).map_err(write_error)?),
+            "dot" => graph.write_dot(out).map_err(write_error),
+            "graphml" => graph.write_graphml(out).map_err(write_error),
+            _ => {
+                for node in &graph.nodes {
+                    write!(out, "Node: {} {:?}\n", node.name, node.kind).map_err(write_error)?;
+                }
+                for wire in &graph.wires {
+                    write!(out, "Wire: {}\n", wire.name).map_err(write_error)?;
+                }
+                for pin in &graph.pins {
+                    let dir = if pin.dir == XC2RRPortDir::Input {"input"} else {"output"};
+                    write!(out, "Pin: {} {} {} {} {}", graph.nodes[pin.node].name, pin.port, pin.index, dir,
+                        graph.wires[pin.wire].name).map_err(write_error)?;
+                    if let Some(ref setting) = pin.setting {
+                        write!(out, " {}", setting).map_err(write_error)?;
+                    }
+                    write!(out, "\n").map_err(write_error)?;
+                }
+                Ok(())
+            },
+        }
+    })
+}
+
+fn cmd_info(matches: &ArgMatches) -> Result<(), String> {
+    let (bitstream, format) = read_bitstream(matches.value_of("INPUT").unwrap())?;
+    let device = bitstream.bits.device_type();
+    let (crbit_w, crbit_h) = bitstream.to_crbit().dim();
+    let utilization = bitstream.bits.utilization();
+
+    write_output(matches.value_of("output").unwrap(), |out| {
+        if matches.value_of("format") == Some("json") {
+            write_json(out, &json!({
+                "format": format.name(),
+                "device": device.to_string(),
+                "speed_grade": bitstream.speed_grade.to_string(),
+                "package": bitstream.package.to_string(),
+                "function_blocks": device.num_fbs(),
+                "iobs": device.num_iobs(),
+                "crbit_width": crbit_w,
+                "crbit_height": crbit_h,
+                "utilization": serde_json::to_value(&utilization).map_err(write_error)?,
+            }))
+        } else {
+            write!(out, "format: {}\n", format.name()).map_err(write_error)?;
+            write!(out, "part: {}-{}-{}\n", device, bitstream.speed_grade, bitstream.package).map_err(write_error)?;
+            write!(out, "function blocks: {}\n", device.num_fbs()).map_err(write_error)?;
+            write!(out, "I/O blocks: {}\n", device.num_iobs()).map_err(write_error)?;
+            write!(out, "crbit size: {}x{}\n\n", crbit_w, crbit_h).map_err(write_error)?;
+            write!(out, "{}", utilization).map_err(write_error)
+        }
+    })
+}
+
+fn cmd_util(matches: &ArgMatches) -> Result<(), String> {
+    let (bitstream, _) = read_bitstream(matches.value_of("INPUT").unwrap())?;
+    let utilization = bitstream.bits.utilization();
+
+    write_output(matches.value_of("output").unwrap(), |out| {
+        if matches.value_of("format") == Some("json") {
+            write_json(out, &serde_json::to_value(&utilization).map_err(write_error)?)
+        } else {
+            write!(out, "{}", utilization).map_err(write_error)
+        }
+    })
+}
+
+fn cmd_eqn(matches: &ArgMatches) -> Result<(), String> {
+    let (bitstream, _) = read_bitstream(matches.value_of("INPUT").unwrap())?;
+    let syntax = if matches.is_present("verilog") {XC2EqnSyntax::Verilog} else {XC2EqnSyntax::Abel};
+
+    // Pin names are given as "name FBn_m" lines
+    let names = match matches.value_of("pins") {
+        Some(pins_file_name) => {
+            let data = read_input(pins_file_name)?;
+            let data = String::from_utf8(data)
+                .map_err(|_| format!("{}: not valid UTF-8", display_name(pins_file_name)))?;
+            parse_pin_names(bitstream.bits.device_type(), &data)
+                .map_err(|err| format!("{}: {}", display_name(pins_file_name), err))?
+        },
+        None => HashMap::new(),
+    };
+
+    write_output(matches.value_of("output").unwrap(), |out| {
+        bitstream.bits.write_equations(syntax, &names, out).map_err(write_error)
+    })
+}
+
+fn cmd_svg(matches: &ArgMatches) -> Result<(), String> {
+    let (bitstream, _) = read_bitstream(matches.value_of("INPUT").unwrap())?;
+    let device = bitstream.bits.device_type();
+
+    let other_fuse_array = match matches.value_of("COMPARE") {
+        Some(compare_file_name) => {
+            let (other, _) = read_bitstream(compare_file_name)?;
+            if other.bits.device_type() != device {
+                return Err(format!("{} is for a different device", compare_file_name));
+            }
+            Some(other.to_crbit())
+        },
+        None => None,
+    };
+
+    write_output(matches.value_of("output").unwrap(), |out| {
+        bitstream.to_crbit().write_svg(device, other_fuse_array.as_ref(), out).map_err(write_error)
+    })
+}
+
+fn cmd_diff(matches: &ArgMatches) -> Result<(), String> {
+    let (old, _) = read_bitstream(matches.value_of("OLD").unwrap())?;
+    let (new, _) = read_bitstream(matches.value_of("NEW").unwrap())?;
+
+    write_output(matches.value_of("output").unwrap(), |out| {
+        if old.speed_grade != new.speed_grade {
+            write!(out, "speed grade {}→{}\n", old.speed_grade, new.speed_grade).map_err(write_error)?;
+        }
+        if old.package != new.package {
+            write!(out, "package {}→{}\n", old.package, new.package).map_err(write_error)?;
+        }
+        for diff in old.bits.diff(&new.bits) {
+            write!(out, "{}\n", diff).map_err(write_error)?;
+        }
+        Ok(())
+    })
+}
+
+fn cmd_equiv(matches: &ArgMatches) -> Result<(), String> {
+    let (old, _) = read_bitstream(matches.value_of("OLD").unwrap())?;
+    let (new, _) = read_bitstream(matches.value_of("NEW").unwrap())?;
+    let mismatches = old.bits.check_equivalence(&new.bits);
+
+    write_output(matches.value_of("output").unwrap(), |out| {
+        for mismatch in &mismatches {
+            write!(out, "{}\n", mismatch).map_err(write_error)?;
+        }
+        if mismatches.len() == 0 {
+            write!(out, "designs are equivalent\n").map_err(write_error)?;
+        }
+        Ok(())
+    })?;
+
+    if mismatches.len() > 0 {
+        return Err(format!("designs are not equivalent ({} mismatches)", mismatches.len()));
+    }
+    Ok(())
+}
+
+fn cmd_from_netlist(matches: &ArgMatches) -> Result<(), String> {
+    let file_name = matches.value_of("INPUT").unwrap();
+    let netlist = Netlist::from_slice(&read_input(file_name)?)
+        .map_err(|err| format!("{}: invalid netlist: {}", display_name(file_name), err))?;
+    let bitstream = XC2Bitstream::from_netlist(&netlist)
+        .map_err(|err| format!("{}: {}", display_name(file_name), err))?;
+    let format = BitstreamFormat::from_name(matches.value_of("format").unwrap());
+
+    write_output(matches.value_of("output").unwrap(), |out| write_bitstream(out, &bitstream, format))
+}
+
+/// Finds the .jed files given on the command line, looking through directories recursively
+fn collect_jeds(path: &Path, jeds: &mut Vec<PathBuf>) -> Result<(), String> {
+    if path.is_dir() {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(path).map_err(|err| format!("{}: {}", path.display(), err))? {
+            entries.push(entry.map_err(|err| format!("{}: {}", path.display(), err))?.path());
+        }
+        entries.sort();
+        for entry in entries {
+            if entry.is_dir() || entry.extension() == Some("jed".as_ref()) {
+                collect_jeds(&entry, jeds)?;
+            }
+        }
+    } else {
+        jeds.push(path.to_owned());
+    }
+
+    Ok(())
+}
+
+fn read_jed(path: &Path) -> Result<JEDECFile, String> {
+    let data = fs::read(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    JEDECFile::from_bytes(&data).map_err(|err| format!("{}: invalid .jed file: {}", path.display(), err))
+}
+
+fn cmd_coverage(matches: &ArgMatches) -> Result<(), String> {
+    let mut jeds = Vec::new();
+    for path in matches.values_of("PATH").unwrap() {
+        collect_jeds(Path::new(path), &mut jeds)?;
+    }
+
+    let mut num_bad_files = 0;
+    write_output(matches.value_of("output").unwrap(), |out| {
+        for path in &jeds {
+            let unconsumed = read_jed(path)
+                .and_then(|jed| find_unconsumed_fuses(&jed).map_err(|err| format!("{}: {}", path.display(), err)));
+
+            match unconsumed {
+                Err(err) => {
+                    write!(out, "{}\n", err).map_err(write_error)?;
+                    num_bad_files += 1;
+                },
+                Ok(ref unconsumed) if unconsumed.len() > 0 => {
+                    write!(out, "{}: {} unconsumed fuses\n", path.display(), unconsumed.len()).map_err(write_error)?;
+                    for fuse in unconsumed {
+                        write!(out, "  JED {}", fuse.jed).map_err(write_error)?;
+                        if let Some((x, y)) = fuse.crbit {
+                            write!(out, ", crbit {}|{}", x, y).map_err(write_error)?;
+                        }
+                        write!(out, " ({})\n", fuse.function).map_err(write_error)?;
+                    }
+                    num_bad_files += 1;
+                },
+                Ok(_) => {},
+            }
+        }
+        if num_bad_files == 0 {
+            write!(out, "{} files checked, all fuses are understood\n", jeds.len()).map_err(write_error)?;
+        }
+        Ok(())
+    })?;
+
+    if num_bad_files > 0 {
+        return Err(format!("{} of {} files contain fuses that are not understood", num_bad_files, jeds.len()));
+    }
+    Ok(())
+}
+
+fn cmd_solve(matches: &ArgMatches) -> Result<(), String> {
+    let mut jeds = Vec::new();
+    for path in matches.values_of("PATH").unwrap() {
+        collect_jeds(Path::new(path), &mut jeds)?;
+    }
+
+    let mut corpus = XC2FuseCorpus::new();
+    let mut device = None;
+    for path in &jeds {
+        let jed = read_jed(path)?;
+
+        let tags_path = path.with_extension("tags");
+        let tags_data = fs::read_to_string(&tags_path).map_err(|err| format!("{}: {}", tags_path.display(), err))?;
+        let tags = tags_data.lines()
+            .map(|x| x.trim())
+            .filter(|x| x.len() > 0 && !x.starts_with('#'))
+            .collect::<Vec<_>>();
+
+        if let Some(ref dev_name) = jed.dev_name_str {
+            if let Some(part) = XC2DeviceSpeedPackage::from_str(dev_name) {
+                if device.is_some() && device != Some(part.dev) {
+                    return Err(format!("{}: all files must be for the same device", path.display()));
+                }
+                device = Some(part.dev);
+            }
+        }
+
+        corpus.add_jed(&jed, &tags).map_err(|_| format!("{}: wrong number of fuses", path.display()))?;
+    }
+
+    // Describe fuses using the existing fuse map, if possible
+    let fuse_map = device.map(XC2FuseMap::new);
+    let describe = |i: usize| {
+        let fuse = fuse_map.as_ref().and_then(|fuse_map| fuse_map.jed_fuse(i));
+        match fuse {
+            Some(&XC2Fuse{function, crbit: Some((x, y)), ..}) => format!("JED {} crbit {}|{} ({})", i, x, y, function),
+            Some(&XC2Fuse{function, crbit: None, ..}) => format!("JED {} ({})", i, function),
+            None => format!("JED {}", i),
+        }
+    };
+
+    let mut num_problems = 0;
+    write_output(matches.value_of("output").unwrap(), |out| {
+        for solution in corpus.solve() {
+            let status = if solution.num_samples == corpus.num_samples() {
+                num_problems += 1;
+                "present in every sample"
+            } else if solution.conflicts.len() > 0 {
+                num_problems += 1;
+                "CONFLICT"
+            } else if solution.fuses.len() == 0 {
+                num_problems += 1;
+                "no correlated fuses"
+            } else if !solution.is_determined() {
+                num_problems += 1;
+                "under-determined"
+            } else {
+                "ok"
+            };
+            write!(out, "{} ({} samples): {}\n", solution.tag, solution.num_samples, status).map_err(write_error)?;
+
+            for &(i, val) in &solution.fuses {
+                let certainty = if solution.certain.contains(&i) {""} else {"?"};
+                write!(out, "  {} = {}{}\n", describe(i), val as u8, certainty).map_err(write_error)?;
+            }
+            for &i in &solution.conflicts {
+                write!(out, "  conflicts with {}\n", jeds[i].display()).map_err(write_error)?;
+            }
+        }
+        Ok(())
+    })?;
+
+    if num_problems > 0 {
+        return Err(format!("{} tags could not be solved", num_problems));
+    }
+    Ok(())
+}
+
+fn main() {
+    let input_arg = Arg::with_name("INPUT")
+        .help("Input bitstream in .jed or crbit format, or - for standard input")
+        .default_value("-")
+        .index(1);
+    let part_arg = Arg::with_name("PART")
+        .help("Part name (<device>-<speed>-<package>)")
+        .required(true)
+        .index(1);
+    let output_arg = Arg::with_name("output")
+        .help("Output file name, or - for standard output")
+        .short("o")
+        .long("output")
+        .takes_value(true)
+        .default_value("-");
+    let old_arg = Arg::with_name("OLD")
+        .help("Old bitstream in .jed or crbit format")
+        .required(true)
+        .index(1);
+    let new_arg = Arg::with_name("NEW")
+        .help("New bitstream in .jed or crbit format")
+        .required(true)
+        .index(2);
+    let paths_arg = Arg::with_name("PATH")
+        .help(".jed files, or directories that are searched for them")
+        .required(true)
+        .multiple(true)
+        .index(1);
+    let format_arg = Arg::with_name("format")
+        .help("Output format")
+        .short("f")
+        .long("format")
+        .takes_value(true);
+
+    let matches = App::new("xc2")
+        .author("Robert Ou <rqou@robertou.com>")
+        .about("Tool for working with Xilinx Coolrunner-II bitstreams")
+        .version(env!("CARGO_PKG_VERSION"))
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .setting(AppSettings::VersionlessSubcommands)
+
+        .subcommand(SubCommand::with_name("convert")
+            .about("Converts a bitstream between .jed and crbit format (by default, into the format it is not in)")
+            .arg(input_arg.clone())
+            .arg(output_arg.clone())
+            .arg(format_arg.clone().possible_values(&["jed", "crbit"])))
+        .subcommand(SubCommand::with_name("dump")
+            .about("Prints a human-readable description of every setting in a bitstream")
+            .arg(input_arg.clone())
+            .arg(output_arg.clone())
+            .arg(format_arg.clone().possible_values(&["text", "json"]).default_value("text")))
+        .subcommand(SubCommand::with_name("blank")
+            .about("Writes a blank but valid bitstream for the given part")
+            .arg(part_arg.clone())
+            .arg(output_arg.clone())
+            .arg(format_arg.clone().possible_values(&["jed", "crbit"]).default_value("jed")))
+        .subcommand(SubCommand::with_name("netlist")
+            .about("Converts a bitstream into a Yosys JSON netlist of placed primitives")
+            .arg(input_arg.clone())
+            .arg(output_arg.clone()))
+        .subcommand(SubCommand::with_name("from-netlist")
+            .about("Converts a Yosys JSON netlist of placed primitives (as written by `xc2 netlist`) into a bitstream")
+            .arg(Arg::with_name("INPUT")
+                .help("Input netlist, or - for standard input")
+                .default_value("-")
+                .index(1))
+            .arg(output_arg.clone())
+            .arg(format_arg.clone().possible_values(&["jed", "crbit"]).default_value("jed")))
+        .subcommand(SubCommand::with_name("structure")
+            .about("Prints the routing graph (nodes, wires and the connections between them) of the given part")
+            .arg(part_arg.clone())
+            .arg(output_arg.clone())
+            .arg(format_arg.clone().possible_values(&["text", "json", "dot", "graphml"]).default_value("text")))
+        .subcommand(SubCommand::with_name("info")
+            .about("Prints the part, format and resource utilization of a bitstream")
+            .arg(input_arg.clone())
+            .arg(output_arg.clone())
+            .arg(format_arg.clone().possible_values(&["text", "json"]).default_value("text")))
+        .subcommand(SubCommand::with_name("util")
+            .about("Prints the resource utilization of a bitstream")
+            .arg(input_arg.clone())
+            .arg(output_arg.clone())
+            .arg(format_arg.clone().possible_values(&["text", "json"]).default_value("text")))
+        .subcommand(SubCommand::with_name("eqn")
+            .about("Prints the minimized equations of every macrocell in a bitstream")
+            .arg(input_arg.clone())
+            .arg(output_arg.clone())
+            .arg(Arg::with_name("pins")
+                .help("File naming the pins, with one \"name FBn_m\" line per pin")
+                .long("pins")
+                .takes_value(true))
+            .arg(Arg::with_name("verilog")
+                .help("Prints Verilog assignments instead of ABEL equations")
+                .long("verilog")))
+        .subcommand(SubCommand::with_name("svg")
+            .about("Renders the fuse array of a bitstream as an SVG image")
+            .arg(input_arg.clone())
+            .arg(Arg::with_name("COMPARE")
+                .help("Second bitstream for the same device, whose differences are highlighted")
+                .index(2))
+            .arg(output_arg.clone()))
+        .subcommand(SubCommand::with_name("diff")
+            .about("Prints the functional differences between two bitstreams")
+            .arg(old_arg.clone())
+            .arg(new_arg.clone())
+            .arg(output_arg.clone()))
+        .subcommand(SubCommand::with_name("equiv")
+            .about("Checks whether two bitstreams implement the same logic, matching the designs by pin")
+            .arg(old_arg.clone())
+            .arg(new_arg.clone())
+            .arg(output_arg.clone()))
+        .subcommand(SubCommand::with_name("coverage")
+            .about("Reports fuses in .jed files that are not understood by xc2bit")
+            .arg(paths_arg.clone())
+            .arg(output_arg.clone()))
+        .subcommand(SubCommand::with_name("solve")
+            .about("Solves for the fuses corresponding to the tags of a corpus of .jed files. Each file.jed must have \
+                a file.tags next to it listing the features that are enabled in it, one per line.")
+            .arg(paths_arg.clone())
+            .arg(output_arg.clone()))
+
+        .get_matches();
+
+    let result = match matches.subcommand() {
+        ("convert", Some(m)) => cmd_convert(m),
+        ("dump", Some(m)) => cmd_dump(m),
+        ("blank", Some(m)) => cmd_blank(m),
+        ("netlist", Some(m)) => cmd_netlist(m),
+        ("from-netlist", Some(m)) => cmd_from_netlist(m),
+        ("structure", Some(m)) => cmd_structure(m),
+        ("info", Some(m)) => cmd_info(m),
+        ("util", Some(m)) => cmd_util(m),
+        ("eqn", Some(m)) => cmd_eqn(m),
+        ("svg", Some(m)) => cmd_svg(m),
+        ("diff", Some(m)) => cmd_diff(m),
+        ("equiv", Some(m)) => cmd_equiv(m),
+        ("coverage", Some(m)) => cmd_coverage(m),
+        ("solve", Some(m)) => cmd_solve(m),
+        _ => unreachable!(),
+    };
+
+    if let Err(err) = result {
+        eprintln!("xc2: error: {}", err);
+        process::exit(1);
+    }
+}
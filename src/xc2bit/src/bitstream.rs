@@ -25,7 +25,12 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 // Toplevel bitstrem stuff
 
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+#[cfg(feature = "std")]
 use std::io;
+#[cfg(feature = "std")]
 use std::io::Write;
 
 use jedec::*;
@@ -47,6 +52,7 @@ pub struct XC2Bitstream {
 
 impl XC2Bitstream {
     /// Dump a human-readable explanation of the bitstream to the given `writer` object.
+    #[cfg(feature = "std")]
     pub fn dump_human_readable<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
         write!(writer, "xc2bit dump\n")?;
         write!(writer, "device speed grade: {}\n", self.speed_grade)?;
@@ -57,7 +63,7 @@ impl XC2Bitstream {
     }
 
     /// Write a .jed representation of the bitstream to the given `writer` object.
-    pub fn to_jed<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
+    pub fn to_jed<W: ByteWrite>(&self, mut writer: W) -> Result<(), W::Error> {
         write!(writer, ".JED fuse map written by xc2bit\n")?;
         write!(writer, "https://github.com/azonenberg/openfpga\n\n")?;
 
@@ -66,17 +72,17 @@ impl XC2Bitstream {
         jed.dev_name_str = Some(format!("{}-{}-{}", self.bits.device_type(), self.speed_grade, self.package));
         self.bits.to_jed(&mut jed, &mut linebreaks);
 
-        jed.write_custom_linebreaks(&mut writer, linebreaks.iter())?;
-
-        Ok(())
+        jed.write_custom_linebreaks(writer, linebreaks.iter())
     }
 
-    /// Converts the bitstream into a FuseArray object so that it can be written to the native "crbit" format
+    /// Converts the bitstream into a FuseArray object so that it can be written to the native "crbit" format. The
+    /// `tool` of the result names this version of xc2bit.
     pub fn to_crbit(&self) -> FuseArray {
         let (w, h) = fuse_array_dims(self.bits.device_type());
         let mut fuse_array = FuseArray::from_dim(w, h);
 
         fuse_array.dev_name_str = Some(format!("{}-{}-{}", self.bits.device_type(), self.speed_grade, self.package));
+        fuse_array.tool = Some(format!("xc2bit {}", env!("CARGO_PKG_VERSION")));
 
         self.bits.to_crbit(&mut fuse_array);
 
@@ -174,7 +180,8 @@ impl XC2Bitstream {
         }
     }
 
-    /// Processes a fuse array (in physical addressing) into a bitstream object
+    /// Processes a fuse array (in physical addressing) into a bitstream object. The `tool` of the fuse array only
+    /// describes where the file came from and is not kept, and neither is its `usercode`.
     pub fn from_crbit(fuse_array: &FuseArray) -> Result<Self, XC2BitError> {
         // FIXME: Can we guess the device type from the dimensions?
         if fuse_array.dev_name_str.is_none() {
@@ -671,6 +678,15 @@ impl XC2BitstreamBits {
         }
     }
 
+    /// Helper to extract only the input-only pin data without having to perform an explicit `match`
+    pub fn get_inpin(&self) -> Option<&XC2ExtraIBuf> {
+        match self {
+            &XC2BitstreamBits::XC2C32{ref inpin, ..} => Some(inpin),
+            &XC2BitstreamBits::XC2C32A{ref inpin, ..} => Some(inpin),
+            _ => None,
+        }
+    }
+
     /// Convert the actual bitstream bits to crbit format
     pub fn to_crbit(&self, fuse_array: &mut FuseArray) {
         // FBs
@@ -826,6 +842,7 @@ impl XC2BitstreamBits {
     }
 
     /// Dump a human-readable explanation of the bitstream to the given `writer` object.
+    #[cfg(feature = "std")]
     pub fn dump_human_readable<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
         write!(writer, "device type: {}\n", self.device_type())?;
 
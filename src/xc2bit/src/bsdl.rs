@@ -0,0 +1,585 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Minimal parser for BSDL files, used to check vendor files against the built-in JTAG model
+//!
+//! Only the attributes describing the instruction register, IDCODE, and boundary-scan register are understood. All
+//! other statements are skipped.
+
+use std::error;
+use std::fmt;
+
+use crate::*;
+
+/// Errors that can occur when parsing a BSDL file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum XC2BsdlError {
+    /// The file does not contain an entity declaration
+    MissingEntity,
+    /// The value of the given attribute could not be parsed
+    BadAttribute(String),
+    /// The given boundary register cell description could not be parsed
+    BadCell(String),
+}
+
+impl error::Error for XC2BsdlError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        None
+    }
+}
+
+impl fmt::Display for XC2BsdlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &XC2BsdlError::MissingEntity => {
+                write!(f, "no entity declaration found")
+            },
+            &XC2BsdlError::BadAttribute(ref name) => {
+                write!(f, "invalid value for attribute {}", name)
+            },
+            &XC2BsdlError::BadCell(ref cell) => {
+                write!(f, "invalid boundary register cell \"{}\"", cell)
+            },
+        }
+    }
+}
+
+/// One cell of the boundary-scan register as described in a BSDL file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XC2BsdlCell {
+    pub num: usize,
+    /// Cell type, such as "BC_1"
+    pub cell_type: String,
+    /// Port name, or `None` for cells that are not attached to a port ("*")
+    pub port: Option<String>,
+    pub function: XC2BoundaryCellFunction,
+    /// Safe value, or `None` if it is "X"
+    pub safe: Option<bool>,
+    /// For output cells, the control cell and the value that disables the output
+    pub control: Option<(usize, bool)>,
+}
+
+/// The parts of a BSDL file that describe the JTAG interface of a device
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct XC2BsdlFile {
+    pub entity: String,
+    pub instruction_length: Option<usize>,
+    /// Instruction names and opcodes. Some instructions have more than one opcode.
+    pub instructions: Vec<(String, Vec<u32>)>,
+    /// IDCODE value and mask, where the mask has a 0 for each "X" bit
+    pub idcode: Option<(u32, u32)>,
+    pub boundary_length: Option<usize>,
+    pub boundary_cells: Vec<XC2BsdlCell>,
+    /// Port names and the package pins they are bonded out to, from the first PIN_MAP_STRING constant
+    pub pin_map: Vec<(String, Vec<String>)>,
+}
+
+/// Differences found between a BSDL file and the built-in JTAG model of a part
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum XC2BsdlMismatch {
+    InstructionLength {
+        expected: usize,
+        found: Option<usize>,
+    },
+    Instruction {
+        instr: XC2JtagInstruction,
+        found: Option<Vec<u32>>,
+    },
+    Idcode {
+        expected: u32,
+        found: Option<u32>,
+    },
+    BoundaryLength {
+        expected: usize,
+        found: usize,
+    },
+    BoundaryCell {
+        cell: usize,
+        expected: XC2BoundaryCellFunction,
+        found: Option<XC2BoundaryCellFunction>,
+    },
+    BoundaryControl {
+        cell: usize,
+        expected: Option<usize>,
+        found: Option<usize>,
+    },
+}
+
+impl fmt::Display for XC2BsdlMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &XC2BsdlMismatch::InstructionLength{expected, found} => {
+                write!(f, "instruction length is {:?}, expected {}", found, expected)
+            },
+            &XC2BsdlMismatch::Instruction{instr, ref found} => {
+                match found {
+                    &Some(ref found) => write!(f, "instruction {} has opcodes {:?}, expected {:08b}",
+                        instr, found.iter().map(|x| format!("{:08b}", x)).collect::<Vec<_>>(), instr.opcode()),
+                    &None => write!(f, "instruction {} is missing", instr),
+                }
+            },
+            &XC2BsdlMismatch::Idcode{expected, found} => {
+                match found {
+                    Some(found) => write!(f, "IDCODE is {:08X}, expected {:08X}", found, expected),
+                    None => write!(f, "IDCODE is missing"),
+                }
+            },
+            &XC2BsdlMismatch::BoundaryLength{expected, found} => {
+                write!(f, "boundary register has {} cells, expected {}", found, expected)
+            },
+            &XC2BsdlMismatch::BoundaryCell{cell, expected, found} => {
+                match found {
+                    Some(found) => write!(f, "boundary cell {} is {}, expected {}", cell, found, expected),
+                    None => write!(f, "boundary cell {} is missing, expected {}", cell, expected),
+                }
+            },
+            &XC2BsdlMismatch::BoundaryControl{cell, expected, found} => {
+                write!(f, "boundary cell {} is controlled by {:?}, expected {:?}", cell, found, expected)
+            },
+        }
+    }
+}
+
+/// Removes comments and returns the statements of the file
+fn bsdl_statements(text: &str) -> Vec<String> {
+    let mut stripped = String::new();
+    for line in text.lines() {
+        let line = match line.find("--") {
+            Some(idx) => &line[..idx],
+            None => line,
+        };
+        stripped.push_str(line);
+        stripped.push(' ');
+    }
+
+    stripped.split(';').map(|x| x.trim().to_owned()).filter(|x| x.len() > 0).collect()
+}
+
+/// Concatenates all string literals in an attribute value, which are joined using `&` in BSDL
+fn bsdl_string_value(value: &str) -> String {
+    value.split('"').skip(1).step_by(2).collect()
+}
+
+/// Splits a string on commas that are not inside parentheses
+fn split_outside_parens(s: &str) -> Vec<&str> {
+    let mut ret = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                ret.push(s[start..i].trim());
+                start = i + 1;
+            },
+            _ => {},
+        }
+    }
+    ret.push(s[start..].trim());
+    ret.into_iter().filter(|x| x.len() > 0).collect()
+}
+
+/// Splits "NAME (a, b, c)" into the name and the comma-separated items inside the parentheses
+fn split_name_and_list(s: &str) -> Option<(&str, Vec<&str>)> {
+    let open = s.find('(')?;
+    let close = s.rfind(')')?;
+    if close < open {
+        return None;
+    }
+    Some((s[..open].trim(), s[open + 1..close].split(',').map(|x| x.trim()).collect()))
+}
+
+fn parse_cell(s: &str) -> Option<XC2BsdlCell> {
+    let (num, fields) = split_name_and_list(s)?;
+    if fields.len() != 4 && fields.len() != 7 {
+        return None;
+    }
+
+    let num = num.parse().ok()?;
+    let port = if fields[1] == "*" { None } else { Some(fields[1].to_owned()) };
+    let function = XC2BoundaryCellFunction::from_name(fields[2])?;
+    let parse_bit = |x: &str| match x {
+        "0" => Some(Some(false)),
+        "1" => Some(Some(true)),
+        "X" | "x" => Some(None),
+        _ => None,
+    };
+    let safe = parse_bit(fields[3])?;
+    let control = if fields.len() == 7 {
+        Some((fields[4].parse().ok()?, parse_bit(fields[5])??))
+    } else {
+        None
+    };
+
+    Some(XC2BsdlCell {
+        num,
+        cell_type: fields[0].to_owned(),
+        port,
+        function,
+        safe,
+        control,
+    })
+}
+
+impl XC2BsdlFile {
+    /// Parses the contents of a BSDL file
+    pub fn from_str(text: &str) -> Result<Self, XC2BsdlError> {
+        let mut ret = XC2BsdlFile::default();
+        let mut found_entity = false;
+
+        let mut found_pin_map = false;
+
+        for stmt in bsdl_statements(text) {
+            let words = stmt.split_whitespace().collect::<Vec<_>>();
+            if !found_entity && words.len() >= 2 && words[0].eq_ignore_ascii_case("entity") {
+                ret.entity = words[1].to_owned();
+                found_entity = true;
+                continue;
+            }
+
+            // constant NAME : PIN_MAP_STRING := VALUE
+            if !found_pin_map && words.len() >= 2 && words[0].eq_ignore_ascii_case("constant") &&
+                stmt.to_ascii_uppercase().contains("PIN_MAP_STRING") {
+
+                let bad = || XC2BsdlError::BadAttribute("PIN_MAP_STRING".to_owned());
+                let value = bsdl_string_value(&stmt[stmt.find(":=").ok_or_else(bad)?..]);
+                for entry in split_outside_parens(&value) {
+                    let colon_idx = entry.find(':').ok_or_else(bad)?;
+                    let port = entry[..colon_idx].trim();
+                    let pins = entry[colon_idx + 1..].trim().trim_start_matches('(').trim_end_matches(')');
+                    let pins = pins.split(',').map(|x| x.trim().to_owned()).collect::<Vec<_>>();
+                    if port.len() == 0 || pins.iter().any(|x| x.len() == 0) {
+                        return Err(bad());
+                    }
+                    ret.pin_map.push((port.to_owned(), pins));
+                }
+                found_pin_map = true;
+                continue;
+            }
+
+            // attribute NAME of ENTITY : entity is VALUE
+            if words.len() < 2 || !words[0].eq_ignore_ascii_case("attribute") {
+                continue;
+            }
+            let name = words[1].to_ascii_uppercase();
+            let colon_idx = match stmt.find(':') {
+                Some(idx) => idx,
+                None => continue,
+            };
+            let value = match stmt[colon_idx..].to_ascii_lowercase().find(" is ") {
+                Some(idx) => stmt[colon_idx + idx + 4..].trim(),
+                None => continue,
+            };
+            let bad = || XC2BsdlError::BadAttribute(name.clone());
+
+            match &name[..] {
+                "INSTRUCTION_LENGTH" => {
+                    ret.instruction_length = Some(value.parse().map_err(|_| bad())?);
+                },
+                "BOUNDARY_LENGTH" => {
+                    ret.boundary_length = Some(value.parse().map_err(|_| bad())?);
+                },
+                "INSTRUCTION_OPCODE" => {
+                    let value = bsdl_string_value(value);
+                    for instr in split_outside_parens(&value) {
+                        let (instr_name, opcodes) = split_name_and_list(instr).ok_or_else(bad)?;
+                        let opcodes = opcodes.into_iter().map(|x| u32::from_str_radix(x, 2))
+                            .collect::<Result<Vec<_>, _>>().map_err(|_| bad())?;
+                        ret.instructions.push((instr_name.to_ascii_uppercase(), opcodes));
+                    }
+                },
+                "IDCODE_REGISTER" => {
+                    let value = bsdl_string_value(value);
+                    if value.len() != 32 {
+                        return Err(bad());
+                    }
+                    let mut idcode = 0;
+                    let mut mask = 0;
+                    for c in value.chars() {
+                        idcode <<= 1;
+                        mask <<= 1;
+                        match c {
+                            '0' => mask |= 1,
+                            '1' => {
+                                idcode |= 1;
+                                mask |= 1;
+                            },
+                            'X' | 'x' => {},
+                            _ => return Err(bad()),
+                        }
+                    }
+                    ret.idcode = Some((idcode, mask));
+                },
+                "BOUNDARY_REGISTER" => {
+                    let value = bsdl_string_value(value);
+                    for cell in split_outside_parens(&value) {
+                        let cell = parse_cell(cell).ok_or_else(|| XC2BsdlError::BadCell(cell.to_owned()))?;
+                        ret.boundary_cells.push(cell);
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        if !found_entity {
+            return Err(XC2BsdlError::MissingEntity);
+        }
+
+        ret.boundary_cells.sort_by_key(|cell| cell.num);
+        Ok(ret)
+    }
+
+    /// Returns the opcodes of the instruction with the given name
+    pub fn instruction(&self, name: &str) -> Option<&[u32]> {
+        self.instructions.iter().find(|x| x.0.eq_ignore_ascii_case(name)).map(|x| &x.1[..])
+    }
+
+    /// Returns the port name of the given boundary-scan cell
+    pub fn port_for_cell(&self, num: usize) -> Option<&str> {
+        self.boundary_cells.iter().find(|cell| cell.num == num).and_then(|cell| cell.port.as_ref().map(|x| &x[..]))
+    }
+
+    /// Returns the package pins of the given port
+    pub fn pins_for_port(&self, port: &str) -> Option<&[String]> {
+        self.pin_map.iter().find(|x| x.0.eq_ignore_ascii_case(port)).map(|x| &x.1[..])
+    }
+
+    /// Returns the package pinout described by this file, as the package pin names and the sites of the given
+    /// built-in JTAG model that are bonded out to them. Sites whose input cell has no port, or whose port has no
+    /// pin, are left out.
+    pub fn pinout<'a>(&'a self, info: &XC2JtagInfo) -> Vec<(&'a str, XC2BoundaryCellSite)> {
+        let mut ret = Vec::new();
+        for cell in &self.boundary_cells {
+            if cell.function != XC2BoundaryCellFunction::Input || cell.num >= info.boundary.len() ||
+                info.boundary[cell.num].function != XC2BoundaryCellFunction::Input {
+                continue;
+            }
+            let pins = cell.port.as_ref().and_then(|port| self.pins_for_port(port));
+            if let Some(pins) = pins {
+                for pin in pins {
+                    ret.push((&pin[..], info.boundary[cell.num].site));
+                }
+            }
+        }
+        ret
+    }
+
+    /// Compares this file against the built-in JTAG model of a part and returns all differences
+    pub fn check(&self, info: &XC2JtagInfo) -> Vec<XC2BsdlMismatch> {
+        let mut ret = Vec::new();
+
+        if self.instruction_length != Some(info.ir_len) {
+            ret.push(XC2BsdlMismatch::InstructionLength {
+                expected: info.ir_len,
+                found: self.instruction_length,
+            });
+        }
+
+        for &instr in XC2JtagInstruction::all() {
+            let found = self.instruction(instr.name());
+            if found.map(|x| x.contains(&(instr.opcode() as u32))) != Some(true) {
+                ret.push(XC2BsdlMismatch::Instruction {
+                    instr,
+                    found: found.map(|x| x.to_vec()),
+                });
+            }
+        }
+
+        match self.idcode {
+            Some((idcode, mask)) if mask & info.idcode_mask == info.idcode_mask &&
+                idcode & info.idcode_mask == info.idcode => {},
+            _ => ret.push(XC2BsdlMismatch::Idcode {
+                expected: info.idcode,
+                found: self.idcode.map(|x| x.0),
+            }),
+        }
+
+        let found_len = self.boundary_length.unwrap_or(self.boundary_cells.len());
+        if found_len != info.boundary.len() {
+            ret.push(XC2BsdlMismatch::BoundaryLength {
+                expected: info.boundary.len(),
+                found: found_len,
+            });
+        }
+
+        for (i, expected) in info.boundary.iter().enumerate() {
+            let found = self.boundary_cells.iter().find(|cell| cell.num == i);
+            if found.map(|cell| cell.function) != Some(expected.function) {
+                ret.push(XC2BsdlMismatch::BoundaryCell {
+                    cell: i,
+                    expected: expected.function,
+                    found: found.map(|cell| cell.function),
+                });
+            } else {
+                let found_control = found.unwrap().control.map(|x| x.0);
+                if found_control != expected.control {
+                    ret.push(XC2BsdlMismatch::BoundaryControl {
+                        cell: i,
+                        expected: expected.control,
+                        found: found_control,
+                    });
+                }
+            }
+        }
+
+        ret
+    }
+}
+
+impl XC2DeviceSpeedPackage {
+    /// Returns the JTAG details of this part, with the package pinout taken from its vendor BSDL file
+    pub fn jtag_info_with_bsdl(&self, bsdl: &XC2BsdlFile) -> XC2JtagInfo {
+        self.jtag_info_with_pinout(&bsdl.pinout(&self.dev.jtag_info()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Handwritten in the layout of the vendor files. Only the first two macrocells and the input-only pin are
+    // described, and the pin numbers are made up.
+    const XC2C32A_EXCERPT: &str = r#"
+-- handwritten excerpt, not a vendor file
+entity XC2C32A_VQ44 is
+    generic (PHYSICAL_PIN_MAP : string := "VQ44");
+    port (PB00_00: inout bit; PB00_01: inout bit; IN0: in bit);
+    use STD_1149_1_2001.all;
+    attribute COMPONENT_CONFORMANCE of XC2C32A_VQ44 : entity is "STD_1149_1_2001";
+    attribute PIN_MAP of XC2C32A_VQ44 : entity is PHYSICAL_PIN_MAP;
+    constant VQ44: PIN_MAP_STRING:=
+        "PB00_00:P38," &
+        "IN0:(P21, P22)";
+    attribute INSTRUCTION_LENGTH of XC2C32A_VQ44 : entity is 8;
+    attribute INSTRUCTION_OPCODE of XC2C32A_VQ44 : entity is
+        "BYPASS (11111111)," &
+        "CLAMP (11111010)," &
+        "EXTEST (00000000)," &
+        "HIGHZ (11111100)," &
+        "IDCODE (00000001)," &
+        "INTEST (00000010)," &
+        "ISC_DISABLE (11000000)," &
+        "ISC_ENABLE (11101000)," &
+        "ISC_ENABLEOTF (11100100)," &
+        "ISC_ENABLE_CLAMP (11101001)," &
+        "ISC_ERASE (11101101)," &
+        "ISC_INIT (11110000)," &
+        "ISC_NOOP (11100000)," &
+        "ISC_PROGRAM (11101010)," &
+        "ISC_READ (11101110)," &
+        "ISC_SRAM_READ (11100111)," &
+        "ISC_SRAM_WRITE (11100110)," &
+        "SAMPLE (00000011)," &
+        "USERCODE (11111101)";
+    attribute IDCODE_REGISTER of XC2C32A_VQ44 : entity is
+        "XXXX" &            -- version
+        "0110111000011100" & -- part number
+        "00001001001" &      -- manufacturer
+        "1";
+    attribute BOUNDARY_LENGTH of XC2C32A_VQ44 : entity is 97;
+    attribute BOUNDARY_REGISTER of XC2C32A_VQ44 : entity is
+        "96 (BC_1, IN0, input, X)," &
+        "5 (BC_1, PB00_01, output3, X, 4, 0, Z)," &
+        "4 (BC_1, *, control, 0)," &
+        "3 (BC_1, PB00_01, input, X)," &
+        "2 (BC_1, PB00_00, output3, X, 1, 0, Z)," &
+        "1 (BC_1, *, control, 0)," &
+        "0 (BC_1, PB00_00, input, X)";
+end XC2C32A_VQ44;
+"#;
+
+    #[test]
+    fn check_handwritten_bsdl() {
+        let part = XC2DeviceSpeedPackage::from_str("xc2c32a-4-vq44").unwrap();
+        let info = part.dev.jtag_info();
+
+        let bsdl = XC2BsdlFile::from_str(XC2C32A_EXCERPT).unwrap();
+        assert_eq!(bsdl.entity, "XC2C32A_VQ44");
+        assert_eq!(bsdl.instruction("idcode"), Some(&[1][..]));
+        assert_eq!(bsdl.idcode, Some((0x06E1C093, 0x0FFFFFFF)));
+        assert_eq!(bsdl.port_for_cell(2), Some("PB00_00"));
+        assert_eq!(bsdl.pins_for_port("in0"), Some(&["P21".to_owned(), "P22".to_owned()][..]));
+
+        // Everything that is described matches; only the left-out cells are reported
+        let mismatches = bsdl.check(&info);
+        assert_eq!(mismatches.len(), 97 - 7);
+        assert!(mismatches.iter().all(|x| match x {
+            &XC2BsdlMismatch::BoundaryCell{cell, found: None, ..} => (6..96).contains(&cell),
+            _ => false,
+        }));
+
+        let bsdl = XC2BsdlFile::from_str(&XC2C32A_EXCERPT.replace("HIGHZ (11111100)", "HIGHZ (11111110)")).unwrap();
+        assert!(bsdl.check(&info).contains(&XC2BsdlMismatch::Instruction {
+            instr: XC2JtagInstruction::Highz,
+            found: Some(vec![0xFE]),
+        }));
+
+        let info64 = XC2Device::XC2C64A.jtag_info();
+        let mismatches = bsdl.check(&info64);
+        assert!(mismatches.contains(&XC2BsdlMismatch::BoundaryLength{expected: 192, found: 97}));
+
+        let info = part.jtag_info_with_bsdl(&bsdl);
+        let fb1_1 = XC2BoundaryCellSite::Macrocell{fb: 0, mc: 0};
+        let fb1_2 = XC2BoundaryCellSite::Macrocell{fb: 0, mc: 1};
+        assert_eq!(info.package_pin(fb1_1), Some(&XC2PackagePin::Bonded("P38".to_owned())));
+        assert_eq!(info.package_pin(fb1_2), Some(&XC2PackagePin::Unbonded));
+        assert!(!info.is_bonded(fb1_2));
+        assert_eq!(info.site_for_pin("p21"), Some(XC2BoundaryCellSite::InputOnly));
+        assert_eq!(info.site_for_pin("P1"), None);
+        assert_eq!(info.pins.as_ref().unwrap().len(), 33);
+
+        assert!(XC2BsdlFile::from_str(&XC2C32A_EXCERPT.replace("\"PB00_00:P38,\"", "\"PB00_00,\"")).is_err());
+    }
+
+    // Vendor BSDL files are named after the entity, e.g. xc2c32a_vq44.bsd. This is only called by the tests generated
+    // from the files in tests/xc2bit/bsdl.
+    #[allow(dead_code)]
+    fn run_one_bsdltest(bsd_filename: &'static str) {
+        let text = std::fs::read_to_string(bsd_filename).expect("failed to read bsd file");
+        let bsdl = XC2BsdlFile::from_str(&text).expect("failed to parse bsd file");
+
+        let mut entity_split = bsdl.entity.splitn(2, '_');
+        let dev = entity_split.next().unwrap();
+        let pkg = entity_split.next().expect("entity name does not contain a package");
+        let part = ["4", "5", "6", "7", "10"].iter()
+            .filter_map(|spd| XC2DeviceSpeedPackage::from_str(&format!("{}-{}-{}", dev, spd, pkg))).next()
+            .expect("entity name is not a known part");
+
+        let info = part.jtag_info_with_bsdl(&bsdl);
+        assert_eq!(bsdl.check(&info), vec![]);
+        assert!(info.pins.as_ref().unwrap().iter().any(|x| x.1 != XC2PackagePin::Unbonded));
+    }
+
+    // Include list of vendor files to check
+    include!(concat!(env!("OUT_DIR"), "/bsdltests.rs"));
+
+    #[test]
+    fn bad_bsdl() {
+        assert_eq!(XC2BsdlFile::from_str("-- nothing here"), Err(XC2BsdlError::MissingEntity));
+        assert_eq!(XC2BsdlFile::from_str("entity X is; attribute BOUNDARY_REGISTER of X : entity is \"0 (BC_1)\";"),
+            Err(XC2BsdlError::BadCell("0 (BC_1)".to_owned())));
+    }
+}
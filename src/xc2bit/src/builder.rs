@@ -0,0 +1,528 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! High-level builder for hand-placed designs
+//!
+//! This allocates ZIA rows and product terms automatically so that small designs and test fixtures can be written in
+//! terms of sum-of-products equations over pins and macrocells, using the same signals as `XC2EqnSignal`, instead of
+//! setting individual bits.
+
+use std::error;
+use std::fmt;
+
+use crate::*;
+
+/// A product term, as a list of signals and whether each one is complemented. An empty product is a constant 1.
+pub type XC2Product = Vec<(XC2EqnSignal, bool)>;
+
+/// Errors that can occur while building a bitstream
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum XC2BuilderError {
+    /// The function block or macrocell does not exist on this device
+    InvalidLocation {
+        fb: u32,
+        mc: u32,
+    },
+    /// The product term index does not exist
+    InvalidProductTerm(u32),
+    /// The macrocell does not have a pin
+    NoIOB {
+        fb: u32,
+        mc: u32,
+    },
+    /// The logic of the macrocell has already been placed
+    MacrocellInUse {
+        fb: u32,
+        mc: u32,
+    },
+    /// The output of the pin is already being driven
+    PinInUse {
+        fb: u32,
+        mc: u32,
+    },
+    /// The signal needs a feedback path into the ZIA that is already being used for a different signal
+    FeedbackConflict(XC2EqnSignal),
+    /// The signal cannot be used as an input to a product term
+    NotRoutable(XC2EqnSignal),
+    /// None of the free ZIA rows of the function block can select the signal
+    ZiaFull {
+        fb: u32,
+        signal: XC2EqnSignal,
+    },
+    /// All of the product terms of the function block are used
+    ProductTermsFull(u32),
+    /// The special product term is already being used with different inputs
+    ProductTermConflict {
+        fb: u32,
+        term: u32,
+    },
+}
+
+impl error::Error for XC2BuilderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        None
+    }
+}
+
+impl fmt::Display for XC2BuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &XC2BuilderError::InvalidLocation{fb, mc} => {
+                write!(f, "FB{}_{} does not exist", fb + 1, mc + 1)
+            },
+            &XC2BuilderError::InvalidProductTerm(term) => {
+                write!(f, "product term {} does not exist", term)
+            },
+            &XC2BuilderError::NoIOB{fb, mc} => {
+                write!(f, "FB{}_{} does not have a pin", fb + 1, mc + 1)
+            },
+            &XC2BuilderError::MacrocellInUse{fb, mc} => {
+                write!(f, "FB{}_{} is already in use", fb + 1, mc + 1)
+            },
+            &XC2BuilderError::PinInUse{fb, mc} => {
+                write!(f, "pin of FB{}_{} is already being driven", fb + 1, mc + 1)
+            },
+            &XC2BuilderError::FeedbackConflict(signal) => {
+                write!(f, "feedback path for {:?} is already in use by a different signal", signal)
+            },
+            &XC2BuilderError::NotRoutable(signal) => {
+                write!(f, "{:?} cannot be used in a product term", signal)
+            },
+            &XC2BuilderError::ZiaFull{fb, signal} => {
+                write!(f, "no free ZIA row in FB{} can select {:?}", fb + 1, signal)
+            },
+            &XC2BuilderError::ProductTermsFull(fb) => {
+                write!(f, "no free product terms in FB{}", fb + 1)
+            },
+            &XC2BuilderError::ProductTermConflict{fb, term} => {
+                write!(f, "product term {} in FB{} is already in use", term, fb + 1)
+            },
+        }
+    }
+}
+
+/// Configuration of a register placed using `XC2BitstreamBuilder::place_register`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XC2RegisterSpec {
+    pub mode: XC2MCRegMode,
+    pub clk: XC2MCRegClkSrc,
+    /// `true` for falling-edge triggered flip-flops and transparent-when-low latches
+    pub clk_invert: bool,
+    pub is_ddr: bool,
+    pub init_state: bool,
+    pub set: XC2MCRegSetSrc,
+    pub reset: XC2MCRegResetSrc,
+    /// D (or T) input of the register
+    pub input: Vec<XC2Product>,
+}
+
+impl XC2RegisterSpec {
+    /// A rising-edge D flip-flop with the given clock and input, initialized to 0, with no set or reset
+    pub fn dff(clk: XC2MCRegClkSrc, input: Vec<XC2Product>) -> Self {
+        XC2RegisterSpec {
+            mode: XC2MCRegMode::DFF,
+            clk,
+            clk_invert: false,
+            is_ddr: false,
+            init_state: false,
+            set: XC2MCRegSetSrc::Disabled,
+            reset: XC2MCRegResetSrc::Disabled,
+            input,
+        }
+    }
+}
+
+/// Order in which product terms are allocated for OR terms. The terms that cannot be used for anything else come
+/// first, followed by the special terms starting from the ones belonging to the last macrocell.
+fn general_term_order() -> impl Iterator<Item = usize> {
+    (0..4).chain((4..ANDTERMS_PER_FB).rev())
+}
+
+/// Allocation state of a function block
+#[derive(Clone, Debug)]
+struct FBState {
+    zia: [Option<XC2ZIAInput>; INPUTS_PER_ANDTERM],
+    /// Inputs of every product term that is in use, as sorted ZIA rows and complement flags
+    terms: Vec<Option<Vec<(usize, bool)>>>,
+    mcs: [bool; MCS_PER_FB],
+}
+
+/// Builds a bitstream from placed registers, combinatorial logic, and pins
+pub struct XC2BitstreamBuilder {
+    bitstream: XC2Bitstream,
+    fbs: Vec<FBState>,
+    driven: Vec<bool>,
+}
+
+impl XC2BitstreamBuilder {
+    /// Starts building a new design for the given part
+    pub fn new(part: XC2DeviceSpeedPackage) -> Self {
+        let device = part.dev;
+        XC2BitstreamBuilder {
+            bitstream: XC2Bitstream::blank_bitstream(part),
+            fbs: vec![FBState {
+                zia: [None; INPUTS_PER_ANDTERM],
+                terms: vec![None; ANDTERMS_PER_FB],
+                mcs: [false; MCS_PER_FB],
+            }; device.num_fbs()],
+            driven: vec![false; device.num_iobs()],
+        }
+    }
+
+    fn device(&self) -> XC2Device {
+        self.bitstream.bits.device_type()
+    }
+
+    fn check_location(&self, fb: u32, mc: u32) -> Result<(), XC2BuilderError> {
+        if fb as usize >= self.device().num_fbs() || mc as usize >= MCS_PER_FB {
+            return Err(XC2BuilderError::InvalidLocation{fb, mc});
+        }
+        Ok(())
+    }
+
+    /// Runs `f` and undoes all of its changes if it fails, so that a failed call leaves the builder unchanged
+    fn transaction<T, F>(&mut self, f: F) -> Result<T, XC2BuilderError>
+        where F: FnOnce(&mut Self) -> Result<T, XC2BuilderError> {
+
+        let bits = self.bitstream.bits;
+        let fbs = self.fbs.clone();
+        let ret = f(self);
+        if ret.is_err() {
+            self.bitstream.bits = bits;
+            self.fbs = fbs;
+        }
+        ret
+    }
+
+    /// Sets the ZIA input mode of the IOB of a macrocell, which must not already be used differently
+    fn set_iob_zia_mode(&mut self, signal: XC2EqnSignal, fb: u32, mc: u32, mode: XC2IOBZIAMode)
+        -> Result<u32, XC2BuilderError> {
+
+        let iob = fb_mc_num_to_iob_num(self.device(), fb, mc).ok_or(XC2BuilderError::NoIOB{fb, mc})?;
+        let bits = &mut self.bitstream.bits;
+        let zia_mode = if let Some(iob) = bits.get_mut_small_iob(iob as usize) {
+            &mut iob.zia_mode
+        } else {
+            &mut bits.get_mut_large_iob(iob as usize).unwrap().zia_mode
+        };
+
+        if *zia_mode != XC2IOBZIAMode::Disabled && *zia_mode != mode {
+            return Err(XC2BuilderError::FeedbackConflict(signal));
+        }
+        *zia_mode = mode;
+        Ok(iob)
+    }
+
+    /// Enables the feedback path needed for `signal` and returns the corresponding ZIA input
+    fn route_signal(&mut self, signal: XC2EqnSignal) -> Result<XC2ZIAInput, XC2BuilderError> {
+        match signal {
+            XC2EqnSignal::Pad{fb, mc} => {
+                self.check_location(fb, mc)?;
+                let iob = self.set_iob_zia_mode(signal, fb, mc, XC2IOBZIAMode::PAD)?;
+                Ok(XC2ZIAInput::IBuf{ibuf: iob as u16})
+            },
+            XC2EqnSignal::Reg{fb, mc} | XC2EqnSignal::Comb{fb, mc} => {
+                self.check_location(fb, mc)?;
+                let mode = if let XC2EqnSignal::Reg{..} = signal {
+                    XC2MCFeedbackMode::REG
+                } else {
+                    XC2MCFeedbackMode::COMB
+                };
+                let fb_mode = &mut self.bitstream.bits.get_fb_mut()[fb as usize].mcs[mc as usize].fb_mode;
+                if *fb_mode != XC2MCFeedbackMode::Disabled && *fb_mode != mode {
+                    return Err(XC2BuilderError::FeedbackConflict(signal));
+                }
+                *fb_mode = mode;
+                Ok(XC2ZIAInput::Macrocell{fb: fb as u8, mc: mc as u8})
+            },
+            XC2EqnSignal::InputOnly if self.bitstream.bits.get_inpin().is_some() => {
+                Ok(XC2ZIAInput::DedicatedInput)
+            },
+            _ => Err(XC2BuilderError::NotRoutable(signal)),
+        }
+    }
+
+    /// Returns a ZIA row of the function block that selects `signal`, allocating one if needed
+    fn alloc_zia_row(&mut self, fb: u32, signal: XC2EqnSignal) -> Result<usize, XC2BuilderError> {
+        let input = self.route_signal(signal)?;
+        let device = self.device();
+        let state = &mut self.fbs[fb as usize];
+
+        if let Some(row) = state.zia.iter().position(|&x| x == Some(input)) {
+            return Ok(row);
+        }
+
+        let row = (0..INPUTS_PER_ANDTERM).find(|&row| state.zia[row].is_none() &&
+            zia_table_get_row(device, row).contains(&input))
+            .ok_or(XC2BuilderError::ZiaFull{fb, signal})?;
+        state.zia[row] = Some(input);
+        *self.bitstream.bits.get_fb_mut()[fb as usize].get_mut_zia(row) = input;
+        Ok(row)
+    }
+
+    /// Places a product term in the function block and returns its index. If `special` is given, that term is used,
+    /// otherwise an identical existing term is shared or a free one is allocated.
+    fn alloc_term(&mut self, fb: u32, product: &[(XC2EqnSignal, bool)], special: Option<u32>)
+        -> Result<u32, XC2BuilderError> {
+
+        let mut inputs = Vec::new();
+        for &(signal, complement) in product {
+            inputs.push((self.alloc_zia_row(fb, signal)?, complement));
+        }
+        inputs.sort();
+        inputs.dedup();
+
+        let state = &mut self.fbs[fb as usize];
+        let term = match special {
+            Some(term) => {
+                match state.terms[term as usize] {
+                    Some(ref existing) if *existing != inputs => {
+                        return Err(XC2BuilderError::ProductTermConflict{fb, term});
+                    },
+                    _ => term as usize,
+                }
+            },
+            None => {
+                if let Some(term) = state.terms.iter().position(|x| x.as_ref() == Some(&inputs)) {
+                    return Ok(term as u32);
+                }
+                general_term_order().find(|&term| state.terms[term].is_none())
+                    .ok_or(XC2BuilderError::ProductTermsFull(fb))?
+            },
+        };
+
+        let and_term = self.bitstream.bits.get_fb_mut()[fb as usize].get_mut_andterm(term);
+        for &(row, complement) in &inputs {
+            if complement {
+                and_term.set_b(row, true);
+            } else {
+                and_term.set(row, true);
+            }
+        }
+        state.terms[term] = Some(inputs);
+        Ok(term as u32)
+    }
+
+    /// Connects the OR term of a macrocell to the given sum of products and marks the macrocell as used
+    fn place_sop(&mut self, fb: u32, mc: u32, sop: &[XC2Product]) -> Result<(), XC2BuilderError> {
+        self.check_location(fb, mc)?;
+        if self.fbs[fb as usize].mcs[mc as usize] {
+            return Err(XC2BuilderError::MacrocellInUse{fb, mc});
+        }
+
+        for product in sop {
+            let term = self.alloc_term(fb, product, None)?;
+            self.bitstream.bits.get_fb_mut()[fb as usize].or_terms[mc as usize].set(term as usize, true);
+        }
+
+        self.fbs[fb as usize].mcs[mc as usize] = true;
+        self.bitstream.bits.get_fb_mut()[fb as usize].mcs[mc as usize].xor_mode = XC2MCXorMode::ZERO;
+        Ok(())
+    }
+
+    /// Places combinatorial logic computing the given sum of products in a macrocell. Nothing is changed if this
+    /// fails.
+    pub fn place_comb(&mut self, fb: u32, mc: u32, sop: &[XC2Product]) -> Result<(), XC2BuilderError> {
+        self.transaction(|x| x.place_sop(fb, mc, sop))
+    }
+
+    /// Places a register in a macrocell. Any product terms used for the clock, set, or reset (e.g. `PTC` or `CTR`)
+    /// have to be placed separately using `place_special_term`. Nothing is changed if this fails.
+    pub fn place_register(&mut self, fb: u32, mc: u32, reg: &XC2RegisterSpec) -> Result<(), XC2BuilderError> {
+        self.transaction(|x| x.place_sop(fb, mc, &reg.input))?;
+
+        let global_nets = self.bitstream.bits.get_global_nets_mut();
+        match reg.clk {
+            XC2MCRegClkSrc::GCK0 => global_nets.gck_enable[0] = true,
+            XC2MCRegClkSrc::GCK1 => global_nets.gck_enable[1] = true,
+            XC2MCRegClkSrc::GCK2 => global_nets.gck_enable[2] = true,
+            _ => {},
+        }
+        if reg.set == XC2MCRegSetSrc::GSR || reg.reset == XC2MCRegResetSrc::GSR {
+            global_nets.gsr_enable = true;
+        }
+
+        let macrocell = &mut self.bitstream.bits.get_fb_mut()[fb as usize].mcs[mc as usize];
+        macrocell.reg_mode = reg.mode;
+        macrocell.clk_src = reg.clk;
+        macrocell.clk_invert_pol = reg.clk_invert;
+        macrocell.is_ddr = reg.is_ddr;
+        macrocell.init_state = reg.init_state;
+        macrocell.s_src = reg.set;
+        macrocell.r_src = reg.reset;
+        macrocell.ff_in_ibuf = false;
+        Ok(())
+    }
+
+    /// Places a product term at a fixed index, such as `CTC` or `get_ptb(mc)`, so that it can be used for one of its
+    /// special functions. Nothing is changed if this fails.
+    pub fn place_special_term(&mut self, fb: u32, term: u32, product: &[(XC2EqnSignal, bool)])
+        -> Result<(), XC2BuilderError> {
+
+        self.check_location(fb, 0)?;
+        if term as usize >= ANDTERMS_PER_FB {
+            return Err(XC2BuilderError::InvalidProductTerm(term));
+        }
+        self.transaction(|x| x.alloc_term(fb, product, Some(term)))?;
+        Ok(())
+    }
+
+    /// Drives the pin of a macrocell from either its register or its combinatorial output. Output enables using
+    /// `TriStatePTB` or `TriStateCTE` need the corresponding product term to be placed using `place_special_term`.
+    pub fn drive_pin(&mut self, fb: u32, mc: u32, from_register: bool, mode: XC2IOBOBufMode)
+        -> Result<(), XC2BuilderError> {
+
+        self.check_location(fb, mc)?;
+        let iob = fb_mc_num_to_iob_num(self.device(), fb, mc).ok_or(XC2BuilderError::NoIOB{fb, mc})?;
+        if self.driven[iob as usize] {
+            return Err(XC2BuilderError::PinInUse{fb, mc});
+        }
+        self.driven[iob as usize] = true;
+
+        let global_nets = self.bitstream.bits.get_global_nets_mut();
+        match mode {
+            XC2IOBOBufMode::TriStateGTS0 => global_nets.gts_enable[0] = true,
+            XC2IOBOBufMode::TriStateGTS1 => global_nets.gts_enable[1] = true,
+            XC2IOBOBufMode::TriStateGTS2 => global_nets.gts_enable[2] = true,
+            XC2IOBOBufMode::TriStateGTS3 => global_nets.gts_enable[3] = true,
+            _ => {},
+        }
+
+        let bits = &mut self.bitstream.bits;
+        if let Some(iob) = bits.get_mut_small_iob(iob as usize) {
+            iob.obuf_mode = mode;
+            iob.obuf_uses_ff = from_register;
+        } else {
+            let iob = bits.get_mut_large_iob(iob as usize).unwrap();
+            iob.obuf_mode = mode;
+            iob.obuf_uses_ff = from_register;
+        }
+        Ok(())
+    }
+
+    /// Returns the bitstream built so far
+    pub fn bitstream(&self) -> &XC2Bitstream {
+        &self.bitstream
+    }
+
+    /// Finishes building and returns the bitstream
+    pub fn build(self) -> XC2Bitstream {
+        self.bitstream
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    fn pad(fb: u32, mc: u32) -> XC2EqnSignal {
+        XC2EqnSignal::Pad{fb, mc}
+    }
+
+    #[test]
+    fn build_register() {
+        let mut builder = XC2BitstreamBuilder::new(XC2DeviceSpeedPackage::from_str("xc2c32a-4-vq44").unwrap());
+        let input = vec![
+            vec![(pad(0, 0), false), (pad(0, 1), true)],
+            vec![(XC2EqnSignal::Reg{fb: 1, mc: 4}, false)],
+        ];
+        builder.place_register(1, 4, &XC2RegisterSpec::dff(XC2MCRegClkSrc::GCK0, input)).unwrap();
+        builder.drive_pin(1, 4, true, XC2IOBOBufMode::TriStatePTB).unwrap();
+        builder.place_special_term(1, get_ptb(4), &[(pad(0, 3), false)]).unwrap();
+        let bitstream = builder.build();
+
+        let eqns = bitstream.bits.equations();
+        assert_eq!(eqns.len(), 1);
+        let mut abel = Vec::new();
+        eqns[0].write(XC2EqnSyntax::Abel, &HashMap::new(), &mut abel).unwrap();
+        assert_eq!(String::from_utf8(abel).unwrap(),
+            "\" D flip-flop register, initial state 0\n\
+             FB2_5.D = FB1_1.PIN & !FB1_2.PIN # FB2_5.Q;\n\
+             FB2_5.CLK = GCK0;\n\
+             FB2_5.O = FB2_5.Q;\n\
+             FB2_5.OE = FB1_4.PIN;\n");
+        assert!(bitstream.bits.get_global_nets().gck_enable[0]);
+
+        // The result must survive a round trip through a .jed file
+        let mut jed = Vec::new();
+        bitstream.to_jed(&mut jed).unwrap();
+        let parsed = XC2Bitstream::from_jed(&jedec::JEDECFile::from_bytes(&jed).unwrap()).unwrap();
+        assert_eq!(parsed.bits, bitstream.bits);
+    }
+
+    #[test]
+    fn builder_conflicts() {
+        let mut builder = XC2BitstreamBuilder::new(XC2DeviceSpeedPackage::from_str("xc2c32a-4-vq44").unwrap());
+        builder.place_comb(0, 0, &[vec![(pad(0, 1), false)]]).unwrap();
+        assert_eq!(builder.place_comb(0, 0, &[]), Err(XC2BuilderError::MacrocellInUse{fb: 0, mc: 0}));
+        assert_eq!(builder.place_comb(0, 16, &[]), Err(XC2BuilderError::InvalidLocation{fb: 0, mc: 16}));
+        assert_eq!(builder.place_comb(0, 2, &[vec![(XC2EqnSignal::GCK(0), false)]]),
+            Err(XC2BuilderError::NotRoutable(XC2EqnSignal::GCK(0))));
+
+        builder.place_comb(1, 0, &[vec![(XC2EqnSignal::Comb{fb: 0, mc: 0}, false)]]).unwrap();
+        assert_eq!(builder.place_comb(1, 1, &[vec![(XC2EqnSignal::Reg{fb: 0, mc: 0}, false)]]),
+            Err(XC2BuilderError::FeedbackConflict(XC2EqnSignal::Reg{fb: 0, mc: 0})));
+
+        builder.place_special_term(0, CTC, &[(pad(0, 1), false)]).unwrap();
+        assert_eq!(builder.place_special_term(0, CTC, &[(pad(0, 1), true)]),
+            Err(XC2BuilderError::ProductTermConflict{fb: 0, term: CTC}));
+
+        builder.drive_pin(0, 0, false, XC2IOBOBufMode::PushPull).unwrap();
+        assert_eq!(builder.drive_pin(0, 0, false, XC2IOBOBufMode::PushPull),
+            Err(XC2BuilderError::PinInUse{fb: 0, mc: 0}));
+    }
+
+    #[test]
+    fn failed_calls_change_nothing() {
+        let mut builder = XC2BitstreamBuilder::new(XC2DeviceSpeedPackage::from_str("xc2c32a-4-vq44").unwrap());
+        builder.place_comb(0, 0, &[vec![(pad(0, 1), false)]]).unwrap();
+        builder.place_comb(1, 0, &[vec![(XC2EqnSignal::Comb{fb: 0, mc: 0}, false)]]).unwrap();
+        let before = builder.bitstream().bits;
+
+        // The first product is placed before the second one fails
+        assert_eq!(builder.place_comb(1, 1, &[vec![(pad(0, 6), false)], vec![(XC2EqnSignal::Reg{fb: 0, mc: 0}, false)]]),
+            Err(XC2BuilderError::FeedbackConflict(XC2EqnSignal::Reg{fb: 0, mc: 0})));
+        assert_eq!(builder.bitstream().bits, before);
+
+        // The ZIA row for the new pin is allocated before the term conflicts
+        builder.place_special_term(0, CTC, &[(pad(0, 1), false)]).unwrap();
+        let before = builder.bitstream().bits;
+        assert_eq!(builder.place_special_term(0, CTC, &[(pad(0, 5), false)]),
+            Err(XC2BuilderError::ProductTermConflict{fb: 0, term: CTC}));
+        assert_eq!(builder.bitstream().bits, before);
+
+        // The macrocell and all resources are still free afterwards
+        builder.place_comb(1, 1, &[vec![(pad(0, 6), false)]]).unwrap();
+        builder.drive_pin(1, 1, false, XC2IOBOBufMode::PushPull).unwrap();
+        let eqns = builder.build().bits.equations();
+        let mut abel = Vec::new();
+        for eqn in &eqns {
+            eqn.write(XC2EqnSyntax::Abel, &HashMap::new(), &mut abel).unwrap();
+        }
+        assert_eq!(String::from_utf8(abel).unwrap(), "FB1_1 = FB1_2.PIN;\nFB2_2 = FB1_7.PIN;\nFB2_2.O = FB2_2;\n");
+    }
+}
@@ -0,0 +1,108 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Coverage analysis that finds .jed fuses that are not understood by the decoder
+
+use jedec::*;
+
+use crate::*;
+use crate::fusemap_logical::{total_logical_fuse_count};
+use crate::util::{LinebreakSet};
+
+/// A fuse that is set in an input .jed file but does not survive decoding the file with `XC2Bitstream::from_jed` and
+/// encoding it again. This means that the decoder did not consume the fuse and the information it carries is lost.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+pub struct XC2UnconsumedFuse {
+    /// Index of the fuse in the .jed file
+    pub jed: usize,
+    /// Coordinates of the fuse in the crbit format, if known
+    pub crbit: Option<(usize, usize)>,
+    /// What the fuse map believes this fuse to be
+    pub function: XC2FuseFunction,
+}
+
+/// Decodes the given .jed file and reports every fuse that is set but not consumed by the decoder. A set fuse is
+/// considered consumed if it is still set after a decode/encode round trip. Returns an error if the file cannot be
+/// decoded at all.
+pub fn find_unconsumed_fuses(jed: &JEDECFile) -> Result<Vec<XC2UnconsumedFuse>, XC2BitError> {
+    let bitstream = XC2Bitstream::from_jed(jed)?;
+    let device = bitstream.bits.device_type();
+
+    let mut roundtrip_jed = JEDECFile::new(total_logical_fuse_count(device));
+    let mut linebreaks = LinebreakSet::new();
+    bitstream.bits.to_jed(&mut roundtrip_jed, &mut linebreaks);
+
+    // XC2FuseMap covers every .jed fuse (see fuse_map_covers_all_jed_fuses)
+    let fuse_map = XC2FuseMap::new(device);
+
+    let mut ret = Vec::new();
+    for i in 0..jed.f.len() {
+        if jed.f[i] && !roundtrip_jed.f[i] {
+            let fuse = fuse_map.jed_fuse(i).unwrap();
+            ret.push(XC2UnconsumedFuse {
+                jed: i,
+                crbit: fuse.crbit,
+                function: fuse.function,
+            });
+        }
+    }
+
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+    use std::io::Read;
+
+    #[test]
+    fn unconsumed_fuses() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/xc2bit/reftests/32-fb.jed");
+        let mut jed_data = Vec::new();
+        File::open(path).expect("failed to open jed file")
+            .read_to_end(&mut jed_data).expect("failed to read jed file");
+        let mut jed = JEDECFile::from_bytes(&jed_data).expect("failed to read jed");
+
+        assert_eq!(find_unconsumed_fuses(&jed).unwrap(), vec![]);
+
+        // The first bit of clk_src is ignored when GCK0 is selected, so setting it cannot be preserved
+        assert!(!jed.f[5723]);
+        jed.f[5723] = true;
+        let unconsumed = find_unconsumed_fuses(&jed).unwrap();
+        assert_eq!(unconsumed.len(), 1);
+        assert_eq!(unconsumed[0].jed, 5723);
+        assert_eq!(unconsumed[0].crbit, Some((1, 3)));
+        assert_eq!(unconsumed[0].function, XC2FuseFunction::Macrocell{fb: 0, mc: 1, field: "clk_src", bit: Some(0)});
+        jed.f[5723] = false;
+
+        // Both encodings of this bit of FB1_1 fb_mode decode to the same setting. Clearing it is not preserved either,
+        // but only fuses that are set in the input are reported.
+        assert!(jed.f[5709]);
+        jed.f[5709] = false;
+        assert_eq!(find_unconsumed_fuses(&jed).unwrap(), vec![]);
+    }
+}
@@ -23,14 +23,250 @@ OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 */
 
-//! Contains routines for dealing with xc2bit's "native" crbit format. TODO: Document this format.
+//! Contains routines for dealing with xc2bit's "native" crbit format.
+//!
+//! A crbit file is an ASCII file containing the fuses in physical addressing as rows of '1' and '0' characters, with
+//! one line per row of the fuse array. All rows must have the same length. (This is intended to be compatible with
+//! `$readmemb`.) Empty lines are ignored, and lines starting with `//` are comments.
+//!
+//! Comments that appear before the first row of fuses and that start with one of the following keywords form the
+//! header of the file. Each of them may appear at most once.
+//!
+//! * `// CRBIT <version>` - the version of the format, currently 1. Files without this line are treated as version 1.
+//! * `// DEVICE <device>-<speed>-<package>` - the part that the file is for
+//! * `// USERCODE <hex>` - the 32-bit USERCODE, as 8 hex digits. Like `TOOL`, this is not carried over when the fuses
+//!   are converted into a bitstream, because the USERCODE fuses are not decoded yet.
+//! * `// TOOL <text>` - the program that created the file. This is informational only and is not carried over when the
+//!   fuses are converted into a bitstream.
+//! * `// CHECKSUM <hex>` - the checksum of the fuses, as 4 hex digits. This is computed in the same way as the fuse
+//!   checksum of a .jed file, but with the fuses taken in crbit order (row by row, from the top-left corner).
+//!
+//! All other comments are ignored, so older files that only contain a `DEVICE` line can still be read.
 
 use crate::util::{b2s};
 
-use std::io;
-use std::io::Write;
-use std::ops::{Index, IndexMut};
-use std::str;
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::num::Wrapping;
+use core::ops::{Index, IndexMut};
+use core::str;
+#[cfg(feature = "std")]
+use std::error;
+
+use jedec::ByteWrite;
+
+/// The newest version of the crbit format that is understood. This is the version that is written.
+pub const CRBIT_VERSION: u32 = 1;
+
+/// Errors that can occur when parsing a crbit file. Line numbers start from 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CrbitParseError {
+    /// An invalid UTF-8 sequence occurred
+    InvalidUtf8(str::Utf8Error),
+    /// A row of fuses contains a character that is not allowed
+    InvalidCharacter {
+        line: usize,
+        c: char,
+    },
+    /// A row of fuses has a different length than the first row
+    RowLengthMismatch {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A header field has a value that could not be parsed
+    BadHeaderValue {
+        line: usize,
+        field: &'static str,
+    },
+    /// A header field appears more than once
+    DuplicateHeader {
+        line: usize,
+        field: &'static str,
+    },
+    /// The file is in a newer version of the format
+    UnsupportedVersion {
+        line: usize,
+        version: u32,
+    },
+    /// The fuses do not match the `CHECKSUM` header
+    BadChecksum {
+        expected: u16,
+        found: u16,
+    },
+    /// The file contained no fuses
+    NoData,
+}
+
+#[cfg(feature = "std")]
+impl error::Error for CrbitParseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            &CrbitParseError::InvalidUtf8(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CrbitParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &CrbitParseError::InvalidUtf8(err) => write!(f, "invalid utf8 character: {}", err),
+            &CrbitParseError::InvalidCharacter{line, c} => write!(f, "line {}: invalid character {:?} in crbit", line, c),
+            &CrbitParseError::RowLengthMismatch{line, expected, found} => {
+                write!(f, "line {}: row has {} fuses but previous rows have {}", line, found, expected)
+            },
+            &CrbitParseError::BadHeaderValue{line, field} => write!(f, "line {}: invalid value for {}", line, field),
+            &CrbitParseError::DuplicateHeader{line, field} => write!(f, "line {}: duplicate {} header", line, field),
+            &CrbitParseError::UnsupportedVersion{line, version} => {
+                write!(f, "line {}: crbit version {} is not supported (newest is {})", line, version, CRBIT_VERSION)
+            },
+            &CrbitParseError::BadChecksum{expected, found} => {
+                write!(f, "fuse checksum is {:04X} but header says {:04X}", found, expected)
+            },
+            &CrbitParseError::NoData => write!(f, "crbit contained no data"),
+        }
+    }
+}
+
+impl From<str::Utf8Error> for CrbitParseError {
+    fn from(err: str::Utf8Error) -> Self {
+        CrbitParseError::InvalidUtf8(err)
+    }
+}
+
+/// Contents of the header of a crbit file
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct CrbitHeader {
+    pub dev_name_str: Option<String>,
+    pub usercode: Option<u32>,
+    pub tool: Option<String>,
+    pub checksum: Option<u16>,
+}
+
+/// Parses a crbit file, calling `fuse_from_char` to convert each character of a row of fuses. Returns the header, the
+/// fuses, and the width of the array.
+pub(crate) fn parse_crbit<T, F>(in_bytes: &[u8], fuse_from_char: F) -> Result<(CrbitHeader, Vec<T>, usize),
+    CrbitParseError> where F: Fn(char) -> Option<T> {
+
+    // This capacity is approximate but close enough
+    let mut v = Vec::with_capacity(in_bytes.len());
+    let mut w = None;
+    let mut header = CrbitHeader::default();
+    let mut version_seen = false;
+
+    for (line_i, l) in str::from_utf8(in_bytes)?.split('\n').enumerate() {
+        let line = line_i + 1;
+        let l = l.trim_matches(|c| c == ' ' || c == '\r' || c == '\n');
+        if l.len() == 0 {
+            // ignore empty lines
+            continue;
+        }
+
+        if let Some(comment) = l.strip_prefix("//") {
+            if w.is_some() {
+                // Header fields are only recognized before the fuses
+                continue;
+            }
+
+            let mut words = comment.trim_start().splitn(2, ' ');
+            let field = words.next().unwrap();
+            let value = words.next().unwrap_or("").trim();
+            match field {
+                "CRBIT" => {
+                    if version_seen {
+                        return Err(CrbitParseError::DuplicateHeader{line, field: "CRBIT"});
+                    }
+                    version_seen = true;
+                    let version = value.parse::<u32>()
+                        .map_err(|_| CrbitParseError::BadHeaderValue{line, field: "CRBIT"})?;
+                    if version > CRBIT_VERSION {
+                        return Err(CrbitParseError::UnsupportedVersion{line, version});
+                    }
+                },
+                "DEVICE" => {
+                    if header.dev_name_str.is_some() {
+                        return Err(CrbitParseError::DuplicateHeader{line, field: "DEVICE"});
+                    }
+                    header.dev_name_str = Some(value.to_owned());
+                },
+                "USERCODE" => {
+                    if header.usercode.is_some() {
+                        return Err(CrbitParseError::DuplicateHeader{line, field: "USERCODE"});
+                    }
+                    if value.len() != 8 {
+                        return Err(CrbitParseError::BadHeaderValue{line, field: "USERCODE"});
+                    }
+                    header.usercode = Some(u32::from_str_radix(value, 16)
+                        .map_err(|_| CrbitParseError::BadHeaderValue{line, field: "USERCODE"})?);
+                },
+                "TOOL" => {
+                    if header.tool.is_some() {
+                        return Err(CrbitParseError::DuplicateHeader{line, field: "TOOL"});
+                    }
+                    header.tool = Some(value.to_owned());
+                },
+                "CHECKSUM" => {
+                    if header.checksum.is_some() {
+                        return Err(CrbitParseError::DuplicateHeader{line, field: "CHECKSUM"});
+                    }
+                    header.checksum = Some(u16::from_str_radix(value, 16)
+                        .map_err(|_| CrbitParseError::BadHeaderValue{line, field: "CHECKSUM"})?);
+                },
+                // Any other comment
+                _ => {},
+            }
+        } else {
+            let row_len = l.chars().count();
+            if w.is_none() {
+                w = Some(row_len);
+            } else if w != Some(row_len) {
+                return Err(CrbitParseError::RowLengthMismatch{line, expected: w.unwrap(), found: row_len});
+            }
+
+            for c in l.chars() {
+                match fuse_from_char(c) {
+                    Some(x) => v.push(x),
+                    None => return Err(CrbitParseError::InvalidCharacter{line, c}),
+                }
+            }
+        }
+    }
+
+    if w.is_none() {
+        return Err(CrbitParseError::NoData);
+    }
+
+    Ok((header, v, w.unwrap()))
+}
+
+/// Writes the header of a crbit file. `kind` describes the file in the first comment line.
+pub(crate) fn write_crbit_header<W: ByteWrite + ?Sized>(writer: &mut W, kind: &str, header: &CrbitHeader)
+    -> Result<(), W::Error> {
+
+    write!(writer, "// {} file written by xc2bit\n", kind)?;
+    write!(writer, "// https://github.com/azonenberg/openfpga\n\n")?;
+
+    write!(writer, "// CRBIT {}\n", CRBIT_VERSION)?;
+    if let Some(ref dev_name) = header.dev_name_str {
+        write!(writer, "// DEVICE {}\n", dev_name)?;
+    }
+    if let Some(usercode) = header.usercode {
+        write!(writer, "// USERCODE {:08X}\n", usercode)?;
+    }
+    if let Some(ref tool) = header.tool {
+        write!(writer, "// TOOL {}\n", tool)?;
+    }
+    if let Some(checksum) = header.checksum {
+        write!(writer, "// CHECKSUM {:04X}\n", checksum)?;
+    }
+    write!(writer, "\n")?;
+
+    Ok(())
+}
 
 /// Struct representing a 2-dimensional fuse array and handles converting xy-coordinates into a single linear index.
 /// The x-axis is horizontal and the y-axis is vertical. The origin is at the top-left corner. (This is the standard
@@ -42,6 +278,10 @@ pub struct FuseArray {
     w: usize,
     /// Possibly contains a device name
     pub dev_name_str: Option<String>,
+    /// Possibly contains a USERCODE
+    pub usercode: Option<u32>,
+    /// Possibly contains the name of the program that created the fuse array
+    pub tool: Option<String>,
 }
 
 impl FuseArray {
@@ -60,52 +300,42 @@ impl FuseArray {
         (self.w, self.v.len() / self.w)
     }
 
-    /// Processes the given data and converts it into a `FuseArray` struct.
-    pub fn from_file_contents(in_bytes: &[u8]) -> Result<Self, &'static str> {
-        // This capacity is approximate but close enough
-        let mut v = Vec::with_capacity(in_bytes.len());
-        let mut w = None;
-        let mut dev_name_str = None;
-
-        let in_str = str::from_utf8(in_bytes);
-        if in_str.is_err() {
-            return Err("invalid characters in crbit");
+    /// Computes the checksum that is stored in the `CHECKSUM` header
+    pub fn checksum(&self) -> u16 {
+        let mut csum = Wrapping(0u16);
+        for (i, &fuse) in self.v.iter().enumerate() {
+            if fuse {
+                csum += Wrapping(1u16 << (i % 8));
+            }
         }
+        csum.0
+    }
 
-        for l in in_str.unwrap().split('\n') {
-            let l = l.trim_matches(|c| c == ' ' || c == '\r' || c == '\n');
-            if l.len() == 0 {
-                // ignore empty lines
-                continue;
-            }
+    /// Processes the given data and converts it into a `FuseArray` struct. If the data has a `CHECKSUM` header, the
+    /// fuses are checked against it.
+    pub fn from_file_contents(in_bytes: &[u8]) -> Result<Self, CrbitParseError> {
+        let (header, v, w) = parse_crbit(in_bytes, |c| match c {
+            '0' => Some(false),
+            '1' => Some(true),
+            _ => None,
+        })?;
 
-            if l.starts_with("// DEVICE ") {
-                dev_name_str = Some(l["// DEVICE ".len()..].to_owned());
-            } else if !l.starts_with("//") {
-                // not a comment
-                if w.is_none() {
-                    w = Some(l.len());
-                }
+        let ret = FuseArray {
+            v,
+            w,
+            dev_name_str: header.dev_name_str,
+            usercode: header.usercode,
+            tool: header.tool,
+        };
 
-                for c in l.chars() {
-                    match c {
-                        '0' => v.push(false),
-                        '1' => v.push(true),
-                        _ => return Err("invalid character in crbit"),
-                    }
-                }
+        if let Some(expected) = header.checksum {
+            let found = ret.checksum();
+            if found != expected {
+                return Err(CrbitParseError::BadChecksum{expected, found});
             }
         }
 
-        if w.is_none() {
-            return Err("crbit contained no data");
-        }
-
-        Ok(FuseArray {
-            v,
-            w: w.unwrap(),
-            dev_name_str
-        })
+        Ok(ret)
     }
 
     /// Constructs a new `FuseArray` object with the given dimensions and filled with 0s
@@ -114,18 +344,21 @@ impl FuseArray {
             w,
             v: vec![false; w*h],
             dev_name_str: None,
+            usercode: None,
+            tool: None,
         }
     }
 
     /// Writes the fuse array to the internal "crbit" file format, which is an ASCII file containing '1' and '0'.
-    /// (This format is intended to be compatible with `$readmemb`.)
-    pub fn write_to_writer<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
-        write!(writer, "// crbit native bitstream file written by xc2bit\n")?;
-        write!(writer, "// https://github.com/azonenberg/openfpga\n\n")?;
-
-        if let Some(ref dev_name) = self.dev_name_str {
-            write!(writer, "// DEVICE {}\n\n", dev_name)?;
-        }
+    /// (This format is intended to be compatible with `$readmemb`.) The header always includes the format version and
+    /// the checksum.
+    pub fn write_to_writer<W: ByteWrite>(&self, mut writer: W) -> Result<(), W::Error> {
+        write_crbit_header(&mut writer, "crbit native bitstream", &CrbitHeader {
+            dev_name_str: self.dev_name_str.clone(),
+            usercode: self.usercode,
+            tool: self.tool.clone(),
+            checksum: Some(self.checksum()),
+        })?;
 
         let (w, h) = self.dim();
         for y in 0..h {
@@ -138,6 +371,75 @@ impl FuseArray {
 
         Ok(())
     }
+
+    /// Returns the rows of the fuse array in the order that they are programmed into the device, from the top to the
+    /// bottom
+    pub fn programming_rows(&self) -> FuseRowIter<'_> {
+        FuseRowIter {
+            array: self,
+            y: 0,
+        }
+    }
+
+    /// Returns the number of bits in the address of a row, which is enough bits to count all of the rows
+    pub fn row_address_bits(&self) -> usize {
+        let (_, h) = self.dim();
+        let mut bits = 0;
+        while (1 << bits) < h {
+            bits += 1;
+        }
+        bits
+    }
+}
+
+/// One row of a fuse array, as it is programmed into the device
+#[derive(Copy, Clone)]
+pub struct FuseRow<'a> {
+    array: &'a FuseArray,
+    /// The index of the row, counting from the top of the array
+    pub y: usize,
+    /// The address that selects the row when programming the device. This is the Gray code of `y`, and it is
+    /// `FuseArray::row_address_bits` bits long.
+    pub address: u32,
+}
+
+impl<'a> FuseRow<'a> {
+    /// Returns the fuse at the given x coordinate
+    pub fn get(&self, x: usize) -> bool {
+        self.array.get(x, self.y)
+    }
+
+    /// Returns the fuses of the row from left to right
+    pub fn fuses(&self) -> impl Iterator<Item = bool> + 'a {
+        let array = self.array;
+        let y = self.y;
+        (0..array.w).map(move |x| array.get(x, y))
+    }
+}
+
+/// Iterator over the rows of a fuse array in programming order, returned by `FuseArray::programming_rows`
+pub struct FuseRowIter<'a> {
+    array: &'a FuseArray,
+    y: usize,
+}
+
+impl<'a> Iterator for FuseRowIter<'a> {
+    type Item = FuseRow<'a>;
+
+    fn next(&mut self) -> Option<FuseRow<'a>> {
+        let (_, h) = self.array.dim();
+        if self.y >= h {
+            return None;
+        }
+
+        let y = self.y;
+        self.y += 1;
+        Some(FuseRow {
+            array: self.array,
+            y,
+            address: (y ^ (y >> 1)) as u32,
+        })
+    }
 }
 
 impl Index<(usize, usize)> for FuseArray {
@@ -154,3 +456,79 @@ impl IndexMut<(usize, usize)> for FuseArray {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crbit_legacy() {
+        let fuse_array = FuseArray::from_file_contents(
+            b"// crbit native bitstream file written by xc2bit\n// https://github.com/azonenberg/openfpga\n\n\
+            // DEVICE XC2C32A-6-VQ44\n\n0110\n1000\n\n").unwrap();
+        assert_eq!(fuse_array.dim(), (4, 2));
+        assert_eq!(fuse_array.dev_name_str.as_ref().unwrap(), "XC2C32A-6-VQ44");
+        assert_eq!(fuse_array.usercode, None);
+        assert_eq!(fuse_array.tool, None);
+        assert!(fuse_array.get(1, 0) && !fuse_array.get(1, 1));
+    }
+
+    #[test]
+    fn crbit_header_roundtrip() {
+        let mut fuse_array = FuseArray::from_dim(10, 3);
+        fuse_array.set(9, 2, true);
+        fuse_array.set(0, 1, true);
+        fuse_array.dev_name_str = Some("XC2C64A-5-VQ44".to_owned());
+        fuse_array.usercode = Some(0xDEADBEEF);
+        fuse_array.tool = Some("test".to_owned());
+
+        let mut out = Vec::new();
+        fuse_array.write_to_writer(&mut out).unwrap();
+        let out_str = String::from_utf8(out.clone()).unwrap();
+        assert!(out_str.contains("// CRBIT 1\n// DEVICE XC2C64A-5-VQ44\n// USERCODE DEADBEEF\n// TOOL test\n"));
+
+        let read = FuseArray::from_file_contents(&out).unwrap();
+        assert_eq!(read.v, fuse_array.v);
+        assert_eq!(read.usercode, Some(0xDEADBEEF));
+        assert_eq!(read.tool.as_ref().unwrap(), "test");
+
+        // Flip a fuse so that the checksum no longer matches
+        let corrupted = out_str.replace("0000000001\n", "0000000000\n");
+        match FuseArray::from_file_contents(corrupted.as_bytes()) {
+            Err(CrbitParseError::BadChecksum{..}) => {},
+            _ => panic!("checksum was not checked"),
+        }
+    }
+
+    #[test]
+    fn crbit_programming_rows() {
+        let mut fuse_array = FuseArray::from_dim(3, 5);
+        fuse_array.set(2, 3, true);
+
+        let rows = fuse_array.programming_rows().collect::<Vec<_>>();
+        assert_eq!(rows.iter().map(|row| row.y).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+        assert_eq!(rows.iter().map(|row| row.address).collect::<Vec<_>>(), vec![0, 1, 3, 2, 6]);
+        assert_eq!(rows[3].fuses().collect::<Vec<_>>(), vec![false, false, true]);
+        assert_eq!(fuse_array.row_address_bits(), 3);
+    }
+
+    #[test]
+    fn crbit_errors() {
+        assert_eq!(FuseArray::from_file_contents(b"// CRBIT 1\n\n01\n012\n").err(),
+            Some(CrbitParseError::RowLengthMismatch{line: 4, expected: 2, found: 3}));
+        assert_eq!(FuseArray::from_file_contents(b"01\n0x\n").err(),
+            Some(CrbitParseError::InvalidCharacter{line: 2, c: 'x'}));
+        assert_eq!(FuseArray::from_file_contents(b"// CRBIT 2\n01\n").err(),
+            Some(CrbitParseError::UnsupportedVersion{line: 1, version: 2}));
+        assert_eq!(FuseArray::from_file_contents(b"// CHECKSUM hello\n01\n").err(),
+            Some(CrbitParseError::BadHeaderValue{line: 1, field: "CHECKSUM"}));
+        assert_eq!(FuseArray::from_file_contents(b"// USERCODE hello\n01\n").err(),
+            Some(CrbitParseError::BadHeaderValue{line: 1, field: "USERCODE"}));
+        assert_eq!(FuseArray::from_file_contents(b"// USERCODE BEEF\n01\n").err(),
+            Some(CrbitParseError::BadHeaderValue{line: 1, field: "USERCODE"}));
+        assert_eq!(FuseArray::from_file_contents(b"// USERCODE 00000001\n// USERCODE 00000002\n01\n").err(),
+            Some(CrbitParseError::DuplicateHeader{line: 2, field: "USERCODE"}));
+        assert_eq!(FuseArray::from_file_contents(b"// DEVICE a\n// DEVICE b\n01\n").err(),
+            Some(CrbitParseError::DuplicateHeader{line: 2, field: "DEVICE"}));
+        assert_eq!(FuseArray::from_file_contents(b"// just a comment\n").err(), Some(CrbitParseError::NoData));
+    }
+}
@@ -0,0 +1,370 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Functionality for comparing two bitstreams resource-by-resource
+
+use std::fmt;
+
+use crate::*;
+
+/// A single functional difference between two bitstreams. Differences are reported per resource rather than
+/// per fuse. The fields hold the same 0-based indices used throughout this crate, but the `Display` implementation
+/// names function blocks and macrocells 1-based (e.g. FB1_1) to match `dump_human_readable`.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+pub enum XC2BitstreamDiff {
+    /// The bitstreams are for different devices and were not compared any further
+    DeviceMismatch {
+        old: XC2Device,
+        new: XC2Device,
+    },
+    /// A device-wide setting (global nets, clock divider, bank voltages, etc.) changed
+    Global {
+        field: String,
+        old: String,
+        new: String,
+    },
+    /// A setting of an I/O block changed
+    Iob {
+        iob: u32,
+        field: &'static str,
+        old: String,
+        new: String,
+    },
+    /// A setting of the input-only pin on 32-macrocell devices changed
+    InPin {
+        field: &'static str,
+        old: String,
+        new: String,
+    },
+    /// A setting of a macrocell changed
+    Macrocell {
+        fb: u32,
+        mc: u32,
+        field: &'static str,
+        old: String,
+        new: String,
+    },
+    /// The signal selected by a ZIA row changed
+    Zia {
+        fb: u32,
+        row: u32,
+        old: XC2ZIAInput,
+        new: XC2ZIAInput,
+    },
+    /// An input was connected to (`added` = `true`) or disconnected from an AND term
+    AndTerm {
+        fb: u32,
+        term: u32,
+        input: u32,
+        complement: bool,
+        added: bool,
+    },
+    /// An AND term was connected to (`added` = `true`) or disconnected from an OR term
+    OrTerm {
+        fb: u32,
+        mc: u32,
+        term: u32,
+        added: bool,
+    },
+}
+
+impl fmt::Display for XC2BitstreamDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &XC2BitstreamDiff::DeviceMismatch{old, new} =>
+                write!(f, "device {}→{}", old, new),
+            &XC2BitstreamDiff::Global{ref field, ref old, ref new} =>
+                write!(f, "{} {}→{}", field, old, new),
+            &XC2BitstreamDiff::Iob{iob, field, ref old, ref new} =>
+                write!(f, "IOB {} {} {}→{}", iob, field, old, new),
+            &XC2BitstreamDiff::InPin{field, ref old, ref new} =>
+                write!(f, "input-only pin {} {}→{}", field, old, new),
+            &XC2BitstreamDiff::Macrocell{fb, mc, field, ref old, ref new} =>
+                write!(f, "FB{}_{} {} {}→{}", fb + 1, mc + 1, field, old, new),
+            &XC2BitstreamDiff::Zia{fb, row, old, new} =>
+                write!(f, "FB{} ZIA row {} {:?}→{:?}", fb + 1, row, old, new),
+            &XC2BitstreamDiff::AndTerm{fb, term, input, complement, added} =>
+                write!(f, "FB{} AND term {} input {}{} {}", fb + 1, term, if complement {"~"} else {""}, input,
+                    if added {"added"} else {"removed"}),
+            &XC2BitstreamDiff::OrTerm{fb, mc, term, added} =>
+                write!(f, "FB{}_{} OR term input {} {}", fb + 1, mc + 1, term, if added {"added"} else {"removed"}),
+        }
+    }
+}
+
+/// Collects the (name, value) pairs of all device-wide settings. Both bitstreams being compared are of the same
+/// device type, so the lists line up index-for-index.
+fn global_settings(bits: &XC2BitstreamBits) -> Vec<(String, String)> {
+    let mut ret = Vec::new();
+
+    let global_nets = bits.get_global_nets();
+    for i in 0..NUM_BUFG_CLK {
+        ret.push((format!("gck_enable[{}]", i), format!("{}", global_nets.gck_enable[i])));
+    }
+    ret.push((String::from("gsr_enable"), format!("{}", global_nets.gsr_enable)));
+    ret.push((String::from("gsr_invert"), format!("{}", global_nets.gsr_invert)));
+    for i in 0..NUM_BUFG_GTS {
+        ret.push((format!("gts_enable[{}]", i), format!("{}", global_nets.gts_enable[i])));
+        ret.push((format!("gts_invert[{}]", i), format!("{}", global_nets.gts_invert[i])));
+    }
+    ret.push((String::from("global_pu"), format!("{}", global_nets.global_pu)));
+
+    if let Some(clock_div) = bits.get_clock_div() {
+        ret.push((String::from("clock_div.div_ratio"), format!("{:?}", clock_div.div_ratio)));
+        ret.push((String::from("clock_div.delay"), format!("{}", clock_div.delay)));
+        ret.push((String::from("clock_div.enabled"), format!("{}", clock_div.enabled)));
+    }
+
+    let voltage = |v: bool| String::from(if v {"high"} else {"low"});
+    match bits {
+        &XC2BitstreamBits::XC2C32 {ivoltage, ovoltage, ..} |
+        &XC2BitstreamBits::XC2C64 {ivoltage, ovoltage, ..} => {
+            ret.push((String::from("ivoltage"), voltage(ivoltage)));
+            ret.push((String::from("ovoltage"), voltage(ovoltage)));
+        },
+        &XC2BitstreamBits::XC2C32A {legacy_ivoltage, legacy_ovoltage, ref ivoltage, ref ovoltage, ..} |
+        &XC2BitstreamBits::XC2C64A {legacy_ivoltage, legacy_ovoltage, ref ivoltage, ref ovoltage, ..} => {
+            ret.push((String::from("legacy_ivoltage"), voltage(legacy_ivoltage)));
+            ret.push((String::from("legacy_ovoltage"), voltage(legacy_ovoltage)));
+            for i in 0..2 {
+                ret.push((format!("bank {} ivoltage", i), voltage(ivoltage[i])));
+                ret.push((format!("bank {} ovoltage", i), voltage(ovoltage[i])));
+            }
+        },
+        &XC2BitstreamBits::XC2C128 {ref ivoltage, ref ovoltage, data_gate, use_vref, ..} |
+        &XC2BitstreamBits::XC2C256 {ref ivoltage, ref ovoltage, data_gate, use_vref, ..} => {
+            for i in 0..2 {
+                ret.push((format!("bank {} ivoltage", i), voltage(ivoltage[i])));
+                ret.push((format!("bank {} ovoltage", i), voltage(ovoltage[i])));
+            }
+            ret.push((String::from("data_gate"), format!("{}", data_gate)));
+            ret.push((String::from("use_vref"), format!("{}", use_vref)));
+        },
+        &XC2BitstreamBits::XC2C384 {ref ivoltage, ref ovoltage, data_gate, use_vref, ..} |
+        &XC2BitstreamBits::XC2C512 {ref ivoltage, ref ovoltage, data_gate, use_vref, ..} => {
+            for i in 0..4 {
+                ret.push((format!("bank {} ivoltage", i), voltage(ivoltage[i])));
+                ret.push((format!("bank {} ovoltage", i), voltage(ovoltage[i])));
+            }
+            ret.push((String::from("data_gate"), format!("{}", data_gate)));
+            ret.push((String::from("use_vref"), format!("{}", use_vref)));
+        },
+    }
+
+    ret
+}
+
+/// Collects the (name, value) pairs of all settings of the given I/O block
+fn iob_settings(bits: &XC2BitstreamBits, i: usize) -> Vec<(&'static str, String)> {
+    let slew = |fast: bool| String::from(if fast {"fast"} else {"slow"});
+
+    if let Some(iob) = bits.get_small_iob(i) {
+        vec![
+            ("zia_mode", format!("{:?}", iob.zia_mode)),
+            ("schmitt_trigger", format!("{}", iob.schmitt_trigger)),
+            ("obuf_uses_ff", format!("{}", iob.obuf_uses_ff)),
+            ("obuf_mode", format!("{:?}", iob.obuf_mode)),
+            ("termination_enabled", format!("{}", iob.termination_enabled)),
+            ("slew", slew(iob.slew_is_fast)),
+        ]
+    } else if let Some(iob) = bits.get_large_iob(i) {
+        vec![
+            ("zia_mode", format!("{:?}", iob.zia_mode)),
+            ("ibuf_mode", format!("{:?}", iob.ibuf_mode)),
+            ("obuf_uses_ff", format!("{}", iob.obuf_uses_ff)),
+            ("obuf_mode", format!("{:?}", iob.obuf_mode)),
+            ("termination_enabled", format!("{}", iob.termination_enabled)),
+            ("slew", slew(iob.slew_is_fast)),
+            ("uses_data_gate", format!("{}", iob.uses_data_gate)),
+        ]
+    } else {
+        unreachable!();
+    }
+}
+
+/// Collects the (name, value) pairs of all settings of the given macrocell
+fn mc_settings(mc: &XC2Macrocell) -> Vec<(&'static str, String)> {
+    vec![
+        ("clk_src", format!("{:?}", mc.clk_src)),
+        ("clk_invert_pol", format!("{}", mc.clk_invert_pol)),
+        ("is_ddr", format!("{}", mc.is_ddr)),
+        ("r_src", format!("{:?}", mc.r_src)),
+        ("s_src", format!("{:?}", mc.s_src)),
+        ("init_state", format!("{}", mc.init_state)),
+        ("reg_mode", format!("{:?}", mc.reg_mode)),
+        ("fb_mode", format!("{:?}", mc.fb_mode)),
+        ("ff_in_ibuf", format!("{}", mc.ff_in_ibuf)),
+        ("xor_mode", format!("{:?}", mc.xor_mode)),
+    ]
+}
+
+impl XC2BitstreamBits {
+    /// Compares this bitstream (the "old" one) against `other` (the "new" one) and returns a list of every resource
+    /// whose configuration differs. An empty list means that the two bitstreams are functionally identical.
+    pub fn diff(&self, other: &XC2BitstreamBits) -> Vec<XC2BitstreamDiff> {
+        let device = self.device_type();
+        if device != other.device_type() {
+            return vec![XC2BitstreamDiff::DeviceMismatch {
+                old: device,
+                new: other.device_type(),
+            }];
+        }
+
+        let mut ret = Vec::new();
+
+        // Global settings
+        for ((field, old), (_, new)) in global_settings(self).into_iter().zip(global_settings(other)) {
+            if old != new {
+                ret.push(XC2BitstreamDiff::Global{field, old, new});
+            }
+        }
+
+        // IOBs
+        for iob in 0..device.num_iobs() {
+            for ((field, old), (_, new)) in iob_settings(self, iob).into_iter().zip(iob_settings(other, iob)) {
+                if old != new {
+                    ret.push(XC2BitstreamDiff::Iob{iob: iob as u32, field, old, new});
+                }
+            }
+        }
+
+        // Input-only pin
+        match (self, other) {
+            (&XC2BitstreamBits::XC2C32{inpin: ref old_inpin, ..}, &XC2BitstreamBits::XC2C32{inpin: ref new_inpin, ..}) |
+            (&XC2BitstreamBits::XC2C32A{inpin: ref old_inpin, ..}, &XC2BitstreamBits::XC2C32A{inpin: ref new_inpin, ..}) => {
+                if old_inpin.schmitt_trigger != new_inpin.schmitt_trigger {
+                    ret.push(XC2BitstreamDiff::InPin {
+                        field: "schmitt_trigger",
+                        old: format!("{}", old_inpin.schmitt_trigger),
+                        new: format!("{}", new_inpin.schmitt_trigger),
+                    });
+                }
+                if old_inpin.termination_enabled != new_inpin.termination_enabled {
+                    ret.push(XC2BitstreamDiff::InPin {
+                        field: "termination_enabled",
+                        old: format!("{}", old_inpin.termination_enabled),
+                        new: format!("{}", new_inpin.termination_enabled),
+                    });
+                }
+            },
+            _ => {}
+        }
+
+        // FBs
+        for (fb_i, (old_fb, new_fb)) in self.get_fb().iter().zip(other.get_fb().iter()).enumerate() {
+            let fb = fb_i as u32;
+
+            for mc_i in 0..MCS_PER_FB {
+                let mc = mc_i as u32;
+                for ((field, old), (_, new)) in mc_settings(&old_fb.mcs[mc_i]).into_iter()
+                    .zip(mc_settings(&new_fb.mcs[mc_i])) {
+
+                    if old != new {
+                        ret.push(XC2BitstreamDiff::Macrocell{fb, mc, field, old, new});
+                    }
+                }
+            }
+
+            for row in 0..INPUTS_PER_ANDTERM {
+                let old = *old_fb.get_zia(row);
+                let new = *new_fb.get_zia(row);
+                if old != new {
+                    ret.push(XC2BitstreamDiff::Zia{fb, row: row as u32, old, new});
+                }
+            }
+
+            for term in 0..ANDTERMS_PER_FB {
+                let old_and = old_fb.get_andterm(term);
+                let new_and = new_fb.get_andterm(term);
+                for input in 0..INPUTS_PER_ANDTERM {
+                    if old_and.get(input) != new_and.get(input) {
+                        ret.push(XC2BitstreamDiff::AndTerm {
+                            fb,
+                            term: term as u32,
+                            input: input as u32,
+                            complement: false,
+                            added: new_and.get(input),
+                        });
+                    }
+                    if old_and.get_b(input) != new_and.get_b(input) {
+                        ret.push(XC2BitstreamDiff::AndTerm {
+                            fb,
+                            term: term as u32,
+                            input: input as u32,
+                            complement: true,
+                            added: new_and.get_b(input),
+                        });
+                    }
+                }
+            }
+
+            for mc in 0..MCS_PER_FB {
+                for term in 0..ANDTERMS_PER_FB {
+                    let new = new_fb.or_terms[mc].get(term);
+                    if old_fb.or_terms[mc].get(term) != new {
+                        ret.push(XC2BitstreamDiff::OrTerm {
+                            fb,
+                            mc: mc as u32,
+                            term: term as u32,
+                            added: new,
+                        });
+                    }
+                }
+            }
+        }
+
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_identical() {
+        let a = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c64a-5-vq44").unwrap());
+        let b = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c64a-5-vq44").unwrap());
+
+        assert_eq!(a.bits.diff(&b.bits), vec![]);
+    }
+
+    #[test]
+    fn diff_resources() {
+        let a = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c32a-4-vq44").unwrap());
+        let mut b = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c32a-4-vq44").unwrap());
+
+        b.bits.get_fb_mut()[1].mcs[7].clk_src = XC2MCRegClkSrc::PTC;
+        b.bits.get_mut_small_iob(12).unwrap().slew_is_fast = false;
+        b.bits.get_fb_mut()[0].get_mut_andterm(17).set(5, true);
+
+        let diff = a.bits.diff(&b.bits).iter().map(|x| format!("{}", x)).collect::<Vec<_>>();
+        assert_eq!(diff, vec![
+            "IOB 12 slew fast→slow",
+            "FB1 AND term 17 input 5 added",
+            "FB2_8 clk_src GCK0→PTC",
+        ]);
+    }
+}
@@ -0,0 +1,799 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Extraction of minimized sum-of-products equations for every macrocell
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::io;
+use std::io::Write;
+
+use crate::*;
+use crate::netlist::{parse_fb_mc};
+
+/// A signal that can appear in an equation
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize)]
+pub enum XC2EqnSignal {
+    /// The input pad of the I/O pin belonging to a macrocell
+    Pad {
+        fb: u32,
+        mc: u32,
+    },
+    /// The output of the register of a macrocell
+    Reg {
+        fb: u32,
+        mc: u32,
+    },
+    /// The combinatorial output (from the XOR gate) of a macrocell
+    Comb {
+        fb: u32,
+        mc: u32,
+    },
+    /// The input-only pin on 32-macrocell devices
+    InputOnly,
+    /// A global clock pin
+    GCK(u32),
+    /// The global set/reset pin
+    GSR,
+    /// A global tristate pin
+    GTS(u32),
+}
+
+/// A boolean expression
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+pub enum XC2EqnExpr {
+    /// Sum of products. Each product is a list of signals along with whether the signal is complemented. An empty
+    /// sum is a constant 0 and an empty product is a constant 1.
+    Sop(Vec<Vec<(XC2EqnSignal, bool)>>),
+    Signal(XC2EqnSignal),
+    Not(Box<XC2EqnExpr>),
+    Xor(Box<XC2EqnExpr>, Box<XC2EqnExpr>),
+}
+
+/// Equations for the register of a macrocell
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+pub struct XC2RegEquations {
+    pub mode: XC2MCRegMode,
+    /// D, T, or latch data input
+    pub input: XC2EqnExpr,
+    /// Clock, or latch enable
+    pub clk: XC2EqnExpr,
+    /// Whether the register triggers on both clock edges
+    pub is_ddr: bool,
+    /// Clock enable, only for `DFFCE`
+    pub ce: Option<XC2EqnExpr>,
+    /// Asynchronous set
+    pub set: Option<XC2EqnExpr>,
+    /// Asynchronous reset
+    pub reset: Option<XC2EqnExpr>,
+    pub init_state: bool,
+}
+
+/// All of the equations for one macrocell
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+pub struct XC2MacrocellEquations {
+    pub fb: u32,
+    pub mc: u32,
+    /// Output of the XOR gate, if it is used by the ZIA or the output pin
+    pub comb: Option<XC2EqnExpr>,
+    /// The register, if its output is used
+    pub reg: Option<XC2RegEquations>,
+    /// The signal driving the output pin, if the output buffer is enabled
+    pub output: Option<XC2EqnSignal>,
+    /// Output enable of the output pin
+    pub oe: Option<XC2EqnExpr>,
+}
+
+/// Syntax used when writing equations
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum XC2EqnSyntax {
+    /// ABEL-like syntax (`!`, `&`, `#`, `$`)
+    Abel,
+    /// Verilog expression syntax (`~`, `&`, `|`, `^`)
+    Verilog,
+}
+
+/// A product term over up to 64 variables. `pos` and `neg` have a bit set for every variable that appears
+/// uncomplemented/complemented respectively.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
+struct Cube {
+    pos: u64,
+    neg: u64,
+}
+
+impl Cube {
+    fn lits(self) -> u64 {
+        self.pos | self.neg
+    }
+
+    /// Returns whether every minterm of `other` is also in `self`
+    fn contains(self, other: Cube) -> bool {
+        self.pos & !other.pos == 0 && self.neg & !other.neg == 0
+    }
+
+    fn disjoint(self, other: Cube) -> bool {
+        self.pos & other.neg != 0 || self.neg & other.pos != 0
+    }
+}
+
+/// Computes the cofactor of `cover` with respect to the cube `c`
+fn cofactor(cover: &[Cube], c: Cube) -> Vec<Cube> {
+    cover.iter()
+        .filter(|d| !d.disjoint(c))
+        .map(|d| Cube {pos: d.pos & !c.lits(), neg: d.neg & !c.lits()})
+        .collect()
+}
+
+/// Checks whether `cover` is always true using unate recursion
+fn is_tautology(cover: &[Cube]) -> bool {
+    if cover.iter().any(|c| c.lits() == 0) {
+        return true;
+    }
+
+    // Split on the most binate variable. A unate cover is only a tautology if it contains the universal cube.
+    let mut split = None;
+    let mut split_count = 0;
+    for v in 0..64 {
+        let bit = 1u64 << v;
+        let num_pos = cover.iter().filter(|c| c.pos & bit != 0).count();
+        let num_neg = cover.iter().filter(|c| c.neg & bit != 0).count();
+        if num_pos > 0 && num_neg > 0 && num_pos + num_neg > split_count {
+            split = Some(bit);
+            split_count = num_pos + num_neg;
+        }
+    }
+
+    match split {
+        None => false,
+        Some(bit) => is_tautology(&cofactor(cover, Cube {pos: bit, neg: 0})) &&
+                     is_tautology(&cofactor(cover, Cube {pos: 0, neg: bit})),
+    }
+}
+
+/// Minimizes a cover using an Espresso-style expand and irredundant pass. The result implements exactly the same
+/// function as the input.
+fn minimize(cover: &[Cube]) -> Vec<Cube> {
+    let mut cubes = cover.iter().cloned().filter(|c| c.pos & c.neg == 0).collect::<Vec<_>>();
+    let on_set = cubes.clone();
+
+    // Expand every cube as far as possible while staying inside the on-set, starting with the largest cubes
+    cubes.sort_by_key(|c| c.lits().count_ones());
+    for cube in cubes.iter_mut() {
+        for v in 0..64 {
+            let bit = 1u64 << v;
+            if cube.lits() & bit == 0 {
+                continue;
+            }
+
+            let expanded = Cube {pos: cube.pos & !bit, neg: cube.neg & !bit};
+            if is_tautology(&cofactor(&on_set, expanded)) {
+                *cube = expanded;
+            }
+        }
+    }
+
+    // Remove cubes that are contained in another single cube
+    let mut ret: Vec<Cube> = Vec::new();
+    for cube in cubes {
+        if !ret.iter().any(|d| d.contains(cube)) {
+            ret.push(cube);
+        }
+    }
+
+    // Remove cubes that are covered by the rest of the cover, starting with the smallest ones
+    let mut i = ret.len();
+    while i > 0 {
+        i -= 1;
+        let others = ret.iter().enumerate().filter(|&(j, _)| j != i).map(|(_, &c)| c).collect::<Vec<_>>();
+        if is_tautology(&cofactor(&others, ret[i])) {
+            ret.remove(i);
+        }
+    }
+
+    ret.sort();
+    ret
+}
+
+/// What a ZIA row of a function block contributes to an AND term
+#[derive(Copy, Clone)]
+enum RowLit {
+    Zero,
+    One,
+    Var(usize),
+}
+
+/// Internal helper that maps the ZIA rows of one function block onto equation variables
+struct FBVars {
+    rows: [RowLit; INPUTS_PER_ANDTERM],
+    signals: Vec<XC2EqnSignal>,
+}
+
+/// Internal helper that returns the ZIA mode, whether the output uses the register, and the output mode of the IOB
+/// belonging to a macrocell, if there is one
+pub(crate) fn mc_iob(bits: &XC2BitstreamBits, fb: u32, mc: u32) -> Option<(XC2IOBZIAMode, bool, XC2IOBOBufMode)> {
+    let iob = fb_mc_num_to_iob_num(bits.device_type(), fb, mc)?;
+    bits.get_iob(iob).map(|iob| (iob.zia_mode(), iob.obuf_uses_ff(), iob.obuf_mode()))
+}
+
+impl FBVars {
+    fn new(bits: &XC2BitstreamBits, fb: &XC2BitstreamFB) -> Self {
+        let device = bits.device_type();
+        let mut rows = [RowLit::Zero; INPUTS_PER_ANDTERM];
+        let mut signals = Vec::new();
+
+        for (i, row) in rows.iter_mut().enumerate() {
+            let signal = match *fb.get_zia(i) {
+                XC2ZIAInput::Zero => {
+                    *row = RowLit::Zero;
+                    continue;
+                },
+                XC2ZIAInput::One => {
+                    *row = RowLit::One;
+                    continue;
+                },
+                XC2ZIAInput::Macrocell{fb, mc} => {
+                    let (fb, mc) = (fb as u32, mc as u32);
+                    if bits.get_fb()[fb as usize].mcs[mc as usize].fb_mode == XC2MCFeedbackMode::REG {
+                        XC2EqnSignal::Reg{fb, mc}
+                    } else {
+                        XC2EqnSignal::Comb{fb, mc}
+                    }
+                },
+                XC2ZIAInput::IBuf{ibuf} => {
+                    let (fb, mc) = iob_num_to_fb_mc_num(device, ibuf as u32).unwrap();
+                    match mc_iob(bits, fb, mc) {
+                        Some((XC2IOBZIAMode::REG, _, _)) => XC2EqnSignal::Reg{fb, mc},
+                        _ => XC2EqnSignal::Pad{fb, mc},
+                    }
+                },
+                XC2ZIAInput::DedicatedInput => XC2EqnSignal::InputOnly,
+            };
+
+            let var = match signals.iter().position(|&x| x == signal) {
+                Some(var) => var,
+                None => {
+                    signals.push(signal);
+                    signals.len() - 1
+                }
+            };
+            *row = RowLit::Var(var);
+        }
+
+        FBVars {
+            rows,
+            signals,
+        }
+    }
+
+    /// Converts an AND term into a cube, or `None` if the AND term is always false
+    fn and_term_cube(&self, term: &XC2PLAAndTerm) -> Option<Cube> {
+        let mut cube = Cube {pos: 0, neg: 0};
+        for i in 0..INPUTS_PER_ANDTERM {
+            if term.get(i) {
+                match self.rows[i] {
+                    RowLit::Zero => return None,
+                    RowLit::One => {},
+                    RowLit::Var(v) => cube.pos |= 1 << v,
+                }
+            }
+            if term.get_b(i) {
+                match self.rows[i] {
+                    RowLit::Zero => {},
+                    RowLit::One => return None,
+                    RowLit::Var(v) => cube.neg |= 1 << v,
+                }
+            }
+        }
+
+        if cube.pos & cube.neg != 0 {
+            None
+        } else {
+            Some(cube)
+        }
+    }
+
+    /// Builds a minimized sum of products from the given cubes
+    fn sop(&self, cubes: &[Cube]) -> XC2EqnExpr {
+        let products = minimize(cubes).into_iter().map(|cube| {
+            (0..self.signals.len())
+                .filter(|&v| cube.lits() & (1 << v) != 0)
+                .map(|v| (self.signals[v], cube.neg & (1 << v) != 0))
+                .collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+
+        XC2EqnExpr::Sop(products)
+    }
+
+    /// Builds an expression for a single AND term of the function block
+    fn and_term(&self, fb: &XC2BitstreamFB, term: u32) -> XC2EqnExpr {
+        let cubes = self.and_term_cube(fb.get_andterm(term as usize)).into_iter().collect::<Vec<_>>();
+        self.sop(&cubes)
+    }
+}
+
+fn not(expr: XC2EqnExpr) -> XC2EqnExpr {
+    XC2EqnExpr::Not(Box::new(expr))
+}
+
+impl XC2EqnExpr {
+    fn is_const(&self, val: bool) -> bool {
+        match self {
+            &XC2EqnExpr::Sop(ref products) => if val {
+                products.iter().any(|p| p.is_empty())
+            } else {
+                products.is_empty()
+            },
+            _ => false,
+        }
+    }
+
+    fn is_single_signal(&self) -> bool {
+        match self {
+            &XC2EqnExpr::Sop(ref products) => products.len() == 1 && products[0].len() == 1 && !products[0][0].1,
+            &XC2EqnExpr::Signal(_) => true,
+            _ => false,
+        }
+    }
+
+    fn write<W: Write>(&self, syntax: XC2EqnSyntax, names: &HashMap<(u32, u32), String>, writer: &mut W)
+        -> Result<(), io::Error> {
+
+        let (op_not, op_and, op_or, op_xor) = match syntax {
+            XC2EqnSyntax::Abel => ("!", " & ", " # ", " $ "),
+            XC2EqnSyntax::Verilog => ("~", " & ", " | ", " ^ "),
+        };
+
+        match self {
+            &XC2EqnExpr::Sop(ref products) => {
+                if products.is_empty() {
+                    write!(writer, "{}", const_str(syntax, false))?;
+                }
+                for (i, product) in products.iter().enumerate() {
+                    if i != 0 {
+                        write!(writer, "{}", op_or)?;
+                    }
+                    if product.is_empty() {
+                        write!(writer, "{}", const_str(syntax, true))?;
+                    }
+                    for (j, &(signal, complement)) in product.iter().enumerate() {
+                        if j != 0 {
+                            write!(writer, "{}", op_and)?;
+                        }
+                        if complement {
+                            write!(writer, "{}", op_not)?;
+                        }
+                        write!(writer, "{}", signal_name(syntax, names, signal))?;
+                    }
+                }
+            },
+            &XC2EqnExpr::Signal(signal) => {
+                write!(writer, "{}", signal_name(syntax, names, signal))?;
+            },
+            &XC2EqnExpr::Not(ref expr) => {
+                if expr.is_single_signal() {
+                    write!(writer, "{}", op_not)?;
+                    expr.write(syntax, names, writer)?;
+                } else {
+                    write!(writer, "{}(", op_not)?;
+                    expr.write(syntax, names, writer)?;
+                    write!(writer, ")")?;
+                }
+            },
+            &XC2EqnExpr::Xor(ref a, ref b) => {
+                write!(writer, "(")?;
+                a.write(syntax, names, writer)?;
+                write!(writer, "){}(", op_xor)?;
+                b.write(syntax, names, writer)?;
+                write!(writer, ")")?;
+            },
+        }
+
+        Ok(())
+    }
+}
+
+fn const_str(syntax: XC2EqnSyntax, val: bool) -> &'static str {
+    match (syntax, val) {
+        (XC2EqnSyntax::Abel, false) => "0",
+        (XC2EqnSyntax::Abel, true) => "1",
+        (XC2EqnSyntax::Verilog, false) => "1'b0",
+        (XC2EqnSyntax::Verilog, true) => "1'b1",
+    }
+}
+
+fn mc_name(names: &HashMap<(u32, u32), String>, fb: u32, mc: u32) -> String {
+    match names.get(&(fb, mc)) {
+        Some(name) => name.clone(),
+        None => format!("FB{}_{}", fb + 1, mc + 1),
+    }
+}
+
+pub(crate) fn signal_name(syntax: XC2EqnSyntax, names: &HashMap<(u32, u32), String>, signal: XC2EqnSignal) -> String {
+    let sep = match syntax {
+        XC2EqnSyntax::Abel => ".",
+        XC2EqnSyntax::Verilog => "_",
+    };
+
+    match signal {
+        XC2EqnSignal::Pad{fb, mc} => format!("{}{}PIN", mc_name(names, fb, mc), sep),
+        XC2EqnSignal::Reg{fb, mc} => format!("{}{}Q", mc_name(names, fb, mc), sep),
+        XC2EqnSignal::Comb{fb, mc} => mc_name(names, fb, mc),
+        XC2EqnSignal::InputOnly => "INPUT_ONLY".to_owned(),
+        XC2EqnSignal::GCK(i) => format!("GCK{}", i),
+        XC2EqnSignal::GSR => "GSR".to_owned(),
+        XC2EqnSignal::GTS(i) => format!("GTS{}", i),
+    }
+}
+
+impl XC2MacrocellEquations {
+    /// Writes the equations for this macrocell, one per line. Macrocells are named using `names` if they are in it,
+    /// and as FBn_m otherwise.
+    pub fn write<W: Write>(&self, syntax: XC2EqnSyntax, names: &HashMap<(u32, u32), String>, mut writer: W)
+        -> Result<(), io::Error> {
+
+        let name = mc_name(names, self.fb, self.mc);
+        let write_eqn = |writer: &mut W, suffix: &str, expr: &XC2EqnExpr| -> Result<(), io::Error> {
+            match syntax {
+                XC2EqnSyntax::Abel => {
+                    let sep = if suffix.is_empty() {""} else {"."};
+                    write!(writer, "{}{}{} = ", name, sep, suffix)?;
+                },
+                XC2EqnSyntax::Verilog => {
+                    let sep = if suffix.is_empty() {""} else {"_"};
+                    write!(writer, "assign {}{}{} = ", name, sep, suffix)?;
+                },
+            }
+            expr.write(syntax, names, writer)?;
+            write!(writer, ";\n")
+        };
+        let comment = match syntax {
+            XC2EqnSyntax::Abel => "\"",
+            XC2EqnSyntax::Verilog => "//",
+        };
+
+        if let Some(ref comb) = self.comb {
+            write_eqn(&mut writer, "", comb)?;
+        }
+        if let Some(ref reg) = self.reg {
+            let (input, clk) = match reg.mode {
+                XC2MCRegMode::DFF | XC2MCRegMode::DFFCE => ("D", "CLK"),
+                XC2MCRegMode::TFF => ("T", "CLK"),
+                XC2MCRegMode::LATCH => ("D", "LE"),
+            };
+            write!(writer, "{} {} register, initial state {}{}\n", comment, match reg.mode {
+                XC2MCRegMode::DFF => "D flip-flop",
+                XC2MCRegMode::DFFCE => "D flip-flop with clock-enable",
+                XC2MCRegMode::TFF => "T flip-flop",
+                XC2MCRegMode::LATCH => "latch",
+            }, reg.init_state as u8, if reg.is_ddr {", dual-edge triggered"} else {""})?;
+            write_eqn(&mut writer, input, &reg.input)?;
+            write_eqn(&mut writer, clk, &reg.clk)?;
+            if let Some(ref ce) = reg.ce {
+                write_eqn(&mut writer, "CE", ce)?;
+            }
+            if let Some(ref set) = reg.set {
+                write_eqn(&mut writer, "AP", set)?;
+            }
+            if let Some(ref reset) = reg.reset {
+                write_eqn(&mut writer, "AR", reset)?;
+            }
+        }
+        if let Some(output) = self.output {
+            write_eqn(&mut writer, "O", &XC2EqnExpr::Signal(output))?;
+        }
+        if let Some(ref oe) = self.oe {
+            write_eqn(&mut writer, "OE", oe)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl XC2BitstreamBits {
+    /// Extracts the equations for every macrocell that is used. AND terms with no inputs are treated as constant 1.
+    /// All sums of products are minimized.
+    pub fn equations(&self) -> Vec<XC2MacrocellEquations> {
+        let global_nets = self.get_global_nets();
+        let mut ret = Vec::new();
+
+        for (fb_i, fb) in self.get_fb().iter().enumerate() {
+            let fb_i = fb_i as u32;
+            let vars = FBVars::new(self, fb);
+
+            for mc_i in 0..MCS_PER_FB as u32 {
+                let mc = &fb.mcs[mc_i as usize];
+                let iob = mc_iob(self, fb_i, mc_i);
+
+                // Work out which outputs of the macrocell are used
+                let obuf = iob.and_then(|(_, obuf_uses_ff, obuf_mode)| if obuf_mode == XC2IOBOBufMode::Disabled {
+                    None
+                } else {
+                    Some((obuf_uses_ff, obuf_mode))
+                });
+                let reg_used = mc.fb_mode == XC2MCFeedbackMode::REG ||
+                    obuf.map(|(obuf_uses_ff, _)| obuf_uses_ff) == Some(true) ||
+                    iob.map(|(zia_mode, _, _)| zia_mode) == Some(XC2IOBZIAMode::REG);
+                let comb_used = mc.fb_mode == XC2MCFeedbackMode::COMB ||
+                    obuf.map(|(obuf_uses_ff, _)| obuf_uses_ff) == Some(false);
+                if !reg_used && !comb_used {
+                    continue;
+                }
+
+                // XOR gate
+                let or_cubes = (0..ANDTERMS_PER_FB)
+                    .filter(|&i| fb.or_terms[mc_i as usize].get(i))
+                    .filter_map(|i| vars.and_term_cube(fb.get_andterm(i)))
+                    .collect::<Vec<_>>();
+                let sum = vars.sop(&or_cubes);
+                let ptc = vars.and_term(fb, get_ptc(mc_i));
+                let comb = match mc.xor_mode {
+                    XC2MCXorMode::ZERO => sum,
+                    XC2MCXorMode::ONE => if sum.is_const(false) {
+                        XC2EqnExpr::Sop(vec![vec![]])
+                    } else {
+                        not(sum)
+                    },
+                    XC2MCXorMode::PTC => if sum.is_const(false) {
+                        ptc
+                    } else {
+                        XC2EqnExpr::Xor(Box::new(sum), Box::new(ptc))
+                    },
+                    XC2MCXorMode::PTCB => if sum.is_const(false) {
+                        not(ptc)
+                    } else {
+                        XC2EqnExpr::Xor(Box::new(sum), Box::new(not(ptc)))
+                    },
+                };
+
+                // Register
+                let reg = if reg_used {
+                    let input = if mc.ff_in_ibuf {
+                        XC2EqnExpr::Signal(XC2EqnSignal::Pad{fb: fb_i, mc: mc_i})
+                    } else {
+                        comb.clone()
+                    };
+                    let clk = match mc.clk_src {
+                        XC2MCRegClkSrc::GCK0 => XC2EqnExpr::Signal(XC2EqnSignal::GCK(0)),
+                        XC2MCRegClkSrc::GCK1 => XC2EqnExpr::Signal(XC2EqnSignal::GCK(1)),
+                        XC2MCRegClkSrc::GCK2 => XC2EqnExpr::Signal(XC2EqnSignal::GCK(2)),
+                        XC2MCRegClkSrc::PTC => vars.and_term(fb, get_ptc(mc_i)),
+                        XC2MCRegClkSrc::CTC => vars.and_term(fb, CTC),
+                    };
+                    let clk = if mc.clk_invert_pol {not(clk)} else {clk};
+                    let gsr = if global_nets.gsr_invert {
+                        XC2EqnExpr::Signal(XC2EqnSignal::GSR)
+                    } else {
+                        not(XC2EqnExpr::Signal(XC2EqnSignal::GSR))
+                    };
+                    let set = match mc.s_src {
+                        XC2MCRegSetSrc::Disabled => None,
+                        XC2MCRegSetSrc::PTA => Some(vars.and_term(fb, get_pta(mc_i))),
+                        XC2MCRegSetSrc::GSR => Some(gsr.clone()),
+                        XC2MCRegSetSrc::CTS => Some(vars.and_term(fb, CTS)),
+                    };
+                    let reset = match mc.r_src {
+                        XC2MCRegResetSrc::Disabled => None,
+                        XC2MCRegResetSrc::PTA => Some(vars.and_term(fb, get_pta(mc_i))),
+                        XC2MCRegResetSrc::GSR => Some(gsr),
+                        XC2MCRegResetSrc::CTR => Some(vars.and_term(fb, CTR)),
+                    };
+                    let ce = if mc.reg_mode == XC2MCRegMode::DFFCE {
+                        Some(vars.and_term(fb, get_ptc(mc_i)))
+                    } else {
+                        None
+                    };
+
+                    Some(XC2RegEquations {
+                        mode: mc.reg_mode,
+                        input,
+                        clk,
+                        is_ddr: mc.is_ddr,
+                        ce,
+                        set,
+                        reset,
+                        init_state: mc.init_state,
+                    })
+                } else {
+                    None
+                };
+
+                // Output pin
+                let output = obuf.map(|(obuf_uses_ff, _)| if obuf_uses_ff {
+                    XC2EqnSignal::Reg{fb: fb_i, mc: mc_i}
+                } else {
+                    XC2EqnSignal::Comb{fb: fb_i, mc: mc_i}
+                });
+                let oe = obuf.and_then(|(_, obuf_mode)| {
+                    let gts = |i: usize| if global_nets.gts_invert[i] {
+                        Some(XC2EqnExpr::Signal(XC2EqnSignal::GTS(i as u32)))
+                    } else {
+                        Some(not(XC2EqnExpr::Signal(XC2EqnSignal::GTS(i as u32))))
+                    };
+                    match obuf_mode {
+                        XC2IOBOBufMode::Disabled | XC2IOBOBufMode::PushPull | XC2IOBOBufMode::CGND => None,
+                        XC2IOBOBufMode::OpenDrain => Some(not(XC2EqnExpr::Signal(output.unwrap()))),
+                        XC2IOBOBufMode::TriStateGTS0 => gts(0),
+                        XC2IOBOBufMode::TriStateGTS1 => gts(1),
+                        XC2IOBOBufMode::TriStateGTS2 => gts(2),
+                        XC2IOBOBufMode::TriStateGTS3 => gts(3),
+                        XC2IOBOBufMode::TriStatePTB => Some(vars.and_term(fb, get_ptb(mc_i))),
+                        XC2IOBOBufMode::TriStateCTE => Some(vars.and_term(fb, CTE)),
+                    }
+                });
+
+                ret.push(XC2MacrocellEquations {
+                    fb: fb_i,
+                    mc: mc_i,
+                    comb: if comb_used {Some(comb)} else {None},
+                    reg,
+                    output,
+                    oe,
+                });
+            }
+        }
+
+        ret
+    }
+
+    /// Writes the equations for every macrocell that is used. Macrocells are named using `names` if they are in it,
+    /// and as FBn_m otherwise.
+    pub fn write_equations<W: Write>(&self, syntax: XC2EqnSyntax, names: &HashMap<(u32, u32), String>,
+        mut writer: W) -> Result<(), io::Error> {
+
+        for eqns in self.equations() {
+            eqns.write(syntax, names, &mut writer)?;
+            write!(writer, "\n")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An error in a pin name file that was passed to `parse_pin_names`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XC2PinNamesError {
+    /// The line that could not be parsed (1-based)
+    pub line: usize,
+    /// The contents of that line
+    pub text: String,
+}
+
+impl fmt::Display for XC2PinNamesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: invalid pin name assignment \"{}\"", self.line, self.text)
+    }
+}
+
+impl error::Error for XC2PinNamesError {}
+
+/// Parses a list of pin names that can be passed to `write_equations`. Each line contains a name followed by the
+/// macrocell it belongs to, e.g. `led0 FB1_3`. Empty lines and everything after a `#` are ignored. Every macrocell
+/// can only be named once.
+pub fn parse_pin_names(device: XC2Device, data: &str) -> Result<HashMap<(u32, u32), String>, XC2PinNamesError> {
+    let mut ret = HashMap::new();
+
+    for (i, line) in data.lines().enumerate() {
+        let err = || XC2PinNamesError {
+            line: i + 1,
+            text: line.to_owned(),
+        };
+
+        let contents = line.split('#').next().unwrap();
+        let words = contents.split_whitespace().collect::<Vec<_>>();
+        if words.len() == 0 {
+            continue;
+        }
+        if words.len() != 2 {
+            return Err(err());
+        }
+
+        let loc = parse_fb_mc(device, &words[1].to_ascii_uppercase()).ok_or_else(err)?;
+        if ret.insert(loc, words[0].to_owned()).is_some() {
+            return Err(err());
+        }
+    }
+
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minimize_cubes() {
+        // a&b | a&!b | !a&b&c => a | b&c
+        let cover = [Cube {pos: 0b011, neg: 0}, Cube {pos: 0b001, neg: 0b010}, Cube {pos: 0b110, neg: 0b001}];
+        assert_eq!(minimize(&cover), vec![Cube {pos: 0b001, neg: 0}, Cube {pos: 0b110, neg: 0}]);
+
+        // a | !a => 1
+        let cover = [Cube {pos: 0b1, neg: 0}, Cube {pos: 0, neg: 0b1}];
+        assert_eq!(minimize(&cover), vec![Cube {pos: 0, neg: 0}]);
+
+        // consensus term b&c is redundant in a&b | !a&c | b&c
+        let cover = [Cube {pos: 0b011, neg: 0}, Cube {pos: 0b100, neg: 0b001}, Cube {pos: 0b110, neg: 0}];
+        assert_eq!(minimize(&cover).len(), 2);
+    }
+
+    #[test]
+    fn extract_equations() {
+        let mut bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c32a-4-vq44").unwrap());
+        {
+            let fb = &mut bitstream.bits.get_fb_mut()[0];
+            *fb.get_mut_zia(0) = XC2ZIAInput::IBuf{ibuf: 5};
+            *fb.get_mut_zia(1) = XC2ZIAInput::DedicatedInput;
+            // FB1_6.PIN & INPUT_ONLY | FB1_6.PIN & !INPUT_ONLY
+            fb.get_mut_andterm(20).set(0, true);
+            fb.get_mut_andterm(20).set(1, true);
+            fb.get_mut_andterm(21).set(0, true);
+            fb.get_mut_andterm(21).set_b(1, true);
+            fb.or_terms[2].set(20, true);
+            fb.or_terms[2].set(21, true);
+            fb.mcs[2].fb_mode = XC2MCFeedbackMode::COMB;
+            fb.mcs[2].xor_mode = XC2MCXorMode::ONE;
+        }
+
+        let eqns = bitstream.bits.equations();
+        assert_eq!(eqns.len(), 1);
+
+        let mut abel = Vec::new();
+        eqns[0].write(XC2EqnSyntax::Abel, &HashMap::new(), &mut abel).unwrap();
+        assert_eq!(String::from_utf8(abel).unwrap(), "FB1_3 = !FB1_6.PIN;\n");
+
+        let mut names = HashMap::new();
+        names.insert((0, 5), "foo".to_owned());
+        let mut verilog = Vec::new();
+        eqns[0].write(XC2EqnSyntax::Verilog, &names, &mut verilog).unwrap();
+        assert_eq!(String::from_utf8(verilog).unwrap(), "assign FB1_3 = ~foo_PIN;\n");
+    }
+
+    #[test]
+    fn pin_names() {
+        let device = XC2Device::XC2C32A;
+        let names = parse_pin_names(device, "# LEDs\nled0 FB1_3\n\nbtn fb2_16  # active low\n").unwrap();
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[&(0, 2)], "led0");
+        assert_eq!(names[&(1, 15)], "btn");
+
+        assert_eq!(parse_pin_names(device, "led0 FB1_3\nled1 FB1_3\n"),
+            Err(XC2PinNamesError {line: 2, text: "led1 FB1_3".to_owned()}));
+        assert!(parse_pin_names(device, "led0 FB3_1\n").is_err());
+        assert!(parse_pin_names(device, "led0\n").is_err());
+
+        // Equations use the names for both the macrocell itself and its pin
+        let mut bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c32a-4-vq44").unwrap());
+        {
+            let fb = &mut bitstream.bits.get_fb_mut()[0];
+            *fb.get_mut_zia(0) = XC2ZIAInput::IBuf{ibuf: 31};
+            fb.get_mut_andterm(20).set_b(0, true);
+            fb.or_terms[2].set(20, true);
+            fb.mcs[2].fb_mode = XC2MCFeedbackMode::COMB;
+        }
+        let mut abel = Vec::new();
+        bitstream.bits.write_equations(XC2EqnSyntax::Abel, &names, &mut abel).unwrap();
+        assert!(String::from_utf8(abel).unwrap().contains("led0 = !btn.PIN;\n"));
+    }
+}
@@ -0,0 +1,570 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! BDD-based logic equivalence checking between two bitstreams
+//!
+//! The two designs are matched by pin: every I/O pin must have the same output enable function and, whenever the
+//! output is enabled, the same output value. Input pins are shared between the two designs. Registers can be placed
+//! differently in the two designs, so they are matched up by signal correspondence: registers are repeatedly
+//! partitioned by their next-state, clock, set, reset and clock-enable functions (assuming registers in the same
+//! class are equal) until the partition no longer changes. Registers that end up in the same class are equivalent.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::*;
+use crate::equations::{mc_iob, signal_name};
+
+const BDD_FALSE: u32 = 0;
+const BDD_TRUE: u32 = 1;
+
+/// Minimal reduced ordered binary decision diagram package. Variables are ordered by their index.
+struct Bdd {
+    /// (variable, low child, high child). The first two nodes are the constants.
+    nodes: Vec<(u32, u32, u32)>,
+    unique: HashMap<(u32, u32, u32), u32>,
+    ite_cache: HashMap<(u32, u32, u32), u32>,
+}
+
+impl Bdd {
+    fn new() -> Self {
+        Bdd {
+            nodes: vec![(u32::MAX, 0, 0), (u32::MAX, 1, 1)],
+            unique: HashMap::new(),
+            ite_cache: HashMap::new(),
+        }
+    }
+
+    fn mk(&mut self, var: u32, lo: u32, hi: u32) -> u32 {
+        if lo == hi {
+            return lo;
+        }
+
+        if let Some(&node) = self.unique.get(&(var, lo, hi)) {
+            return node;
+        }
+        let node = self.nodes.len() as u32;
+        self.nodes.push((var, lo, hi));
+        self.unique.insert((var, lo, hi), node);
+        node
+    }
+
+    fn var(&mut self, var: u32) -> u32 {
+        self.mk(var, BDD_FALSE, BDD_TRUE)
+    }
+
+    /// Returns the cofactors of `f` with respect to `var`, which must not be below the top variable of `f`
+    fn cofactors(&self, f: u32, var: u32) -> (u32, u32) {
+        let (f_var, lo, hi) = self.nodes[f as usize];
+        if f_var == var {
+            (lo, hi)
+        } else {
+            (f, f)
+        }
+    }
+
+    fn ite(&mut self, f: u32, g: u32, h: u32) -> u32 {
+        if f == BDD_TRUE {
+            return g;
+        }
+        if f == BDD_FALSE {
+            return h;
+        }
+        if g == h {
+            return g;
+        }
+        if g == BDD_TRUE && h == BDD_FALSE {
+            return f;
+        }
+        if let Some(&node) = self.ite_cache.get(&(f, g, h)) {
+            return node;
+        }
+
+        let var = self.nodes[f as usize].0.min(self.nodes[g as usize].0).min(self.nodes[h as usize].0);
+        let (f_lo, f_hi) = self.cofactors(f, var);
+        let (g_lo, g_hi) = self.cofactors(g, var);
+        let (h_lo, h_hi) = self.cofactors(h, var);
+        let lo = self.ite(f_lo, g_lo, h_lo);
+        let hi = self.ite(f_hi, g_hi, h_hi);
+        let node = self.mk(var, lo, hi);
+
+        self.ite_cache.insert((f, g, h), node);
+        node
+    }
+
+    fn not(&mut self, f: u32) -> u32 {
+        self.ite(f, BDD_FALSE, BDD_TRUE)
+    }
+
+    fn and(&mut self, f: u32, g: u32) -> u32 {
+        self.ite(f, g, BDD_FALSE)
+    }
+
+    fn or(&mut self, f: u32, g: u32) -> u32 {
+        self.ite(f, BDD_TRUE, g)
+    }
+
+    fn xor(&mut self, f: u32, g: u32) -> u32 {
+        let not_g = self.not(g);
+        self.ite(f, not_g, g)
+    }
+
+    /// Returns one assignment of variables that makes `f` true. Variables that are not in the result don't matter.
+    fn sat_one(&self, mut f: u32) -> Option<Vec<(u32, bool)>> {
+        if f == BDD_FALSE {
+            return None;
+        }
+
+        let mut ret = Vec::new();
+        while f != BDD_TRUE {
+            let (var, lo, hi) = self.nodes[f as usize];
+            if lo != BDD_FALSE {
+                ret.push((var, false));
+                f = lo;
+            } else {
+                ret.push((var, true));
+                f = hi;
+            }
+        }
+
+        Some(ret)
+    }
+}
+
+/// A difference in behavior between two bitstreams found by `XC2BitstreamBits::check_equivalence`
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+pub enum XC2EquivMismatch {
+    /// The bitstreams are for different devices and cannot be compared
+    DeviceMismatch {
+        old: XC2Device,
+        new: XC2Device,
+    },
+    /// A function belonging to the pin of the given macrocell differs. `counterexample` contains an assignment of
+    /// input pins and register states for which the two designs behave differently. Signals that are not listed
+    /// don't matter.
+    Function {
+        fb: u32,
+        mc: u32,
+        function: &'static str,
+        counterexample: Vec<(String, bool)>,
+    },
+}
+
+impl fmt::Display for XC2EquivMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &XC2EquivMismatch::DeviceMismatch{old, new} =>
+                write!(f, "device {}→{}", old, new),
+            &XC2EquivMismatch::Function{fb, mc, function, ref counterexample} => {
+                write!(f, "FB{}_{} {} differs when", fb + 1, mc + 1, function)?;
+                if counterexample.is_empty() {
+                    write!(f, " always")?;
+                }
+                for &(ref name, val) in counterexample {
+                    write!(f, " {}={}", name, val as u8)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+enum VarKey {
+    /// A pin shared by both designs
+    Input(XC2EqnSignal),
+    /// The state of all registers in a correspondence class
+    RegClass(usize),
+    /// The state of a register that is not part of the correspondence (design, fb, mc)
+    Reg(usize, u32, u32),
+}
+
+/// The register equations of one design along with the class each register is in
+struct EquivReg<'a> {
+    design: usize,
+    fb: u32,
+    mc: u32,
+    eqns: &'a XC2RegEquations,
+}
+
+struct EquivChecker<'a> {
+    bdd: Bdd,
+    vars: Vec<VarKey>,
+    var_lookup: HashMap<VarKey, u32>,
+    /// The equations of each design, indexed by (fb, mc)
+    eqns: [HashMap<(u32, u32), &'a XC2MacrocellEquations>; 2],
+    regs: Vec<EquivReg<'a>>,
+    /// The class of each register in `regs`
+    reg_class: Vec<usize>,
+    reg_lookup: HashMap<(usize, u32, u32), usize>,
+    /// Memoized combinatorial functions for the current partition, `None` while under construction
+    comb_cache: HashMap<(usize, u32, u32), Option<u32>>,
+}
+
+impl<'a> EquivChecker<'a> {
+    fn var(&mut self, key: VarKey) -> u32 {
+        let var = match self.var_lookup.get(&key) {
+            Some(&var) => var,
+            None => {
+                let var = self.vars.len() as u32;
+                self.vars.push(key);
+                self.var_lookup.insert(key, var);
+                var
+            }
+        };
+        self.bdd.var(var)
+    }
+
+    fn signal(&mut self, design: usize, signal: XC2EqnSignal) -> u32 {
+        match signal {
+            XC2EqnSignal::Comb{fb, mc} => {
+                match self.comb_cache.get(&(design, fb, mc)) {
+                    Some(&Some(node)) => return node,
+                    // Combinatorial loop, cut it with a register-like variable
+                    Some(&None) => return self.var(VarKey::Reg(design, fb, mc)),
+                    None => {},
+                }
+
+                self.comb_cache.insert((design, fb, mc), None);
+                let comb = self.eqns[design].get(&(fb, mc)).and_then(|x| x.comb.as_ref());
+                let node = match comb {
+                    Some(comb) => self.expr(design, comb),
+                    None => BDD_FALSE,
+                };
+                self.comb_cache.insert((design, fb, mc), Some(node));
+                node
+            },
+            XC2EqnSignal::Reg{fb, mc} => {
+                match self.reg_lookup.get(&(design, fb, mc)) {
+                    Some(&reg) => {
+                        let class = self.reg_class[reg];
+                        self.var(VarKey::RegClass(class))
+                    },
+                    None => self.var(VarKey::Reg(design, fb, mc)),
+                }
+            },
+            _ => self.var(VarKey::Input(signal)),
+        }
+    }
+
+    fn expr(&mut self, design: usize, expr: &XC2EqnExpr) -> u32 {
+        match expr {
+            &XC2EqnExpr::Sop(ref products) => {
+                let mut sum = BDD_FALSE;
+                for product in products {
+                    let mut prod = BDD_TRUE;
+                    for &(signal, complement) in product {
+                        let mut lit = self.signal(design, signal);
+                        if complement {
+                            lit = self.bdd.not(lit);
+                        }
+                        prod = self.bdd.and(prod, lit);
+                    }
+                    sum = self.bdd.or(sum, prod);
+                }
+                sum
+            },
+            &XC2EqnExpr::Signal(signal) => self.signal(design, signal),
+            &XC2EqnExpr::Not(ref a) => {
+                let a = self.expr(design, a);
+                self.bdd.not(a)
+            },
+            &XC2EqnExpr::Xor(ref a, ref b) => {
+                let a = self.expr(design, a);
+                let b = self.expr(design, b);
+                self.bdd.xor(a, b)
+            },
+        }
+    }
+
+    fn opt_expr(&mut self, design: usize, expr: &Option<XC2EqnExpr>, default: u32) -> u32 {
+        match expr {
+            &Some(ref expr) => self.expr(design, expr),
+            &None => default,
+        }
+    }
+
+    /// Returns the functions of a register: input, clock, clock enable, set, reset
+    fn reg_functions(&mut self, reg: usize) -> [(&'static str, u32); 5] {
+        let design = self.regs[reg].design;
+        let eqns = self.regs[reg].eqns;
+        let input = self.expr(design, &eqns.input);
+        let clk = self.expr(design, &eqns.clk);
+        let ce = self.opt_expr(design, &eqns.ce, BDD_TRUE);
+        let set = self.opt_expr(design, &eqns.set, BDD_FALSE);
+        let reset = self.opt_expr(design, &eqns.reset, BDD_FALSE);
+
+        [("register input", input), ("register clock", clk), ("register clock enable", ce),
+         ("register set", set), ("register reset", reset)]
+    }
+
+    /// Partitions the registers until the partition is stable
+    fn refine(&mut self) {
+        // Registers can only correspond if their type and initial state match
+        let mut classes = HashMap::new();
+        for i in 0..self.regs.len() {
+            let eqns = self.regs[i].eqns;
+            let key = (eqns.mode, eqns.is_ddr, eqns.init_state);
+            let num_classes = classes.len();
+            self.reg_class[i] = *classes.entry(key).or_insert(num_classes);
+        }
+
+        loop {
+            self.comb_cache.clear();
+            let num_old_classes = self.reg_class.iter().max().map_or(0, |&x| x + 1);
+
+            let mut classes = HashMap::new();
+            let mut new_reg_class = vec![0; self.regs.len()];
+            for (i, new_class) in new_reg_class.iter_mut().enumerate() {
+                let functions = self.reg_functions(i);
+                let key = (self.reg_class[i], functions.iter().map(|&(_, node)| node).collect::<Vec<_>>());
+                let num_classes = classes.len();
+                *new_class = *classes.entry(key).or_insert(num_classes);
+            }
+
+            self.reg_class = new_reg_class;
+            if classes.len() == num_old_classes {
+                break;
+            }
+        }
+
+        self.comb_cache.clear();
+    }
+
+    fn var_name(&self, var: u32) -> String {
+        let names = HashMap::new();
+        let design_name = ["old", "new"];
+        match self.vars[var as usize] {
+            VarKey::Input(signal) => signal_name(XC2EqnSyntax::Abel, &names, signal),
+            VarKey::RegClass(class) => {
+                let members = (0..self.regs.len())
+                    .filter(|&i| self.reg_class[i] == class)
+                    .map(|i| format!("{}({})",
+                        signal_name(XC2EqnSyntax::Abel, &names, XC2EqnSignal::Reg{fb: self.regs[i].fb, mc: self.regs[i].mc}),
+                        design_name[self.regs[i].design]))
+                    .collect::<Vec<_>>();
+                members.join("=")
+            },
+            VarKey::Reg(design, fb, mc) => format!("{}({})",
+                signal_name(XC2EqnSyntax::Abel, &names, XC2EqnSignal::Reg{fb, mc}), design_name[design]),
+        }
+    }
+
+    fn counterexample(&self, diff: u32) -> Vec<(String, bool)> {
+        self.bdd.sat_one(diff).unwrap().into_iter().map(|(var, val)| (self.var_name(var), val)).collect()
+    }
+
+    /// Returns the (output enable, output value) functions of the pin belonging to a macrocell
+    fn pin_functions(&mut self, design: usize, bits: &XC2BitstreamBits, fb: u32, mc: u32) -> (u32, u32) {
+        let obuf_mode = match mc_iob(bits, fb, mc) {
+            Some((_, _, obuf_mode)) => obuf_mode,
+            None => return (BDD_FALSE, BDD_FALSE),
+        };
+
+        let eqns = self.eqns[design].get(&(fb, mc)).cloned();
+        let output = match eqns.and_then(|x| x.output) {
+            Some(output) => self.signal(design, output),
+            None => BDD_FALSE,
+        };
+        let oe = match eqns.and_then(|x| x.oe.as_ref()) {
+            Some(oe) => self.expr(design, oe),
+            None => BDD_TRUE,
+        };
+
+        match obuf_mode {
+            XC2IOBOBufMode::Disabled => (BDD_FALSE, BDD_FALSE),
+            XC2IOBOBufMode::CGND => (BDD_TRUE, BDD_FALSE),
+            XC2IOBOBufMode::OpenDrain => (oe, BDD_FALSE),
+            _ => (oe, output),
+        }
+    }
+}
+
+impl XC2BitstreamBits {
+    /// Checks whether this bitstream and `other` implement the same logic function, matching the two designs by pin.
+    /// Returns every pin whose behavior differs.
+    pub fn check_equivalence(&self, other: &XC2BitstreamBits) -> Vec<XC2EquivMismatch> {
+        let device = self.device_type();
+        if device != other.device_type() {
+            return vec![XC2EquivMismatch::DeviceMismatch{old: device, new: other.device_type()}];
+        }
+
+        let all_eqns = [self.equations(), other.equations()];
+        let mut checker = EquivChecker {
+            bdd: Bdd::new(),
+            vars: Vec::new(),
+            var_lookup: HashMap::new(),
+            eqns: [HashMap::new(), HashMap::new()],
+            regs: Vec::new(),
+            reg_class: Vec::new(),
+            reg_lookup: HashMap::new(),
+            comb_cache: HashMap::new(),
+        };
+        for (design, design_eqns) in all_eqns.iter().enumerate() {
+            for eqns in design_eqns {
+                checker.eqns[design].insert((eqns.fb, eqns.mc), eqns);
+                if let Some(ref reg) = eqns.reg {
+                    checker.reg_lookup.insert((design, eqns.fb, eqns.mc), checker.regs.len());
+                    checker.regs.push(EquivReg {
+                        design,
+                        fb: eqns.fb,
+                        mc: eqns.mc,
+                        eqns: reg,
+                    });
+                    checker.reg_class.push(0);
+                }
+            }
+        }
+        checker.refine();
+
+        let bits = [self, other];
+        let mut ret = Vec::new();
+        for fb in 0..device.num_fbs() as u32 {
+            for mc in 0..MCS_PER_FB as u32 {
+                let (oe_a, out_a) = checker.pin_functions(0, bits[0], fb, mc);
+                let (oe_b, out_b) = checker.pin_functions(1, bits[1], fb, mc);
+
+                if oe_a != oe_b {
+                    let diff = checker.bdd.xor(oe_a, oe_b);
+                    ret.push(XC2EquivMismatch::Function {
+                        fb,
+                        mc,
+                        function: "output enable",
+                        counterexample: checker.counterexample(diff),
+                    });
+                    continue;
+                }
+
+                let out_diff = checker.bdd.xor(out_a, out_b);
+                let diff = checker.bdd.and(oe_a, out_diff);
+                if diff == BDD_FALSE {
+                    continue;
+                }
+
+                // If both pins are driven by registers, report which of the register functions differs
+                let reg_a = checker.eqns[0].get(&(fb, mc)).and_then(|x| x.output);
+                let reg_b = checker.eqns[1].get(&(fb, mc)).and_then(|x| x.output);
+                if let (Some(XC2EqnSignal::Reg{fb: fb_a, mc: mc_a}), Some(XC2EqnSignal::Reg{fb: fb_b, mc: mc_b})) =
+                    (reg_a, reg_b) {
+
+                    let reg_a = checker.reg_lookup[&(0, fb_a, mc_a)];
+                    let reg_b = checker.reg_lookup[&(1, fb_b, mc_b)];
+                    let eqns_a = checker.regs[reg_a].eqns;
+                    let eqns_b = checker.regs[reg_b].eqns;
+                    let functions_a = checker.reg_functions(reg_a);
+                    let functions_b = checker.reg_functions(reg_b);
+
+                    let mismatch = if eqns_a.mode != eqns_b.mode || eqns_a.is_ddr != eqns_b.is_ddr {
+                        Some(("register mode", Vec::new()))
+                    } else if eqns_a.init_state != eqns_b.init_state {
+                        Some(("register initial state", Vec::new()))
+                    } else {
+                        functions_a.iter().zip(functions_b.iter())
+                            .find(|&(&(_, a), &(_, b))| a != b)
+                            .map(|(&(function, a), &(_, b))| {
+                                let diff = checker.bdd.xor(a, b);
+                                (function, checker.counterexample(diff))
+                            })
+                    };
+
+                    if let Some((function, counterexample)) = mismatch {
+                        ret.push(XC2EquivMismatch::Function {
+                            fb,
+                            mc,
+                            function,
+                            counterexample,
+                        });
+                        continue;
+                    }
+                }
+
+                ret.push(XC2EquivMismatch::Function {
+                    fb,
+                    mc,
+                    function: "output",
+                    counterexample: checker.counterexample(diff),
+                });
+            }
+        }
+
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a design where FB1_3 outputs FB1_6.PIN & INPUT_ONLY using the given AND term, ZIA rows, and register
+    fn and_design(term: usize, row_a: usize, row_b: usize, registered: bool) -> XC2BitstreamBits {
+        let mut bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c32a-4-vq44").unwrap());
+        {
+            let fb = &mut bitstream.bits.get_fb_mut()[0];
+            *fb.get_mut_zia(row_a) = XC2ZIAInput::IBuf{ibuf: 5};
+            *fb.get_mut_zia(row_b) = XC2ZIAInput::DedicatedInput;
+            fb.get_mut_andterm(term).set(row_a, true);
+            fb.get_mut_andterm(term).set(row_b, true);
+            fb.or_terms[2].set(term, true);
+        }
+        bitstream.bits.get_mut_small_iob(2).unwrap().obuf_mode = XC2IOBOBufMode::PushPull;
+        bitstream.bits.get_mut_small_iob(2).unwrap().obuf_uses_ff = registered;
+
+        bitstream.bits
+    }
+
+    #[test]
+    fn equivalent_designs() {
+        let a = and_design(20, 0, 1, false);
+        let b = and_design(33, 9, 4, false);
+        assert_eq!(a.check_equivalence(&b), vec![]);
+
+        let a = and_design(20, 0, 1, true);
+        let b = and_design(33, 9, 4, true);
+        assert_eq!(a.check_equivalence(&b), vec![]);
+    }
+
+    #[test]
+    fn different_designs() {
+        let a = and_design(20, 0, 1, false);
+        let mut b = and_design(20, 0, 1, false);
+        b.get_fb_mut()[0].get_mut_andterm(20).set(1, false);
+
+        let mismatches = a.check_equivalence(&b);
+        assert_eq!(mismatches, vec![XC2EquivMismatch::Function {
+            fb: 0,
+            mc: 2,
+            function: "output",
+            counterexample: vec![("FB1_6.PIN".to_owned(), true), ("INPUT_ONLY".to_owned(), false)],
+        }]);
+        assert_eq!(format!("{}", mismatches[0]), "FB1_3 output differs when FB1_6.PIN=1 INPUT_ONLY=0");
+
+        let a = and_design(20, 0, 1, true);
+        let mut b = and_design(20, 0, 1, true);
+        b.get_fb_mut()[0].mcs[2].clk_src = XC2MCRegClkSrc::GCK1;
+        let mismatches = a.check_equivalence(&b);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(format!("{}", mismatches[0]), "FB1_3 register clock differs when GCK0=0 GCK1=1");
+    }
+}
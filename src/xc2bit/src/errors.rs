@@ -24,9 +24,13 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 */
 
 use crate::util::{b2s};
+use crate::crbit::{CrbitParseError};
 
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
 use std::error;
-use std::fmt;
 
 use jedec::*;
 
@@ -35,6 +39,8 @@ use jedec::*;
 pub enum XC2BitError {
     /// The .jed file could not be parsed
     JedParseError(JedParserError),
+    /// The .crbit file could not be parsed
+    CrbitParseError(CrbitParseError),
     /// The device name is invalid
     BadDeviceName(String),
     /// The number of fuses was incorrect for the device
@@ -51,10 +57,18 @@ impl From<JedParserError> for XC2BitError {
     }
 }
 
+impl From<CrbitParseError> for XC2BitError {
+    fn from(err: CrbitParseError) -> Self {
+        XC2BitError::CrbitParseError(err)
+    }
+}
+
+#[cfg(feature = "std")]
 impl error::Error for XC2BitError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             &XC2BitError::JedParseError(ref err) => Some(err),
+            &XC2BitError::CrbitParseError(ref err) => Some(err),
             &XC2BitError::BadDeviceName(_) => None,
             &XC2BitError::WrongFuseCount => None,
             &XC2BitError::UnsupportedOeConfiguration(_) => None,
@@ -69,6 +83,9 @@ impl fmt::Display for XC2BitError {
             &XC2BitError::JedParseError(err) => {
                 write!(f, ".jed parsing failed: {}", err)
             },
+            &XC2BitError::CrbitParseError(ref err) => {
+                write!(f, ".crbit parsing failed: {}", err)
+            },
             &XC2BitError::BadDeviceName(ref devname) => {
                 write!(f, "device name \"{}\" is invalid/unsupported", devname)
             },
@@ -25,12 +25,16 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 //! Contains functions pertaining to function blocks
 
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use std::io;
+#[cfg(feature = "std")]
 use std::io::Write;
 
 use jedec::*;
 
 use crate::*;
+use crate::fusemap_logical::{fb_fuse_idx};
 use crate::fusemap_physical::{zia_block_loc, and_block_loc, or_block_loc};
 use crate::util::{LinebreakSet};
 use crate::zia::{zia_get_row_width};
@@ -140,6 +144,7 @@ impl XC2BitstreamFB {
     /// Dump a human-readable explanation of the settings for this FB to the given `writer` object.
     /// `device` must be the device type this FB was extracted from and is needed to decode I/O pin numbers.
     /// `fb` must be the index of this function block.
+    #[cfg(feature = "std")]
     pub fn dump_human_readable<W: Write>(&self, device: XC2Device, fb: u32, mut writer: W) -> Result<(), io::Error> {
         for i in 0..MCS_PER_FB {
             write!(writer, "\n")?;
@@ -621,6 +626,88 @@ impl XC2BitstreamFB {
         }
     }
 
+    /// Internal function that adds the ZIA, AND term, and OR term fuses of the given function block to a fuse map
+    pub(crate) fn fuse_map(device: XC2Device, fb: u32, fuses: &mut Vec<XC2Fuse>) {
+        let fuse_base = fb_fuse_idx(device, fb);
+        let zia_row_width = zia_get_row_width(device);
+        let is_type1 = match device {
+            XC2Device::XC2C32 | XC2Device::XC2C32A | XC2Device::XC2C64 | XC2Device::XC2C64A | XC2Device::XC2C256 => true,
+            XC2Device::XC2C128 | XC2Device::XC2C384 | XC2Device::XC2C512 => false,
+        };
+
+        // ZIA
+        let (x, y) = zia_block_loc(device, fb);
+        for zia_row in 0..INPUTS_PER_ANDTERM {
+            let mut out_y = y + zia_row;
+            if is_type1 && zia_row >= 20 {
+                // There is an OR array in the middle, 8 rows high
+                out_y += 8;
+            }
+
+            for zia_bit in 0..zia_row_width {
+                fuses.push(XC2Fuse {
+                    function: XC2FuseFunction::Zia{fb, row: zia_row as u32, bit: zia_bit as u32},
+                    jed: Some(fuse_base + zia_row * zia_row_width + zia_bit),
+                    crbit: Some((x + (zia_row_width - 1 - zia_bit) * 2, out_y)),
+                });
+            }
+        }
+
+        // AND block
+        let (x, y, mirror) = and_block_loc(device, fb);
+        let and_fuse_base = fuse_base + zia_row_width * INPUTS_PER_ANDTERM;
+        for phys_term_idx in 0..ANDTERMS_PER_FB {
+            let term_idx = if is_type1 {phys_term_idx} else {AND_BLOCK_TYPE2_P2L_MAP[phys_term_idx]};
+            let true_x = if !mirror {x + phys_term_idx * 2 + 1} else {x - phys_term_idx * 2 - 1};
+            let comp_x = if !mirror {x + phys_term_idx * 2} else {x - phys_term_idx * 2};
+
+            for input_idx in 0..INPUTS_PER_ANDTERM {
+                let mut out_y = y + input_idx;
+                if is_type1 && input_idx >= 20 {
+                    // There is an OR array in the middle, 8 rows high
+                    out_y += 8;
+                }
+
+                let jed_idx = and_fuse_base + term_idx * INPUTS_PER_ANDTERM * 2 + input_idx * 2;
+                fuses.push(XC2Fuse {
+                    function: XC2FuseFunction::AndTerm{fb, term: term_idx as u32, input: input_idx as u32,
+                        complement: false},
+                    jed: Some(jed_idx),
+                    crbit: Some((true_x, out_y)),
+                });
+                fuses.push(XC2Fuse {
+                    function: XC2FuseFunction::AndTerm{fb, term: term_idx as u32, input: input_idx as u32,
+                        complement: true},
+                    jed: Some(jed_idx + 1),
+                    crbit: Some((comp_x, out_y)),
+                });
+            }
+        }
+
+        // OR block
+        let (x, y, mirror) = or_block_loc(device, fb);
+        let or_fuse_base = and_fuse_base + ANDTERMS_PER_FB * INPUTS_PER_ANDTERM * 2;
+        for or_term_idx in 0..MCS_PER_FB {
+            for and_term_idx in 0..ANDTERMS_PER_FB {
+                let (off_x, out_y) = if is_type1 {
+                    (and_term_idx * 2 + (or_term_idx % 2), y + (or_term_idx / 2))
+                } else {
+                    let row = OR_BLOCK_TYPE2_ROW_MAP[and_term_idx / 2];
+                    // Same as in to_crbit, rows >= 23 are "reversed"
+                    let odd_column = if row >= 23 {and_term_idx % 2 == 0} else {and_term_idx % 2 == 1};
+                    (or_term_idx * 2 + if odd_column {1} else {0}, y + row)
+                };
+                let out_x = if !mirror {x + off_x} else {x - off_x};
+
+                fuses.push(XC2Fuse {
+                    function: XC2FuseFunction::OrTerm{fb, mc: or_term_idx as u32, term: and_term_idx as u32},
+                    jed: Some(or_fuse_base + and_term_idx * MCS_PER_FB + or_term_idx),
+                    crbit: Some((out_x, out_y)),
+                });
+            }
+        }
+    }
+
     /// Internal function that reads a function block
     pub fn from_jed(device: XC2Device, fuses: &[bool], fb: u32, fuse_base: usize)
         -> Result<XC2BitstreamFB, XC2BitError> {
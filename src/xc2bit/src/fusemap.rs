@@ -0,0 +1,557 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Queryable map from every fuse of a device to its function and back
+
+use alloc::collections::BTreeSet;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::{Index, IndexMut};
+
+use jedec::*;
+
+use crate::*;
+use crate::util::LinebreakSet;
+use crate::fusemap_logical::{gck_fuse_idx, gsr_fuse_idx, gts_fuse_idx, global_term_fuse_idx, clock_div_fuse_idx,
+                             total_logical_fuse_count};
+use crate::fusemap_physical::{fuse_array_dims, gck_fuse_coords, gsr_fuse_coords, gts_fuse_coords,
+                              global_term_fuse_coord, clock_div_fuse_coord};
+
+/// The function of a single fuse. Index fields are 0-based like everywhere else in this crate, but the `Display`
+/// implementation names function blocks and macrocells 1-based (e.g. FB1_1) to match `dump_human_readable`.
+///
+/// `bit` is `None` for settings that consist of only a single fuse. For multi-fuse settings, it is the index of the
+/// fuse within the setting (for enums this is the bit index used by `encode`/`decode`, for arrays such as
+/// `gck_enable` it is the array index).
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+pub enum XC2FuseFunction {
+    /// One bit of the encoded mux selection for a ZIA row feeding a function block
+    Zia {
+        fb: u32,
+        row: u32,
+        bit: u32,
+    },
+    /// Connects a ZIA row (or its complement) to an AND term
+    AndTerm {
+        fb: u32,
+        term: u32,
+        input: u32,
+        complement: bool,
+    },
+    /// Connects an AND term to the OR term of a macrocell
+    OrTerm {
+        fb: u32,
+        mc: u32,
+        term: u32,
+    },
+    /// A setting of a macrocell
+    Macrocell {
+        fb: u32,
+        mc: u32,
+        field: &'static str,
+        bit: Option<u32>,
+    },
+    /// A setting of an I/O block
+    Iob {
+        iob: u32,
+        field: &'static str,
+        bit: Option<u32>,
+    },
+    /// A setting of the input-only pin on 32-macrocell devices
+    InPin {
+        field: &'static str,
+    },
+    /// A device-wide setting (global nets, clock divider, bank voltages, etc.)
+    Global {
+        field: &'static str,
+        bit: Option<u32>,
+    },
+}
+
+impl XC2FuseFunction {
+    /// Returns whether `other` refers to the same setting as `self`, possibly differing only in the bit index
+    pub fn same_field(&self, other: &XC2FuseFunction) -> bool {
+        match (self, other) {
+            (&XC2FuseFunction::Zia{fb, row, ..}, &XC2FuseFunction::Zia{fb: fb2, row: row2, ..}) =>
+                fb == fb2 && row == row2,
+            (&XC2FuseFunction::Macrocell{fb, mc, field, ..},
+             &XC2FuseFunction::Macrocell{fb: fb2, mc: mc2, field: field2, ..}) =>
+                fb == fb2 && mc == mc2 && field == field2,
+            (&XC2FuseFunction::Iob{iob, field, ..}, &XC2FuseFunction::Iob{iob: iob2, field: field2, ..}) =>
+                iob == iob2 && field == field2,
+            (&XC2FuseFunction::Global{field, ..}, &XC2FuseFunction::Global{field: field2, ..}) =>
+                field == field2,
+            _ => self == other,
+        }
+    }
+}
+
+impl fmt::Display for XC2FuseFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &XC2FuseFunction::Zia{fb, row, bit} =>
+                write!(f, "FB{} ZIA row {} bit {}", fb + 1, row, bit),
+            &XC2FuseFunction::AndTerm{fb, term, input, complement} =>
+                write!(f, "FB{} AND term {} ZIA input {}{}", fb + 1, term, input,
+                    if complement {" complement"} else {""}),
+            &XC2FuseFunction::OrTerm{fb, mc, term} =>
+                write!(f, "FB{}_{} OR term AND term {}", fb + 1, mc + 1, term),
+            &XC2FuseFunction::Macrocell{fb, mc, field, bit} => {
+                write!(f, "FB{}_{} {}", fb + 1, mc + 1, field)?;
+                if let Some(bit) = bit {
+                    write!(f, "[{}]", bit)?;
+                }
+                Ok(())
+            },
+            &XC2FuseFunction::Iob{iob, field, bit} => {
+                write!(f, "IOB {} {}", iob, field)?;
+                if let Some(bit) = bit {
+                    write!(f, "[{}]", bit)?;
+                }
+                Ok(())
+            },
+            &XC2FuseFunction::InPin{field} =>
+                write!(f, "input-only pin {}", field),
+            &XC2FuseFunction::Global{field, bit} => {
+                write!(f, "{}", field)?;
+                if let Some(bit) = bit {
+                    write!(f, "[{}]", bit)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+/// A single fuse along with its location in both the .jed and crbit formats. A location is `None` if the fuse does
+/// not exist in that format (e.g. `ff_in_ibuf` of buried macrocells only exists in the crbit format).
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+pub struct XC2Fuse {
+    pub function: XC2FuseFunction,
+    pub jed: Option<usize>,
+    pub crbit: Option<(usize, usize)>,
+}
+
+/// Map of every fuse modelled by this crate for one particular device. Fuses can be looked up by .jed fuse index,
+/// by crbit coordinates, or by function.
+pub struct XC2FuseMap {
+    device: XC2Device,
+    fuses: Vec<XC2Fuse>,
+    jed_lookup: Vec<Option<usize>>,
+    crbit_lookup: Vec<Option<usize>>,
+}
+
+impl XC2FuseMap {
+    /// Builds the fuse map for the given device
+    pub fn new(device: XC2Device) -> Self {
+        let mut fuses = Vec::new();
+
+        // FBs
+        for fb in 0..device.num_fbs() as u32 {
+            XC2BitstreamFB::fuse_map(device, fb, &mut fuses);
+            for mc in 0..MCS_PER_FB as u32 {
+                XC2Macrocell::fuse_map(device, fb, mc, &mut fuses);
+            }
+        }
+
+        // IOBs
+        for iob in 0..device.num_iobs() as u32 {
+            if device.is_small_iob() {
+                XC2MCSmallIOB::fuse_map(device, iob, &mut fuses);
+            }
+            if device.is_large_iob() {
+                XC2MCLargeIOB::fuse_map(device, iob, &mut fuses);
+            }
+        }
+        if device == XC2Device::XC2C32 || device == XC2Device::XC2C32A {
+            XC2ExtraIBuf::fuse_map(&mut fuses);
+        }
+
+        global_fuse_map(device, &mut fuses);
+
+        let mut jed_lookup = vec![None; total_logical_fuse_count(device)];
+        let (w, h) = fuse_array_dims(device);
+        let mut crbit_lookup = vec![None; w * h];
+        for (i, fuse) in fuses.iter().enumerate() {
+            if let Some(jed) = fuse.jed {
+                jed_lookup[jed] = Some(i);
+            }
+            if let Some((x, y)) = fuse.crbit {
+                crbit_lookup[y * w + x] = Some(i);
+            }
+        }
+
+        XC2FuseMap {
+            device,
+            fuses,
+            jed_lookup,
+            crbit_lookup,
+        }
+    }
+
+    /// Returns the device this fuse map describes
+    pub fn device(&self) -> XC2Device {
+        self.device
+    }
+
+    /// Returns every fuse in the map
+    pub fn fuses(&self) -> &[XC2Fuse] {
+        &self.fuses
+    }
+
+    /// Looks up the fuse at the given .jed fuse index
+    pub fn jed_fuse(&self, idx: usize) -> Option<&XC2Fuse> {
+        self.jed_lookup.get(idx).cloned().unwrap_or(None).map(|i| &self.fuses[i])
+    }
+
+    /// Looks up the fuse at the given crbit coordinates
+    pub fn crbit_fuse(&self, x: usize, y: usize) -> Option<&XC2Fuse> {
+        let (w, h) = fuse_array_dims(self.device);
+        if x >= w || y >= h {
+            return None;
+        }
+
+        self.crbit_lookup[y * w + x].map(|i| &self.fuses[i])
+    }
+
+    /// Looks up the fuse with exactly the given function
+    pub fn lookup(&self, function: &XC2FuseFunction) -> Option<&XC2Fuse> {
+        self.fuses.iter().find(|x| x.function == *function)
+    }
+
+    /// Returns all of the fuses belonging to the same setting as `function` (ignoring its bit index), in bit order
+    pub fn field_fuses(&self, function: &XC2FuseFunction) -> Vec<&XC2Fuse> {
+        self.fuses.iter().filter(|x| x.function.same_field(function)).collect()
+    }
+
+    /// Converts a .jed fuse index into crbit coordinates
+    pub fn jed_to_crbit(&self, idx: usize) -> Option<(usize, usize)> {
+        self.jed_fuse(idx).and_then(|x| x.crbit)
+    }
+
+    /// Converts crbit coordinates into a .jed fuse index
+    pub fn crbit_to_jed(&self, x: usize, y: usize) -> Option<usize> {
+        self.crbit_fuse(x, y).and_then(|x| x.jed)
+    }
+}
+
+/// Internal helper that adds the global net, clock divider, and bank voltage fuses
+fn global_fuse_map(device: XC2Device, fuses: &mut Vec<XC2Fuse>) {
+    let mut push = |field, bit, jed, crbit| {
+        fuses.push(XC2Fuse {
+            function: XC2FuseFunction::Global{field, bit},
+            jed: Some(jed),
+            crbit: Some(crbit),
+        });
+    };
+
+    let (gck0, gck1, gck2) = gck_fuse_coords(device);
+    push("gck_enable", Some(0), gck_fuse_idx(device) + 0, gck0);
+    push("gck_enable", Some(1), gck_fuse_idx(device) + 1, gck1);
+    push("gck_enable", Some(2), gck_fuse_idx(device) + 2, gck2);
+
+    let (gsr_en, gsr_inv) = gsr_fuse_coords(device);
+    push("gsr_invert", None, gsr_fuse_idx(device) + 0, gsr_inv);
+    push("gsr_enable", None, gsr_fuse_idx(device) + 1, gsr_en);
+
+    let ((gts0_en, gts0_inv), (gts1_en, gts1_inv), (gts2_en, gts2_inv), (gts3_en, gts3_inv)) =
+        gts_fuse_coords(device);
+    push("gts_invert", Some(0), gts_fuse_idx(device) + 0, gts0_inv);
+    push("gts_enable", Some(0), gts_fuse_idx(device) + 1, gts0_en);
+    push("gts_invert", Some(1), gts_fuse_idx(device) + 2, gts1_inv);
+    push("gts_enable", Some(1), gts_fuse_idx(device) + 3, gts1_en);
+    push("gts_invert", Some(2), gts_fuse_idx(device) + 4, gts2_inv);
+    push("gts_enable", Some(2), gts_fuse_idx(device) + 5, gts2_en);
+    push("gts_invert", Some(3), gts_fuse_idx(device) + 6, gts3_inv);
+    push("gts_enable", Some(3), gts_fuse_idx(device) + 7, gts3_en);
+
+    push("global_pu", None, global_term_fuse_idx(device), global_term_fuse_coord(device));
+
+    if device.is_large_iob() {
+        let (clken, clkdiv0, clkdiv1, clkdiv2, clkdelay) = clock_div_fuse_coord(device);
+        let clock_fuse_block = clock_div_fuse_idx(device);
+        push("clock_div.enabled", None, clock_fuse_block, clken);
+        push("clock_div.div_ratio", Some(0), clock_fuse_block + 1, clkdiv0);
+        push("clock_div.div_ratio", Some(1), clock_fuse_block + 2, clkdiv1);
+        push("clock_div.div_ratio", Some(2), clock_fuse_block + 3, clkdiv2);
+        push("clock_div.delay", None, clock_fuse_block + 4, clkdelay);
+    }
+
+    // Bank voltages and miscellaneous. These are found by flipping each setting of a blank bitstream and looking for
+    // the fuse that changes, so that they always agree with the encoding in bitstream.rs.
+    let part = XC2DeviceSpeedPackage {
+        dev: device,
+        spd: device.valid_speeds()[0],
+        pkg: device.valid_packages()[0],
+    };
+    let mut blank = XC2Bitstream::blank_bitstream(part).bits;
+    let (blank_jed, blank_crbit) = encode_bits(&blank);
+    let settings = misc_settings(&mut blank).into_iter().map(|(field, bit, _)| (field, bit)).collect::<Vec<_>>();
+    for (setting_i, (field, bit)) in settings.into_iter().enumerate() {
+        let mut bits = blank;
+        {
+            let mut settings = misc_settings(&mut bits);
+            let val = &mut settings[setting_i].2;
+            **val = !**val;
+        }
+        let (jed, crbit) = encode_bits(&bits);
+
+        let jed_idx = (0..jed.len()).find(|&i| jed[i] != blank_jed[i]).unwrap();
+        let (w, h) = crbit.dim();
+        let crbit_coords = (0..h).flat_map(|y| (0..w).map(move |x| (x, y)))
+            .find(|&(x, y)| crbit.get(x, y) != blank_crbit.get(x, y)).unwrap();
+        push(field, bit, jed_idx, crbit_coords);
+    }
+}
+
+/// Internal helper that encodes `bits` into both .jed fuses and a crbit fuse array
+fn encode_bits(bits: &XC2BitstreamBits) -> (Vec<bool>, FuseArray) {
+    let device = bits.device_type();
+
+    let mut jed = JEDECFile::new(total_logical_fuse_count(device));
+    bits.to_jed(&mut jed, &mut LinebreakSet::new());
+
+    let (w, h) = fuse_array_dims(device);
+    let mut fuse_array = FuseArray::from_dim(w, h);
+    bits.to_crbit(&mut fuse_array);
+
+    (jed.f, fuse_array)
+}
+
+/// Internal helper that returns the bank voltage and miscellaneous settings of `bits`, each with its name and bit
+/// index as used in `XC2FuseFunction::Global`
+fn misc_settings(bits: &mut XC2BitstreamBits) -> Vec<(&'static str, Option<u32>, &mut bool)> {
+    let mut ret = Vec::new();
+    match bits {
+        &mut XC2BitstreamBits::XC2C32{ref mut ivoltage, ref mut ovoltage, ..} |
+        &mut XC2BitstreamBits::XC2C64{ref mut ivoltage, ref mut ovoltage, ..} => {
+            ret.push(("ovoltage", None, ovoltage));
+            ret.push(("ivoltage", None, ivoltage));
+        },
+        &mut XC2BitstreamBits::XC2C32A{ref mut legacy_ivoltage, ref mut legacy_ovoltage, ref mut ivoltage,
+            ref mut ovoltage, ..} |
+        &mut XC2BitstreamBits::XC2C64A{ref mut legacy_ivoltage, ref mut legacy_ovoltage, ref mut ivoltage,
+            ref mut ovoltage, ..} => {
+            ret.push(("legacy_ovoltage", None, legacy_ovoltage));
+            ret.push(("legacy_ivoltage", None, legacy_ivoltage));
+            for (i, (ivoltage, ovoltage)) in ivoltage.iter_mut().zip(ovoltage.iter_mut()).enumerate() {
+                ret.push(("ivoltage", Some(i as u32), ivoltage));
+                ret.push(("ovoltage", Some(i as u32), ovoltage));
+            }
+        },
+        &mut XC2BitstreamBits::XC2C128{ref mut ivoltage, ref mut ovoltage, ref mut data_gate, ref mut use_vref, ..} |
+        &mut XC2BitstreamBits::XC2C256{ref mut ivoltage, ref mut ovoltage, ref mut data_gate, ref mut use_vref, ..} => {
+            ret.push(("data_gate", None, data_gate));
+            for (i, ivoltage) in ivoltage.iter_mut().enumerate() {
+                ret.push(("ivoltage", Some(i as u32), ivoltage));
+            }
+            for (i, ovoltage) in ovoltage.iter_mut().enumerate() {
+                ret.push(("ovoltage", Some(i as u32), ovoltage));
+            }
+            ret.push(("use_vref", None, use_vref));
+        },
+        &mut XC2BitstreamBits::XC2C384{ref mut ivoltage, ref mut ovoltage, ref mut data_gate, ref mut use_vref, ..} |
+        &mut XC2BitstreamBits::XC2C512{ref mut ivoltage, ref mut ovoltage, ref mut data_gate, ref mut use_vref, ..} => {
+            ret.push(("data_gate", None, data_gate));
+            for (i, ivoltage) in ivoltage.iter_mut().enumerate() {
+                ret.push(("ivoltage", Some(i as u32), ivoltage));
+            }
+            for (i, ovoltage) in ovoltage.iter_mut().enumerate() {
+                ret.push(("ovoltage", Some(i as u32), ovoltage));
+            }
+            ret.push(("use_vref", None, use_vref));
+        },
+    }
+    ret
+}
+
+/// Internal fuse container that only remembers which fuses were written and in what order. This is used to find out
+/// which fuses are occupied by structures that are encoded using bittwiddler.
+pub(crate) struct FuseRecorder<K> {
+    writes: Vec<(K, bool)>,
+    dummy: bool,
+}
+
+impl<K> FuseRecorder<K> {
+    pub fn new() -> Self {
+        Self {
+            writes: Vec::new(),
+            dummy: false,
+        }
+    }
+}
+
+impl<K> Index<K> for FuseRecorder<K> {
+    type Output = bool;
+
+    fn index(&self, _: K) -> &bool {
+        &self.dummy
+    }
+}
+
+impl<K> IndexMut<K> for FuseRecorder<K> {
+    fn index_mut(&mut self, coords: K) -> &mut bool {
+        self.writes.push((coords, false));
+        &mut self.writes.last_mut().unwrap().1
+    }
+}
+
+/// Internal helper that figures out which fuses belong to which field of a bittwiddler-encoded structure. Each entry
+/// in `probes` must differ from `default` only in the named field, and all entries for the same field must be
+/// adjacent. Together they must cover every possible encoding of the field. The closures encode the structure at its
+/// actual location in the .jed and crbit formats respectively. `f` creates the function of each fuse from the field
+/// name and bit index.
+pub(crate) fn probe_fuse_map<S, FJ, FC, F>(default: &S, probes: &[(&'static str, S)],
+    encode_jed: FJ, encode_crbit: FC, f: F, fuses: &mut Vec<XC2Fuse>)
+    where FJ: Fn(&S, &mut FuseRecorder<usize>),
+          FC: Fn(&S, &mut FuseRecorder<(usize, usize)>),
+          F: Fn(&'static str, Option<u32>) -> XC2FuseFunction {
+
+    let mut jed_default = FuseRecorder::new();
+    encode_jed(default, &mut jed_default);
+    let mut crbit_default = FuseRecorder::new();
+    encode_crbit(default, &mut crbit_default);
+
+    // Positions within the sequence of writes. bittwiddler writes each field in bit order, so sorting these yields
+    // the bits in order.
+    let mut jed_changed = BTreeSet::new();
+    let mut crbit_changed = BTreeSet::new();
+
+    for (probe_i, &(field, ref probe)) in probes.iter().enumerate() {
+        let mut jed = FuseRecorder::new();
+        encode_jed(probe, &mut jed);
+        for (pos, (a, b)) in jed_default.writes.iter().zip(jed.writes.iter()).enumerate() {
+            if a.1 != b.1 {
+                jed_changed.insert(pos);
+            }
+        }
+
+        let mut crbit = FuseRecorder::new();
+        encode_crbit(probe, &mut crbit);
+        for (pos, (a, b)) in crbit_default.writes.iter().zip(crbit.writes.iter()).enumerate() {
+            if a.1 != b.1 {
+                crbit_changed.insert(pos);
+            }
+        }
+
+        if probe_i == probes.len() - 1 || probes[probe_i + 1].0 != field {
+            // Last probe for this field
+            let jed_locs = jed_changed.iter().map(|&pos| jed_default.writes[pos].0).collect::<Vec<_>>();
+            let crbit_locs = crbit_changed.iter().map(|&pos| crbit_default.writes[pos].0).collect::<Vec<_>>();
+            let num_bits = core::cmp::max(jed_locs.len(), crbit_locs.len());
+
+            for bit in 0..num_bits {
+                fuses.push(XC2Fuse {
+                    function: f(field, if num_bits == 1 {None} else {Some(bit as u32)}),
+                    jed: jed_locs.get(bit).cloned(),
+                    crbit: crbit_locs.get(bit).cloned(),
+                });
+            }
+
+            jed_changed.clear();
+            crbit_changed.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+    use std::io::Read;
+
+    #[test]
+    fn fuse_map_covers_all_jed_fuses() {
+        for &device in &[XC2Device::XC2C32, XC2Device::XC2C32A, XC2Device::XC2C64, XC2Device::XC2C64A,
+                         XC2Device::XC2C128, XC2Device::XC2C256, XC2Device::XC2C384, XC2Device::XC2C512] {
+            let map = XC2FuseMap::new(device);
+
+            let mut seen_jed = vec![false; total_logical_fuse_count(device)];
+            let (w, h) = fuse_array_dims(device);
+            let mut seen_crbit = vec![false; w * h];
+            for fuse in map.fuses() {
+                if let Some(jed) = fuse.jed {
+                    assert!(!seen_jed[jed], "{:?} jed fuse {} used twice", device, jed);
+                    seen_jed[jed] = true;
+                }
+                if let Some((x, y)) = fuse.crbit {
+                    assert!(!seen_crbit[y * w + x], "{:?} crbit fuse {}|{} used twice", device, x, y);
+                    seen_crbit[y * w + x] = true;
+                }
+            }
+            assert!(seen_jed.iter().all(|&x| x), "{:?} has unmapped jed fuses", device);
+        }
+    }
+
+    #[test]
+    fn fuse_map_matches_reftests() {
+        let reftests_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/xc2bit/reftests");
+        let mut maps: Vec<XC2FuseMap> = Vec::new();
+
+        for file in ::std::fs::read_dir(reftests_dir).expect("failed to read reftests") {
+            let path = file.expect("failed to get path").path();
+            if path.extension().unwrap() != "jed" {
+                continue;
+            }
+
+            let mut jed_data = Vec::new();
+            File::open(&path).expect("failed to open jed file")
+                .read_to_end(&mut jed_data).expect("failed to read jed file");
+            let jed = JEDECFile::from_bytes(&jed_data).expect("failed to read jed");
+            let bitstream = XC2Bitstream::from_jed(&jed).expect("failed to process jed");
+            let fuse_array = bitstream.to_crbit();
+
+            let device = bitstream.bits.device_type();
+            if !maps.iter().any(|x| x.device() == device) {
+                maps.push(XC2FuseMap::new(device));
+            }
+            let map = maps.iter().find(|x| x.device() == device).unwrap();
+
+            for fuse in map.fuses() {
+                if let (Some(jed_idx), Some(crbit)) = (fuse.jed, fuse.crbit) {
+                    assert_eq!(jed.f[jed_idx], fuse_array[crbit], "{} mismatch in {:?}", fuse.function, path);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fuse_map_lookup() {
+        let map = XC2FuseMap::new(XC2Device::XC2C256);
+
+        let fuse = map.lookup(&XC2FuseFunction::AndTerm{fb: 4, term: 22, input: 13, complement: true}).unwrap();
+        assert_eq!(format!("{}", fuse.function), "FB5 AND term 22 ZIA input 13 complement");
+        assert_eq!(map.jed_fuse(fuse.jed.unwrap()), Some(fuse));
+        assert_eq!(map.jed_to_crbit(fuse.jed.unwrap()), fuse.crbit);
+        let (x, y) = fuse.crbit.unwrap();
+        assert_eq!(map.crbit_to_jed(x, y), fuse.jed);
+
+        let clk_src = map.field_fuses(&XC2FuseFunction::Macrocell{fb: 2, mc: 3, field: "clk_src", bit: None});
+        assert_eq!(clk_src.len(), 3);
+        assert_eq!(format!("{}", clk_src[2].function), "FB3_4 clk_src[2]");
+    }
+}
@@ -24,6 +24,7 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 */
 
 use crate::*;
+use crate::zia::{zia_get_row_width};
 
 /// Helper function that returns the first fuse for a given function block. This is made more complicated by buried
 /// macrocells in the larger devices
@@ -148,6 +149,26 @@ pub fn fb_fuse_idx(device: XC2Device, fb: u32) -> usize {
     }
 }
 
+/// Helper function that returns the first fuse for the given macrocell (and its IOB, if it has one)
+pub fn mc_fuse_idx(device: XC2Device, fb: u32, mc: u32) -> usize {
+    let zia_row_width = zia_get_row_width(device);
+    let mc_block_base = fb_fuse_idx(device, fb) + zia_row_width * INPUTS_PER_ANDTERM +
+        ANDTERMS_PER_FB * INPUTS_PER_ANDTERM * 2 + ANDTERMS_PER_FB * MCS_PER_FB;
+
+    if device.is_small_iob() {
+        mc_block_base + (mc as usize) * 27
+    } else {
+        // Buried macrocells are smaller
+        (0..mc).fold(mc_block_base, |fuse_idx, i| {
+            if fb_mc_num_to_iob_num(device, fb, i).is_some() {
+                fuse_idx + 29
+            } else {
+                fuse_idx + 16
+            }
+        })
+    }
+}
+
 pub fn gck_fuse_idx(device: XC2Device) -> usize {
     match device {
         XC2Device::XC2C32 | XC2Device::XC2C32A => 12256,
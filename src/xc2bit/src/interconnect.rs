@@ -0,0 +1,746 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Boundary-scan interconnect test generation
+//!
+//! Given a JTAG chain and the nets on the board connecting Coolrunner-II pins, this generates EXTEST vectors that
+//! drive a unique code onto every net, writes them out as SVF, and turns the data captured while running them back
+//! into a list of opens, shorts, and stuck nets. The codes are a true/complement counting sequence: every code is
+//! driven once as-is and once inverted, so that shorts between nets can be told apart from stuck nets.
+
+use std::collections::HashSet;
+use std::error;
+use std::fmt;
+use std::io;
+use std::io::Write;
+
+use crate::*;
+
+/// A device in a JTAG chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum XC2ChainDevice {
+    /// A Coolrunner-II part whose boundary-scan register is used by the test. Pins are named using `ports` if they
+    /// are found there, then by the package pins in `info`, and otherwise by the macrocell they belong to (e.g.
+    /// "FB1_5") or "INPUT_ONLY".
+    Coolrunner {
+        name: String,
+        info: XC2JtagInfo,
+        ports: Vec<(String, XC2BoundaryCellSite)>,
+    },
+    /// Any other device, which is kept in BYPASS during the test
+    Bypass {
+        name: String,
+        ir_len: usize,
+    },
+}
+
+impl XC2ChainDevice {
+    /// A Coolrunner-II part whose pins are named by macrocell. The pinout of the package is not known, so every IOB
+    /// of the device can be used.
+    pub fn coolrunner(name: &str, part: XC2DeviceSpeedPackage) -> Self {
+        XC2ChainDevice::Coolrunner {
+            name: name.to_owned(),
+            info: part.dev.jtag_info(),
+            ports: Vec::new(),
+        }
+    }
+
+    /// A Coolrunner-II part with the given package pinout (see `XC2DeviceSpeedPackage::jtag_info_with_pinout`).
+    /// Pins can also be named using the package pins, and pads that are not bonded out cannot be used.
+    pub fn coolrunner_with_pinout(name: &str, part: XC2DeviceSpeedPackage, pinout: &[(&str, XC2BoundaryCellSite)])
+        -> Self {
+
+        XC2ChainDevice::Coolrunner {
+            name: name.to_owned(),
+            info: part.jtag_info_with_pinout(pinout),
+            ports: Vec::new(),
+        }
+    }
+
+    /// A Coolrunner-II part whose pins are named using the ports of a BSDL file. The package pinout is also taken
+    /// from the file.
+    pub fn coolrunner_with_bsdl(name: &str, part: XC2DeviceSpeedPackage, bsdl: &XC2BsdlFile) -> Self {
+        let info = part.jtag_info_with_bsdl(bsdl);
+        let mut ports = Vec::new();
+        for cell in &bsdl.boundary_cells {
+            if cell.function != XC2BoundaryCellFunction::Input || cell.num >= info.boundary.len() {
+                continue;
+            }
+            if let Some(ref port) = cell.port {
+                ports.push((port.clone(), info.boundary[cell.num].site));
+            }
+        }
+
+        XC2ChainDevice::Coolrunner {
+            name: name.to_owned(),
+            info,
+            ports,
+        }
+    }
+
+    /// Any other device, which will be kept in BYPASS
+    pub fn bypass(name: &str, ir_len: usize) -> Self {
+        XC2ChainDevice::Bypass {
+            name: name.to_owned(),
+            ir_len,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            &XC2ChainDevice::Coolrunner{ref name, ..} => name,
+            &XC2ChainDevice::Bypass{ref name, ..} => name,
+        }
+    }
+
+    /// Returns the length of the instruction register
+    pub fn ir_len(&self) -> usize {
+        match self {
+            &XC2ChainDevice::Coolrunner{ref info, ..} => info.ir_len,
+            &XC2ChainDevice::Bypass{ir_len, ..} => ir_len,
+        }
+    }
+
+    /// Returns the length of the data register that is selected during the test
+    pub fn dr_len(&self) -> usize {
+        match self {
+            &XC2ChainDevice::Coolrunner{ref info, ..} => info.boundary.len(),
+            &XC2ChainDevice::Bypass{..} => 1,
+        }
+    }
+
+    /// Returns the site of the given pin name, or `None` if there is no such pin or its pad is not bonded out
+    fn resolve_pin(&self, pin: &str) -> Option<XC2BoundaryCellSite> {
+        let (info, ports) = match self {
+            &XC2ChainDevice::Coolrunner{ref info, ref ports, ..} => (info, ports),
+            &XC2ChainDevice::Bypass{..} => return None,
+        };
+
+        let site = if let Some(&(_, site)) = ports.iter().find(|x| x.0.eq_ignore_ascii_case(pin)) {
+            site
+        } else if let Some(site) = info.site_for_pin(pin) {
+            site
+        } else if pin.eq_ignore_ascii_case("INPUT_ONLY") {
+            XC2BoundaryCellSite::InputOnly
+        } else {
+            let upper = pin.to_ascii_uppercase();
+            if !upper.starts_with("FB") {
+                return None;
+            }
+            let mut split = upper[2..].split('_');
+            let fb = split.next()?.parse::<u32>().ok()?;
+            let mc = split.next()?.parse::<u32>().ok()?;
+            if split.next().is_some() || fb == 0 || mc == 0 {
+                return None;
+            }
+            XC2BoundaryCellSite::Macrocell{fb: fb - 1, mc: mc - 1}
+        };
+
+        if info.is_bonded(site) &&
+            info.boundary.iter().any(|cell| cell.site == site && cell.function == XC2BoundaryCellFunction::Input) {
+            Some(site)
+        } else {
+            None
+        }
+    }
+}
+
+/// A pin of a device in the chain, e.g. "U3" and "FB2_4"
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct XC2BoardPin {
+    pub device: String,
+    pub pin: String,
+}
+
+impl XC2BoardPin {
+    pub fn new(device: &str, pin: &str) -> Self {
+        XC2BoardPin {
+            device: device.to_owned(),
+            pin: pin.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for XC2BoardPin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.device, self.pin)
+    }
+}
+
+/// A net on the board connecting pins of devices in the chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XC2BoardNet {
+    pub name: String,
+    pub pins: Vec<XC2BoardPin>,
+}
+
+/// Boundary-scan cells of a pin, as indices into the data register of the whole chain
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct XC2PinCells {
+    pub input: usize,
+    /// Control and output cells, or `None` for input-only pins
+    pub output: Option<(usize, usize)>,
+}
+
+/// Errors that can occur when generating an interconnect test
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum XC2ScanChainError {
+    /// The net refers to a device that is not in the chain
+    UnknownDevice(String),
+    /// The device does not have the given pin, the pad of the pin is not bonded out in the package, or the device is
+    /// not a Coolrunner-II part
+    UnknownPin(XC2BoardPin),
+    /// The pin appears in more than one net
+    PinInMultipleNets(XC2BoardPin),
+}
+
+impl error::Error for XC2ScanChainError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        None
+    }
+}
+
+impl fmt::Display for XC2ScanChainError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &XC2ScanChainError::UnknownDevice(ref name) => {
+                write!(f, "device \"{}\" is not in the chain", name)
+            },
+            &XC2ScanChainError::UnknownPin(ref pin) => {
+                write!(f, "pin {} does not exist", pin)
+            },
+            &XC2ScanChainError::PinInMultipleNets(ref pin) => {
+                write!(f, "pin {} is in more than one net", pin)
+            },
+        }
+    }
+}
+
+/// A JTAG chain. Devices are listed starting from the one connected to TDI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XC2ScanChain {
+    pub devices: Vec<XC2ChainDevice>,
+}
+
+impl XC2ScanChain {
+    pub fn new(devices: Vec<XC2ChainDevice>) -> Self {
+        XC2ScanChain {
+            devices,
+        }
+    }
+
+    /// Returns the total length of all instruction registers
+    pub fn ir_len(&self) -> usize {
+        self.devices.iter().map(|dev| dev.ir_len()).sum()
+    }
+
+    /// Returns the total length of the data registers selected during the test
+    pub fn dr_len(&self) -> usize {
+        self.devices.iter().map(|dev| dev.dr_len()).sum()
+    }
+
+    /// Returns the position of the first bit of the given device in the data register of the whole chain. Bit 0 is
+    /// the bit closest to TDO.
+    pub fn dr_offset(&self, device: usize) -> usize {
+        self.devices[device + 1..].iter().map(|dev| dev.dr_len()).sum()
+    }
+
+    /// Returns the position of the first bit of the given device in the instruction register of the whole chain
+    pub fn ir_offset(&self, device: usize) -> usize {
+        self.devices[device + 1..].iter().map(|dev| dev.ir_len()).sum()
+    }
+
+    /// Returns the contents of the instruction register that load `instr` into every Coolrunner-II part and BYPASS
+    /// into every other device
+    pub fn ir_vector(&self, instr: XC2JtagInstruction) -> Vec<bool> {
+        let mut ret = vec![true; self.ir_len()];
+        for (i, dev) in self.devices.iter().enumerate() {
+            if let &XC2ChainDevice::Coolrunner{ref info, ..} = dev {
+                let offset = self.ir_offset(i);
+                for bit in 0..info.ir_len {
+                    ret[offset + bit] = instr.opcode() & (1 << bit) != 0;
+                }
+            }
+        }
+        ret
+    }
+
+    /// Returns the boundary-scan cells of the given pin
+    pub fn pin_cells(&self, pin: &XC2BoardPin) -> Result<XC2PinCells, XC2ScanChainError> {
+        let dev_idx = self.devices.iter().position(|dev| dev.name() == pin.device)
+            .ok_or_else(|| XC2ScanChainError::UnknownDevice(pin.device.clone()))?;
+        let dev = &self.devices[dev_idx];
+        let site = dev.resolve_pin(&pin.pin).ok_or_else(|| XC2ScanChainError::UnknownPin(pin.clone()))?;
+        let info = match dev {
+            &XC2ChainDevice::Coolrunner{ref info, ..} => info,
+            &XC2ChainDevice::Bypass{..} => unreachable!(),
+        };
+
+        let offset = self.dr_offset(dev_idx);
+        let find = |function| info.boundary.iter().position(|cell| cell.site == site && cell.function == function);
+        let input = find(XC2BoundaryCellFunction::Input).unwrap();
+        let output = find(XC2BoundaryCellFunction::Output3)
+            .map(|output| (info.boundary[output].control.unwrap() + offset, output + offset));
+
+        Ok(XC2PinCells {
+            input: input + offset,
+            output,
+        })
+    }
+
+    /// Generates the vectors for testing the given nets
+    pub fn generate_interconnect_test(&self, nets: &[XC2BoardNet])
+        -> Result<XC2InterconnectTest, XC2ScanChainError> {
+
+        let mut seen_pins = HashSet::new();
+        let mut test_nets = Vec::new();
+        let mut untestable = Vec::new();
+
+        for net in nets {
+            let mut pins = Vec::new();
+            for pin in &net.pins {
+                let cells = self.pin_cells(pin)?;
+                if !seen_pins.insert(pin.clone()) {
+                    return Err(XC2ScanChainError::PinInMultipleNets(pin.clone()));
+                }
+                pins.push((pin.clone(), cells));
+            }
+
+            match pins.iter().position(|x| x.1.output.is_some()) {
+                Some(driver) => {
+                    let driver = pins.remove(driver);
+                    test_nets.push(XC2TestNet {
+                        name: net.name.clone(),
+                        code: test_nets.len() as u32 + 1,
+                        driver: driver.0,
+                        driver_cells: driver.1,
+                        receivers: pins,
+                    });
+                },
+                None => untestable.push(net.name.clone()),
+            }
+        }
+
+        // Codes are 1 to N, so that neither all-zeros nor all-ones is used
+        let mut width = 2;
+        while (1 << width) - 2 < test_nets.len() {
+            width += 1;
+        }
+
+        let dr_len = self.dr_len();
+        let vectors = (0..width * 2).map(|bit| {
+            let mut vector = XC2ScanVector {
+                tdi: vec![false; dr_len],
+                expected: vec![false; dr_len],
+                mask: vec![false; dr_len],
+            };
+
+            for net in &test_nets {
+                let val = (net.code & (1 << (bit % width)) != 0) ^ (bit >= width);
+                let (control, output) = net.driver_cells.output.unwrap();
+                vector.tdi[control] = true;
+                vector.tdi[output] = val;
+
+                for cells in Some(&net.driver_cells).into_iter().chain(net.receivers.iter().map(|x| &x.1)) {
+                    vector.expected[cells.input] = val;
+                    vector.mask[cells.input] = true;
+                }
+            }
+
+            vector
+        }).collect();
+
+        Ok(XC2InterconnectTest {
+            chain: self.clone(),
+            nets: test_nets,
+            untestable,
+            width,
+            vectors,
+        })
+    }
+}
+
+/// A net as driven during the test
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XC2TestNet {
+    pub name: String,
+    /// Code driven onto the net. Bit `i` is driven during vector `i`, and its complement during vector `i + width`.
+    pub code: u32,
+    pub driver: XC2BoardPin,
+    pub driver_cells: XC2PinCells,
+    pub receivers: Vec<(XC2BoardPin, XC2PinCells)>,
+}
+
+/// One EXTEST vector for the whole chain. Bit 0 is the bit closest to TDO.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XC2ScanVector {
+    /// Data to shift in
+    pub tdi: Vec<bool>,
+    /// Data expected to be captured while this vector is applied
+    pub expected: Vec<bool>,
+    /// Which bits of `expected` are checked
+    pub mask: Vec<bool>,
+}
+
+/// Faults found by an interconnect test
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum XC2InterconnectFault {
+    /// The pin does not receive the value driven onto the net
+    Open {
+        net: String,
+        pin: XC2BoardPin,
+    },
+    /// The two nets are shorted together
+    Short {
+        net_a: String,
+        net_b: String,
+    },
+    /// The net always reads as the same value
+    StuckAt {
+        net: String,
+        value: bool,
+    },
+    /// The driver of the net reads back a value that is not explained by any of the other faults
+    Unexplained {
+        net: String,
+        observed: u32,
+    },
+}
+
+impl fmt::Display for XC2InterconnectFault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &XC2InterconnectFault::Open{ref net, ref pin} => {
+                write!(f, "net {} is open at {}", net, pin)
+            },
+            &XC2InterconnectFault::Short{ref net_a, ref net_b} => {
+                write!(f, "nets {} and {} are shorted", net_a, net_b)
+            },
+            &XC2InterconnectFault::StuckAt{ref net, value} => {
+                write!(f, "net {} is stuck at {}", net, if value {1} else {0})
+            },
+            &XC2InterconnectFault::Unexplained{ref net, observed} => {
+                write!(f, "net {} reads back unexpected code {:b}", net, observed)
+            },
+        }
+    }
+}
+
+/// Converts bits to an SVF hex string. Bit 0 is the least significant bit, which is shifted first.
+fn svf_hex(bits: &[bool]) -> String {
+    let digits = bits.len().div_ceil(4);
+    (0..digits).rev().map(|digit| {
+        let mut val = 0;
+        for bit in 0..4 {
+            if bits.get(digit * 4 + bit) == Some(&true) {
+                val |= 1 << bit;
+            }
+        }
+        ::std::char::from_digit(val, 16).unwrap().to_ascii_uppercase()
+    }).collect()
+}
+
+/// A generated interconnect test
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XC2InterconnectTest {
+    pub chain: XC2ScanChain,
+    pub nets: Vec<XC2TestNet>,
+    /// Nets that cannot be tested because none of their pins can drive
+    pub untestable: Vec<String>,
+    /// Number of bits in each code. There are twice as many vectors, for the code and its complement.
+    pub width: usize,
+    pub vectors: Vec<XC2ScanVector>,
+}
+
+impl XC2InterconnectTest {
+    /// Writes the test as SVF. The first vector is loaded using SAMPLE/PRELOAD before switching to EXTEST, and each
+    /// shift checks the response to the previous vector while loading the next one.
+    pub fn write_svf<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        let ir_len = self.chain.ir_len();
+        let dr_len = self.chain.dr_len();
+
+        write!(writer, "! Boundary-scan interconnect test written by xc2bit\n")?;
+        write!(writer, "! {} nets, {} untestable, {} vectors\n", self.nets.len(), self.untestable.len(),
+            self.vectors.len())?;
+        for net in &self.untestable {
+            write!(writer, "! untestable net {}\n", net)?;
+        }
+        write!(writer, "TRST ABSENT;\nENDIR IDLE;\nENDDR IDLE;\nSTATE RESET;\nSTATE IDLE;\n")?;
+
+        if self.vectors.len() > 0 {
+            write!(writer, "! preload\n")?;
+            write!(writer, "SIR {} TDI ({});\n", ir_len, svf_hex(&self.chain.ir_vector(XC2JtagInstruction::Sample)))?;
+            write!(writer, "SDR {} TDI ({});\n", dr_len, svf_hex(&self.vectors[0].tdi))?;
+            write!(writer, "SIR {} TDI ({});\n", ir_len, svf_hex(&self.chain.ir_vector(XC2JtagInstruction::Extest)))?;
+
+            for (i, vector) in self.vectors.iter().enumerate() {
+                let next = self.vectors.get(i + 1).unwrap_or(vector);
+                write!(writer, "! vector {}\n", i)?;
+                write!(writer, "SDR {} TDI ({}) TDO ({}) MASK ({});\n", dr_len, svf_hex(&next.tdi),
+                    svf_hex(&vector.expected), svf_hex(&vector.mask))?;
+            }
+        }
+
+        write!(writer, "SIR {} TDI ({});\n", ir_len, svf_hex(&self.chain.ir_vector(XC2JtagInstruction::Bypass)))?;
+        write!(writer, "STATE RESET;\n")?;
+
+        Ok(())
+    }
+
+    /// Returns the code together with its complement, as it should be read back over all vectors
+    fn full_code(&self, code: u32) -> u32 {
+        let mask = (1 << self.width) - 1;
+        code | ((!code & mask) << self.width)
+    }
+
+    /// Reads back the code seen at the given input cell over all vectors
+    fn observed_code(&self, captures: &[Vec<bool>], cell: usize) -> u32 {
+        let mut ret = 0;
+        for (bit, capture) in captures.iter().enumerate() {
+            if capture[cell] {
+                ret |= 1 << bit;
+            }
+        }
+        ret
+    }
+
+    /// Turns the data captured during each vector into a list of faults. `captures` must contain the whole data
+    /// register of the chain, captured while each of the vectors was applied.
+    pub fn diagnose(&self, captures: &[Vec<bool>]) -> Vec<XC2InterconnectFault> {
+        assert_eq!(captures.len(), self.vectors.len());
+        let all_ones = (1 << (self.width * 2)) - 1;
+        let mut ret = Vec::new();
+
+        for net in &self.nets {
+            let code = self.full_code(net.code);
+            let readback = self.observed_code(captures, net.driver_cells.input);
+            if readback != code {
+                let fault = if readback == 0 || readback == all_ones {
+                    XC2InterconnectFault::StuckAt {
+                        net: net.name.clone(),
+                        value: readback != 0,
+                    }
+                } else if let Some(other) = self.nets.iter().find(|other| {
+                    let other_code = self.full_code(other.code);
+                    other.name != net.name &&
+                        (readback == other_code || readback == code & other_code || readback == code | other_code)
+                }) {
+
+                    let (net_a, net_b) = if net.name < other.name {
+                        (net.name.clone(), other.name.clone())
+                    } else {
+                        (other.name.clone(), net.name.clone())
+                    };
+                    XC2InterconnectFault::Short {
+                        net_a,
+                        net_b,
+                    }
+                } else {
+                    XC2InterconnectFault::Unexplained {
+                        net: net.name.clone(),
+                        observed: readback,
+                    }
+                };
+
+                if !ret.contains(&fault) {
+                    ret.push(fault);
+                }
+                continue;
+            }
+
+            for &(ref pin, cells) in &net.receivers {
+                if self.observed_code(captures, cells.input) != code {
+                    ret.push(XC2InterconnectFault::Open {
+                        net: net.name.clone(),
+                        pin: pin.clone(),
+                    });
+                }
+            }
+        }
+
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    /// Faults that can be injected into the simulated board
+    enum SimFault {
+        Open(XC2BoardPin),
+        Short(&'static str, &'static str),
+        Stuck(&'static str, bool),
+    }
+
+    /// Simulates the board while the given data register contents are applied in EXTEST, and returns the captured
+    /// data register. Open pins read as 1 because of the pull-ups, and shorts behave as wired-AND.
+    fn simulate(chain: &XC2ScanChain, nets: &[XC2BoardNet], faults: &[SimFault], tdi: &[bool]) -> Vec<bool> {
+        let is_open = |pin: &XC2BoardPin| faults.iter().any(|f| match f {
+            &SimFault::Open(ref x) => x == pin,
+            _ => false,
+        });
+
+        let mut values = HashMap::new();
+        for net in nets {
+            let mut val = true;
+            for pin in &net.pins {
+                let cells = chain.pin_cells(pin).unwrap();
+                if let Some((control, output)) = cells.output {
+                    if tdi[control] && !is_open(pin) {
+                        val &= tdi[output];
+                    }
+                }
+            }
+            values.insert(net.name.as_str(), val);
+        }
+        for fault in faults {
+            match fault {
+                &SimFault::Short(a, b) => {
+                    let val = values[a] & values[b];
+                    values.insert(a, val);
+                    values.insert(b, val);
+                },
+                &SimFault::Stuck(net, val) => {
+                    values.insert(net, val);
+                },
+                _ => {},
+            }
+        }
+
+        let mut capture = tdi.to_vec();
+        for (i, dev) in chain.devices.iter().enumerate() {
+            if let &XC2ChainDevice::Bypass{..} = dev {
+                capture[chain.dr_offset(i)] = false;
+            }
+        }
+        for net in nets {
+            for pin in &net.pins {
+                let cells = chain.pin_cells(pin).unwrap();
+                capture[cells.input] = if is_open(pin) { true } else { values[net.name.as_str()] };
+            }
+        }
+        capture
+    }
+
+    fn test_board() -> (XC2ScanChain, Vec<XC2BoardNet>) {
+        let chain = XC2ScanChain::new(vec![
+            XC2ChainDevice::coolrunner("U1", XC2DeviceSpeedPackage::from_str("xc2c32a-4-vq44").unwrap()),
+            XC2ChainDevice::bypass("U2", 6),
+            XC2ChainDevice::coolrunner("U3", XC2DeviceSpeedPackage::from_str("xc2c64a-5-vq44").unwrap()),
+        ]);
+        let nets = vec![
+            XC2BoardNet {
+                name: "A".to_owned(),
+                pins: vec![XC2BoardPin::new("U1", "FB1_1"), XC2BoardPin::new("U3", "FB1_1")],
+            },
+            XC2BoardNet {
+                name: "B".to_owned(),
+                pins: vec![XC2BoardPin::new("U1", "FB1_2"), XC2BoardPin::new("U3", "FB2_5")],
+            },
+            XC2BoardNet {
+                name: "C".to_owned(),
+                pins: vec![XC2BoardPin::new("U3", "FB4_16"), XC2BoardPin::new("U1", "INPUT_ONLY")],
+            },
+            XC2BoardNet {
+                name: "D".to_owned(),
+                pins: vec![XC2BoardPin::new("U1", "FB2_3"), XC2BoardPin::new("U3", "FB3_3")],
+            },
+        ];
+        (chain, nets)
+    }
+
+    fn run(faults: &[SimFault]) -> Vec<XC2InterconnectFault> {
+        let (chain, nets) = test_board();
+        let test = chain.generate_interconnect_test(&nets).unwrap();
+        let captures = test.vectors.iter().map(|v| simulate(&chain, &nets, faults, &v.tdi)).collect::<Vec<_>>();
+        test.diagnose(&captures)
+    }
+
+    #[test]
+    fn simulated_chain() {
+        assert_eq!(run(&[]), vec![]);
+
+        assert_eq!(run(&[SimFault::Open(XC2BoardPin::new("U3", "FB2_5"))]), vec![XC2InterconnectFault::Open {
+            net: "B".to_owned(),
+            pin: XC2BoardPin::new("U3", "FB2_5"),
+        }]);
+
+        assert_eq!(run(&[SimFault::Short("A", "D")]), vec![XC2InterconnectFault::Short {
+            net_a: "A".to_owned(),
+            net_b: "D".to_owned(),
+        }]);
+
+        assert_eq!(run(&[SimFault::Stuck("C", false)]), vec![XC2InterconnectFault::StuckAt {
+            net: "C".to_owned(),
+            value: false,
+        }]);
+    }
+
+    #[test]
+    fn svf_output() {
+        let (chain, nets) = test_board();
+        assert_eq!(chain.ir_len(), 8 + 6 + 8);
+        assert_eq!(chain.dr_len(), 97 + 1 + 192);
+
+        let test = chain.generate_interconnect_test(&nets).unwrap();
+        assert_eq!(test.width, 3);
+
+        let mut svf = Vec::new();
+        test.write_svf(&mut svf).unwrap();
+        let svf = String::from_utf8(svf).unwrap();
+        // EXTEST for both Coolrunner-II parts with BYPASS in between
+        assert!(svf.contains("SIR 22 TDI (003F00);\n"));
+        assert_eq!(svf.matches("TDO").count(), 6);
+
+        assert_eq!(svf_hex(&[true, false, false, false, true]), "11");
+
+        let bad = vec![XC2BoardNet {
+            name: "X".to_owned(),
+            pins: vec![XC2BoardPin::new("U2", "FB1_1")],
+        }];
+        assert_eq!(chain.generate_interconnect_test(&bad),
+            Err(XC2ScanChainError::UnknownPin(XC2BoardPin::new("U2", "FB1_1"))));
+    }
+
+    #[test]
+    fn unbonded_pins() {
+        let part = XC2DeviceSpeedPackage::from_str("xc2c64a-5-vq44").unwrap();
+        let chain = XC2ScanChain::new(vec![
+            XC2ChainDevice::coolrunner_with_pinout("U1", part, &[("P1", XC2BoundaryCellSite::Macrocell{fb: 0, mc: 3})]),
+            XC2ChainDevice::coolrunner("U2", part),
+        ]);
+
+        let by_name = chain.pin_cells(&XC2BoardPin::new("U1", "p1")).unwrap();
+        assert_eq!(chain.pin_cells(&XC2BoardPin::new("U1", "FB1_4")), Ok(by_name));
+        assert_eq!(chain.pin_cells(&XC2BoardPin::new("U1", "FB1_1")),
+            Err(XC2ScanChainError::UnknownPin(XC2BoardPin::new("U1", "FB1_1"))));
+        assert_eq!(chain.pin_cells(&XC2BoardPin::new("U1", "P2")),
+            Err(XC2ScanChainError::UnknownPin(XC2BoardPin::new("U1", "P2"))));
+        assert!(chain.pin_cells(&XC2BoardPin::new("U2", "FB1_1")).is_ok());
+    }
+}
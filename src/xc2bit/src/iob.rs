@@ -25,11 +25,15 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 //! Contains functions pertaining to the I/O pins
 
+use alloc::vec::Vec;
 use core::fmt;
+use core::ops::IndexMut;
 
 use jedec::*;
 
 use crate::*;
+use crate::fusemap::{FuseRecorder, probe_fuse_map};
+use crate::fusemap_logical::{mc_fuse_idx};
 use crate::fusemap_physical::{mc_block_loc};
 use crate::mc::{MC_TO_ROW_MAP_LARGE};
 use crate::zia::{zia_get_row_width};
@@ -38,6 +42,7 @@ use crate::zia::{zia_get_row_width};
 /// input pin directly or from the output of the register in the macrocell corresponding to this I/O pin. The latter
 /// is used to allow for buried combinatorial feedback in a macrocell without "wasting" the register.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[derive(BitPattern)]
 pub enum XC2IOBZIAMode {
     #[bits = "X1"]
@@ -60,6 +65,7 @@ impl fmt::Display for XC2IOBZIAMode {
 
 /// Mode selection for the I/O pin's output buffer. See the Xilinx Coolrunner-II documentation for more information.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[derive(BitPattern)]
 #[bits_default = "XC2BitError::UnsupportedOeConfiguration(x)"]
 #[bits_errtype = "XC2BitError"]
@@ -105,6 +111,7 @@ impl fmt::Display for XC2IOBOBufMode {
 
 /// Represents an I/O pin on "small" (32 and 64 macrocell) devices.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[derive(BitTwiddler)]
 // FIXME: Probably should not be pub
 #[bittwiddler = "jed_internal pub err=XC2BitError"]
@@ -180,7 +187,9 @@ impl XC2MCSmallIOB {
     /// Write the crbit representation of the settings for this IO pin to the given `fuse_array`.
     /// `device` must be the device type this FB was extracted from.
     /// `iob` must be the index of this IO pin.
-    pub fn to_crbit(&self, device: XC2Device, iob: u32, fuse_array: &mut FuseArray) {
+    pub fn to_crbit<T>(&self, device: XC2Device, iob: u32, fuse_array: &mut T)
+        where T: IndexMut<(usize, usize), Output=bool> + ?Sized {
+
         let (fb, mc) = iob_num_to_fb_mc_num(device, iob).unwrap();
         let (x, y, mirror) = mc_block_loc(device, fb);
         match device {
@@ -228,6 +237,37 @@ impl XC2MCSmallIOB {
         Self::decode_jed_internal(fuses, fuse_idx)
     }
 
+    /// Internal function that adds the fuses of the given IO pin to a fuse map
+    pub(crate) fn fuse_map(device: XC2Device, iob: u32, fuses: &mut Vec<XC2Fuse>) {
+        let d = XC2MCSmallIOB::default();
+        let probes = [
+            ("zia_mode", XC2MCSmallIOB{zia_mode: XC2IOBZIAMode::PAD, ..d}),
+            ("zia_mode", XC2MCSmallIOB{zia_mode: XC2IOBZIAMode::REG, ..d}),
+            ("schmitt_trigger", XC2MCSmallIOB{schmitt_trigger: !d.schmitt_trigger, ..d}),
+            ("obuf_uses_ff", XC2MCSmallIOB{obuf_uses_ff: !d.obuf_uses_ff, ..d}),
+            ("obuf_mode", XC2MCSmallIOB{obuf_mode: XC2IOBOBufMode::PushPull, ..d}),
+            ("obuf_mode", XC2MCSmallIOB{obuf_mode: XC2IOBOBufMode::OpenDrain, ..d}),
+            ("obuf_mode", XC2MCSmallIOB{obuf_mode: XC2IOBOBufMode::TriStateGTS0, ..d}),
+            ("obuf_mode", XC2MCSmallIOB{obuf_mode: XC2IOBOBufMode::TriStateGTS1, ..d}),
+            ("obuf_mode", XC2MCSmallIOB{obuf_mode: XC2IOBOBufMode::TriStateGTS2, ..d}),
+            ("obuf_mode", XC2MCSmallIOB{obuf_mode: XC2IOBOBufMode::TriStateGTS3, ..d}),
+            ("obuf_mode", XC2MCSmallIOB{obuf_mode: XC2IOBOBufMode::TriStatePTB, ..d}),
+            ("obuf_mode", XC2MCSmallIOB{obuf_mode: XC2IOBOBufMode::TriStateCTE, ..d}),
+            ("obuf_mode", XC2MCSmallIOB{obuf_mode: XC2IOBOBufMode::CGND, ..d}),
+            ("termination_enabled", XC2MCSmallIOB{termination_enabled: !d.termination_enabled, ..d}),
+            ("slew", XC2MCSmallIOB{slew_is_fast: !d.slew_is_fast, ..d}),
+        ];
+
+        let (fb, mc) = iob_num_to_fb_mc_num(device, iob).unwrap();
+        let fuse_idx = mc_fuse_idx(device, fb, mc);
+
+        probe_fuse_map(&d, &probes,
+            |x, rec: &mut FuseRecorder<usize>| x.encode_jed_internal(rec, fuse_idx),
+            |x, rec: &mut FuseRecorder<(usize, usize)>| x.to_crbit(device, iob, rec),
+            |field, bit| XC2FuseFunction::Iob{iob, field, bit},
+            fuses);
+    }
+
     /// Helper that prints the IOB and macrocell configuration on the "small" parts
     pub fn to_jed(&self, jed: &mut JEDECFile, device: XC2Device, fuse_base: usize, i: usize) {
         let zia_row_width = zia_get_row_width(device);
@@ -240,6 +280,7 @@ impl XC2MCSmallIOB {
 
 /// Input mode selection on larger parts with VREF
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[derive(BitPattern)]
 pub enum XC2IOBIbufMode {
     /// This input buffer is not using VREF, and it is also not using the Schmitt trigger
@@ -269,6 +310,7 @@ impl fmt::Display for XC2IOBIbufMode {
 
 /// Represents an I/O pin on "large" (128 and greater macrocell) devices.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[derive(BitTwiddler)]
 // FIXME: Probably should not be pub
 #[bittwiddler = "jed_internal pub err=XC2BitError"]
@@ -350,7 +392,9 @@ impl XC2MCLargeIOB {
     /// Write the crbit representation of the settings for this IO pin to the given `fuse_array`.
     /// `device` must be the device type this FB was extracted from.
     /// `iob` must be the index of this IO pin.
-    pub fn to_crbit(&self, device: XC2Device, iob: u32, fuse_array: &mut FuseArray) {
+    pub fn to_crbit<T>(&self, device: XC2Device, iob: u32, fuse_array: &mut T)
+        where T: IndexMut<(usize, usize), Output=bool> + ?Sized {
+
         let (fb, mc) = iob_num_to_fb_mc_num(device, iob).unwrap();
         let (x, y, mirror) = mc_block_loc(device, fb);
         match device {
@@ -398,6 +442,40 @@ impl XC2MCLargeIOB {
         Self::decode_jed_internal(fuses, fuse_idx)
     }
 
+    /// Internal function that adds the fuses of the given IO pin to a fuse map
+    pub(crate) fn fuse_map(device: XC2Device, iob: u32, fuses: &mut Vec<XC2Fuse>) {
+        let d = XC2MCLargeIOB::default();
+        let probes = [
+            ("zia_mode", XC2MCLargeIOB{zia_mode: XC2IOBZIAMode::PAD, ..d}),
+            ("zia_mode", XC2MCLargeIOB{zia_mode: XC2IOBZIAMode::REG, ..d}),
+            ("ibuf_mode", XC2MCLargeIOB{ibuf_mode: XC2IOBIbufMode::NoVrefNoSt, ..d}),
+            ("ibuf_mode", XC2MCLargeIOB{ibuf_mode: XC2IOBIbufMode::UsesVref, ..d}),
+            ("ibuf_mode", XC2MCLargeIOB{ibuf_mode: XC2IOBIbufMode::IsVref, ..d}),
+            ("obuf_uses_ff", XC2MCLargeIOB{obuf_uses_ff: !d.obuf_uses_ff, ..d}),
+            ("obuf_mode", XC2MCLargeIOB{obuf_mode: XC2IOBOBufMode::PushPull, ..d}),
+            ("obuf_mode", XC2MCLargeIOB{obuf_mode: XC2IOBOBufMode::OpenDrain, ..d}),
+            ("obuf_mode", XC2MCLargeIOB{obuf_mode: XC2IOBOBufMode::TriStateGTS0, ..d}),
+            ("obuf_mode", XC2MCLargeIOB{obuf_mode: XC2IOBOBufMode::TriStateGTS1, ..d}),
+            ("obuf_mode", XC2MCLargeIOB{obuf_mode: XC2IOBOBufMode::TriStateGTS2, ..d}),
+            ("obuf_mode", XC2MCLargeIOB{obuf_mode: XC2IOBOBufMode::TriStateGTS3, ..d}),
+            ("obuf_mode", XC2MCLargeIOB{obuf_mode: XC2IOBOBufMode::TriStatePTB, ..d}),
+            ("obuf_mode", XC2MCLargeIOB{obuf_mode: XC2IOBOBufMode::TriStateCTE, ..d}),
+            ("obuf_mode", XC2MCLargeIOB{obuf_mode: XC2IOBOBufMode::CGND, ..d}),
+            ("termination_enabled", XC2MCLargeIOB{termination_enabled: !d.termination_enabled, ..d}),
+            ("slew", XC2MCLargeIOB{slew_is_fast: !d.slew_is_fast, ..d}),
+            ("uses_data_gate", XC2MCLargeIOB{uses_data_gate: !d.uses_data_gate, ..d}),
+        ];
+
+        let (fb, mc) = iob_num_to_fb_mc_num(device, iob).unwrap();
+        let fuse_idx = mc_fuse_idx(device, fb, mc);
+
+        probe_fuse_map(&d, &probes,
+            |x, rec: &mut FuseRecorder<usize>| x.encode_jed_internal(rec, fuse_idx),
+            |x, rec: &mut FuseRecorder<(usize, usize)>| x.to_crbit(device, iob, rec),
+            |field, bit| XC2FuseFunction::Iob{iob, field, bit},
+            fuses);
+    }
+
     /// Helper that prints the IOB configuration on the "large" parts
     pub fn to_jed(&self, jed: &mut JEDECFile, fuse_base: usize) {
         self.encode_jed_internal(&mut jed.f, fuse_base);
@@ -406,6 +484,7 @@ impl XC2MCLargeIOB {
 
 /// Represents the one additional special input-only pin on 32-macrocell devices.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[derive(BitTwiddler)]
 #[bittwiddler = "jed pub abs"]
 #[bittwiddler = "crbit pub abs"]
@@ -430,6 +509,23 @@ impl Default for XC2ExtraIBuf {
     }
 }
 
+impl XC2ExtraIBuf {
+    /// Internal function that adds the fuses of the input-only pin to a fuse map
+    pub(crate) fn fuse_map(fuses: &mut Vec<XC2Fuse>) {
+        let d = XC2ExtraIBuf::default();
+        let probes = [
+            ("schmitt_trigger", XC2ExtraIBuf{schmitt_trigger: !d.schmitt_trigger, ..d}),
+            ("termination_enabled", XC2ExtraIBuf{termination_enabled: !d.termination_enabled, ..d}),
+        ];
+
+        probe_fuse_map(&d, &probes,
+            |x, rec: &mut FuseRecorder<usize>| x.encode_jed(rec),
+            |x, rec: &mut FuseRecorder<(usize, usize)>| x.encode_crbit(rec),
+            |field, _| XC2FuseFunction::InPin{field},
+            fuses);
+    }
+}
+
 impl fmt::Display for XC2ExtraIBuf {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Schmitt trigger input: {}\n", if self.schmitt_trigger {"yes"} else {"no"})?;
@@ -0,0 +1,399 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! JTAG details of Coolrunner-II parts: IDCODE, instruction register, and boundary-scan register
+//!
+//! The boundary-scan register contains three cells for every macrocell, in the order input, control, output,
+//! starting from FB1_1 next to TDO. Buried macrocells still have their cells, but they are marked as internal. On
+//! the XC2C32(A), the last cell belongs to the input-only pin. Vendor BSDL files can be compared against this model
+//! using `XC2BsdlFile::check`.
+//!
+//! Which pads are bonded out, and to which pins, depends on the package. This is described by the `pins` of
+//! `XC2JtagInfo`, which come from the PIN_MAP_STRING of the vendor BSDL file of the part.
+
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::*;
+
+/// Length of the JTAG instruction register of all Coolrunner-II parts
+pub const XC2_IR_LEN: usize = 8;
+
+/// JEDEC manufacturer ID of Xilinx, as it appears in bits 11 to 1 of the IDCODE
+pub const XILINX_MANUFACTURER_ID: u32 = 0x049;
+
+/// All Coolrunner-II dies, used when identifying a device from its IDCODE
+const ALL_DEVICES: [XC2Device; 8] = [XC2Device::XC2C32, XC2Device::XC2C32A, XC2Device::XC2C64, XC2Device::XC2C64A,
+    XC2Device::XC2C128, XC2Device::XC2C256, XC2Device::XC2C384, XC2Device::XC2C512];
+
+/// Returns the device with the given IDCODE that was scanned out of a JTAG chain, or `None` if this is not a
+/// Coolrunner-II device
+pub fn identify_idcode(idcode: u32) -> Option<XC2Device> {
+    ALL_DEVICES.iter().cloned().find(|dev| dev.idcode_matches(idcode))
+}
+
+/// JTAG instructions supported by Coolrunner-II parts
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum XC2JtagInstruction {
+    Extest,
+    Idcode,
+    Intest,
+    Sample,
+    Highz,
+    Clamp,
+    Usercode,
+    Bypass,
+    IscEnable,
+    IscEnableClamp,
+    IscEnableOtf,
+    IscSramWrite,
+    IscSramRead,
+    IscDisable,
+    IscProgram,
+    IscErase,
+    IscRead,
+    IscInit,
+    IscNoop,
+}
+
+impl XC2JtagInstruction {
+    /// Returns all of the instructions
+    pub fn all() -> &'static [XC2JtagInstruction] {
+        &[XC2JtagInstruction::Extest, XC2JtagInstruction::Idcode, XC2JtagInstruction::Intest,
+          XC2JtagInstruction::Sample, XC2JtagInstruction::Highz, XC2JtagInstruction::Clamp,
+          XC2JtagInstruction::Usercode, XC2JtagInstruction::Bypass, XC2JtagInstruction::IscEnable,
+          XC2JtagInstruction::IscEnableClamp, XC2JtagInstruction::IscEnableOtf, XC2JtagInstruction::IscSramWrite,
+          XC2JtagInstruction::IscSramRead, XC2JtagInstruction::IscDisable, XC2JtagInstruction::IscProgram,
+          XC2JtagInstruction::IscErase, XC2JtagInstruction::IscRead, XC2JtagInstruction::IscInit,
+          XC2JtagInstruction::IscNoop]
+    }
+
+    /// Returns the value that has to be shifted into the instruction register
+    pub fn opcode(&self) -> u8 {
+        match *self {
+            XC2JtagInstruction::Extest => 0x00,
+            XC2JtagInstruction::Idcode => 0x01,
+            XC2JtagInstruction::Intest => 0x02,
+            XC2JtagInstruction::Sample => 0x03,
+            XC2JtagInstruction::Highz => 0xFC,
+            XC2JtagInstruction::Clamp => 0xFA,
+            XC2JtagInstruction::Usercode => 0xFD,
+            XC2JtagInstruction::Bypass => 0xFF,
+            XC2JtagInstruction::IscEnable => 0xE8,
+            XC2JtagInstruction::IscEnableClamp => 0xE9,
+            XC2JtagInstruction::IscEnableOtf => 0xE4,
+            XC2JtagInstruction::IscSramWrite => 0xE6,
+            XC2JtagInstruction::IscSramRead => 0xE7,
+            XC2JtagInstruction::IscDisable => 0xC0,
+            XC2JtagInstruction::IscProgram => 0xEA,
+            XC2JtagInstruction::IscErase => 0xED,
+            XC2JtagInstruction::IscRead => 0xEE,
+            XC2JtagInstruction::IscInit => 0xF0,
+            XC2JtagInstruction::IscNoop => 0xE0,
+        }
+    }
+
+    /// Returns the name of the instruction as used in BSDL files
+    pub fn name(&self) -> &'static str {
+        match *self {
+            XC2JtagInstruction::Extest => "EXTEST",
+            XC2JtagInstruction::Idcode => "IDCODE",
+            XC2JtagInstruction::Intest => "INTEST",
+            XC2JtagInstruction::Sample => "SAMPLE",
+            XC2JtagInstruction::Highz => "HIGHZ",
+            XC2JtagInstruction::Clamp => "CLAMP",
+            XC2JtagInstruction::Usercode => "USERCODE",
+            XC2JtagInstruction::Bypass => "BYPASS",
+            XC2JtagInstruction::IscEnable => "ISC_ENABLE",
+            XC2JtagInstruction::IscEnableClamp => "ISC_ENABLE_CLAMP",
+            XC2JtagInstruction::IscEnableOtf => "ISC_ENABLEOTF",
+            XC2JtagInstruction::IscSramWrite => "ISC_SRAM_WRITE",
+            XC2JtagInstruction::IscSramRead => "ISC_SRAM_READ",
+            XC2JtagInstruction::IscDisable => "ISC_DISABLE",
+            XC2JtagInstruction::IscProgram => "ISC_PROGRAM",
+            XC2JtagInstruction::IscErase => "ISC_ERASE",
+            XC2JtagInstruction::IscRead => "ISC_READ",
+            XC2JtagInstruction::IscInit => "ISC_INIT",
+            XC2JtagInstruction::IscNoop => "ISC_NOOP",
+        }
+    }
+}
+
+impl fmt::Display for XC2JtagInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Function of a boundary-scan cell, using the BSDL names
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum XC2BoundaryCellFunction {
+    Input,
+    Output2,
+    Output3,
+    Control,
+    ControlR,
+    Bidir,
+    Clock,
+    ObserveOnly,
+    Internal,
+}
+
+impl XC2BoundaryCellFunction {
+    /// Returns the name of the function as used in BSDL files
+    pub fn name(&self) -> &'static str {
+        match *self {
+            XC2BoundaryCellFunction::Input => "input",
+            XC2BoundaryCellFunction::Output2 => "output2",
+            XC2BoundaryCellFunction::Output3 => "output3",
+            XC2BoundaryCellFunction::Control => "control",
+            XC2BoundaryCellFunction::ControlR => "controlr",
+            XC2BoundaryCellFunction::Bidir => "bidir",
+            XC2BoundaryCellFunction::Clock => "clock",
+            XC2BoundaryCellFunction::ObserveOnly => "observe_only",
+            XC2BoundaryCellFunction::Internal => "internal",
+        }
+    }
+
+    /// Parses a BSDL cell function name. The comparison is case-insensitive.
+    pub fn from_name(name: &str) -> Option<Self> {
+        [XC2BoundaryCellFunction::Input, XC2BoundaryCellFunction::Output2, XC2BoundaryCellFunction::Output3,
+         XC2BoundaryCellFunction::Control, XC2BoundaryCellFunction::ControlR, XC2BoundaryCellFunction::Bidir,
+         XC2BoundaryCellFunction::Clock, XC2BoundaryCellFunction::ObserveOnly, XC2BoundaryCellFunction::Internal]
+            .iter().cloned().find(|x| x.name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for XC2BoundaryCellFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The resource that a boundary-scan cell belongs to
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum XC2BoundaryCellSite {
+    /// Cell of the IOB attached to a macrocell, or an internal cell of a buried macrocell
+    Macrocell {
+        fb: u32,
+        mc: u32,
+    },
+    /// The input-only pin on the XC2C32(A)
+    InputOnly,
+}
+
+/// One cell of the boundary-scan register
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct XC2BoundaryCell {
+    pub function: XC2BoundaryCellFunction,
+    pub site: XC2BoundaryCellSite,
+    /// Index of the IOB, or `None` for buried macrocells and the input-only pin
+    pub iob: Option<u32>,
+    /// For output cells, the index of the control cell that enables the output. The output is disabled when the
+    /// control cell contains 0.
+    pub control: Option<usize>,
+}
+
+/// Package pin of an IOB or of the input-only pin
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum XC2PackagePin {
+    /// The pad is bonded out to the pin with the given name, such as "P12" or "A3"
+    Bonded(String),
+    /// The pad is not bonded out in this package
+    Unbonded,
+}
+
+/// JTAG details of a part
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct XC2JtagInfo {
+    /// Expected IDCODE, with the bits that are not checked set to 0
+    pub idcode: u32,
+    /// Bits of the IDCODE that identify this part
+    pub idcode_mask: u32,
+    /// Length of the instruction register
+    pub ir_len: usize,
+    /// Boundary-scan register, starting from the cell closest to TDO
+    pub boundary: Vec<XC2BoundaryCell>,
+    /// Package pin of every site that has an input cell, in the order of the boundary-scan register, or `None` if
+    /// the pinout of the package is not known
+    pub pins: Option<Vec<(XC2BoundaryCellSite, XC2PackagePin)>>,
+}
+
+impl XC2JtagInfo {
+    /// Returns `true` if the given IDCODE read over JTAG belongs to this part
+    pub fn idcode_matches(&self, idcode: u32) -> bool {
+        idcode & self.idcode_mask == self.idcode
+    }
+
+    /// Returns the input, control, and output cell indices of the given IOB
+    pub fn iob_cells(&self, iob: u32) -> Option<(usize, usize, usize)> {
+        let input = self.boundary.iter().position(|cell|
+            cell.iob == Some(iob) && cell.function == XC2BoundaryCellFunction::Input)?;
+        let output = self.boundary.iter().position(|cell|
+            cell.iob == Some(iob) && cell.function == XC2BoundaryCellFunction::Output3)?;
+        Some((input, self.boundary[output].control?, output))
+    }
+
+    /// Returns the cell index of the input-only pin, if there is one
+    pub fn input_only_cell(&self) -> Option<usize> {
+        self.boundary.iter().position(|cell| cell.site == XC2BoundaryCellSite::InputOnly)
+    }
+
+    /// Returns the package pin of the given site, or `None` if the pinout is not known or the site has no pad
+    pub fn package_pin(&self, site: XC2BoundaryCellSite) -> Option<&XC2PackagePin> {
+        self.pins.as_ref()?.iter().find(|x| x.0 == site).map(|x| &x.1)
+    }
+
+    /// Returns the site that is bonded out to the package pin with the given name. The comparison is
+    /// case-insensitive.
+    pub fn site_for_pin(&self, pin: &str) -> Option<XC2BoundaryCellSite> {
+        self.pins.as_ref()?.iter().find(|x| match x.1 {
+            XC2PackagePin::Bonded(ref name) => name.eq_ignore_ascii_case(pin),
+            XC2PackagePin::Unbonded => false,
+        }).map(|x| x.0)
+    }
+
+    /// Returns `false` if the pad of the given site is known to be unbonded in this package
+    pub fn is_bonded(&self, site: XC2BoundaryCellSite) -> bool {
+        self.package_pin(site) != Some(&XC2PackagePin::Unbonded)
+    }
+}
+
+impl XC2Device {
+    /// Returns the boundary-scan register of the device, starting from the cell closest to TDO
+    pub fn boundary_scan_cells(&self) -> Vec<XC2BoundaryCell> {
+        let mut ret = Vec::new();
+
+        for fb in 0..self.num_fbs() as u32 {
+            for mc in 0..MCS_PER_FB as u32 {
+                let site = XC2BoundaryCellSite::Macrocell{fb, mc};
+                let iob = fb_mc_num_to_iob_num(*self, fb, mc);
+                if iob.is_some() {
+                    let control = ret.len() + 1;
+                    ret.push(XC2BoundaryCell{function: XC2BoundaryCellFunction::Input, site, iob, control: None});
+                    ret.push(XC2BoundaryCell{function: XC2BoundaryCellFunction::Control, site, iob, control: None});
+                    ret.push(XC2BoundaryCell{
+                        function: XC2BoundaryCellFunction::Output3, site, iob, control: Some(control)});
+                } else {
+                    for _ in 0..3 {
+                        ret.push(XC2BoundaryCell{
+                            function: XC2BoundaryCellFunction::Internal, site, iob: None, control: None});
+                    }
+                }
+            }
+        }
+
+        if *self == XC2Device::XC2C32 || *self == XC2Device::XC2C32A {
+            ret.push(XC2BoundaryCell {
+                function: XC2BoundaryCellFunction::Input,
+                site: XC2BoundaryCellSite::InputOnly,
+                iob: None,
+                control: None,
+            });
+        }
+
+        ret
+    }
+
+    /// Returns the JTAG details of the device. The registers do not depend on the package, but the pinout does, so
+    /// `pins` is `None`.
+    pub fn jtag_info(&self) -> XC2JtagInfo {
+        XC2JtagInfo {
+            idcode: self.idcode(),
+            idcode_mask: XC2_IDCODE_MASK,
+            ir_len: XC2_IR_LEN,
+            boundary: self.boundary_scan_cells(),
+            pins: None,
+        }
+    }
+}
+
+impl XC2DeviceSpeedPackage {
+    /// Returns the JTAG details of this part, with `pins` taken from the given package pinout. The pinout is a list
+    /// of package pin names and the sites that are bonded out to them. A site that appears more than once is bonded
+    /// out to the first of its pins, and every site with an input cell that does not appear is marked as unbonded.
+    pub fn jtag_info_with_pinout(&self, pinout: &[(&str, XC2BoundaryCellSite)]) -> XC2JtagInfo {
+        let mut info = self.dev.jtag_info();
+        let pins = info.boundary.iter().filter(|cell| cell.function == XC2BoundaryCellFunction::Input)
+            .map(|cell| {
+                let pin = match pinout.iter().find(|x| x.1 == cell.site) {
+                    Some(&(name, _)) => XC2PackagePin::Bonded(name.to_owned()),
+                    None => XC2PackagePin::Unbonded,
+                };
+                (cell.site, pin)
+            }).collect();
+        info.pins = Some(pins);
+        info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identify() {
+        assert_eq!(identify_idcode(0x06E1C093), Some(XC2Device::XC2C32A));
+        assert_eq!(identify_idcode(0x26D4A093), Some(XC2Device::XC2C256));
+        assert_eq!(identify_idcode(0x06E1C095), None);
+        assert_eq!(identify_idcode(0xFFFFFFFF), None);
+    }
+
+    #[test]
+    fn boundary_lengths() {
+        let info = XC2Device::XC2C32A.jtag_info();
+        assert_eq!(info.boundary.len(), 97);
+        assert_eq!(info.input_only_cell(), Some(96));
+        let (input, control, output) = info.iob_cells(5).unwrap();
+        assert_eq!(info.boundary[input].function, XC2BoundaryCellFunction::Input);
+        assert_eq!(info.boundary[control].function, XC2BoundaryCellFunction::Control);
+        assert_eq!(info.boundary[output].control, Some(control));
+
+        let info = XC2Device::XC2C128.jtag_info();
+        assert_eq!(info.boundary.len(), 128 * 3);
+        assert_eq!(info.boundary.iter().filter(|c| c.function == XC2BoundaryCellFunction::Internal).count(),
+            28 * 3);
+        assert_eq!(info.pins, None);
+        assert!(info.is_bonded(XC2BoundaryCellSite::Macrocell{fb: 0, mc: 0}));
+    }
+
+    #[test]
+    fn package_pinout() {
+        let part = XC2DeviceSpeedPackage::from_str("xc2c64a-5-vq44").unwrap();
+        let info = part.jtag_info_with_pinout(&[
+            ("P1", XC2BoundaryCellSite::Macrocell{fb: 0, mc: 3}),
+            ("P2", XC2BoundaryCellSite::Macrocell{fb: 3, mc: 15}),
+        ]);
+        let pins = info.pins.as_ref().unwrap();
+        assert_eq!(pins.len(), 64);
+        assert_eq!(pins.iter().filter(|x| x.1 != XC2PackagePin::Unbonded).count(), 2);
+        assert_eq!(info.package_pin(XC2BoundaryCellSite::Macrocell{fb: 3, mc: 15}),
+            Some(&XC2PackagePin::Bonded("P2".to_owned())));
+        assert!(!info.is_bonded(XC2BoundaryCellSite::Macrocell{fb: 0, mc: 0}));
+        assert_eq!(info.site_for_pin("p1"), Some(XC2BoundaryCellSite::Macrocell{fb: 0, mc: 3}));
+    }
+}
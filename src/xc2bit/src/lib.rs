@@ -41,6 +41,18 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //! a function block. (The reverse is not always true - on larger devices, there are macrocells that are not connected
 //! to IOBs.) As a special exception, the smallest 32-macrocell devices also have one single input-only pin that is
 //! connected directly into the global interconnect and does not have a corresponding macrocell.
+//!
+//! The `std` feature is enabled by default. Without it, only `alloc` is needed, and what remains is reading .jed and
+//! crbit files into an `XC2Bitstream`, writing it back out, and iterating over the rows of a `FuseArray` in the order
+//! that they are programmed. Output is then written to a `jedec::ByteWrite` instead of a `std::io::Write`. The crate
+//! in `tests/no_std` checks that this still builds without std.
+//!
+//! The optional `arbitrary` feature implements `arbitrary::Arbitrary` for `XC2Bitstream` and its parts, which can be
+//! used to generate random bitstreams that are valid for their device (see `XC2Bitstream::arbitrary_for_device`).
+
+#![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
+
+extern crate alloc;
 
 use bittwiddler::*;
 use serde_derive::{Deserialize, Serialize};
@@ -68,8 +80,39 @@ pub const NUM_BUFG_GSR: usize = 1;
 mod bitstream;
 pub use crate::bitstream::{XC2Bitstream, XC2BitstreamBits};
 
+#[cfg(feature = "std")]
+mod bsdl;
+#[cfg(feature = "std")]
+pub use crate::bsdl::{XC2BsdlError, XC2BsdlCell, XC2BsdlFile, XC2BsdlMismatch};
+
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
+pub use crate::builder::{XC2Product, XC2BuilderError, XC2RegisterSpec, XC2BitstreamBuilder};
+
+#[cfg(feature = "std")]
+mod coverage;
+#[cfg(feature = "std")]
+pub use crate::coverage::{XC2UnconsumedFuse, find_unconsumed_fuses};
+
 mod crbit;
-pub use crate::crbit::{FuseArray};
+pub use crate::crbit::{FuseArray, CrbitParseError, CRBIT_VERSION};
+
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "std")]
+pub use crate::diff::{XC2BitstreamDiff};
+
+#[cfg(feature = "std")]
+mod equations;
+#[cfg(feature = "std")]
+pub use crate::equations::{XC2EqnSignal, XC2EqnExpr, XC2RegEquations, XC2MacrocellEquations, XC2EqnSyntax,
+    XC2PinNamesError, parse_pin_names};
+
+#[cfg(feature = "std")]
+mod equiv;
+#[cfg(feature = "std")]
+pub use crate::equiv::{XC2EquivMismatch};
 
 mod errors;
 pub use crate::errors::{XC2BitError};
@@ -77,32 +120,87 @@ pub use crate::errors::{XC2BitError};
 mod fb;
 pub use crate::fb::{XC2BitstreamFB, CTC, CTR, CTS, CTE, get_pta, get_ptb, get_ptc};
 
+mod fusemap;
+pub use crate::fusemap::{XC2FuseMap, XC2Fuse, XC2FuseFunction};
+
 mod fusemap_logical;
 mod fusemap_physical;
 
 mod globalbits;
 pub use crate::globalbits::{XC2GlobalNets, XC2ClockDivRatio, XC2ClockDiv};
 
+#[cfg(feature = "std")]
+mod interconnect;
+#[cfg(feature = "std")]
+pub use crate::interconnect::{XC2ChainDevice, XC2BoardPin, XC2BoardNet, XC2PinCells, XC2ScanChainError, XC2ScanChain,
+    XC2TestNet, XC2ScanVector, XC2InterconnectFault, XC2InterconnectTest};
+
 mod iob;
 pub use crate::iob::{XC2MCSmallIOB, XC2IOBZIAMode, XC2IOBOBufMode, XC2ExtraIBuf, XC2IOBIbufMode, XC2MCLargeIOB,
                      iob_num_to_fb_mc_num, fb_mc_num_to_iob_num};
 
+mod jtag;
+pub use crate::jtag::{XC2_IR_LEN, XILINX_MANUFACTURER_ID, identify_idcode, XC2JtagInstruction,
+    XC2BoundaryCellFunction, XC2BoundaryCellSite, XC2BoundaryCell, XC2PackagePin, XC2JtagInfo};
+
 mod mc;
 pub use crate::mc::{XC2Macrocell, XC2MCRegClkSrc, XC2MCRegResetSrc, XC2MCRegSetSrc, XC2MCRegMode, XC2MCFeedbackMode,
                     XC2MCXorMode};
 
+#[cfg(feature = "std")]
+mod netlist;
+#[cfg(feature = "std")]
+pub use crate::netlist::{XC2NetlistError};
+
 mod partdb;
-pub use crate::partdb::{XC2Device, XC2Speed, XC2Package, XC2DeviceSpeedPackage};
+pub use crate::partdb::{XC2Device, XC2Speed, XC2Package, XC2DeviceSpeedPackage, XC2Family, XC2TempGrade, XC2Part,
+    XC2_IDCODE_MASK};
 
 mod pla;
 pub use crate::pla::{XC2PLAAndTerm, XC2PLAOrTerm};
 
+#[cfg(feature = "std")]
+mod rrgraph;
+#[cfg(feature = "std")]
+pub use crate::rrgraph::{XC2RRNodeKind, XC2RRMuxSetting, XC2RRNode, XC2RRWire, XC2RRPortDir, XC2RRPin,
+    XC2RoutingGraph};
+
+#[cfg(feature = "std")]
+mod solver;
+#[cfg(feature = "std")]
+pub use crate::solver::{XC2FuseCorpus, XC2TagSolution};
+
+#[cfg(feature = "std")]
 mod structure;
-pub use crate::structure::{get_gck, get_gts, get_gsr, get_cdrst, get_dge, get_device_structure};
+#[cfg(feature = "std")]
+pub use crate::structure::{get_gck, get_gts, get_gsr, get_cdrst, get_dge, get_device_structure, XC2ConnectionKind,
+    XC2ZIAFeedbackPath};
+
+#[cfg(feature = "std")]
+mod svg;
+
+#[cfg(feature = "std")]
+mod ternary;
+#[cfg(feature = "std")]
+pub use crate::ternary::{TernaryFuse, TernaryFuseArray, modelled_crbit_mask, modelled_jed_mask};
+
+#[cfg(feature = "std")]
+mod utilization;
+#[cfg(feature = "std")]
+pub use crate::utilization::{XC2Utilization, XC2FBUtilization};
+
+#[cfg(feature = "std")]
+mod visit;
+#[cfg(feature = "std")]
+pub use crate::visit::{XC2IOBRef, XC2FBRef, XC2ZIASelection, XC2AndTermRef, XC2OrTermRef, XC2MacrocellRef, XC2IOBLoc,
+    XC2ResourceVisitor};
+
+#[cfg(any(test, feature = "arbitrary"))]
+mod random;
 
 mod zia;
-pub use crate::zia::{XC2ZIAInput, zia_table_get_row, ZIA_MAP_32, ZIA_MAP_64, ZIA_MAP_128, ZIA_MAP_256,
-                     ZIA_MAP_384, ZIA_MAP_512};
+pub use crate::zia::{XC2ZIAInput, zia_table_get_row, zia_encode_choice, zia_rows_for_input, zia_inverse_table,
+    zia_assign_rows, ZIA_MAP_32, ZIA_MAP_64, ZIA_MAP_128, ZIA_MAP_256, ZIA_MAP_384, ZIA_MAP_512};
 
 mod util;
 
@@ -146,10 +244,20 @@ mod tests {
         parsed_bitstream_data.to_jed(&mut new_jed).expect("failed to write jed");
         assert_eq!(jed_data, new_jed);
 
-        let mut human_readable_data = Vec::new();
-        parsed_bitstream_data.dump_human_readable(&mut human_readable_data)
-            .expect("failed to get human readable");
-        assert_eq!(txt_data, human_readable_data);
+        // Converting to a netlist and back must give exactly the same fuses
+        #[cfg(feature = "std")]
+        {
+            let netlist = parsed_bitstream_data.to_netlist();
+            let netlist_bitstream = XC2Bitstream::from_netlist(&netlist).expect("failed to process netlist");
+            let mut netlist_jed = Vec::new();
+            netlist_bitstream.to_jed(&mut netlist_jed).expect("failed to write jed");
+            assert_eq!(jed_data, netlist_jed);
+
+            let mut human_readable_data = Vec::new();
+            parsed_bitstream_data.dump_human_readable(&mut human_readable_data)
+                .expect("failed to get human readable");
+            assert_eq!(txt_data, human_readable_data);
+        }
     }
 
     // Include list of actual tests to run
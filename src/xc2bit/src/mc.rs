@@ -25,17 +25,22 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 //! Contains functions pertaining to macrocells
 
+use alloc::vec::Vec;
 use core::fmt;
+use core::ops::IndexMut;
 
 use jedec::*;
 
 use crate::*;
+use crate::fusemap::{FuseRecorder, probe_fuse_map};
+use crate::fusemap_logical::{mc_fuse_idx};
 use crate::fusemap_physical::{mc_block_loc};
 use crate::util::{LinebreakSet};
 use crate::zia::{zia_get_row_width};
 
 /// Clock source for the register in a macrocell
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[derive(BitPattern)]
 pub enum XC2MCRegClkSrc {
     #[bits = "x00"]
@@ -52,6 +57,7 @@ pub enum XC2MCRegClkSrc {
 
 /// Reset source for the register in a macrocell
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[derive(BitPattern)]
 pub enum XC2MCRegResetSrc {
     #[bits = "11"]
@@ -66,6 +72,7 @@ pub enum XC2MCRegResetSrc {
 
 /// Set source for the register in a macrocell
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[derive(BitPattern)]
 pub enum XC2MCRegSetSrc {
     #[bits = "11"]
@@ -80,6 +87,7 @@ pub enum XC2MCRegSetSrc {
 
 /// Mode of the register in a macrocell.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[derive(BitPattern)]
 pub enum XC2MCRegMode {
     /// D-type flip-flop
@@ -99,6 +107,7 @@ pub enum XC2MCRegMode {
 /// Mux selection for the ZIA input from this macrocell. The ZIA input can be chosen to come from either the XOR gate
 /// or from the output of the register.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[derive(BitPattern)]
 pub enum XC2MCFeedbackMode {
     #[bits = "X1"]
@@ -112,6 +121,7 @@ pub enum XC2MCFeedbackMode {
 /// Mux selection for the "not from OR gate" input to the XOR gate. The XOR gate in a macrocell contains two inputs,
 /// the output of the corresponding OR term from the PLA and a specific dedicated AND term from the PLA.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[derive(BitPattern)]
 pub enum XC2MCXorMode {
     /// A constant zero which results in this XOR outputting the value of the OR term
@@ -130,6 +140,7 @@ pub enum XC2MCXorMode {
 
 /// Represents a macrocell.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 #[derive(BitTwiddler)]
 #[bittwiddler = "jed_internal_small"]
 #[bittwiddler = "jed_internal_large"]
@@ -313,7 +324,9 @@ impl fmt::Display for XC2Macrocell {
 
 impl XC2Macrocell {
     /// Write the crbit representation of this macrocell to the given `fuse_array`.
-    pub fn to_crbit(&self, device: XC2Device, fb: u32, mc: u32, fuse_array: &mut FuseArray) {
+    pub fn to_crbit<T>(&self, device: XC2Device, fb: u32, mc: u32, fuse_array: &mut T)
+        where T: IndexMut<(usize, usize), Output=bool> + ?Sized {
+
         let (x, y, mirror) = mc_block_loc(device, fb);
         match device {
             XC2Device::XC2C32 | XC2Device::XC2C32A => {
@@ -374,6 +387,52 @@ impl XC2Macrocell {
         }
     }
 
+    /// Internal function that adds the fuses of the given macrocell to a fuse map
+    pub(crate) fn fuse_map(device: XC2Device, fb: u32, mc: u32, fuses: &mut Vec<XC2Fuse>) {
+        let d = XC2Macrocell::default();
+        let probes = [
+            ("clk_src", XC2Macrocell{clk_src: XC2MCRegClkSrc::GCK1, ..d}),
+            ("clk_src", XC2Macrocell{clk_src: XC2MCRegClkSrc::GCK2, ..d}),
+            ("clk_src", XC2Macrocell{clk_src: XC2MCRegClkSrc::PTC, ..d}),
+            ("clk_src", XC2Macrocell{clk_src: XC2MCRegClkSrc::CTC, ..d}),
+            ("clk_invert_pol", XC2Macrocell{clk_invert_pol: !d.clk_invert_pol, ..d}),
+            ("is_ddr", XC2Macrocell{is_ddr: !d.is_ddr, ..d}),
+            ("r_src", XC2Macrocell{r_src: XC2MCRegResetSrc::PTA, ..d}),
+            ("r_src", XC2Macrocell{r_src: XC2MCRegResetSrc::GSR, ..d}),
+            ("r_src", XC2Macrocell{r_src: XC2MCRegResetSrc::CTR, ..d}),
+            ("s_src", XC2Macrocell{s_src: XC2MCRegSetSrc::PTA, ..d}),
+            ("s_src", XC2Macrocell{s_src: XC2MCRegSetSrc::GSR, ..d}),
+            ("s_src", XC2Macrocell{s_src: XC2MCRegSetSrc::CTS, ..d}),
+            ("init_state", XC2Macrocell{init_state: !d.init_state, ..d}),
+            ("reg_mode", XC2Macrocell{reg_mode: XC2MCRegMode::LATCH, ..d}),
+            ("reg_mode", XC2Macrocell{reg_mode: XC2MCRegMode::TFF, ..d}),
+            ("reg_mode", XC2Macrocell{reg_mode: XC2MCRegMode::DFFCE, ..d}),
+            ("fb_mode", XC2Macrocell{fb_mode: XC2MCFeedbackMode::COMB, ..d}),
+            ("fb_mode", XC2Macrocell{fb_mode: XC2MCFeedbackMode::REG, ..d}),
+            ("ff_in_ibuf", XC2Macrocell{ff_in_ibuf: !d.ff_in_ibuf, ..d}),
+            ("xor_mode", XC2Macrocell{xor_mode: XC2MCXorMode::ONE, ..d}),
+            ("xor_mode", XC2Macrocell{xor_mode: XC2MCXorMode::PTC, ..d}),
+            ("xor_mode", XC2Macrocell{xor_mode: XC2MCXorMode::PTCB, ..d}),
+        ];
+
+        let fuse_idx = mc_fuse_idx(device, fb, mc);
+        let is_buried = fb_mc_num_to_iob_num(device, fb, mc).is_none();
+
+        probe_fuse_map(&d, &probes,
+            |x, rec: &mut FuseRecorder<usize>| {
+                if device.is_small_iob() {
+                    x.encode_jed_internal_small(rec, fuse_idx);
+                } else if !is_buried {
+                    x.encode_jed_internal_large(rec, fuse_idx);
+                } else {
+                    x.encode_jed_internal_large_buried(rec, fuse_idx);
+                }
+            },
+            |x, rec: &mut FuseRecorder<(usize, usize)>| x.to_crbit(device, fb, mc, rec),
+            |field, bit| XC2FuseFunction::Macrocell{fb, mc, field, bit},
+            fuses);
+    }
+
     ///  Internal function that reads only the macrocell-related bits from the macrcocell configuration
     pub fn from_jed_small(fuses: &[bool], block_idx: usize, mc_idx: usize) -> Self {
         Self::decode_jed_internal_small(fuses, block_idx + mc_idx * 27)
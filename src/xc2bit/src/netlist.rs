@@ -0,0 +1,1637 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Conversion between bitstreams and Yosys netlists of placed primitives
+//!
+//! `XC2Bitstream::to_netlist` describes the configured device as a module "top" containing cells of the following
+//! types: `BUFG`, `BUFGSR`, `BUFGTS`, `IOBUFE`, `IBUF`, `ANDTERM`, `ORTERM`, `MACROCELL_XOR`, a register (`FDCP`,
+//! `LDCP`, `FTCP`, `FDCPE` and their variants), and `$_BUF_`. Every cell other than the global buffers and `$_BUF_` has
+//! a `LOC` attribute. Inputs using a VREF I/O standard have the attribute `USES_VREF`, and pins configured as the VREF
+//! input have the attribute `IS_VREF`; neither of them has a `SCHMITT_TRIGGER` attribute.
+//!
+//! Settings that are not visible in the connections are recorded as attributes, so that converting the netlist back
+//! gives exactly the same bitstream:
+//!
+//! * The module attribute `FB<n>_ZIA_ROWS` lists what each of the 40 ZIA rows of the function block selects. Entries
+//!   are `0`, `1`, `INPAD`, a macrocell feedback (`FB1_2`) or a pin input (`PAD_FB1_2`).
+//! * `ANDTERM` cells have the attributes `ZIA_ROW` and `ZIA_ROW_B`, giving the ZIA row of each bit of `IN` and `IN_B`.
+//! * `MACROCELL_XOR` cells have the attribute `FB_MODE` (`DISABLED`, `COMB` or `REG`), which is what the macrocell
+//!   feeds into the ZIA.
+//! * `IBUF` and `IOBUFE` cells have the attribute `ZIA_MODE` (`DISABLED`, `PAD` or `REG`), which is what the I/O
+//!   block feeds into the ZIA.
+//!
+//! `XC2Bitstream::from_netlist` accepts a netlist in the same format. All of the above attributes are optional. If
+//! `FB<n>_ZIA_ROWS` is missing, ZIA rows are assigned from the connections of the `ANDTERM` cells of the function block
+//! (which needs the ZIA map of the part, so that this only works for the XC2C32(A) and XC2C64(A)), and the terms must
+//! not have `ZIA_ROW` attributes. If it is given but a term has no `ZIA_ROW` attribute, each input of the term uses the
+//! first row that carries it. Feedback modes that are not given are chosen from what the ZIA rows need, and other
+//! settings that cannot be seen in the netlist are left at the values of a blank bitstream.
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+
+use yosys_netlist_json::*;
+
+use crate::*;
+
+/// Errors that can occur when converting a netlist into a bitstream
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum XC2NetlistError {
+    /// There is no module called "top" and there isn't exactly one module
+    NoModule,
+    /// The `PART_NAME`, `PART_SPEED` and `PART_PKG` attributes of the module do not name a valid part
+    BadPart(String),
+    /// The cell has a type that is not a primitive of this device
+    UnknownCellType {
+        cell: String,
+        cell_type: String,
+    },
+    /// The `LOC` attribute of the cell is missing or is not a valid location for this type of cell
+    BadLoc {
+        cell: String,
+        loc: String,
+    },
+    /// Two cells are placed at the same location
+    DuplicateLoc {
+        cell: String,
+        loc: String,
+    },
+    /// An attribute or parameter of the cell has a value that is not allowed
+    BadAttribute {
+        cell: String,
+        name: String,
+    },
+    /// A net is driven by more than one cell output
+    MultipleDrivers(usize),
+    /// An input of the cell is connected to a net that nothing drives
+    UndrivenNet {
+        cell: String,
+        port: String,
+    },
+    /// An input of the cell is connected to something that the hardware cannot connect it to
+    IllegalConnection {
+        cell: String,
+        port: String,
+        /// Describes what the port can be connected to
+        expected: &'static str,
+    },
+    /// The XOR gate output, the register output and the pin of the macrocell are all used as ZIA inputs, but only two
+    /// of them can be
+    FeedbackConflict {
+        fb: u32,
+        mc: u32,
+    },
+    /// The given inputs of the function block cannot all be assigned ZIA rows
+    NotRoutable {
+        fb: u32,
+        blocking: Vec<XC2ZIAInput>,
+    },
+    /// After assigning ZIA rows to all of the signals, there are not enough rows left for the constant inputs
+    ZiaFull(u32),
+    /// ZIA rows have to be assigned for the function block, but the ZIA map of the part is not known. The rows can be
+    /// given with the `FB<n>_ZIA_ROWS` attribute instead.
+    ZiaMapUnknown(u32),
+}
+
+impl error::Error for XC2NetlistError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        None
+    }
+}
+
+impl fmt::Display for XC2NetlistError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &XC2NetlistError::NoModule => write!(f, "netlist does not contain a top module"),
+            &XC2NetlistError::BadPart(ref part) => write!(f, "part \"{}\" is invalid/unsupported", part),
+            &XC2NetlistError::UnknownCellType{ref cell, ref cell_type} => {
+                write!(f, "cell {} has unknown type {}", cell, cell_type)
+            },
+            &XC2NetlistError::BadLoc{ref cell, ref loc} => write!(f, "cell {} has invalid LOC \"{}\"", cell, loc),
+            &XC2NetlistError::DuplicateLoc{ref cell, ref loc} => {
+                write!(f, "cell {} is placed at {}, which is already used", cell, loc)
+            },
+            &XC2NetlistError::BadAttribute{ref cell, ref name} => {
+                write!(f, "cell {} has an invalid value for {}", cell, name)
+            },
+            &XC2NetlistError::MultipleDrivers(net) => write!(f, "net {} has multiple drivers", net),
+            &XC2NetlistError::UndrivenNet{ref cell, ref port} => {
+                write!(f, "port {} of cell {} is connected to an undriven net", port, cell)
+            },
+            &XC2NetlistError::IllegalConnection{ref cell, ref port, expected} => {
+                write!(f, "port {} of cell {} can only be connected to {}", port, cell, expected)
+            },
+            &XC2NetlistError::FeedbackConflict{fb, mc} => {
+                write!(f, "FB{}_{} needs XOR, register and pin feedback at the same time", fb + 1, mc + 1)
+            },
+            &XC2NetlistError::NotRoutable{fb, ref blocking} => {
+                write!(f, "FB{} cannot get all of these inputs from the ZIA at once: {:?}", fb + 1, blocking)
+            },
+            &XC2NetlistError::ZiaFull(fb) => write!(f, "not enough ZIA rows in FB{} for the constant inputs", fb + 1),
+            &XC2NetlistError::ZiaMapUnknown(fb) => {
+                write!(f, "the ZIA map of this part is not known, so the ZIA rows of FB{} must be given", fb + 1)
+            },
+        }
+    }
+}
+
+/// What drives a net
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+enum NetDriver {
+    /// Outputs of pins, macrocells and global buffers
+    Signal(XC2EqnSignal),
+    PTerm {
+        fb: u32,
+        term: u32,
+    },
+    OrTerm {
+        fb: u32,
+        mc: u32,
+    },
+}
+
+/// The value of a single-bit connection
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+enum NetValue {
+    Const(bool),
+    Driver(NetDriver),
+}
+
+/// Settings of an IOB that are collected before being written into the bitstream
+struct IOBSettings {
+    obuf_mode: XC2IOBOBufMode,
+    obuf_uses_ff: bool,
+    slew_is_fast: Option<bool>,
+    termination_enabled: Option<bool>,
+    schmitt_trigger: Option<bool>,
+    uses_data_gate: Option<bool>,
+    uses_vref: Option<bool>,
+    is_vref: Option<bool>,
+}
+
+/// Connections of an `ANDTERM` cell
+struct AndTermInputs<'a> {
+    cell: &'a str,
+    fb: u32,
+    term: u32,
+    true_inputs: Vec<NetValue>,
+    comp_inputs: Vec<NetValue>,
+    /// The ZIA row of each of `true_inputs` and `comp_inputs`, if given
+    rows: [Option<Vec<u32>>; 2],
+}
+
+/// Settings from the netlist that decide how signals get into the ZIA
+struct ZiaSettings {
+    /// Contents of the `FB<n>_ZIA_ROWS` attribute of each function block
+    rows: Vec<Option<Vec<XC2ZIAInput>>>,
+    fb_modes: HashMap<(u32, u32), XC2MCFeedbackMode>,
+    zia_modes: HashMap<(u32, u32), XC2IOBZIAMode>,
+}
+
+pub(crate) fn parse_fb_mc(device: XC2Device, loc: &str) -> Option<(u32, u32)> {
+    let mut parts = loc.strip_prefix("FB")?.splitn(2, '_');
+    let fb = parts.next()?.parse::<u32>().ok()?;
+    let mc = parts.next()?.parse::<u32>().ok()?;
+    if fb == 0 || fb as usize > device.num_fbs() || mc == 0 || mc as usize > MCS_PER_FB {
+        return None;
+    }
+    Some((fb - 1, mc - 1))
+}
+
+fn parse_fb_pterm(device: XC2Device, loc: &str) -> Option<(u32, u32)> {
+    let mut parts = loc.strip_prefix("FB")?.splitn(2, "_P");
+    let fb = parts.next()?.parse::<u32>().ok()?;
+    let term = parts.next()?.parse::<u32>().ok()?;
+    if fb == 0 || fb as usize > device.num_fbs() || term as usize >= ANDTERMS_PER_FB {
+        return None;
+    }
+    Some((fb - 1, term))
+}
+
+/// Returns the name of a ZIA choice as used in the `FB<n>_ZIA_ROWS` attribute
+fn zia_choice_name(device: XC2Device, choice: XC2ZIAInput) -> String {
+    match choice {
+        XC2ZIAInput::Zero => String::from("0"),
+        XC2ZIAInput::One => String::from("1"),
+        XC2ZIAInput::DedicatedInput => String::from("INPAD"),
+        XC2ZIAInput::Macrocell{fb, mc} => format!("FB{}_{}", fb + 1, mc + 1),
+        XC2ZIAInput::IBuf{ibuf} => {
+            let (fb, mc) = iob_num_to_fb_mc_num(device, ibuf as u32).unwrap();
+            format!("PAD_FB{}_{}", fb + 1, mc + 1)
+        },
+    }
+}
+
+fn parse_zia_choice(device: XC2Device, name: &str) -> Option<XC2ZIAInput> {
+    match name {
+        "0" => Some(XC2ZIAInput::Zero),
+        "1" => Some(XC2ZIAInput::One),
+        "INPAD" if device == XC2Device::XC2C32 || device == XC2Device::XC2C32A => Some(XC2ZIAInput::DedicatedInput),
+        _ => {
+            if let Some(pad) = name.strip_prefix("PAD_") {
+                let (fb, mc) = parse_fb_mc(device, pad)?;
+                Some(XC2ZIAInput::IBuf{ibuf: fb_mc_num_to_iob_num(device, fb, mc)? as u16})
+            } else {
+                let (fb, mc) = parse_fb_mc(device, name)?;
+                Some(XC2ZIAInput::Macrocell{fb: fb as u8, mc: mc as u8})
+            }
+        },
+    }
+}
+
+/// Returns what a ZIA row that selects `choice` carries, given the feedback settings of the bitstream
+fn zia_row_value(bits: &XC2BitstreamBits, choice: XC2ZIAInput) -> NetValue {
+    let device = bits.device_type();
+    match choice {
+        XC2ZIAInput::Zero => NetValue::Const(false),
+        XC2ZIAInput::One => NetValue::Const(true),
+        XC2ZIAInput::DedicatedInput => NetValue::Driver(NetDriver::Signal(XC2EqnSignal::InputOnly)),
+        XC2ZIAInput::Macrocell{fb, mc} => {
+            let (fb, mc) = (fb as u32, mc as u32);
+            match bits.get_fb()[fb as usize].mcs[mc as usize].fb_mode {
+                XC2MCFeedbackMode::Disabled => NetValue::Const(false),
+                XC2MCFeedbackMode::COMB => NetValue::Driver(NetDriver::Signal(XC2EqnSignal::Comb{fb, mc})),
+                XC2MCFeedbackMode::REG => NetValue::Driver(NetDriver::Signal(XC2EqnSignal::Reg{fb, mc})),
+            }
+        },
+        XC2ZIAInput::IBuf{ibuf} => {
+            let (fb, mc) = iob_num_to_fb_mc_num(device, ibuf as u32).unwrap();
+            let zia_mode = match (bits.get_small_iob(ibuf as usize), bits.get_large_iob(ibuf as usize)) {
+                (Some(iob), _) => iob.zia_mode,
+                (_, Some(iob)) => iob.zia_mode,
+                _ => unreachable!(),
+            };
+            match zia_mode {
+                XC2IOBZIAMode::Disabled => NetValue::Const(false),
+                XC2IOBZIAMode::PAD => NetValue::Driver(NetDriver::Signal(XC2EqnSignal::Pad{fb, mc})),
+                XC2IOBZIAMode::REG => NetValue::Driver(NetDriver::Signal(XC2EqnSignal::Reg{fb, mc})),
+            }
+        },
+    }
+}
+
+fn attr_str<'a>(cell: &'a Cell, name: &str) -> Option<&'a str> {
+    match cell.attributes.get(name) {
+        Some(&AttributeVal::S(ref s)) => Some(s),
+        _ => None,
+    }
+}
+
+fn cell_loc(cell_name: &str, cell: &Cell) -> Result<String, XC2NetlistError> {
+    attr_str(cell, "LOC").map(|x| x.to_owned()).ok_or_else(|| XC2NetlistError::BadLoc {
+        cell: cell_name.to_owned(),
+        loc: String::new(),
+    })
+}
+
+/// Reads a boolean attribute that is either "TRUE" or "FALSE"
+fn attr_bool(cell_name: &str, cell: &Cell, name: &str, true_str: &str, false_str: &str)
+    -> Result<Option<bool>, XC2NetlistError> {
+
+    match attr_str(cell, name) {
+        None => Ok(None),
+        Some(s) if s == true_str => Ok(Some(true)),
+        Some(s) if s == false_str => Ok(Some(false)),
+        Some(_) => Err(XC2NetlistError::BadAttribute{cell: cell_name.to_owned(), name: name.to_owned()}),
+    }
+}
+
+/// Reads an attribute listing ZIA rows, which must have one entry for each of `count` inputs
+fn attr_rows(cell_name: &str, cell: &Cell, name: &str, count: usize) -> Result<Option<Vec<u32>>, XC2NetlistError> {
+    let bad = || XC2NetlistError::BadAttribute{cell: cell_name.to_owned(), name: name.to_owned()};
+    match attr_str(cell, name) {
+        None => Ok(None),
+        Some(s) => {
+            let rows = s.split_whitespace().map(|x| x.parse::<u32>().ok().filter(|&x| x < INPUTS_PER_ANDTERM as u32))
+                .collect::<Option<Vec<_>>>().ok_or_else(bad)?;
+            if rows.len() != count {
+                return Err(bad());
+            }
+            Ok(Some(rows))
+        },
+    }
+}
+
+/// Reads a parameter that must be 0 or 1
+fn param_bool(cell_name: &str, cell: &Cell, name: &str) -> Result<Option<bool>, XC2NetlistError> {
+    match cell.parameters.get(name).map(|x| x.to_number()) {
+        None => Ok(None),
+        Some(Some(0)) => Ok(Some(false)),
+        Some(Some(1)) => Ok(Some(true)),
+        Some(_) => Err(XC2NetlistError::BadAttribute{cell: cell_name.to_owned(), name: name.to_owned()}),
+    }
+}
+
+fn port_bits<'a>(cell: &'a Cell, port: &str) -> &'a [BitVal] {
+    cell.connections.get(port).map(|x| &x[..]).unwrap_or(&[])
+}
+
+fn illegal(cell: &str, port: &str, expected: &'static str) -> XC2NetlistError {
+    XC2NetlistError::IllegalConnection{cell: cell.to_owned(), port: port.to_owned(), expected}
+}
+
+/// Nets of the netlist together with what drives them
+struct Nets {
+    drivers: HashMap<usize, NetDriver>,
+    /// Nets that are driven by a `$_BUF_` cell, and the net driving that cell
+    aliases: HashMap<usize, BitVal>,
+}
+
+impl Nets {
+    fn add_driver(&mut self, bits: &[BitVal], driver: NetDriver) -> Result<(), XC2NetlistError> {
+        for bit in bits {
+            if let &BitVal::N(net) = bit {
+                if self.drivers.insert(net, driver).is_some() || self.aliases.contains_key(&net) {
+                    return Err(XC2NetlistError::MultipleDrivers(net));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve(&self, bit: BitVal) -> Option<NetValue> {
+        let mut bit = bit;
+        // Following more aliases than there are means that there is a loop
+        for _ in 0..self.aliases.len() + 1 {
+            match bit {
+                BitVal::S(SpecialBit::_0) => return Some(NetValue::Const(false)),
+                BitVal::S(SpecialBit::_1) => return Some(NetValue::Const(true)),
+                BitVal::S(_) => return None,
+                BitVal::N(net) => {
+                    if let Some(&driver) = self.drivers.get(&net) {
+                        return Some(NetValue::Driver(driver));
+                    }
+                    bit = *self.aliases.get(&net)?;
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the value of a single-bit input port, or `None` if it is not connected
+    fn input(&self, cell_name: &str, cell: &Cell, port: &str) -> Result<Option<NetValue>, XC2NetlistError> {
+        match port_bits(cell, port) {
+            [] => Ok(None),
+            [bit] => Ok(Some(self.resolve(*bit).ok_or_else(|| XC2NetlistError::UndrivenNet {
+                cell: cell_name.to_owned(),
+                port: port.to_owned(),
+            })?)),
+            _ => Err(illegal(cell_name, port, "a single net")),
+        }
+    }
+
+    /// Returns the values of all the bits of an input port
+    fn inputs(&self, cell_name: &str, cell: &Cell, port: &str) -> Result<Vec<NetValue>, XC2NetlistError> {
+        port_bits(cell, port).iter().map(|&bit| self.resolve(bit).ok_or_else(|| XC2NetlistError::UndrivenNet {
+            cell: cell_name.to_owned(),
+            port: port.to_owned(),
+        })).collect()
+    }
+}
+
+/// Finds the index of a global buffer, which is determined by the pin driving it if there is one, or otherwise by the
+/// number at the end of the cell name (as in `bufg_gck_0`) for the buffers that there is more than one of
+fn global_buffer_index<F>(nets: &Nets, cell_name: &str, cell: &Cell, count: usize, pin_of: F)
+    -> Result<(usize, bool), XC2NetlistError> where F: Fn(usize) -> (u32, u32) {
+
+    match nets.input(cell_name, cell, "I")? {
+        Some(NetValue::Driver(NetDriver::Signal(XC2EqnSignal::Pad{fb, mc}))) => {
+            match (0..count).find(|&i| pin_of(i) == (fb, mc)) {
+                Some(i) => Ok((i, true)),
+                None => Err(illegal(cell_name, "I", "a pin with a global buffer")),
+            }
+        },
+        None | Some(NetValue::Const(false)) => {
+            let idx = if count == 1 {
+                Some(0)
+            } else {
+                cell_name.rsplit('_').next().unwrap().parse::<usize>().ok()
+            };
+            match idx {
+                Some(i) if i < count => Ok((i, false)),
+                _ => Err(XC2NetlistError::BadLoc{cell: cell_name.to_owned(), loc: String::new()}),
+            }
+        },
+        Some(_) => Err(illegal(cell_name, "I", "a pin with a global buffer")),
+    }
+}
+
+fn reg_type_mode(cell_type: &str) -> Option<(XC2MCRegMode, bool, bool)> {
+    // (mode, clk_invert_pol, is_ddr)
+    match cell_type {
+        "FDCP" => Some((XC2MCRegMode::DFF, false, false)),
+        "FDCP_N" => Some((XC2MCRegMode::DFF, true, false)),
+        "FDDCP" => Some((XC2MCRegMode::DFF, false, true)),
+        "LDCP" => Some((XC2MCRegMode::LATCH, false, false)),
+        "LDCP_N" => Some((XC2MCRegMode::LATCH, true, false)),
+        "FTCP" => Some((XC2MCRegMode::TFF, false, false)),
+        "FTCP_N" => Some((XC2MCRegMode::TFF, true, false)),
+        "FTDCP" => Some((XC2MCRegMode::TFF, false, true)),
+        "FDCPE" => Some((XC2MCRegMode::DFFCE, false, false)),
+        "FDCPE_N" => Some((XC2MCRegMode::DFFCE, true, false)),
+        "FDDCPE" => Some((XC2MCRegMode::DFFCE, false, true)),
+        _ => None,
+    }
+}
+
+impl XC2Bitstream {
+    /// Converts a netlist of placed primitives, in the format written by `xc2 netlist`, into a bitstream. See the
+    /// documentation of the `netlist` module for what this accepts.
+    pub fn from_netlist(netlist: &Netlist) -> Result<Self, XC2NetlistError> {
+        let module = match netlist.modules.get("top") {
+            Some(module) => module,
+            None if netlist.modules.len() == 1 => netlist.modules.values().next().unwrap(),
+            None => return Err(XC2NetlistError::NoModule),
+        };
+
+        let part_name = format!("{}-{}-{}",
+            module.attributes.get("PART_NAME").and_then(|x| x.to_string_if_string()).unwrap_or(""),
+            module.attributes.get("PART_SPEED").and_then(|x| x.to_string_if_string().map(|x| x.to_owned())
+                .or_else(|| x.to_number().map(|x| x.to_string()))).unwrap_or_default(),
+            module.attributes.get("PART_PKG").and_then(|x| x.to_string_if_string()).unwrap_or(""));
+        let part = XC2DeviceSpeedPackage::from_str(&part_name).ok_or(XC2NetlistError::BadPart(part_name))?;
+        let device = part.dev;
+
+        let mut bitstream = XC2Bitstream::blank_bitstream(part);
+        match module.attributes.get("GLOBAL_TERM").and_then(|x| x.to_string_if_string()) {
+            Some("PULLUP") => bitstream.bits.get_global_nets_mut().global_pu = true,
+            Some("KEEPER") => bitstream.bits.get_global_nets_mut().global_pu = false,
+            None => {},
+            Some(_) => return Err(XC2NetlistError::BadAttribute{cell: String::from("top"),
+                name: String::from("GLOBAL_TERM")}),
+        }
+
+        if let Some(x) = module.attributes.get("USE_VREF") {
+            let vref = match x.to_string_if_string() {
+                Some("TRUE") => true,
+                Some("FALSE") => false,
+                _ => return Err(XC2NetlistError::BadAttribute{cell: String::from("top"), name: String::from("USE_VREF")}),
+            };
+            match bitstream.bits {
+                XC2BitstreamBits::XC2C128{ref mut use_vref, ..} |
+                XC2BitstreamBits::XC2C256{ref mut use_vref, ..} |
+                XC2BitstreamBits::XC2C384{ref mut use_vref, ..} |
+                XC2BitstreamBits::XC2C512{ref mut use_vref, ..} => *use_vref = vref,
+                _ => return Err(XC2NetlistError::BadAttribute{cell: String::from("top"),
+                    name: String::from("USE_VREF")}),
+            }
+        }
+
+        let mut zia = ZiaSettings {
+            rows: Vec::new(),
+            fb_modes: HashMap::new(),
+            zia_modes: HashMap::new(),
+        };
+        for fb in 0..device.num_fbs() {
+            let name = format!("FB{}_ZIA_ROWS", fb + 1);
+            let rows = match module.attributes.get(&name) {
+                None => None,
+                Some(x) => {
+                    let rows = x.to_string_if_string().and_then(|x| x.split_whitespace()
+                        .map(|choice| parse_zia_choice(device, choice)).collect::<Option<Vec<_>>>())
+                        .filter(|rows| rows.len() == INPUTS_PER_ANDTERM && (0..INPUTS_PER_ANDTERM)
+                            .all(|row| zia_encode_choice(device, row as u32, rows[row]).is_some()));
+                    if rows.is_none() {
+                        return Err(XC2NetlistError::BadAttribute{cell: String::from("top"), name});
+                    }
+                    rows
+                },
+            };
+            zia.rows.push(rows);
+        }
+
+        // Sort the cells so that errors are reported deterministically
+        let mut cells = module.cells.iter().collect::<Vec<_>>();
+        cells.sort_by(|a, b| a.0.cmp(b.0));
+
+        // Find what drives every net, except for the global buffers which need the pins first
+        let mut nets = Nets {
+            drivers: HashMap::new(),
+            aliases: HashMap::new(),
+        };
+        let mut locs: HashMap<String, &str> = HashMap::new();
+        for &(cell_name, cell) in &cells {
+            let (driver, port) = match cell.cell_type.as_ref() {
+                "BUFG" | "BUFGSR" | "BUFGTS" => continue,
+                "$_BUF_" => {
+                    match (port_bits(cell, "A"), port_bits(cell, "Y")) {
+                        ([a], [BitVal::N(y)]) => {
+                            if nets.aliases.insert(*y, *a).is_some() || nets.drivers.contains_key(y) {
+                                return Err(XC2NetlistError::MultipleDrivers(*y));
+                            }
+                        },
+                        _ => return Err(illegal(cell_name, "A", "a single net")),
+                    }
+                    continue;
+                },
+                "IOBUFE" | "IBUF" => {
+                    let loc = cell_loc(cell_name, cell)?;
+                    if loc == "INPAD" && cell.cell_type == "IBUF" && bitstream.bits.get_inpin().is_some() {
+                        (NetDriver::Signal(XC2EqnSignal::InputOnly), "O")
+                    } else {
+                        let (fb, mc) = parse_fb_mc(device, &loc)
+                            .filter(|&(fb, mc)| fb_mc_num_to_iob_num(device, fb, mc).is_some())
+                            .ok_or_else(|| XC2NetlistError::BadLoc{cell: cell_name.clone(), loc: loc.clone()})?;
+                        (NetDriver::Signal(XC2EqnSignal::Pad{fb, mc}), "O")
+                    }
+                },
+                "ANDTERM" => {
+                    let loc = cell_loc(cell_name, cell)?;
+                    let (fb, term) = parse_fb_pterm(device, &loc)
+                        .ok_or_else(|| XC2NetlistError::BadLoc{cell: cell_name.clone(), loc: loc.clone()})?;
+                    (NetDriver::PTerm{fb, term}, "OUT")
+                },
+                "ORTERM" | "MACROCELL_XOR" => {
+                    let loc = cell_loc(cell_name, cell)?;
+                    let (fb, mc) = parse_fb_mc(device, &loc)
+                        .ok_or_else(|| XC2NetlistError::BadLoc{cell: cell_name.clone(), loc: loc.clone()})?;
+                    if cell.cell_type == "ORTERM" {
+                        (NetDriver::OrTerm{fb, mc}, "OUT")
+                    } else {
+                        (NetDriver::Signal(XC2EqnSignal::Comb{fb, mc}), "OUT")
+                    }
+                },
+                cell_type if reg_type_mode(cell_type).is_some() => {
+                    let loc = cell_loc(cell_name, cell)?;
+                    let (fb, mc) = parse_fb_mc(device, &loc)
+                        .ok_or_else(|| XC2NetlistError::BadLoc{cell: cell_name.clone(), loc: loc.clone()})?;
+                    (NetDriver::Signal(XC2EqnSignal::Reg{fb, mc}), "Q")
+                },
+                _ => return Err(XC2NetlistError::UnknownCellType {
+                    cell: cell_name.clone(),
+                    cell_type: cell.cell_type.clone(),
+                }),
+            };
+
+            // The kind of cell is part of the location so that e.g. an OR term and a register can share a LOC
+            let loc_key = format!("{:?}", driver);
+            if locs.insert(loc_key, cell_name).is_some() {
+                return Err(XC2NetlistError::DuplicateLoc{cell: cell_name.clone(), loc: cell_loc(cell_name, cell)?});
+            }
+            nets.add_driver(port_bits(cell, port), driver)?;
+        }
+
+        // Global buffers
+        for &(cell_name, cell) in &cells {
+            let driver = match cell.cell_type.as_ref() {
+                "BUFG" => {
+                    let (i, enable) = global_buffer_index(&nets, cell_name, cell, 3,
+                        |i| get_gck(device, i).unwrap())?;
+                    bitstream.bits.get_global_nets_mut().gck_enable[i] = enable;
+                    NetDriver::Signal(XC2EqnSignal::GCK(i as u32))
+                },
+                "BUFGTS" => {
+                    let (i, enable) = global_buffer_index(&nets, cell_name, cell, 4,
+                        |i| get_gts(device, i).unwrap())?;
+                    let global_nets = bitstream.bits.get_global_nets_mut();
+                    global_nets.gts_enable[i] = enable;
+                    if let Some(invert) = param_bool(cell_name, cell, "INVERT")? {
+                        global_nets.gts_invert[i] = invert;
+                    }
+                    NetDriver::Signal(XC2EqnSignal::GTS(i as u32))
+                },
+                "BUFGSR" => {
+                    let (_, enable) = global_buffer_index(&nets, cell_name, cell, 1, |_| get_gsr(device))
+                        .map_err(|_| illegal(cell_name, "I", "the GSR pin"))?;
+                    let global_nets = bitstream.bits.get_global_nets_mut();
+                    global_nets.gsr_enable = enable;
+                    if let Some(invert) = param_bool(cell_name, cell, "INVERT")? {
+                        global_nets.gsr_invert = invert;
+                    }
+                    NetDriver::Signal(XC2EqnSignal::GSR)
+                },
+                _ => continue,
+            };
+
+            if locs.insert(format!("{:?}", driver), cell_name).is_some() {
+                return Err(XC2NetlistError::DuplicateLoc{cell: cell_name.clone(), loc: format!("{:?}", driver)});
+            }
+            nets.add_driver(port_bits(cell, "O"), driver)?;
+        }
+
+        // A macrocell output that goes to a disabled output buffer shows up as a `$_BUF_` whose output is not used by
+        // anything. This is the only place where the output buffer data source of such a macrocell is visible.
+        let mut net_uses = HashMap::new();
+        for port in module.ports.values() {
+            for bit in &port.bits {
+                if let &BitVal::N(net) = bit {
+                    *net_uses.entry(net).or_insert(0) += 1;
+                }
+            }
+        }
+        for &(_, cell) in &cells {
+            for bits in cell.connections.values() {
+                for bit in bits {
+                    if let &BitVal::N(net) = bit {
+                        *net_uses.entry(net).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+        let mut dangling_reg = HashMap::new();
+        for (&y, &a) in &nets.aliases {
+            if net_uses.get(&y) == Some(&1) {
+                if let Some(NetValue::Driver(NetDriver::Signal(XC2EqnSignal::Reg{fb, mc}))) = nets.resolve(a) {
+                    dangling_reg.insert((fb, mc), ());
+                }
+            }
+        }
+
+        // Everything else
+        let mut and_terms = Vec::new();
+        for &(cell_name, cell) in &cells {
+            let cell_name = &cell_name[..];
+            match cell.cell_type.as_ref() {
+                "IOBUFE" | "IBUF" => {
+                    let loc = cell_loc(cell_name, cell)?;
+                    let mut settings = IOBSettings {
+                        obuf_mode: XC2IOBOBufMode::Disabled,
+                        obuf_uses_ff: false,
+                        slew_is_fast: attr_bool(cell_name, cell, "SLEW", "FAST", "SLOW")?,
+                        termination_enabled: attr_bool(cell_name, cell, "TERM", "TRUE", "FALSE")?,
+                        schmitt_trigger: attr_bool(cell_name, cell, "SCHMITT_TRIGGER", "TRUE", "FALSE")?,
+                        uses_data_gate: attr_bool(cell_name, cell, "DATA_GATE", "TRUE", "FALSE")?,
+                        uses_vref: attr_bool(cell_name, cell, "USES_VREF", "TRUE", "FALSE")?,
+                        is_vref: attr_bool(cell_name, cell, "IS_VREF", "TRUE", "FALSE")?,
+                    };
+
+                    if loc == "INPAD" {
+                        match bitstream.bits {
+                            XC2BitstreamBits::XC2C32{ref mut inpin, ..} |
+                            XC2BitstreamBits::XC2C32A{ref mut inpin, ..} => {
+                                if let Some(x) = settings.termination_enabled {
+                                    inpin.termination_enabled = x;
+                                }
+                                if let Some(x) = settings.schmitt_trigger {
+                                    inpin.schmitt_trigger = x;
+                                }
+                            },
+                            _ => unreachable!(),
+                        }
+                        continue;
+                    }
+
+                    let (fb, mc) = parse_fb_mc(device, &loc).unwrap();
+                    let iob = fb_mc_num_to_iob_num(device, fb, mc).unwrap();
+                    settings.obuf_uses_ff = dangling_reg.contains_key(&(fb, mc));
+                    match attr_str(cell, "ZIA_MODE") {
+                        None => {},
+                        Some("DISABLED") => {zia.zia_modes.insert((fb, mc), XC2IOBZIAMode::Disabled);},
+                        Some("PAD") => {zia.zia_modes.insert((fb, mc), XC2IOBZIAMode::PAD);},
+                        Some("REG") => {zia.zia_modes.insert((fb, mc), XC2IOBZIAMode::REG);},
+                        Some(_) => return Err(XC2NetlistError::BadAttribute{cell: cell_name.to_owned(),
+                            name: String::from("ZIA_MODE")}),
+                    }
+                    let own_output = |x: Option<NetValue>| match x {
+                        Some(NetValue::Driver(NetDriver::Signal(XC2EqnSignal::Comb{fb: x_fb, mc: x_mc})))
+                            if x_fb == fb && x_mc == mc => Some(false),
+                        Some(NetValue::Driver(NetDriver::Signal(XC2EqnSignal::Reg{fb: x_fb, mc: x_mc})))
+                            if x_fb == fb && x_mc == mc => Some(true),
+                        _ => None,
+                    };
+
+                    if cell.cell_type == "IOBUFE" {
+                        let cgnd = cell.attributes.get("CGND").and_then(|x| x.to_number()).unwrap_or(0) != 0;
+                        let i = nets.input(cell_name, cell, "I")?;
+                        let e = nets.input(cell_name, cell, "E")?;
+
+                        if cgnd {
+                            if i != Some(NetValue::Const(false)) {
+                                return Err(illegal(cell_name, "I", "0 when CGND is set"));
+                            }
+                            if e != Some(NetValue::Const(true)) {
+                                return Err(illegal(cell_name, "E", "1 when CGND is set"));
+                            }
+                            settings.obuf_mode = XC2IOBOBufMode::CGND;
+                        } else if i == Some(NetValue::Const(false)) {
+                            settings.obuf_uses_ff = own_output(e).ok_or_else(||
+                                illegal(cell_name, "E", "the XOR gate or register of its macrocell in open-drain mode"))?;
+                            settings.obuf_mode = XC2IOBOBufMode::OpenDrain;
+                        } else {
+                            settings.obuf_uses_ff = own_output(i).ok_or_else(||
+                                illegal(cell_name, "I", "the XOR gate or register of its macrocell, or 0"))?;
+                            settings.obuf_mode = match e {
+                                Some(NetValue::Const(true)) => XC2IOBOBufMode::PushPull,
+                                Some(NetValue::Driver(NetDriver::Signal(XC2EqnSignal::GTS(0)))) =>
+                                    XC2IOBOBufMode::TriStateGTS0,
+                                Some(NetValue::Driver(NetDriver::Signal(XC2EqnSignal::GTS(1)))) =>
+                                    XC2IOBOBufMode::TriStateGTS1,
+                                Some(NetValue::Driver(NetDriver::Signal(XC2EqnSignal::GTS(2)))) =>
+                                    XC2IOBOBufMode::TriStateGTS2,
+                                Some(NetValue::Driver(NetDriver::Signal(XC2EqnSignal::GTS(3)))) =>
+                                    XC2IOBOBufMode::TriStateGTS3,
+                                Some(NetValue::Driver(NetDriver::PTerm{fb: p_fb, term})) if p_fb == fb && term == CTE =>
+                                    XC2IOBOBufMode::TriStateCTE,
+                                Some(NetValue::Driver(NetDriver::PTerm{fb: p_fb, term}))
+                                    if p_fb == fb && term == get_ptb(mc) => XC2IOBOBufMode::TriStatePTB,
+                                _ => return Err(illegal(cell_name, "E", "1, a GTS buffer, or the CTE or PTB term")),
+                            };
+                        }
+                    }
+
+                    if let Some(iob) = bitstream.bits.get_mut_small_iob(iob as usize) {
+                        iob.obuf_mode = settings.obuf_mode;
+                        iob.obuf_uses_ff = settings.obuf_uses_ff;
+                        iob.slew_is_fast = settings.slew_is_fast.unwrap_or(iob.slew_is_fast);
+                        iob.termination_enabled = settings.termination_enabled.unwrap_or(iob.termination_enabled);
+                        iob.schmitt_trigger = settings.schmitt_trigger.unwrap_or(iob.schmitt_trigger);
+                    }
+                    if let Some(iob) = bitstream.bits.get_mut_large_iob(iob as usize) {
+                        iob.obuf_mode = settings.obuf_mode;
+                        iob.obuf_uses_ff = settings.obuf_uses_ff;
+                        iob.slew_is_fast = settings.slew_is_fast.unwrap_or(iob.slew_is_fast);
+                        iob.termination_enabled = settings.termination_enabled.unwrap_or(iob.termination_enabled);
+                        iob.uses_data_gate = settings.uses_data_gate.unwrap_or(iob.uses_data_gate);
+                        match (settings.schmitt_trigger, settings.uses_vref, settings.is_vref) {
+                            (Some(true), None, None) => iob.ibuf_mode = XC2IOBIbufMode::NoVrefSt,
+                            (Some(false), None, None) => iob.ibuf_mode = XC2IOBIbufMode::NoVrefNoSt,
+                            (None, Some(true), None) => iob.ibuf_mode = XC2IOBIbufMode::UsesVref,
+                            (None, None, Some(true)) => iob.ibuf_mode = XC2IOBIbufMode::IsVref,
+                            (None, None, None) => {},
+                            _ => return Err(XC2NetlistError::BadAttribute{cell: cell_name.to_owned(),
+                                name: String::from("USES_VREF")}),
+                        }
+                    } else if settings.uses_vref.is_some() || settings.is_vref.is_some() {
+                        // Only the larger devices have VREF support
+                        return Err(XC2NetlistError::BadAttribute{cell: cell_name.to_owned(),
+                            name: String::from(if settings.is_vref.is_some() {"IS_VREF"} else {"USES_VREF"})});
+                    }
+                },
+                "ANDTERM" => {
+                    let (fb, term) = parse_fb_pterm(device, &cell_loc(cell_name, cell)?).unwrap();
+                    let true_inputs = nets.inputs(cell_name, cell, "IN")?;
+                    let comp_inputs = nets.inputs(cell_name, cell, "IN_B")?;
+                    let rows = [
+                        attr_rows(cell_name, cell, "ZIA_ROW", true_inputs.len())?,
+                        attr_rows(cell_name, cell, "ZIA_ROW_B", comp_inputs.len())?,
+                    ];
+                    if zia.rows[fb as usize].is_none() && (rows[0].is_some() || rows[1].is_some()) {
+                        // Rows of terms can only be given together with the rows of the function block
+                        return Err(XC2NetlistError::BadAttribute{cell: cell_name.to_owned(),
+                            name: String::from(if rows[0].is_some() {"ZIA_ROW"} else {"ZIA_ROW_B"})});
+                    }
+                    and_terms.push(AndTermInputs {
+                        cell: cell_name,
+                        fb,
+                        term,
+                        true_inputs,
+                        comp_inputs,
+                        rows,
+                    });
+                },
+                "ORTERM" => {
+                    let (fb, mc) = parse_fb_mc(device, &cell_loc(cell_name, cell)?).unwrap();
+                    for input in nets.inputs(cell_name, cell, "IN")? {
+                        match input {
+                            NetValue::Driver(NetDriver::PTerm{fb: p_fb, term}) if p_fb == fb => {
+                                bitstream.bits.get_fb_mut()[fb as usize].or_terms[mc as usize]
+                                    .set(term as usize, true);
+                            },
+                            _ => return Err(illegal(cell_name, "IN", "product terms of its function block")),
+                        }
+                    }
+                },
+                "MACROCELL_XOR" => {
+                    let (fb, mc) = parse_fb_mc(device, &cell_loc(cell_name, cell)?).unwrap();
+                    let invert = param_bool(cell_name, cell, "INVERT_OUT")?.unwrap_or(false);
+                    match attr_str(cell, "FB_MODE") {
+                        None => {},
+                        Some("DISABLED") => {zia.fb_modes.insert((fb, mc), XC2MCFeedbackMode::Disabled);},
+                        Some("COMB") => {zia.fb_modes.insert((fb, mc), XC2MCFeedbackMode::COMB);},
+                        Some("REG") => {zia.fb_modes.insert((fb, mc), XC2MCFeedbackMode::REG);},
+                        Some(_) => return Err(XC2NetlistError::BadAttribute{cell: cell_name.to_owned(),
+                            name: String::from("FB_MODE")}),
+                    }
+
+                    match nets.input(cell_name, cell, "IN_ORTERM")? {
+                        Some(NetValue::Driver(NetDriver::OrTerm{fb: o_fb, mc: o_mc})) if o_fb == fb && o_mc == mc => {},
+                        _ => return Err(illegal(cell_name, "IN_ORTERM", "the OR term of its macrocell")),
+                    }
+
+                    let xor_mode = match nets.input(cell_name, cell, "IN_PTC")? {
+                        None | Some(NetValue::Const(false)) => {
+                            if invert {XC2MCXorMode::ONE} else {XC2MCXorMode::ZERO}
+                        },
+                        Some(NetValue::Driver(NetDriver::PTerm{fb: p_fb, term})) if p_fb == fb && term == get_ptc(mc) => {
+                            if invert {XC2MCXorMode::PTCB} else {XC2MCXorMode::PTC}
+                        },
+                        _ => return Err(illegal(cell_name, "IN_PTC", "the PTC term of its macrocell, or 0")),
+                    };
+                    bitstream.bits.get_fb_mut()[fb as usize].mcs[mc as usize].xor_mode = xor_mode;
+                },
+                cell_type if reg_type_mode(cell_type).is_some() => {
+                    let (reg_mode, clk_invert_pol, is_ddr) = reg_type_mode(cell_type).unwrap();
+                    let (fb, mc) = parse_fb_mc(device, &cell_loc(cell_name, cell)?).unwrap();
+                    let own_pterm = |x: Option<NetValue>, term: u32| match x {
+                        Some(NetValue::Driver(NetDriver::PTerm{fb: p_fb, term: p_term})) => p_fb == fb && p_term == term,
+                        _ => false,
+                    };
+                    let mut macrocell = bitstream.bits.get_fb()[fb as usize].mcs[mc as usize];
+                    macrocell.reg_mode = reg_mode;
+                    macrocell.clk_invert_pol = clk_invert_pol;
+                    macrocell.is_ddr = is_ddr;
+                    macrocell.init_state = param_bool(cell_name, cell, "INIT")?.unwrap_or(macrocell.init_state);
+
+                    let d_port = if reg_mode == XC2MCRegMode::TFF {"T"} else {"D"};
+                    macrocell.ff_in_ibuf = match nets.input(cell_name, cell, d_port)? {
+                        Some(NetValue::Driver(NetDriver::Signal(XC2EqnSignal::Comb{fb: x_fb, mc: x_mc})))
+                            if x_fb == fb && x_mc == mc => false,
+                        Some(NetValue::Driver(NetDriver::Signal(XC2EqnSignal::Pad{fb: x_fb, mc: x_mc})))
+                            if x_fb == fb && x_mc == mc => true,
+                        _ => return Err(illegal(cell_name, d_port, "the XOR gate or pin of its macrocell")),
+                    };
+
+                    let clk_port = if reg_mode == XC2MCRegMode::LATCH {"G"} else {"C"};
+                    let clk = nets.input(cell_name, cell, clk_port)?;
+                    macrocell.clk_src = match clk {
+                        Some(NetValue::Driver(NetDriver::Signal(XC2EqnSignal::GCK(0)))) => XC2MCRegClkSrc::GCK0,
+                        Some(NetValue::Driver(NetDriver::Signal(XC2EqnSignal::GCK(1)))) => XC2MCRegClkSrc::GCK1,
+                        Some(NetValue::Driver(NetDriver::Signal(XC2EqnSignal::GCK(2)))) => XC2MCRegClkSrc::GCK2,
+                        _ if own_pterm(clk, CTC) => XC2MCRegClkSrc::CTC,
+                        _ if own_pterm(clk, get_ptc(mc)) => XC2MCRegClkSrc::PTC,
+                        _ => return Err(illegal(cell_name, clk_port, "a GCK buffer or the CTC or PTC term")),
+                    };
+
+                    let set = nets.input(cell_name, cell, "PRE")?;
+                    macrocell.s_src = match set {
+                        None | Some(NetValue::Const(false)) => XC2MCRegSetSrc::Disabled,
+                        Some(NetValue::Driver(NetDriver::Signal(XC2EqnSignal::GSR))) => XC2MCRegSetSrc::GSR,
+                        _ if own_pterm(set, CTS) => XC2MCRegSetSrc::CTS,
+                        _ if own_pterm(set, get_pta(mc)) => XC2MCRegSetSrc::PTA,
+                        _ => return Err(illegal(cell_name, "PRE", "0, the GSR buffer, or the CTS or PTA term")),
+                    };
+
+                    let reset = nets.input(cell_name, cell, "CLR")?;
+                    macrocell.r_src = match reset {
+                        None | Some(NetValue::Const(false)) => XC2MCRegResetSrc::Disabled,
+                        Some(NetValue::Driver(NetDriver::Signal(XC2EqnSignal::GSR))) => XC2MCRegResetSrc::GSR,
+                        _ if own_pterm(reset, CTR) => XC2MCRegResetSrc::CTR,
+                        _ if own_pterm(reset, get_pta(mc)) => XC2MCRegResetSrc::PTA,
+                        _ => return Err(illegal(cell_name, "CLR", "0, the GSR buffer, or the CTR or PTA term")),
+                    };
+
+                    if reg_mode == XC2MCRegMode::DFFCE && !own_pterm(nets.input(cell_name, cell, "CE")?, get_ptc(mc)) {
+                        return Err(illegal(cell_name, "CE", "the PTC term of its macrocell"));
+                    }
+
+                    bitstream.bits.get_fb_mut()[fb as usize].mcs[mc as usize] = macrocell;
+                },
+                _ => {},
+            }
+        }
+
+        route_zia(&mut bitstream.bits, &and_terms, &zia)?;
+
+        Ok(bitstream)
+    }
+}
+
+/// Chooses the feedback paths and ZIA rows for the inputs of the product terms, and sets the product term bits
+fn route_zia(bits: &mut XC2BitstreamBits, and_terms: &[AndTermInputs], zia: &ZiaSettings)
+    -> Result<(), XC2NetlistError> {
+
+    let device = bits.device_type();
+
+    // Which outputs of each macrocell are needed in the ZIA
+    let mut comb_used = HashMap::new();
+    let mut reg_used = HashMap::new();
+    let mut pad_used = HashMap::new();
+    for and_term in and_terms {
+        for (port, input) in and_term.true_inputs.iter().map(|x| ("IN", x))
+            .chain(and_term.comp_inputs.iter().map(|x| ("IN_B", x))) {
+
+            match input {
+                &NetValue::Const(_) => {},
+                &NetValue::Driver(NetDriver::Signal(XC2EqnSignal::Comb{fb, mc})) => {
+                    comb_used.insert((fb, mc), ());
+                },
+                &NetValue::Driver(NetDriver::Signal(XC2EqnSignal::Reg{fb, mc})) => {
+                    reg_used.insert((fb, mc), ());
+                },
+                &NetValue::Driver(NetDriver::Signal(XC2EqnSignal::Pad{fb, mc})) => {
+                    pad_used.insert((fb, mc), ());
+                },
+                &NetValue::Driver(NetDriver::Signal(XC2EqnSignal::InputOnly)) => {},
+                _ => return Err(illegal(and_term.cell, port, "a pin, XOR gate or register output")),
+            }
+        }
+    }
+
+    // Register feedback goes through the macrocell unless the macrocell is already used for the XOR gate feedback.
+    // Modes given in the netlist are used instead, but they still have to provide every signal that is needed.
+    let mut zia_input_of = HashMap::new();
+    for fb in 0..device.num_fbs() as u32 {
+        for mc in 0..MCS_PER_FB as u32 {
+            let comb = comb_used.contains_key(&(fb, mc));
+            let reg = reg_used.contains_key(&(fb, mc));
+            let pad = pad_used.contains_key(&(fb, mc));
+            let iob = fb_mc_num_to_iob_num(device, fb, mc);
+
+            let mut fb_mode = XC2MCFeedbackMode::Disabled;
+            let mut zia_mode = XC2IOBZIAMode::Disabled;
+            if comb {
+                fb_mode = XC2MCFeedbackMode::COMB;
+            }
+            if pad {
+                zia_mode = XC2IOBZIAMode::PAD;
+            }
+            if reg {
+                if !comb {
+                    fb_mode = XC2MCFeedbackMode::REG;
+                } else if !pad && iob.is_some() {
+                    zia_mode = XC2IOBZIAMode::REG;
+                }
+            }
+            if let Some(&x) = zia.fb_modes.get(&(fb, mc)) {
+                fb_mode = x;
+            }
+            if let Some(&x) = zia.zia_modes.get(&(fb, mc)) {
+                zia_mode = x;
+            }
+
+            if (comb && fb_mode != XC2MCFeedbackMode::COMB) || (pad && zia_mode != XC2IOBZIAMode::PAD) ||
+                (reg && fb_mode != XC2MCFeedbackMode::REG && zia_mode != XC2IOBZIAMode::REG) {
+
+                return Err(XC2NetlistError::FeedbackConflict{fb, mc});
+            }
+            if comb {
+                zia_input_of.insert(XC2EqnSignal::Comb{fb, mc}, XC2ZIAInput::Macrocell{fb: fb as u8, mc: mc as u8});
+            }
+            if pad {
+                zia_input_of.insert(XC2EqnSignal::Pad{fb, mc}, XC2ZIAInput::IBuf{ibuf: iob.unwrap() as u16});
+            }
+            if reg {
+                zia_input_of.insert(XC2EqnSignal::Reg{fb, mc}, if fb_mode == XC2MCFeedbackMode::REG {
+                    XC2ZIAInput::Macrocell{fb: fb as u8, mc: mc as u8}
+                } else {
+                    XC2ZIAInput::IBuf{ibuf: iob.unwrap() as u16}
+                });
+            }
+
+            bits.get_fb_mut()[fb as usize].mcs[mc as usize].fb_mode = fb_mode;
+            if let Some(iob) = iob {
+                if let Some(iob) = bits.get_mut_small_iob(iob as usize) {
+                    iob.zia_mode = zia_mode;
+                }
+                if let Some(iob) = bits.get_mut_large_iob(iob as usize) {
+                    iob.zia_mode = zia_mode;
+                }
+            }
+        }
+    }
+    zia_input_of.insert(XC2EqnSignal::InputOnly, XC2ZIAInput::DedicatedInput);
+
+    for fb in 0..device.num_fbs() as u32 {
+        let fb_terms = and_terms.iter().filter(|x| x.fb == fb).collect::<Vec<_>>();
+
+        // The ZIA rows of the true and complement inputs of each term
+        let term_rows = if let Some(ref fb_rows) = zia.rows[fb as usize] {
+            let values = fb_rows.iter().map(|&choice| zia_row_value(bits, choice)).collect::<Vec<_>>();
+            let fb_bits = &mut bits.get_fb_mut()[fb as usize];
+            for row in 0..INPUTS_PER_ANDTERM {
+                *fb_bits.get_mut_zia(row) = fb_rows[row];
+            }
+
+            let mut term_rows = Vec::new();
+            for and_term in &fb_terms {
+                let mut rows = [Vec::new(), Vec::new()];
+                for (polarity, term_inputs) in [&and_term.true_inputs, &and_term.comp_inputs].iter().enumerate() {
+                    for (i, input) in term_inputs.iter().enumerate() {
+                        let row = match and_term.rows[polarity] {
+                            Some(ref given) if values[given[i] as usize] == *input => given[i],
+                            Some(_) => return Err(XC2NetlistError::BadAttribute{cell: and_term.cell.to_owned(),
+                                name: String::from(if polarity == 0 {"ZIA_ROW"} else {"ZIA_ROW_B"})}),
+                            None => match values.iter().position(|x| x == input) {
+                                Some(row) => row as u32,
+                                None => return Err(match input {
+                                    &NetValue::Driver(NetDriver::Signal(signal)) => XC2NetlistError::NotRoutable {
+                                        fb,
+                                        blocking: vec![zia_input_of[&signal]],
+                                    },
+                                    _ => XC2NetlistError::ZiaFull(fb),
+                                }),
+                            },
+                        };
+                        rows[polarity].push(row);
+                    }
+                }
+                term_rows.push(rows);
+            }
+            term_rows
+        } else {
+            // Signals, in order of first use
+            let mut inputs = Vec::new();
+            // Number of rows needed for constant 0 and constant 1 inputs
+            let mut num_const = [0, 0];
+            for and_term in &fb_terms {
+                let mut term_const = [[0, 0], [0, 0]];
+                for (polarity, term_inputs) in [&and_term.true_inputs, &and_term.comp_inputs].iter().enumerate() {
+                    for input in term_inputs.iter() {
+                        match input {
+                            &NetValue::Const(val) => term_const[polarity][val as usize] += 1,
+                            &NetValue::Driver(NetDriver::Signal(signal)) => {
+                                let zia_input = zia_input_of[&signal];
+                                if !inputs.contains(&zia_input) {
+                                    inputs.push(zia_input);
+                                }
+                            },
+                            _ => unreachable!(),
+                        }
+                    }
+                }
+                // A row set to a constant can feed both the true and the complement input of a term
+                for val in 0..2 {
+                    num_const[val] = num_const[val].max(term_const[0][val].max(term_const[1][val]));
+                }
+            }
+
+            let rows = if inputs.len() > 0 {
+                zia_assign_rows(device, &inputs).ok_or(XC2NetlistError::ZiaMapUnknown(fb))?
+                    .map_err(|blocking| XC2NetlistError::NotRoutable{fb, blocking})?
+            } else {
+                Vec::new()
+            };
+            let mut free_rows = (0..INPUTS_PER_ANDTERM as u32).filter(|row| !rows.contains(row));
+            let const_rows = [
+                free_rows.by_ref().take(num_const[0]).collect::<Vec<_>>(),
+                free_rows.by_ref().take(num_const[1]).collect::<Vec<_>>(),
+            ];
+            if const_rows[0].len() < num_const[0] || const_rows[1].len() < num_const[1] {
+                return Err(XC2NetlistError::ZiaFull(fb));
+            }
+
+            let fb_bits = &mut bits.get_fb_mut()[fb as usize];
+            for (i, &input) in inputs.iter().enumerate() {
+                *fb_bits.get_mut_zia(rows[i] as usize) = input;
+            }
+            for &row in &const_rows[0] {
+                *fb_bits.get_mut_zia(row as usize) = XC2ZIAInput::Zero;
+            }
+            for &row in &const_rows[1] {
+                *fb_bits.get_mut_zia(row as usize) = XC2ZIAInput::One;
+            }
+
+            let mut term_rows = Vec::new();
+            for and_term in &fb_terms {
+                let mut term_row = [Vec::new(), Vec::new()];
+                for (polarity, term_inputs) in [&and_term.true_inputs, &and_term.comp_inputs].iter().enumerate() {
+                    let mut next_const = [0, 0];
+                    for input in term_inputs.iter() {
+                        term_row[polarity].push(match input {
+                            &NetValue::Const(val) => {
+                                next_const[val as usize] += 1;
+                                const_rows[val as usize][next_const[val as usize] - 1]
+                            },
+                            &NetValue::Driver(NetDriver::Signal(signal)) => {
+                                let zia_input = zia_input_of[&signal];
+                                rows[inputs.iter().position(|&x| x == zia_input).unwrap()]
+                            },
+                            _ => unreachable!(),
+                        });
+                    }
+                }
+                term_rows.push(term_row);
+            }
+            term_rows
+        };
+
+        let fb_bits = &mut bits.get_fb_mut()[fb as usize];
+        for (and_term, rows) in fb_terms.iter().zip(term_rows.iter()) {
+            let term_bits = fb_bits.get_mut_andterm(and_term.term as usize);
+            for &row in &rows[0] {
+                term_bits.set(row as usize, true);
+            }
+            for &row in &rows[1] {
+                term_bits.set_b(row as usize, true);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A module that is being exported, together with the next unused net number
+struct ExportModule {
+    module: Module,
+    next_net: usize,
+}
+
+impl ExportModule {
+    fn net(&mut self, name: String) -> usize {
+        let net = self.next_net;
+        self.next_net += 1;
+        self.module.netnames.insert(name, Netname {
+            hide_name: 0,
+            bits: vec![BitVal::N(net)],
+            attributes: HashMap::new(),
+        });
+        net
+    }
+
+    fn cell(&mut self, name: String, cell_type: &str, parameters: Vec<(&str, AttributeVal)>,
+        attributes: Vec<(&str, AttributeVal)>, connections: Vec<(&str, Vec<BitVal>)>) {
+
+        self.module.cells.insert(name, Cell {
+            hide_name: 0,
+            cell_type: cell_type.to_owned(),
+            parameters: parameters.into_iter().map(|(k, v)| (k.to_owned(), v)).collect(),
+            attributes: attributes.into_iter().map(|(k, v)| (k.to_owned(), v)).collect(),
+            port_directions: HashMap::new(),
+            connections: connections.into_iter().map(|(k, v)| (k.to_owned(), v)).collect(),
+        });
+    }
+}
+
+fn attr_s(s: &str) -> AttributeVal {
+    AttributeVal::S(s.to_owned())
+}
+
+fn bool_s(b: bool, true_str: &str, false_str: &str) -> AttributeVal {
+    attr_s(if b {true_str} else {false_str})
+}
+
+const ZERO: BitVal = BitVal::S(SpecialBit::_0);
+const ONE: BitVal = BitVal::S(SpecialBit::_1);
+
+impl XC2Bitstream {
+    /// Describes the configured device as a netlist of placed primitives. See the documentation of the `netlist`
+    /// module for the format.
+    pub fn to_netlist(&self) -> Netlist {
+        let device = self.bits.device_type();
+        let global_nets = self.bits.get_global_nets();
+        let fbs = self.bits.get_fb();
+        let num_fbs = device.num_fbs();
+        let num_iobs = device.num_iobs();
+
+        let mut m = ExportModule {
+            module: Module::default(),
+            // Skip 0 and 1 just like yosys does
+            next_net: 2,
+        };
+
+        m.module.attributes.insert(String::from("PART_NAME"), AttributeVal::S(format!("{}", device)));
+        m.module.attributes.insert(String::from("PART_SPEED"), AttributeVal::S(format!("{}", self.speed_grade)));
+        m.module.attributes.insert(String::from("PART_PKG"), AttributeVal::S(format!("{}", self.package)));
+        m.module.attributes.insert(String::from("GLOBAL_TERM"), bool_s(global_nets.global_pu, "PULLUP", "KEEPER"));
+        match self.bits {
+            XC2BitstreamBits::XC2C128{use_vref, ..} |
+            XC2BitstreamBits::XC2C256{use_vref, ..} |
+            XC2BitstreamBits::XC2C384{use_vref, ..} |
+            XC2BitstreamBits::XC2C512{use_vref, ..} => {
+                m.module.attributes.insert(String::from("USE_VREF"), bool_s(use_vref, "TRUE", "FALSE"));
+            },
+            _ => {},
+        }
+
+        // Nets are created in the same order as the wires of get_device_structure
+        let gck = (0..3).map(|i| m.net(format!("gck_{}", i))).collect::<Vec<_>>();
+        let gts = (0..4).map(|i| m.net(format!("gts_{}", i))).collect::<Vec<_>>();
+        let gsr = m.net(String::from("gsr"));
+        let mut pterm = Vec::new();
+        let mut orterm = Vec::new();
+        let mut xor = Vec::new();
+        let mut regout = Vec::new();
+        for fb in 0..num_fbs {
+            pterm.push((0..ANDTERMS_PER_FB).map(|i| m.net(format!("fb{}_pterm{}", fb, i))).collect::<Vec<_>>());
+            orterm.push((0..MCS_PER_FB).map(|i| m.net(format!("fb{}_or{}", fb, i))).collect::<Vec<_>>());
+            xor.push((0..MCS_PER_FB).map(|i| m.net(format!("fb{}_xor{}", fb, i))).collect::<Vec<_>>());
+            regout.push((0..MCS_PER_FB).map(|i| m.net(format!("fb{}_regout{}", fb, i))).collect::<Vec<_>>());
+        }
+        let mut to_iob = Vec::new();
+        let mut from_iob = Vec::new();
+        for iob in 0..num_iobs {
+            to_iob.push(m.net(format!("to_iob_{}", iob)));
+            from_iob.push(m.net(format!("from_iob_{}", iob)));
+        }
+        let pads = (0..num_iobs).map(|iob| {
+            let (fb, mc) = iob_num_to_fb_mc_num(device, iob as u32).unwrap();
+            m.net(format!("PAD_FB{}_{}", fb + 1, mc + 1))
+        }).collect::<Vec<_>>();
+        let inpin = self.bits.get_inpin().map(|inpin| (inpin, m.net(String::from("from_ipad")),
+            m.net(String::from("INPAD"))));
+        let iob_of_mc = |fb: usize, mc: usize| fb_mc_num_to_iob_num(device, fb as u32, mc as u32).map(|x| x as usize);
+
+        // Global buffers
+        for i in 0..3 {
+            let (fb, mc) = get_gck(device, i).unwrap();
+            let input = if global_nets.gck_enable[i] {
+                BitVal::N(from_iob[iob_of_mc(fb as usize, mc as usize).unwrap()])
+            } else {
+                ZERO
+            };
+            m.cell(format!("bufg_gck_{}", i), "BUFG", vec![], vec![],
+                vec![("I", vec![input]), ("O", vec![BitVal::N(gck[i])])]);
+        }
+        for i in 0..4 {
+            let (fb, mc) = get_gts(device, i).unwrap();
+            let input = if global_nets.gts_enable[i] {
+                BitVal::N(from_iob[iob_of_mc(fb as usize, mc as usize).unwrap()])
+            } else {
+                ZERO
+            };
+            m.cell(format!("bufg_gts_{}", i), "BUFGTS",
+                vec![("INVERT", AttributeVal::N(global_nets.gts_invert[i] as usize))], vec![],
+                vec![("I", vec![input]), ("O", vec![BitVal::N(gts[i])])]);
+        }
+        {
+            let (fb, mc) = get_gsr(device);
+            let input = if global_nets.gsr_enable {
+                BitVal::N(from_iob[iob_of_mc(fb as usize, mc as usize).unwrap()])
+            } else {
+                ZERO
+            };
+            m.cell(String::from("bufg_gsr"), "BUFGSR",
+                vec![("INVERT", AttributeVal::N(global_nets.gsr_invert as usize))], vec![],
+                vec![("I", vec![input]), ("O", vec![BitVal::N(gsr)])]);
+        }
+
+        // Function blocks
+        for fb in 0..num_fbs {
+            let fb_bits = &fbs[fb];
+            let zia_rows = (0..INPUTS_PER_ANDTERM).map(|row| zia_choice_name(device, *fb_bits.get_zia(row)))
+                .collect::<Vec<_>>();
+            m.module.attributes.insert(format!("FB{}_ZIA_ROWS", fb + 1), AttributeVal::S(zia_rows.join(" ")));
+
+            for term in 0..ANDTERMS_PER_FB {
+                let mut true_inputs = Vec::new();
+                let mut comp_inputs = Vec::new();
+                let mut true_rows = Vec::new();
+                let mut comp_rows = Vec::new();
+                for row in 0..INPUTS_PER_ANDTERM {
+                    let input = match *fb_bits.get_zia(row) {
+                        XC2ZIAInput::Zero => ZERO,
+                        XC2ZIAInput::One => ONE,
+                        XC2ZIAInput::Macrocell{fb: zia_fb, mc: zia_mc} => {
+                            match fbs[zia_fb as usize].mcs[zia_mc as usize].fb_mode {
+                                XC2MCFeedbackMode::Disabled => ZERO,
+                                XC2MCFeedbackMode::COMB => BitVal::N(xor[zia_fb as usize][zia_mc as usize]),
+                                XC2MCFeedbackMode::REG => BitVal::N(regout[zia_fb as usize][zia_mc as usize]),
+                            }
+                        },
+                        XC2ZIAInput::IBuf{ibuf} => {
+                            let zia_mode = match (self.bits.get_small_iob(ibuf as usize),
+                                self.bits.get_large_iob(ibuf as usize)) {
+
+                                (Some(iob), _) => iob.zia_mode,
+                                (_, Some(iob)) => iob.zia_mode,
+                                _ => unreachable!(),
+                            };
+                            match zia_mode {
+                                XC2IOBZIAMode::Disabled => ZERO,
+                                XC2IOBZIAMode::PAD => BitVal::N(from_iob[ibuf as usize]),
+                                XC2IOBZIAMode::REG => {
+                                    let (iob_fb, iob_mc) = iob_num_to_fb_mc_num(device, ibuf as u32).unwrap();
+                                    BitVal::N(regout[iob_fb as usize][iob_mc as usize])
+                                },
+                            }
+                        },
+                        XC2ZIAInput::DedicatedInput => BitVal::N(inpin.unwrap().1),
+                    };
+
+                    if fb_bits.get_andterm(term).get(row) {
+                        true_inputs.push(input);
+                        true_rows.push(row.to_string());
+                    }
+                    if fb_bits.get_andterm(term).get_b(row) {
+                        comp_inputs.push(input);
+                        comp_rows.push(row.to_string());
+                    }
+                }
+
+                m.cell(format!("fb{}_andgate{}", fb, term), "ANDTERM",
+                    vec![("TRUE_INP", AttributeVal::N(true_inputs.len())),
+                         ("COMP_INP", AttributeVal::N(comp_inputs.len()))],
+                    vec![("LOC", AttributeVal::S(format!("FB{}_P{}", fb + 1, term))),
+                         ("ZIA_ROW", AttributeVal::S(true_rows.join(" "))),
+                         ("ZIA_ROW_B", AttributeVal::S(comp_rows.join(" ")))],
+                    vec![("IN", true_inputs), ("IN_B", comp_inputs), ("OUT", vec![BitVal::N(pterm[fb][term])])]);
+            }
+
+            for mc in 0..MCS_PER_FB {
+                let mc_bits = &fb_bits.mcs[mc];
+                let loc = AttributeVal::S(format!("FB{}_{}", fb + 1, mc + 1));
+                let iob = iob_of_mc(fb, mc);
+                let obuf_uses_ff = match iob.map(|iob| (self.bits.get_small_iob(iob), self.bits.get_large_iob(iob))) {
+                    Some((Some(iob), _)) => Some(iob.obuf_uses_ff),
+                    Some((_, Some(iob))) => Some(iob.obuf_uses_ff),
+                    _ => None,
+                };
+
+                // OR gate
+                let or_inputs = (0..ANDTERMS_PER_FB)
+                    .filter(|&term| fb_bits.or_terms[mc].get(term))
+                    .map(|term| BitVal::N(pterm[fb][term]))
+                    .collect::<Vec<_>>();
+                m.cell(format!("fb{}_orgate{}", fb, mc), "ORTERM",
+                    vec![("WIDTH", AttributeVal::N(or_inputs.len()))], vec![("LOC", loc.clone())],
+                    vec![("IN", or_inputs), ("OUT", vec![BitVal::N(orterm[fb][mc])])]);
+
+                // XOR gate
+                let ptc = BitVal::N(pterm[fb][get_ptc(mc as u32) as usize]);
+                let (invert, xor_ptc) = match mc_bits.xor_mode {
+                    XC2MCXorMode::ZERO => (0, ZERO),
+                    XC2MCXorMode::ONE => (1, ZERO),
+                    XC2MCXorMode::PTC => (0, ptc),
+                    XC2MCXorMode::PTCB => (1, ptc),
+                };
+                let mut xor_out = vec![BitVal::N(xor[fb][mc])];
+                if obuf_uses_ff == Some(false) {
+                    xor_out.push(BitVal::N(to_iob[iob.unwrap()]));
+                }
+                let fb_mode = match mc_bits.fb_mode {
+                    XC2MCFeedbackMode::Disabled => "DISABLED",
+                    XC2MCFeedbackMode::COMB => "COMB",
+                    XC2MCFeedbackMode::REG => "REG",
+                };
+                m.cell(format!("fb{}_xorgate{}", fb, mc), "MACROCELL_XOR",
+                    vec![("INVERT_OUT", AttributeVal::N(invert))],
+                    vec![("LOC", loc.clone()), ("FB_MODE", attr_s(fb_mode))],
+                    vec![("IN_PTC", vec![xor_ptc]), ("IN_ORTERM", vec![BitVal::N(orterm[fb][mc])]), ("OUT", xor_out)]);
+
+                // Register
+                let cell_type = match (mc_bits.reg_mode, mc_bits.is_ddr, mc_bits.clk_invert_pol) {
+                    (XC2MCRegMode::DFF, true, _) => "FDDCP",
+                    (XC2MCRegMode::DFF, false, false) => "FDCP",
+                    (XC2MCRegMode::DFF, false, true) => "FDCP_N",
+                    (XC2MCRegMode::LATCH, _, false) => "LDCP",
+                    (XC2MCRegMode::LATCH, _, true) => "LDCP_N",
+                    (XC2MCRegMode::TFF, true, _) => "FTDCP",
+                    (XC2MCRegMode::TFF, false, false) => "FTCP",
+                    (XC2MCRegMode::TFF, false, true) => "FTCP_N",
+                    (XC2MCRegMode::DFFCE, true, _) => "FDDCPE",
+                    (XC2MCRegMode::DFFCE, false, false) => "FDCPE",
+                    (XC2MCRegMode::DFFCE, false, true) => "FDCPE_N",
+                };
+
+                let mut q = vec![BitVal::N(regout[fb][mc])];
+                if obuf_uses_ff == Some(true) {
+                    q.push(BitVal::N(to_iob[iob.unwrap()]));
+                }
+                let d = match (mc_bits.ff_in_ibuf, iob) {
+                    (false, _) => vec![BitVal::N(xor[fb][mc])],
+                    (true, Some(iob)) => vec![BitVal::N(from_iob[iob])],
+                    (true, None) => vec![],
+                };
+                let clk = BitVal::N(match mc_bits.clk_src {
+                    XC2MCRegClkSrc::GCK0 => gck[0],
+                    XC2MCRegClkSrc::GCK1 => gck[1],
+                    XC2MCRegClkSrc::GCK2 => gck[2],
+                    XC2MCRegClkSrc::CTC => pterm[fb][CTC as usize],
+                    XC2MCRegClkSrc::PTC => pterm[fb][get_ptc(mc as u32) as usize],
+                });
+                let set = match mc_bits.s_src {
+                    XC2MCRegSetSrc::Disabled => ZERO,
+                    XC2MCRegSetSrc::GSR => BitVal::N(gsr),
+                    XC2MCRegSetSrc::CTS => BitVal::N(pterm[fb][CTS as usize]),
+                    XC2MCRegSetSrc::PTA => BitVal::N(pterm[fb][get_pta(mc as u32) as usize]),
+                };
+                let reset = match mc_bits.r_src {
+                    XC2MCRegResetSrc::Disabled => ZERO,
+                    XC2MCRegResetSrc::GSR => BitVal::N(gsr),
+                    XC2MCRegResetSrc::CTR => BitVal::N(pterm[fb][CTR as usize]),
+                    XC2MCRegResetSrc::PTA => BitVal::N(pterm[fb][get_pta(mc as u32) as usize]),
+                };
+
+                let mut connections = vec![("Q", q), ("PRE", vec![set]), ("CLR", vec![reset])];
+                match mc_bits.reg_mode {
+                    XC2MCRegMode::DFF => {
+                        connections.push(("C", vec![clk]));
+                        connections.push(("D", d));
+                    },
+                    XC2MCRegMode::LATCH => {
+                        connections.push(("G", vec![clk]));
+                        connections.push(("D", d));
+                    },
+                    XC2MCRegMode::TFF => {
+                        connections.push(("C", vec![clk]));
+                        connections.push(("T", d));
+                    },
+                    XC2MCRegMode::DFFCE => {
+                        connections.push(("C", vec![clk]));
+                        connections.push(("D", d));
+                        connections.push(("CE", vec![BitVal::N(pterm[fb][get_ptc(mc as u32) as usize])]));
+                    },
+                }
+                m.cell(format!("fb{}_reg{}", fb, mc), cell_type,
+                    vec![("INIT", AttributeVal::N(mc_bits.init_state as usize))], vec![("LOC", loc)], connections);
+            }
+        }
+
+        // IO buffers
+        for iob in 0..num_iobs {
+            let (fb, mc) = iob_num_to_fb_mc_num(device, iob as u32).unwrap();
+            let mut attributes = vec![("LOC", AttributeVal::S(format!("FB{}_{}", fb + 1, mc + 1)))];
+
+            let (obuf_mode, zia_mode) = if let Some(iob_bits) = self.bits.get_small_iob(iob) {
+                attributes.push(("SLEW", bool_s(iob_bits.slew_is_fast, "FAST", "SLOW")));
+                attributes.push(("TERM", bool_s(iob_bits.termination_enabled, "TRUE", "FALSE")));
+                attributes.push(("SCHMITT_TRIGGER", bool_s(iob_bits.schmitt_trigger, "TRUE", "FALSE")));
+                (iob_bits.obuf_mode, iob_bits.zia_mode)
+            } else {
+                let iob_bits = self.bits.get_large_iob(iob).unwrap();
+                attributes.push(("SLEW", bool_s(iob_bits.slew_is_fast, "FAST", "SLOW")));
+                attributes.push(("TERM", bool_s(iob_bits.termination_enabled, "TRUE", "FALSE")));
+                match iob_bits.ibuf_mode {
+                    XC2IOBIbufMode::NoVrefNoSt => attributes.push(("SCHMITT_TRIGGER", attr_s("FALSE"))),
+                    XC2IOBIbufMode::NoVrefSt => attributes.push(("SCHMITT_TRIGGER", attr_s("TRUE"))),
+                    XC2IOBIbufMode::UsesVref => attributes.push(("USES_VREF", attr_s("TRUE"))),
+                    XC2IOBIbufMode::IsVref => attributes.push(("IS_VREF", attr_s("TRUE"))),
+                }
+                if iob_bits.uses_data_gate {
+                    attributes.push(("DATA_GATE", attr_s("TRUE")));
+                }
+                (iob_bits.obuf_mode, iob_bits.zia_mode)
+            };
+            attributes.push(("ZIA_MODE", attr_s(match zia_mode {
+                XC2IOBZIAMode::Disabled => "DISABLED",
+                XC2IOBZIAMode::PAD => "PAD",
+                XC2IOBZIAMode::REG => "REG",
+            })));
+            if obuf_mode == XC2IOBOBufMode::CGND {
+                attributes.push(("CGND", AttributeVal::N(1)));
+            }
+
+            let pad = pads[iob];
+            let to = BitVal::N(to_iob[iob]);
+            let from = BitVal::N(from_iob[iob]);
+
+            let obuf = match obuf_mode {
+                XC2IOBOBufMode::Disabled => None,
+                XC2IOBOBufMode::PushPull => Some((to, ONE)),
+                XC2IOBOBufMode::OpenDrain => Some((ZERO, to)),
+                XC2IOBOBufMode::TriStateGTS0 => Some((to, BitVal::N(gts[0]))),
+                XC2IOBOBufMode::TriStateGTS1 => Some((to, BitVal::N(gts[1]))),
+                XC2IOBOBufMode::TriStateGTS2 => Some((to, BitVal::N(gts[2]))),
+                XC2IOBOBufMode::TriStateGTS3 => Some((to, BitVal::N(gts[3]))),
+                XC2IOBOBufMode::TriStatePTB => Some((to, BitVal::N(pterm[fb as usize][get_ptb(mc) as usize]))),
+                XC2IOBOBufMode::TriStateCTE => Some((to, BitVal::N(pterm[fb as usize][CTE as usize]))),
+                // FIXME: Verify the CGND behavior on hardware
+                XC2IOBOBufMode::CGND => Some((ZERO, ONE)),
+            };
+
+            m.module.ports.insert(format!("PAD_FB{}_{}", fb + 1, mc + 1), Port {
+                direction: if obuf.is_some() {PortDirection::InOut} else {PortDirection::Input},
+                bits: vec![BitVal::N(pad)],
+            });
+            if let Some((input, enable)) = obuf {
+                m.cell(format!("iob_{}", iob), "IOBUFE", vec![], attributes,
+                    vec![("I", vec![input]), ("E", vec![enable]), ("O", vec![from]), ("IO", vec![BitVal::N(pad)])]);
+            } else {
+                m.cell(format!("iob_{}", iob), "IBUF", vec![], attributes,
+                    vec![("I", vec![BitVal::N(pad)]), ("O", vec![from])]);
+            }
+        }
+
+        // Input-only pin
+        if let Some((inpin, from_ipad, pad)) = inpin {
+            m.module.ports.insert(String::from("INPAD"), Port {
+                direction: PortDirection::Input,
+                bits: vec![BitVal::N(pad)],
+            });
+            m.cell(String::from("ipad"), "IBUF", vec![],
+                vec![("LOC", attr_s("INPAD")),
+                     ("TERM", bool_s(inpin.termination_enabled, "TRUE", "FALSE")),
+                     ("SCHMITT_TRIGGER", bool_s(inpin.schmitt_trigger, "TRUE", "FALSE"))],
+                vec![("I", vec![BitVal::N(pad)]), ("O", vec![BitVal::N(from_ipad)])]);
+        }
+
+        // None of the cells have outputs wider than one bit, so extra nets driven by an output get buffers
+        let mut buffers = Vec::new();
+        for (cell_name, cell) in m.module.cells.iter_mut() {
+            let out = match cell.cell_type.as_ref() {
+                "ANDTERM" | "ORTERM" | "MACROCELL_XOR" => cell.connections.get_mut("OUT").unwrap(),
+                "IBUF" | "IOBUFE" | "BUFG" | "BUFGSR" | "BUFGTS" => cell.connections.get_mut("O").unwrap(),
+                _ => cell.connections.get_mut("Q").unwrap(),
+            };
+
+            let first_out = out[0];
+            for (i, other_out) in out.drain(1..).enumerate() {
+                buffers.push((format!("autobuf{}_{}", i, cell_name), first_out, other_out));
+            }
+        }
+        for (name, a, y) in buffers {
+            m.cell(name, "$_BUF_", vec![], vec![], vec![("A", vec![a]), ("Y", vec![y])]);
+        }
+
+        let mut modules = HashMap::new();
+        modules.insert(String::from("top"), m.module);
+        Netlist {
+            creator: format!("xc2bit {}", env!("CARGO_PKG_VERSION")),
+            modules,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // FB1_2 outputs the AND of the pin of FB1_1 and the complement of the pin of FB1_3
+    const SIMPLE_NETLIST: &str = r#"{
+        "modules": {
+            "top": {
+                "attributes": {"PART_NAME": "XC2C32A", "PART_SPEED": "4", "PART_PKG": "VQ44"},
+                "ports": {
+                    "in_a": {"direction": "inout", "bits": [2]},
+                    "in_b": {"direction": "inout", "bits": [3]},
+                    "out": {"direction": "inout", "bits": [4]}
+                },
+                "cells": {
+                    "iob_a": {"type": "IBUF", "attributes": {"LOC": "FB1_1"},
+                        "connections": {"I": [2], "O": [10]}},
+                    "iob_b": {"type": "IBUF", "attributes": {"LOC": "FB1_3"},
+                        "connections": {"I": [3], "O": [11]}},
+                    "and": {"type": "ANDTERM", "attributes": {"LOC": "FB1_P8"},
+                        "parameters": {"TRUE_INP": 1, "COMP_INP": 1},
+                        "connections": {"IN": [10], "IN_B": [11], "OUT": [12]}},
+                    "or": {"type": "ORTERM", "attributes": {"LOC": "FB1_2"}, "parameters": {"WIDTH": 1},
+                        "connections": {"IN": [12], "OUT": [13]}},
+                    "xor": {"type": "MACROCELL_XOR", "attributes": {"LOC": "FB1_2"}, "parameters": {"INVERT_OUT": 0},
+                        "connections": {"IN_PTC": ["0"], "IN_ORTERM": [13], "OUT": [14]}},
+                    "iob_out": {"type": "IOBUFE", "attributes": {"LOC": "FB1_2", "SLEW": "SLOW"},
+                        "connections": {"I": [14], "E": ["1"], "O": [15], "IO": [4]}}
+                }
+            }
+        }
+    }"#;
+
+    fn convert(json: &str) -> Result<XC2Bitstream, XC2NetlistError> {
+        XC2Bitstream::from_netlist(&Netlist::from_slice(json.as_bytes()).unwrap())
+    }
+
+    #[test]
+    fn netlist_simple() {
+        let bitstream = convert(SIMPLE_NETLIST).unwrap();
+        let device = bitstream.bits.device_type();
+        assert_eq!(device, XC2Device::XC2C32A);
+
+        let fb = &bitstream.bits.get_fb()[0];
+        let iob_a = fb_mc_num_to_iob_num(device, 0, 0).unwrap();
+        let iob_b = fb_mc_num_to_iob_num(device, 0, 2).unwrap();
+        let iob_out = fb_mc_num_to_iob_num(device, 0, 1).unwrap();
+        let row_a = (0..INPUTS_PER_ANDTERM).find(|&i| *fb.get_zia(i) == XC2ZIAInput::IBuf{ibuf: iob_a as u16}).unwrap();
+        let row_b = (0..INPUTS_PER_ANDTERM).find(|&i| *fb.get_zia(i) == XC2ZIAInput::IBuf{ibuf: iob_b as u16}).unwrap();
+
+        for i in 0..INPUTS_PER_ANDTERM {
+            assert_eq!(fb.get_andterm(8).get(i), i == row_a);
+            assert_eq!(fb.get_andterm(8).get_b(i), i == row_b);
+        }
+        for term in 0..ANDTERMS_PER_FB {
+            assert_eq!(fb.or_terms[1].get(term), term == 8);
+        }
+        assert_eq!(fb.mcs[1].xor_mode, XC2MCXorMode::ZERO);
+        assert_eq!(fb.mcs[1].fb_mode, XC2MCFeedbackMode::Disabled);
+
+        let iob = bitstream.bits.get_small_iob(iob_out as usize).unwrap();
+        assert_eq!(iob.obuf_mode, XC2IOBOBufMode::PushPull);
+        assert!(!iob.obuf_uses_ff);
+        assert!(!iob.slew_is_fast);
+        assert_eq!(bitstream.bits.get_small_iob(iob_a as usize).unwrap().zia_mode, XC2IOBZIAMode::PAD);
+    }
+
+    #[test]
+    fn netlist_errors() {
+        assert_eq!(convert(&SIMPLE_NETLIST.replace("MACROCELL_XOR", "FOO")).err(),
+            Some(XC2NetlistError::UnknownCellType{cell: String::from("xor"), cell_type: String::from("FOO")}));
+        assert_eq!(convert(&SIMPLE_NETLIST.replace("FB1_P8", "FB3_P8")).err(),
+            Some(XC2NetlistError::BadLoc{cell: String::from("and"), loc: String::from("FB3_P8")}));
+        assert_eq!(convert(&SIMPLE_NETLIST.replace("\"IN\": [12]", "\"IN\": [10]")).err(),
+            Some(XC2NetlistError::IllegalConnection{cell: String::from("or"), port: String::from("IN"),
+                expected: "product terms of its function block"}));
+        assert_eq!(convert(&SIMPLE_NETLIST.replace("\"IN_B\": [11]", "\"IN_B\": [99]")).err(),
+            Some(XC2NetlistError::UndrivenNet{cell: String::from("and"), port: String::from("IN_B")}));
+        assert_eq!(convert(&SIMPLE_NETLIST.replace("\"OUT\": [14]", "\"OUT\": [11]")).err(),
+            Some(XC2NetlistError::MultipleDrivers(11)));
+        assert_eq!(convert(&SIMPLE_NETLIST.replace("\"SLEW\": \"SLOW\"", "\"USES_VREF\": \"TRUE\"")).err(),
+            Some(XC2NetlistError::BadAttribute{cell: String::from("iob_out"), name: String::from("USES_VREF")}));
+        assert_eq!(convert(&SIMPLE_NETLIST.replace("{\"LOC\": \"FB1_P8\"}",
+            "{\"LOC\": \"FB1_P8\", \"ZIA_ROW\": \"3\"}")).err(),
+            Some(XC2NetlistError::BadAttribute{cell: String::from("and"), name: String::from("ZIA_ROW")}));
+        assert_eq!(convert(&SIMPLE_NETLIST.replace("\"XC2C32A\", \"PART_SPEED\": \"4\", \"PART_PKG\": \"VQ44\"",
+            "\"XC2C128\", \"PART_SPEED\": \"6\", \"PART_PKG\": \"VQ100\"")).err(),
+            Some(XC2NetlistError::ZiaMapUnknown(0)));
+    }
+}
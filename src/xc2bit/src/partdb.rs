@@ -25,12 +25,16 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 //! Miscellaneous stuff related to possible part combinations
 
-use std::fmt;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
 
 use crate::*;
 
 /// Coolrunner-II devices
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 pub enum XC2Device {
     XC2C32,
     XC2C32A,
@@ -134,102 +138,58 @@ impl fmt::Display for XC2Package {
     }
 }
 
-/// Determine if the given combination of device, speed, and package is a legal combination or not.
-fn is_valid_part_combination(device: XC2Device, speed: XC2Speed, package: XC2Package) -> bool {
-    match device {
-        XC2Device::XC2C32 => {
-            if speed == XC2Speed::Speed4 || speed == XC2Speed::Speed6 {
-                if package == XC2Package::PC44 || package == XC2Package::VQ44 || package == XC2Package::CP56 {
-                    true
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
-        },
-        XC2Device::XC2C32A => {
-            if speed == XC2Speed::Speed4 || speed == XC2Speed::Speed6 {
-                if package == XC2Package::PC44 || package == XC2Package::VQ44 || package == XC2Package::CP56 || 
-                   package == XC2Package::QFG32 {
-                    true
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
-        },
-        XC2Device::XC2C64 => {
-            if speed == XC2Speed::Speed5 || speed == XC2Speed::Speed7 {
-                if package == XC2Package::PC44 || package == XC2Package::VQ44 || package == XC2Package::CP56 ||
-                    package == XC2Package::VQ100 {
-                    true
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
-        },
-        XC2Device::XC2C64A => {
-            if speed == XC2Speed::Speed5 || speed == XC2Speed::Speed7 {
-                if package == XC2Package::PC44 || package == XC2Package::VQ44 || package == XC2Package::CP56 ||
-                    package == XC2Package::VQ100 || package == XC2Package::QFG48 {
-                    true
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
-        },
-        XC2Device::XC2C128 => {
-            if speed == XC2Speed::Speed6 || speed == XC2Speed::Speed7 {
-                if package == XC2Package::VQ100 || package == XC2Package::CP132 || package == XC2Package::TQ144 {
-                    true
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
-        },
-        XC2Device::XC2C256 => {
-            if speed == XC2Speed::Speed6 || speed == XC2Speed::Speed7 {
-                if package == XC2Package::VQ100 || package == XC2Package::CP132 || package == XC2Package::TQ144 ||
-                   package == XC2Package::PQ208 || package == XC2Package::FT256 {
-                    true
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
-        },
-        XC2Device::XC2C384 => {
-            if speed == XC2Speed::Speed7 || speed == XC2Speed::Speed10 {
-                if package == XC2Package::TQ144 || package == XC2Package::PQ208 || package == XC2Package::FT256 ||
-                   package == XC2Package::FG324 {
-                    true
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
-        },
-        XC2Device::XC2C512 => {
-            if speed == XC2Speed::Speed7 || speed == XC2Speed::Speed10 {
-                if package == XC2Package::PQ208 || package == XC2Package::FT256 || package == XC2Package::FG324 {
-                    true
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
-        },
+/// Mask of the bits of the JTAG IDCODE that identify the device. The top 4 bits are the silicon revision and bits 15
+/// to 12 depend on the package, so neither of these is checked.
+pub const XC2_IDCODE_MASK: u32 = 0x0FFF0FFF;
+
+impl XC2Device {
+    /// Returns the JTAG IDCODE of the device, with the revision and package-dependent bits set to 0.
+    /// See `XC2_IDCODE_MASK`.
+    pub fn idcode(&self) -> u32 {
+        let devid = match *self {
+            XC2Device::XC2C32 => 0x01,
+            XC2Device::XC2C32A => 0x21,
+            XC2Device::XC2C64 => 0x05,
+            XC2Device::XC2C64A => 0x25,
+            XC2Device::XC2C128 => 0x18,
+            XC2Device::XC2C256 => 0x14,
+            XC2Device::XC2C384 => 0x15,
+            XC2Device::XC2C512 => 0x17,
+        };
+
+        // 0110 11 | device | package | Xilinx manufacturer ID | 1
+        0x06C00000 | (devid << 16) | (XILINX_MANUFACTURER_ID << 1) | 1
+    }
+
+    /// Returns `true` if the given IDCODE read over JTAG belongs to this device
+    pub fn idcode_matches(&self, idcode: u32) -> bool {
+        idcode & XC2_IDCODE_MASK == self.idcode()
+    }
+
+    /// Returns the speed grades that this device is sold in
+    pub fn valid_speeds(&self) -> &'static [XC2Speed] {
+        match *self {
+            XC2Device::XC2C32 | XC2Device::XC2C32A => &[XC2Speed::Speed4, XC2Speed::Speed6],
+            XC2Device::XC2C64 | XC2Device::XC2C64A => &[XC2Speed::Speed5, XC2Speed::Speed7],
+            XC2Device::XC2C128 | XC2Device::XC2C256 => &[XC2Speed::Speed6, XC2Speed::Speed7],
+            XC2Device::XC2C384 | XC2Device::XC2C512 => &[XC2Speed::Speed7, XC2Speed::Speed10],
+        }
+    }
+
+    /// Returns the packages that this device is sold in
+    pub fn valid_packages(&self) -> &'static [XC2Package] {
+        match *self {
+            XC2Device::XC2C32 => &[XC2Package::PC44, XC2Package::VQ44, XC2Package::CP56],
+            XC2Device::XC2C32A => &[XC2Package::PC44, XC2Package::VQ44, XC2Package::CP56, XC2Package::QFG32],
+            XC2Device::XC2C64 => &[XC2Package::PC44, XC2Package::VQ44, XC2Package::CP56, XC2Package::VQ100],
+            XC2Device::XC2C64A => &[XC2Package::PC44, XC2Package::VQ44, XC2Package::CP56, XC2Package::VQ100,
+                XC2Package::QFG48],
+            XC2Device::XC2C128 => &[XC2Package::VQ100, XC2Package::CP132, XC2Package::TQ144],
+            XC2Device::XC2C256 => &[XC2Package::VQ100, XC2Package::CP132, XC2Package::TQ144, XC2Package::PQ208,
+                XC2Package::FT256],
+            XC2Device::XC2C384 => &[XC2Package::TQ144, XC2Package::PQ208, XC2Package::FT256, XC2Package::FG324],
+            XC2Device::XC2C512 => &[XC2Package::PQ208, XC2Package::FT256, XC2Package::FG324],
+        }
     }
 }
 
@@ -242,85 +202,288 @@ pub struct XC2DeviceSpeedPackage {
 }
 
 impl XC2DeviceSpeedPackage {
-    /// Parses the given string in <device>-<speed>-<package> format and returns the parsed result if it is a legal
-    /// combination. Returns `None` if the part name string does not represent a valid device.
+    /// Parses the given part name and returns the parsed result if it is a legal combination. Returns `None` if the
+    /// part name string does not represent a valid device. Both the <device>-<speed>-<package> format and full
+    /// ordering codes (see `XC2Part::from_str`) are accepted.
     pub fn from_str(part_name: &str) -> Option<Self> {
-        let dev;
-        let spd;
-        let pkg;
+        XC2Part::from_str(part_name).map(|part| part.device_speed_package())
+    }
+}
 
+impl fmt::Display for XC2DeviceSpeedPackage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}-{}", self.dev, self.spd, self.pkg)
+    }
+}
+
+/// Product lines that Coolrunner-II dies are sold under
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum XC2Family {
+    /// Regular XC2C parts
+    XC2C,
+    /// XA2C automotive parts. These use the same dies as the corresponding XC2C parts.
+    XA2C,
+}
+
+impl fmt::Display for XC2Family {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Temperature grades, specified by the last letter of the ordering code
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum XC2TempGrade {
+    /// "C", 0 to 70 °C
+    Commercial,
+    /// "I", -40 to 85 °C
+    Industrial,
+    /// "Q", -40 to 125 °C
+    Automotive,
+}
+
+impl fmt::Display for XC2TempGrade {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            XC2TempGrade::Commercial => "C",
+            XC2TempGrade::Industrial => "I",
+            XC2TempGrade::Automotive => "Q",
+        })
+    }
+}
+
+impl XC2Family {
+    /// Returns the speed grades that the given device is sold in under this family
+    pub fn valid_speeds(&self, device: XC2Device) -> &'static [XC2Speed] {
+        match *self {
+            XC2Family::XC2C => device.valid_speeds(),
+            XC2Family::XA2C => match device {
+                XC2Device::XC2C32A => &[XC2Speed::Speed6],
+                XC2Device::XC2C64A | XC2Device::XC2C128 | XC2Device::XC2C256 => &[XC2Speed::Speed7],
+                XC2Device::XC2C384 => &[XC2Speed::Speed10],
+                _ => &[],
+            },
+        }
+    }
+
+    /// Returns the packages that the given device is sold in under this family
+    pub fn valid_packages(&self, device: XC2Device) -> &'static [XC2Package] {
+        match *self {
+            XC2Family::XC2C => device.valid_packages(),
+            XC2Family::XA2C => match device {
+                XC2Device::XC2C32A => &[XC2Package::QFG32, XC2Package::VQ44],
+                XC2Device::XC2C64A => &[XC2Package::VQ44, XC2Package::VQ100],
+                XC2Device::XC2C128 => &[XC2Package::VQ100, XC2Package::TQ144],
+                XC2Device::XC2C256 => &[XC2Package::VQ100, XC2Package::TQ144, XC2Package::FT256],
+                XC2Device::XC2C384 => &[XC2Package::TQ144, XC2Package::FT256],
+                _ => &[],
+            },
+        }
+    }
+
+    /// Returns the temperature grades that parts in this family are sold in
+    pub fn valid_temp_grades(&self) -> &'static [XC2TempGrade] {
+        match *self {
+            XC2Family::XC2C => &[XC2TempGrade::Commercial, XC2TempGrade::Industrial],
+            XC2Family::XA2C => &[XC2TempGrade::Industrial, XC2TempGrade::Automotive],
+        }
+    }
+
+    /// Returns `true` if this family only comes in lead-free packages
+    pub fn is_lead_free_only(&self) -> bool {
+        *self == XC2Family::XA2C
+    }
+}
+
+/// Splits a package code such as "VQG44" into the package and whether it is the lead-free variant
+fn parse_package(name: &str) -> Option<(XC2Package, bool)> {
+    let digits_idx = name.find(|c: char| c.is_ascii_digit())?;
+    let (letters, pins) = name.split_at(digits_idx);
+    let letters = letters.to_ascii_uppercase();
+
+    let (base, lead_free) = if letters.len() == 3 && letters.ends_with('G') {
+        (&letters[..2], true)
+    } else {
+        (&letters[..], false)
+    };
+
+    let pkg = match (base, pins) {
+        ("PC", "44") => XC2Package::PC44,
+        // These packages only exist in lead-free form
+        ("QF", "32") if lead_free => XC2Package::QFG32,
+        ("QF", "48") if lead_free => XC2Package::QFG48,
+        ("VQ", "44") => XC2Package::VQ44,
+        ("CP", "56") => XC2Package::CP56,
+        ("VQ", "100") => XC2Package::VQ100,
+        ("CP", "132") => XC2Package::CP132,
+        ("TQ", "144") => XC2Package::TQ144,
+        ("PQ", "208") => XC2Package::PQ208,
+        ("FT", "256") => XC2Package::FT256,
+        ("FG", "324") => XC2Package::FG324,
+        _ => return None,
+    };
+
+    Some((pkg, lead_free))
+}
+
+/// Returns the package code as used in ordering codes, e.g. "VQG44"
+fn package_code(pkg: XC2Package, lead_free: bool) -> String {
+    match pkg {
+        XC2Package::QFG32 | XC2Package::QFG48 => pkg.to_string(),
+        _ if !lead_free => pkg.to_string(),
+        _ => {
+            let name = pkg.to_string();
+            let digits_idx = name.find(|c: char| c.is_ascii_digit()).unwrap();
+            format!("{}G{}", &name[..digits_idx], &name[digits_idx..])
+        }
+    }
+}
+
+/// A complete orderable part, as would be found in a bill of materials
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct XC2Part {
+    pub family: XC2Family,
+    /// The die that is used in this part
+    pub dev: XC2Device,
+    pub spd: XC2Speed,
+    /// The pinout used by this part. Lead-free packages have the same pinout as the regular ones.
+    pub pkg: XC2Package,
+    pub lead_free: bool,
+    /// Temperature grade, if it was specified
+    pub temp: Option<XC2TempGrade>,
+}
+
+impl XC2Part {
+    /// Parses a part name and returns the parsed result if it is a legal combination. Returns `None` if the part name
+    /// string does not represent a valid part.
+    ///
+    /// Both the <device>-<speed>-<package> format (e.g. "xc2c32a-4-vq44") and ordering codes (e.g.
+    /// "XA2C64A-7VQG44Q" or "XC2C128-6CPG132C") are accepted. The temperature grade is optional.
+    pub fn from_str(part_name: &str) -> Option<Self> {
         let name_split = part_name.split('-').collect::<Vec<_>>();
-        if name_split.len() != 3 {
+        let (dev_str, spd_str, pkg_str) = match name_split.len() {
+            3 => (name_split[0], name_split[1], name_split[2]),
+            2 => {
+                let pkg_idx = name_split[1].find(|c: char| !c.is_ascii_digit())?;
+                let (spd_str, pkg_str) = name_split[1].split_at(pkg_idx);
+                (name_split[0], spd_str, pkg_str)
+            },
+            _ => return None,
+        };
+
+        if dev_str.len() < 4 {
             return None;
         }
-
-        if name_split[0].eq_ignore_ascii_case("xc2c32") {
-            dev = XC2Device::XC2C32;
-        } else if name_split[0].eq_ignore_ascii_case("xc2c32a") {
-            dev = XC2Device::XC2C32A;
-        } else if name_split[0].eq_ignore_ascii_case("xc2c64") {
-            dev = XC2Device::XC2C64;
-        } else if name_split[0].eq_ignore_ascii_case("xc2c64a") {
-            dev = XC2Device::XC2C64A;
-        } else if name_split[0].eq_ignore_ascii_case("xc2c128") {
-            dev = XC2Device::XC2C128;
-        } else if name_split[0].eq_ignore_ascii_case("xc2c256") {
-            dev = XC2Device::XC2C256;
-        } else if name_split[0].eq_ignore_ascii_case("xc2c384") {
-            dev = XC2Device::XC2C384;
-        } else if name_split[0].eq_ignore_ascii_case("xc2c512") {
-            dev = XC2Device::XC2C512;
+        let (family_str, density_str) = dev_str.split_at(4);
+        let family = if family_str.eq_ignore_ascii_case("xc2c") {
+            XC2Family::XC2C
+        } else if family_str.eq_ignore_ascii_case("xa2c") {
+            XC2Family::XA2C
         } else {
             return None;
-        }
+        };
+
+        let dev = match &density_str.to_ascii_lowercase()[..] {
+            "32" => XC2Device::XC2C32,
+            "32a" => XC2Device::XC2C32A,
+            "64" => XC2Device::XC2C64,
+            "64a" => XC2Device::XC2C64A,
+            "128" => XC2Device::XC2C128,
+            "256" => XC2Device::XC2C256,
+            "384" => XC2Device::XC2C384,
+            "512" => XC2Device::XC2C512,
+            _ => return None,
+        };
 
-        if name_split[1] == "4" {
-            spd = XC2Speed::Speed4;
-        } else if name_split[1] == "5" {
-            spd = XC2Speed::Speed5;
-        } else if name_split[1] == "6" {
-            spd = XC2Speed::Speed6;
-        } else if name_split[1] == "7" {
-            spd = XC2Speed::Speed7;
-        } else if name_split[1] == "10" {
-            spd = XC2Speed::Speed10;
+        let spd = match spd_str {
+            "4" => XC2Speed::Speed4,
+            "5" => XC2Speed::Speed5,
+            "6" => XC2Speed::Speed6,
+            "7" => XC2Speed::Speed7,
+            "10" => XC2Speed::Speed10,
+            _ => return None,
+        };
+
+        let last = pkg_str.chars().last()?;
+        let (pkg_str, temp) = if last.is_ascii_alphabetic() {
+            let temp = match last.to_ascii_uppercase() {
+                'C' => XC2TempGrade::Commercial,
+                'I' => XC2TempGrade::Industrial,
+                'Q' => XC2TempGrade::Automotive,
+                _ => return None,
+            };
+            (&pkg_str[..pkg_str.len() - 1], Some(temp))
         } else {
+            (pkg_str, None)
+        };
+
+        let (pkg, lead_free) = parse_package(pkg_str)?;
+
+        let part = Self {
+            family, dev, spd, pkg, lead_free, temp
+        };
+
+        if !part.is_valid() {
             return None;
         }
 
-        if name_split[2].eq_ignore_ascii_case("pc44") {
-            pkg = XC2Package::PC44;
-        } else if name_split[2].eq_ignore_ascii_case("qfg32") {
-            pkg = XC2Package::QFG32;
-        } else if name_split[2].eq_ignore_ascii_case("vq44") {
-            pkg = XC2Package::VQ44;
-        } else if name_split[2].eq_ignore_ascii_case("qfg48") {
-            pkg = XC2Package::QFG48;
-        } else if name_split[2].eq_ignore_ascii_case("cp56") {
-            pkg = XC2Package::CP56;
-        } else if name_split[2].eq_ignore_ascii_case("vq100") {
-            pkg = XC2Package::VQ100;
-        } else if name_split[2].eq_ignore_ascii_case("cp132") {
-            pkg = XC2Package::CP132;
-        } else if name_split[2].eq_ignore_ascii_case("tq144") {
-            pkg = XC2Package::TQ144;
-        } else if name_split[2].eq_ignore_ascii_case("pq208") {
-            pkg = XC2Package::PQ208;
-        } else if name_split[2].eq_ignore_ascii_case("ft256") {
-            pkg = XC2Package::FT256;
-        } else if name_split[2].eq_ignore_ascii_case("fg324") {
-            pkg = XC2Package::FG324;
-        } else {
-            return None;
+        Some(part)
+    }
+
+    /// Returns `true` if this combination of family, device, speed, package, and temperature grade is sold
+    pub fn is_valid(&self) -> bool {
+        if !self.family.valid_speeds(self.dev).contains(&self.spd) ||
+           !self.family.valid_packages(self.dev).contains(&self.pkg) {
+            return false;
         }
 
-        if !is_valid_part_combination(dev, spd, pkg) {
-            return None;
+        if self.family.is_lead_free_only() && !self.lead_free {
+            return false;
         }
 
-        Some(Self {
-            dev, spd, pkg
-        })
+        if let Some(temp) = self.temp {
+            if !self.family.valid_temp_grades().contains(&temp) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Returns the die, speed grade, and pinout of this part
+    pub fn device_speed_package(&self) -> XC2DeviceSpeedPackage {
+        XC2DeviceSpeedPackage {
+            dev: self.dev,
+            spd: self.spd,
+            pkg: self.pkg,
+        }
+    }
+
+    /// Returns the speed grades that this part is sold in
+    pub fn valid_speeds(&self) -> &'static [XC2Speed] {
+        self.family.valid_speeds(self.dev)
+    }
+
+    /// Returns the JTAG IDCODE of the die in this part. See `XC2Device::idcode`.
+    pub fn idcode(&self) -> u32 {
+        self.dev.idcode()
+    }
+
+    /// Returns the ordering code of this part, e.g. "XA2C64A-7VQG44Q". The temperature grade is only included if it
+    /// is known.
+    pub fn ordering_code(&self) -> String {
+        let dev_name = self.dev.to_string();
+        let mut ret = format!("{}{}-{}{}", self.family, &dev_name[4..], self.spd, package_code(self.pkg, self.lead_free));
+        if let Some(temp) = self.temp {
+            ret.push_str(&temp.to_string());
+        }
+        ret
+    }
+}
+
+impl fmt::Display for XC2Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.ordering_code())
     }
 }
 
@@ -353,4 +516,38 @@ mod tests {
         assert_eq!(XC2DeviceSpeedPackage::from_str("asdf"), None);
         assert_eq!(XC2DeviceSpeedPackage::from_str("xc2c32a-5-vq44-asdf"), None);
     }
+
+    #[test]
+    fn ordering_codes() {
+        let part = XC2Part::from_str("XA2C64A-7VQG44Q").unwrap();
+        assert_eq!(part.family, XC2Family::XA2C);
+        assert_eq!(part.device_speed_package(), XC2DeviceSpeedPackage {
+            dev: XC2Device::XC2C64A,
+            spd: XC2Speed::Speed7,
+            pkg: XC2Package::VQ44});
+        assert!(part.lead_free);
+        assert_eq!(part.temp, Some(XC2TempGrade::Automotive));
+        assert_eq!(part.ordering_code(), "XA2C64A-7VQG44Q");
+
+        let part = XC2Part::from_str("xc2c128-6cpg132c").unwrap();
+        assert_eq!(part.ordering_code(), "XC2C128-6CPG132C");
+        assert_eq!(XC2DeviceSpeedPackage::from_str("XC2C128-6CPG132C").unwrap().to_string(), "XC2C128-6-CP132");
+        assert_eq!(XC2Part::from_str("xc2c32a-4-qfg32").unwrap().ordering_code(), "XC2C32A-4QFG32");
+        assert_eq!(XC2Part::from_str("xc2c32a-4-vqg44").unwrap().ordering_code(), "XC2C32A-4VQG44");
+
+        // Automotive parts are only lead-free and not commercial grade
+        assert_eq!(XC2Part::from_str("XA2C64A-7VQ44Q"), None);
+        assert_eq!(XC2Part::from_str("XA2C64A-7VQG44C"), None);
+        assert_eq!(XC2Part::from_str("XA2C64A-5VQG44Q"), None);
+        assert_eq!(XC2Part::from_str("XC2C64A-7VQG44Q"), None);
+        assert_eq!(XC2Part::from_str("XC2C32A-4QF32"), None);
+    }
+
+    #[test]
+    fn idcodes() {
+        assert!(XC2Device::XC2C32A.idcode_matches(0x06E1C093));
+        assert!(XC2Device::XC2C32A.idcode_matches(0x16E18093));
+        assert!(!XC2Device::XC2C64A.idcode_matches(0x06E1C093));
+        assert_eq!(XC2Part::from_str("XC2C256-7TQG144C").unwrap().idcode(), 0x06D40093);
+    }
 }
@@ -30,6 +30,7 @@ use crate::*;
 /// Represents one single AND term in the PLA. Each AND term can perform an AND function on any subset of its inputs
 /// and the complement of those inputs. The index for each input is the corresponding ZIA row.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 pub struct XC2PLAAndTerm {
     /// Indicates whether a particular ZIA row output is a part of this AND term.
     ///
@@ -104,6 +105,7 @@ impl XC2PLAAndTerm {
 /// Represents one single OR term in the PLA. Each OR term can perform an OR function on any subset of its inputs.
 /// The index for each input is the index of the corresponding AND term in the same PLA.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "arbitrary"), derive(arbitrary::Arbitrary))]
 pub struct XC2PLAOrTerm {
     /// Indicates whether a particular PLA AND term is a part of this OR term.
     ///
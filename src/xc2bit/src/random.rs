@@ -0,0 +1,281 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Generation of random but valid bitstreams using the `arbitrary` crate, e.g. for property-based testing or fuzzing
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::*;
+
+impl<'a> Arbitrary<'a> for XC2Bitstream {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let device = u.arbitrary()?;
+        XC2Bitstream::arbitrary_for_device(u, device)
+    }
+}
+
+impl XC2Bitstream {
+    /// Generates a random bitstream for the given `device`. Every setting is chosen independently, but only among the
+    /// values that can actually be encoded on that device. For example, ZIA inputs are only chosen from the legal
+    /// choices for their row, and buried macrocells never use the input pin direct path.
+    pub fn arbitrary_for_device(u: &mut Unstructured, device: XC2Device) -> Result<Self> {
+        let speed_grade = *u.choose(device.valid_speeds())?;
+        let package = *u.choose(device.valid_packages())?;
+
+        let mut bits = match device {
+            XC2Device::XC2C32 => XC2BitstreamBits::XC2C32 {
+                fb: [XC2BitstreamFB::default(); 2],
+                iobs: u.arbitrary()?,
+                inpin: u.arbitrary()?,
+                global_nets: u.arbitrary()?,
+                ivoltage: u.arbitrary()?,
+                ovoltage: u.arbitrary()?,
+            },
+            XC2Device::XC2C32A => XC2BitstreamBits::XC2C32A {
+                fb: [XC2BitstreamFB::default(); 2],
+                iobs: u.arbitrary()?,
+                inpin: u.arbitrary()?,
+                global_nets: u.arbitrary()?,
+                legacy_ivoltage: u.arbitrary()?,
+                legacy_ovoltage: u.arbitrary()?,
+                ivoltage: u.arbitrary()?,
+                ovoltage: u.arbitrary()?,
+            },
+            XC2Device::XC2C64 => XC2BitstreamBits::XC2C64 {
+                fb: [XC2BitstreamFB::default(); 4],
+                iobs: u.arbitrary()?,
+                global_nets: u.arbitrary()?,
+                ivoltage: u.arbitrary()?,
+                ovoltage: u.arbitrary()?,
+            },
+            XC2Device::XC2C64A => XC2BitstreamBits::XC2C64A {
+                fb: [XC2BitstreamFB::default(); 4],
+                iobs: u.arbitrary()?,
+                global_nets: u.arbitrary()?,
+                legacy_ivoltage: u.arbitrary()?,
+                legacy_ovoltage: u.arbitrary()?,
+                ivoltage: u.arbitrary()?,
+                ovoltage: u.arbitrary()?,
+            },
+            XC2Device::XC2C128 => XC2BitstreamBits::XC2C128 {
+                fb: [XC2BitstreamFB::default(); 8],
+                iobs: u.arbitrary()?,
+                global_nets: u.arbitrary()?,
+                clock_div: u.arbitrary()?,
+                data_gate: u.arbitrary()?,
+                use_vref: u.arbitrary()?,
+                ivoltage: u.arbitrary()?,
+                ovoltage: u.arbitrary()?,
+            },
+            XC2Device::XC2C256 => XC2BitstreamBits::XC2C256 {
+                fb: [XC2BitstreamFB::default(); 16],
+                iobs: u.arbitrary()?,
+                global_nets: u.arbitrary()?,
+                clock_div: u.arbitrary()?,
+                data_gate: u.arbitrary()?,
+                use_vref: u.arbitrary()?,
+                ivoltage: u.arbitrary()?,
+                ovoltage: u.arbitrary()?,
+            },
+            XC2Device::XC2C384 => XC2BitstreamBits::XC2C384 {
+                fb: [XC2BitstreamFB::default(); 24],
+                iobs: u.arbitrary()?,
+                global_nets: u.arbitrary()?,
+                clock_div: u.arbitrary()?,
+                data_gate: u.arbitrary()?,
+                use_vref: u.arbitrary()?,
+                ivoltage: u.arbitrary()?,
+                ovoltage: u.arbitrary()?,
+            },
+            XC2Device::XC2C512 => XC2BitstreamBits::XC2C512 {
+                fb: [XC2BitstreamFB::default(); 32],
+                iobs: u.arbitrary()?,
+                global_nets: u.arbitrary()?,
+                clock_div: u.arbitrary()?,
+                data_gate: u.arbitrary()?,
+                use_vref: u.arbitrary()?,
+                ivoltage: u.arbitrary()?,
+                ovoltage: u.arbitrary()?,
+            },
+        };
+
+        for (fb_i, fb) in bits.get_fb_mut().iter_mut().enumerate() {
+            *fb = arbitrary_fb(u, device, fb_i as u32)?;
+        }
+
+        Ok(XC2Bitstream {
+            speed_grade,
+            package,
+            bits,
+        })
+    }
+}
+
+/// Generates the contents of function block `fb` of the given `device`
+fn arbitrary_fb(u: &mut Unstructured, device: XC2Device, fb: u32) -> Result<XC2BitstreamFB> {
+    let mut ret = XC2BitstreamFB::default();
+
+    for i in 0..ANDTERMS_PER_FB {
+        *ret.get_mut_andterm(i) = u.arbitrary()?;
+    }
+    ret.or_terms = u.arbitrary()?;
+
+    ret.mcs = u.arbitrary()?;
+    for i in 0..MCS_PER_FB {
+        // There is no fuse for this on buried macrocells
+        if fb_mc_num_to_iob_num(device, fb, i as u32).is_none() {
+            ret.mcs[i].ff_in_ibuf = false;
+        }
+    }
+
+    for row in 0..INPUTS_PER_ANDTERM {
+        // Every row can output a constant in addition to the choices in the table
+        let choices = zia_table_get_row(device, row);
+        let choice_i = u.int_in_range(0..=choices.len() + 1)?;
+        *ret.get_mut_zia(row) = match choice_i {
+            0 => XC2ZIAInput::Zero,
+            1 => XC2ZIAInput::One,
+            _ => choices[choice_i - 2],
+        };
+    }
+
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use jedec::*;
+
+    const CASES_PER_DEVICE: u64 = 4;
+
+    // A small xorshift generator is plenty for producing the input to `Unstructured` and keeps the tests deterministic
+    fn random_bytes(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed.wrapping_mul(0x9E3779B97F4A7C15) | 1;
+        let mut ret = Vec::with_capacity(len);
+        for _ in 0..len {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            ret.push((state >> 32) as u8);
+        }
+        ret
+    }
+
+    fn to_jed_bytes(bitstream: &XC2Bitstream) -> Vec<u8> {
+        let mut ret = Vec::new();
+        bitstream.to_jed(&mut ret).unwrap();
+        ret
+    }
+
+    fn to_crbit_bytes(bitstream: &XC2Bitstream) -> Vec<u8> {
+        let mut ret = Vec::new();
+        bitstream.to_crbit().write_to_writer(&mut ret).unwrap();
+        ret
+    }
+
+    fn check_same(a: &XC2Bitstream, b: &XC2Bitstream) {
+        assert_eq!(a.speed_grade, b.speed_grade);
+        assert_eq!(a.package, b.package);
+        assert_eq!(a.bits, b.bits);
+    }
+
+    fn run_roundtrip(device: XC2Device) {
+        for seed in 0..CASES_PER_DEVICE {
+            let data = random_bytes(seed, 1 << 16);
+            let mut u = Unstructured::new(&data);
+            let bitstream = XC2Bitstream::arbitrary_for_device(&mut u, device).unwrap();
+            assert_eq!(bitstream.bits.device_type(), device);
+            // Otherwise the last parts of the bitstream would have been filled with default values
+            assert!(!u.is_empty());
+
+            // jed -> bitstream -> jed
+            let jed = to_jed_bytes(&bitstream);
+            let from_jed = XC2Bitstream::from_jed(&JEDECFile::from_bytes(&jed).unwrap()).unwrap();
+            check_same(&from_jed, &bitstream);
+            assert_eq!(to_jed_bytes(&from_jed), jed);
+
+            // crbit -> bitstream -> crbit
+            let crbit = to_crbit_bytes(&bitstream);
+            let from_crbit = XC2Bitstream::from_crbit(&FuseArray::from_file_contents(&crbit).unwrap()).unwrap();
+            check_same(&from_crbit, &bitstream);
+            assert_eq!(to_crbit_bytes(&from_crbit), crbit);
+
+            // jed <-> crbit
+            assert_eq!(to_crbit_bytes(&from_jed), crbit);
+            assert_eq!(to_jed_bytes(&from_crbit), jed);
+        }
+    }
+
+    #[test]
+    fn arbitrary_roundtrip_xc2c32() {
+        run_roundtrip(XC2Device::XC2C32);
+    }
+
+    #[test]
+    fn arbitrary_roundtrip_xc2c32a() {
+        run_roundtrip(XC2Device::XC2C32A);
+    }
+
+    #[test]
+    fn arbitrary_roundtrip_xc2c64() {
+        run_roundtrip(XC2Device::XC2C64);
+    }
+
+    #[test]
+    fn arbitrary_roundtrip_xc2c64a() {
+        run_roundtrip(XC2Device::XC2C64A);
+    }
+
+    #[test]
+    fn arbitrary_roundtrip_xc2c128() {
+        run_roundtrip(XC2Device::XC2C128);
+    }
+
+    #[test]
+    fn arbitrary_roundtrip_xc2c256() {
+        run_roundtrip(XC2Device::XC2C256);
+    }
+
+    #[test]
+    fn arbitrary_roundtrip_xc2c384() {
+        run_roundtrip(XC2Device::XC2C384);
+    }
+
+    #[test]
+    fn arbitrary_roundtrip_xc2c512() {
+        run_roundtrip(XC2Device::XC2C512);
+    }
+
+    #[test]
+    fn arbitrary_any_device() {
+        let data = random_bytes(1234, 1 << 16);
+        let bitstream = XC2Bitstream::arbitrary(&mut Unstructured::new(&data)).unwrap();
+        let device = bitstream.bits.device_type();
+        assert!(device.valid_speeds().contains(&bitstream.speed_grade));
+        assert!(device.valid_packages().contains(&bitstream.package));
+    }
+}
@@ -0,0 +1,590 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Typed routing-resource graph of a device
+//!
+//! Unlike `get_device_structure`, this builds an explicit graph in which every mux site is a node of its own. Each
+//! input of a mux records the configuration setting that selects it. Nodes and wires are numbered by their index in
+//! `XC2RoutingGraph::nodes` and `XC2RoutingGraph::wires`, and every wire has exactly one driver.
+//!
+//! The connections of AND and OR terms are programmable but are not muxes, so they do not carry a setting; they are
+//! controlled by `XC2PLAAndTerm` and `XC2PLAOrTerm`.
+//!
+//! The ZIA maps of the XC2C128 and larger parts are not known yet (see `zia_inverse_table`), so on those parts the ZIA
+//! mux nodes are present but have no inputs.
+
+use std::fmt;
+use std::io;
+use std::io::Write;
+
+use crate::*;
+
+/// Kinds of nodes in the routing graph
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+pub enum XC2RRNodeKind {
+    /// Global clock buffer
+    BufGCK(u32),
+    /// Global tristate buffer
+    BufGTS(u32),
+    /// Global set/reset buffer
+    BufGSR,
+    /// Selects the input for one row of the ZIA feeding a function block
+    ZiaMux {
+        fb: u32,
+        row: u32,
+    },
+    AndTerm {
+        fb: u32,
+        term: u32,
+    },
+    OrTerm {
+        fb: u32,
+        mc: u32,
+    },
+    /// XOR gate of a macrocell, with its mux for the input that is not the OR term
+    Xor {
+        fb: u32,
+        mc: u32,
+    },
+    /// Register of a macrocell, with its mux for the D/T input
+    Reg {
+        fb: u32,
+        mc: u32,
+    },
+    /// Clock source mux of a macrocell
+    ClkMux {
+        fb: u32,
+        mc: u32,
+    },
+    /// Set source mux of a macrocell
+    SetMux {
+        fb: u32,
+        mc: u32,
+    },
+    /// Reset source mux of a macrocell
+    ResetMux {
+        fb: u32,
+        mc: u32,
+    },
+    /// Selects whether a macrocell feeds the ZIA from the XOR gate or the register
+    FeedbackMux {
+        fb: u32,
+        mc: u32,
+    },
+    /// Selects whether an IOB feeds the ZIA from the pad or the register
+    IobZiaMux(u32),
+    /// Selects whether an IOB outputs the XOR gate or the register
+    ObufMux(u32),
+    /// Output enable mux of an IOB
+    OeMux(u32),
+    /// The I/O buffer itself
+    Iob(u32),
+    /// The input-only pin on the XC2C32(A)
+    InputOnly,
+}
+
+/// Configuration that selects one input of a mux
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+pub enum XC2RRMuxSetting {
+    /// ZIA row selection, together with the bits of the row as written to the .jed file
+    Zia {
+        input: XC2ZIAInput,
+        bits: Vec<bool>,
+    },
+    XorMode(XC2MCXorMode),
+    /// Value of `XC2Macrocell::ff_in_ibuf`
+    FFInIbuf(bool),
+    /// The clock enable input is only used in this register mode
+    RegMode(XC2MCRegMode),
+    ClkSrc(XC2MCRegClkSrc),
+    SetSrc(XC2MCRegSetSrc),
+    ResetSrc(XC2MCRegResetSrc),
+    FeedbackMode(XC2MCFeedbackMode),
+    IobZiaMode(XC2IOBZIAMode),
+    /// Value of `obuf_uses_ff` of the IOB
+    ObufUsesFF(bool),
+    ObufMode(XC2IOBOBufMode),
+}
+
+impl fmt::Display for XC2RRMuxSetting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &XC2RRMuxSetting::Zia{input, ref bits} => {
+                write!(f, "zia={:?} bits=", input)?;
+                for &bit in bits {
+                    write!(f, "{}", if bit {1} else {0})?;
+                }
+                Ok(())
+            },
+            &XC2RRMuxSetting::XorMode(x) => write!(f, "xor_mode={:?}", x),
+            &XC2RRMuxSetting::FFInIbuf(x) => write!(f, "ff_in_ibuf={}", x),
+            &XC2RRMuxSetting::RegMode(x) => write!(f, "reg_mode={:?}", x),
+            &XC2RRMuxSetting::ClkSrc(x) => write!(f, "clk_src={:?}", x),
+            &XC2RRMuxSetting::SetSrc(x) => write!(f, "s_src={:?}", x),
+            &XC2RRMuxSetting::ResetSrc(x) => write!(f, "r_src={:?}", x),
+            &XC2RRMuxSetting::FeedbackMode(x) => write!(f, "fb_mode={:?}", x),
+            &XC2RRMuxSetting::IobZiaMode(x) => write!(f, "zia_mode={:?}", x),
+            &XC2RRMuxSetting::ObufUsesFF(x) => write!(f, "obuf_uses_ff={}", x),
+            &XC2RRMuxSetting::ObufMode(x) => write!(f, "obuf_mode={:?}", x),
+        }
+    }
+}
+
+/// A node in the routing graph
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+pub struct XC2RRNode {
+    pub kind: XC2RRNodeKind,
+    pub name: String,
+}
+
+/// A wire in the routing graph
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+pub struct XC2RRWire {
+    pub name: String,
+}
+
+/// Direction of a connection, as seen from the node
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+pub enum XC2RRPortDir {
+    Input,
+    Output,
+}
+
+/// A connection between a port of a node and a wire
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+pub struct XC2RRPin {
+    /// Index into `XC2RoutingGraph::nodes`
+    pub node: usize,
+    /// Index into `XC2RoutingGraph::wires`
+    pub wire: usize,
+    pub port: &'static str,
+    pub index: u32,
+    pub dir: XC2RRPortDir,
+    /// For mux inputs, the setting that selects this input
+    pub setting: Option<XC2RRMuxSetting>,
+}
+
+/// Routing-resource graph of a device
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+pub struct XC2RoutingGraph {
+    pub device: XC2Device,
+    pub nodes: Vec<XC2RRNode>,
+    pub wires: Vec<XC2RRWire>,
+    pub pins: Vec<XC2RRPin>,
+}
+
+/// Wires belonging to one macrocell, used while building the graph
+struct MCWires {
+    xor: usize,
+    q: usize,
+    feedback: usize,
+}
+
+impl XC2RoutingGraph {
+    fn add_node(&mut self, kind: XC2RRNodeKind, name: String) -> usize {
+        self.nodes.push(XC2RRNode {
+            kind,
+            name,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn add_wire(&mut self, name: String) -> usize {
+        self.wires.push(XC2RRWire {
+            name,
+        });
+        self.wires.len() - 1
+    }
+
+    fn add_output(&mut self, node: usize, wire: usize, port: &'static str) {
+        self.pins.push(XC2RRPin {
+            node,
+            wire,
+            port,
+            index: 0,
+            dir: XC2RRPortDir::Output,
+            setting: None,
+        });
+    }
+
+    fn add_input(&mut self, node: usize, wire: usize, port: &'static str, index: u32,
+        setting: Option<XC2RRMuxSetting>) {
+
+        self.pins.push(XC2RRPin {
+            node,
+            wire,
+            port,
+            index,
+            dir: XC2RRPortDir::Input,
+            setting,
+        });
+    }
+
+    /// Adds a node driving a new wire of the same name and returns both
+    fn add_driver(&mut self, kind: XC2RRNodeKind, name: String, port: &'static str) -> (usize, usize) {
+        let wire = self.add_wire(name.clone());
+        let node = self.add_node(kind, name);
+        self.add_output(node, wire, port);
+        (node, wire)
+    }
+
+    /// Builds the routing graph of the given device. The ZIA muxes only get their inputs if the ZIA map of the device
+    /// is known.
+    pub fn new(device: XC2Device) -> Self {
+        let mut g = XC2RoutingGraph {
+            device,
+            nodes: Vec::new(),
+            wires: Vec::new(),
+            pins: Vec::new(),
+        };
+
+        // Global buffers. Their inputs are connected once the pads exist.
+        let gck = (0..3).map(|i| g.add_driver(XC2RRNodeKind::BufGCK(i), format!("gck{}", i), "O"))
+            .collect::<Vec<_>>();
+        let gts = (0..4).map(|i| g.add_driver(XC2RRNodeKind::BufGTS(i), format!("gts{}", i), "O"))
+            .collect::<Vec<_>>();
+        let gsr = g.add_driver(XC2RRNodeKind::BufGSR, "gsr".to_owned(), "O");
+
+        // Pads, so that the register direct input path can refer to them
+        let pads = (0..device.num_iobs() as u32).map(|iob| {
+            g.add_driver(XC2RRNodeKind::Iob(iob), format!("iob{}", iob), "PAD")
+        }).collect::<Vec<_>>();
+        let ipad = if device == XC2Device::XC2C32 || device == XC2Device::XC2C32A {
+            Some(g.add_driver(XC2RRNodeKind::InputOnly, "ipad".to_owned(), "PAD").1)
+        } else {
+            None
+        };
+
+        // Function block logic, except for the ZIA muxes which need the feedback wires of all function blocks
+        let mut zia_muxes = Vec::new();
+        let mut mc_wires = Vec::new();
+        let mut pterm_wires = Vec::new();
+        for fb in 0..device.num_fbs() as u32 {
+            let zia = (0..INPUTS_PER_ANDTERM as u32).map(|row| {
+                g.add_driver(XC2RRNodeKind::ZiaMux{fb, row}, format!("fb{}_zia{}", fb, row), "O")
+            }).collect::<Vec<_>>();
+
+            let pterms = (0..ANDTERMS_PER_FB as u32).map(|term| {
+                let (node, wire) = g.add_driver(XC2RRNodeKind::AndTerm{fb, term}, format!("fb{}_pterm{}", fb, term),
+                    "O");
+                for (row, &(_, zia_wire)) in zia.iter().enumerate() {
+                    g.add_input(node, zia_wire, "IN", row as u32, None);
+                }
+                wire
+            }).collect::<Vec<_>>();
+
+            let mcs = (0..MCS_PER_FB as u32).map(|mc| {
+                let (or_node, or_wire) = g.add_driver(XC2RRNodeKind::OrTerm{fb, mc}, format!("fb{}_or{}", fb, mc),
+                    "O");
+                for (term, &pterm) in pterms.iter().enumerate() {
+                    g.add_input(or_node, pterm, "IN", term as u32, None);
+                }
+
+                let ptc = pterms[get_ptc(mc) as usize];
+                let pta = pterms[get_pta(mc) as usize];
+
+                let (xor_node, xor) = g.add_driver(XC2RRNodeKind::Xor{fb, mc}, format!("fb{}_xor{}", fb, mc), "O");
+                g.add_input(xor_node, or_wire, "IN_ORTERM", 0, None);
+                g.add_input(xor_node, ptc, "IN_PTC", 0, Some(XC2RRMuxSetting::XorMode(XC2MCXorMode::PTC)));
+                g.add_input(xor_node, ptc, "IN_PTC", 1, Some(XC2RRMuxSetting::XorMode(XC2MCXorMode::PTCB)));
+
+                let (clk_node, clk) = g.add_driver(XC2RRNodeKind::ClkMux{fb, mc}, format!("fb{}_clk{}", fb, mc),
+                    "O");
+                for (i, &src) in [XC2MCRegClkSrc::GCK0, XC2MCRegClkSrc::GCK1, XC2MCRegClkSrc::GCK2].iter()
+                    .enumerate() {
+
+                    g.add_input(clk_node, gck[i].1, "IN", i as u32, Some(XC2RRMuxSetting::ClkSrc(src)));
+                }
+                g.add_input(clk_node, pterms[CTC as usize], "IN", 3,
+                    Some(XC2RRMuxSetting::ClkSrc(XC2MCRegClkSrc::CTC)));
+                g.add_input(clk_node, ptc, "IN", 4, Some(XC2RRMuxSetting::ClkSrc(XC2MCRegClkSrc::PTC)));
+
+                let (set_node, set) = g.add_driver(XC2RRNodeKind::SetMux{fb, mc}, format!("fb{}_set{}", fb, mc),
+                    "O");
+                g.add_input(set_node, gsr.1, "IN", 0, Some(XC2RRMuxSetting::SetSrc(XC2MCRegSetSrc::GSR)));
+                g.add_input(set_node, pterms[CTS as usize], "IN", 1, Some(XC2RRMuxSetting::SetSrc(XC2MCRegSetSrc::CTS)));
+                g.add_input(set_node, pta, "IN", 2, Some(XC2RRMuxSetting::SetSrc(XC2MCRegSetSrc::PTA)));
+
+                let (reset_node, reset) = g.add_driver(XC2RRNodeKind::ResetMux{fb, mc},
+                    format!("fb{}_reset{}", fb, mc), "O");
+                g.add_input(reset_node, gsr.1, "IN", 0, Some(XC2RRMuxSetting::ResetSrc(XC2MCRegResetSrc::GSR)));
+                g.add_input(reset_node, pterms[CTR as usize], "IN", 1,
+                    Some(XC2RRMuxSetting::ResetSrc(XC2MCRegResetSrc::CTR)));
+                g.add_input(reset_node, pta, "IN", 2, Some(XC2RRMuxSetting::ResetSrc(XC2MCRegResetSrc::PTA)));
+
+                let (reg_node, q) = g.add_driver(XC2RRNodeKind::Reg{fb, mc}, format!("fb{}_reg{}", fb, mc), "Q");
+                g.add_input(reg_node, xor, "D/T", 0, Some(XC2RRMuxSetting::FFInIbuf(false)));
+                if let Some(iob) = fb_mc_num_to_iob_num(device, fb, mc) {
+                    g.add_input(reg_node, pads[iob as usize].1, "D/T", 1, Some(XC2RRMuxSetting::FFInIbuf(true)));
+                }
+                g.add_input(reg_node, ptc, "CE", 0, Some(XC2RRMuxSetting::RegMode(XC2MCRegMode::DFFCE)));
+                g.add_input(reg_node, clk, "CLK", 0, None);
+                g.add_input(reg_node, set, "S", 0, None);
+                g.add_input(reg_node, reset, "R", 0, None);
+
+                let (feedback_node, feedback) = g.add_driver(XC2RRNodeKind::FeedbackMux{fb, mc},
+                    format!("fb{}_feedback{}", fb, mc), "O");
+                g.add_input(feedback_node, xor, "IN", 0,
+                    Some(XC2RRMuxSetting::FeedbackMode(XC2MCFeedbackMode::COMB)));
+                g.add_input(feedback_node, q, "IN", 1, Some(XC2RRMuxSetting::FeedbackMode(XC2MCFeedbackMode::REG)));
+
+                MCWires {
+                    xor,
+                    q,
+                    feedback,
+                }
+            }).collect::<Vec<_>>();
+
+            zia_muxes.push(zia.into_iter().map(|x| x.0).collect::<Vec<_>>());
+            mc_wires.push(mcs);
+            pterm_wires.push(pterms);
+        }
+
+        // I/O blocks
+        let mut iob_zia = Vec::new();
+        for iob in 0..device.num_iobs() as u32 {
+            let (fb, mc) = iob_num_to_fb_mc_num(device, iob).unwrap();
+            let mc_wire = &mc_wires[fb as usize][mc as usize];
+            let (xor, q) = (mc_wire.xor, mc_wire.q);
+            let (pad_node, pad) = pads[iob as usize];
+
+            let (zia_node, zia) = g.add_driver(XC2RRNodeKind::IobZiaMux(iob), format!("iob{}_zia", iob), "O");
+            g.add_input(zia_node, pad, "IN", 0, Some(XC2RRMuxSetting::IobZiaMode(XC2IOBZIAMode::PAD)));
+            g.add_input(zia_node, q, "IN", 1, Some(XC2RRMuxSetting::IobZiaMode(XC2IOBZIAMode::REG)));
+            iob_zia.push(zia);
+
+            let (obuf_node, obuf) = g.add_driver(XC2RRNodeKind::ObufMux(iob), format!("iob{}_o", iob), "O");
+            g.add_input(obuf_node, xor, "IN", 0, Some(XC2RRMuxSetting::ObufUsesFF(false)));
+            g.add_input(obuf_node, q, "IN", 1, Some(XC2RRMuxSetting::ObufUsesFF(true)));
+
+            let (oe_node, oe) = g.add_driver(XC2RRNodeKind::OeMux(iob), format!("iob{}_oe", iob), "O");
+            let gts_modes = [XC2IOBOBufMode::TriStateGTS0, XC2IOBOBufMode::TriStateGTS1,
+                XC2IOBOBufMode::TriStateGTS2, XC2IOBOBufMode::TriStateGTS3];
+            for (i, &mode) in gts_modes.iter().enumerate() {
+                g.add_input(oe_node, gts[i].1, "IN", i as u32, Some(XC2RRMuxSetting::ObufMode(mode)));
+            }
+            g.add_input(oe_node, obuf, "IN", 4, Some(XC2RRMuxSetting::ObufMode(XC2IOBOBufMode::OpenDrain)));
+            g.add_input(oe_node, pterm_wires[fb as usize][CTE as usize], "IN", 5,
+                Some(XC2RRMuxSetting::ObufMode(XC2IOBOBufMode::TriStateCTE)));
+            g.add_input(oe_node, pterm_wires[fb as usize][get_ptb(mc) as usize], "IN", 6,
+                Some(XC2RRMuxSetting::ObufMode(XC2IOBOBufMode::TriStatePTB)));
+
+            g.add_input(pad_node, obuf, "I", 0, None);
+            g.add_input(pad_node, oe, "E", 0, None);
+        }
+
+        // Inputs into the global buffers
+        for (i, &(node, _)) in gck.iter().enumerate() {
+            let (fb, mc) = get_gck(device, i).unwrap();
+            let iob = fb_mc_num_to_iob_num(device, fb, mc).unwrap();
+            g.add_input(node, pads[iob as usize].1, "I", 0, None);
+        }
+        for (i, &(node, _)) in gts.iter().enumerate() {
+            let (fb, mc) = get_gts(device, i).unwrap();
+            let iob = fb_mc_num_to_iob_num(device, fb, mc).unwrap();
+            g.add_input(node, pads[iob as usize].1, "I", 0, None);
+        }
+        {
+            let (fb, mc) = get_gsr(device);
+            let iob = fb_mc_num_to_iob_num(device, fb, mc).unwrap();
+            g.add_input(gsr.0, pads[iob as usize].1, "I", 0, None);
+        }
+
+        // The ZIA, if its map is known. The placeholder maps would connect every row to the first IOB.
+        if zia_inverse_table(device).is_none() {
+            return g;
+        }
+        for rows in &zia_muxes {
+            for (row, &node) in rows.iter().enumerate() {
+                for (choice_i, &choice) in zia_table_get_row(device, row).iter().enumerate() {
+                    let wire = match choice {
+                        XC2ZIAInput::Macrocell{fb, mc} => mc_wires[fb as usize][mc as usize].feedback,
+                        XC2ZIAInput::IBuf{ibuf} => iob_zia[ibuf as usize],
+                        XC2ZIAInput::DedicatedInput => ipad.unwrap(),
+                        // These cannot be in the choices table; they are special cases
+                        _ => unreachable!(),
+                    };
+                    let bits = zia_encode_choice(device, row as u32, choice).unwrap();
+                    g.add_input(node, wire, "IN", choice_i as u32, Some(XC2RRMuxSetting::Zia{input: choice, bits}));
+                }
+            }
+        }
+
+        g
+    }
+
+    /// Returns the index of the node of the given kind
+    pub fn find_node(&self, kind: XC2RRNodeKind) -> Option<usize> {
+        self.nodes.iter().position(|node| node.kind == kind)
+    }
+
+    /// Returns the connections of the given node
+    pub fn node_pins(&self, node: usize) -> impl Iterator<Item = &XC2RRPin> {
+        self.pins.iter().filter(move |pin| pin.node == node)
+    }
+
+    /// Returns the connection that drives the given wire
+    pub fn wire_driver(&self, wire: usize) -> Option<&XC2RRPin> {
+        self.pins.iter().find(|pin| pin.wire == wire && pin.dir == XC2RRPortDir::Output)
+    }
+
+    /// Returns the connections that are driven by the given wire
+    pub fn wire_sinks(&self, wire: usize) -> impl Iterator<Item = &XC2RRPin> {
+        self.pins.iter().filter(move |pin| pin.wire == wire && pin.dir == XC2RRPortDir::Input)
+    }
+
+    /// Writes the graph in Graphviz DOT format. Wires are drawn as small points between their driver and sinks.
+    pub fn write_dot<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        write!(writer, "digraph {} {{\n", self.device)?;
+        for (i, node) in self.nodes.iter().enumerate() {
+            write!(writer, "    n{} [shape=box, label=\"{}\"];\n", i, node.name)?;
+        }
+        for (i, wire) in self.wires.iter().enumerate() {
+            write!(writer, "    w{} [shape=point, label=\"{}\"];\n", i, wire.name)?;
+        }
+        for pin in &self.pins {
+            let label = match pin.setting {
+                Some(ref setting) => format!("{}[{}] {}", pin.port, pin.index, setting),
+                None => format!("{}[{}]", pin.port, pin.index),
+            };
+            match pin.dir {
+                XC2RRPortDir::Output => write!(writer, "    n{} -> w{} [label=\"{}\"];\n", pin.node, pin.wire, label)?,
+                XC2RRPortDir::Input => write!(writer, "    w{} -> n{} [label=\"{}\"];\n", pin.wire, pin.node, label)?,
+            }
+        }
+        write!(writer, "}}\n")?;
+
+        Ok(())
+    }
+
+    /// Writes the graph in GraphML format. Both nodes and wires become GraphML nodes, distinguished by the "type" key.
+    pub fn write_graphml<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        write!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n")?;
+        write!(writer, "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n")?;
+        write!(writer, "  <key id=\"type\" for=\"node\" attr.name=\"type\" attr.type=\"string\"/>\n")?;
+        write!(writer, "  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n")?;
+        write!(writer, "  <key id=\"port\" for=\"edge\" attr.name=\"port\" attr.type=\"string\"/>\n")?;
+        write!(writer, "  <key id=\"index\" for=\"edge\" attr.name=\"index\" attr.type=\"int\"/>\n")?;
+        write!(writer, "  <key id=\"setting\" for=\"edge\" attr.name=\"setting\" attr.type=\"string\"/>\n")?;
+        write!(writer, "  <graph id=\"{}\" edgedefault=\"directed\">\n", self.device)?;
+
+        for (i, node) in self.nodes.iter().enumerate() {
+            write!(writer, "    <node id=\"n{}\"><data key=\"type\">node</data><data key=\"kind\">{:?}</data></node>\n",
+                i, node.kind)?;
+        }
+        for (i, wire) in self.wires.iter().enumerate() {
+            write!(writer, "    <node id=\"w{}\"><data key=\"type\">wire</data><data key=\"kind\">{}</data></node>\n",
+                i, wire.name)?;
+        }
+        for pin in &self.pins {
+            let (source, target) = match pin.dir {
+                XC2RRPortDir::Output => (format!("n{}", pin.node), format!("w{}", pin.wire)),
+                XC2RRPortDir::Input => (format!("w{}", pin.wire), format!("n{}", pin.node)),
+            };
+            write!(writer, "    <edge source=\"{}\" target=\"{}\"><data key=\"port\">{}</data>\
+                <data key=\"index\">{}</data>", source, target, pin.port, pin.index)?;
+            if let Some(ref setting) = pin.setting {
+                write!(writer, "<data key=\"setting\">{}</data>", setting)?;
+            }
+            write!(writer, "</edge>\n")?;
+        }
+
+        write!(writer, "  </graph>\n</graphml>\n")?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zia::zia_get_row_width;
+
+    #[test]
+    fn graph_structure() {
+        for &device in &[XC2Device::XC2C32A, XC2Device::XC2C64A, XC2Device::XC2C128] {
+            let g = XC2RoutingGraph::new(device);
+
+            // Every wire has exactly one driver
+            let mut drivers = vec![0; g.wires.len()];
+            for pin in &g.pins {
+                if pin.dir == XC2RRPortDir::Output {
+                    drivers[pin.wire] += 1;
+                }
+            }
+            assert!(drivers.iter().all(|&x| x == 1));
+
+            // Every ZIA mux has one input per choice, each with a setting, unless the ZIA map is unknown
+            let mux = g.find_node(XC2RRNodeKind::ZiaMux{fb: 1, row: 7}).unwrap();
+            let inputs = g.node_pins(mux).filter(|pin| pin.dir == XC2RRPortDir::Input).collect::<Vec<_>>();
+            if device == XC2Device::XC2C128 {
+                assert_eq!(inputs.len(), 0);
+                continue;
+            }
+            assert_eq!(inputs.len(), zia_table_get_row(device, 7).len());
+            for pin in inputs {
+                match pin.setting {
+                    Some(XC2RRMuxSetting::Zia{input, ref bits}) => {
+                        assert_eq!(bits.len(), zia_get_row_width(device));
+                        assert!(zia_rows_for_input(device, input).unwrap().contains(&7));
+                    },
+                    _ => panic!("ZIA mux input without a setting"),
+                }
+            }
+        }
+
+        // No ZIA mux of a part with an unknown ZIA map has inputs
+        let g = XC2RoutingGraph::new(XC2Device::XC2C256);
+        assert!(g.pins.iter().all(|pin| match g.nodes[pin.node].kind {
+            XC2RRNodeKind::ZiaMux{..} => pin.dir == XC2RRPortDir::Output,
+            _ => true,
+        }));
+
+        let g = XC2RoutingGraph::new(XC2Device::XC2C32A);
+        let oe = g.find_node(XC2RRNodeKind::OeMux(3)).unwrap();
+        let ptb = g.node_pins(oe).find(|pin|
+            pin.setting == Some(XC2RRMuxSetting::ObufMode(XC2IOBOBufMode::TriStatePTB))).unwrap();
+        assert_eq!(g.wires[ptb.wire].name, format!("fb0_pterm{}", get_ptb(3)));
+        // One input of each OR term, plus the OE mux
+        assert_eq!(g.wire_sinks(ptb.wire).count(), MCS_PER_FB + 1);
+    }
+
+    #[test]
+    fn graph_export() {
+        let g = XC2RoutingGraph::new(XC2Device::XC2C32A);
+
+        let mut dot = Vec::new();
+        g.write_dot(&mut dot).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+        assert!(dot.starts_with("digraph XC2C32A {\n"));
+        assert_eq!(dot.matches(" -> ").count(), g.pins.len());
+
+        let mut graphml = Vec::new();
+        g.write_graphml(&mut graphml).unwrap();
+        let graphml = String::from_utf8(graphml).unwrap();
+        assert_eq!(graphml.matches("<edge ").count(), g.pins.len());
+        assert_eq!(graphml.matches("<node ").count(), g.nodes.len() + g.wires.len());
+    }
+}
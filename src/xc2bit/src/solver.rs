@@ -0,0 +1,206 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Fuse-correlation solver for reverse-engineering bitstreams from a corpus of tagged .jed files
+//!
+//! Each sample in the corpus is a set of fuses along with the list of features ("tags") that are known to be enabled
+//! in it, for example "FB2_5 slew=fast" or "FB2 ZIA row 7 = IBuf 33". For each tag, the solver finds the fuses that
+//! have the same value in every sample with the tag and determines which of them are actually needed to distinguish
+//! those samples from the samples without the tag.
+
+use std::collections::BTreeSet;
+
+use jedec::*;
+
+use crate::*;
+
+/// A corpus of tagged samples that all have the same number of fuses
+pub struct XC2FuseCorpus {
+    num_fuses: Option<usize>,
+    samples: Vec<(Vec<bool>, BTreeSet<String>)>,
+}
+
+/// The result of solving for a single tag
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+pub struct XC2TagSolution {
+    /// The tag that was solved for
+    pub tag: String,
+    /// Number of samples with the tag
+    pub num_samples: usize,
+    /// Fuses that have the same value in every sample with the tag and vary somewhere in the corpus, along with that
+    /// value. Matching all of these fuses is what the corpus says the tag looks like.
+    pub fuses: Vec<(usize, bool)>,
+    /// The subset of `fuses` that is definitely part of the encoding of the tag because there is a sample without the
+    /// tag that differs from the pattern only in this fuse.
+    pub certain: Vec<usize>,
+    /// Indices of samples that do not have the tag but match the entire pattern. If this is not empty, either the
+    /// samples are tagged incorrectly or the tag does not correspond to any combination of fuses.
+    pub conflicts: Vec<usize>,
+}
+
+impl XC2TagSolution {
+    /// Returns whether the corpus is sufficient to know exactly which fuses make up the tag. If this is false, more
+    /// samples are needed that differ from the samples with the tag in only some of the fuses in `fuses`.
+    pub fn is_determined(&self) -> bool {
+        self.conflicts.is_empty() && self.fuses.len() == self.certain.len()
+    }
+
+    /// Returns the fuses in `fuses` that are not known to be part of the encoding
+    pub fn undetermined(&self) -> Vec<usize> {
+        self.fuses.iter().map(|&(i, _)| i).filter(|i| !self.certain.contains(i)).collect()
+    }
+}
+
+impl Default for XC2FuseCorpus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl XC2FuseCorpus {
+    /// Creates a new, empty corpus
+    pub fn new() -> Self {
+        XC2FuseCorpus {
+            num_fuses: None,
+            samples: Vec::new(),
+        }
+    }
+
+    /// Adds a sample consisting of the given fuses and tags. Returns the index of the sample, or an error if the
+    /// number of fuses does not match the samples that are already in the corpus.
+    pub fn add_sample<S: AsRef<str>>(&mut self, fuses: &[bool], tags: &[S]) -> Result<usize, XC2BitError> {
+        if self.num_fuses.is_some() && self.num_fuses != Some(fuses.len()) {
+            return Err(XC2BitError::WrongFuseCount);
+        }
+        self.num_fuses = Some(fuses.len());
+
+        self.samples.push((fuses.to_vec(), tags.iter().map(|x| x.as_ref().to_owned()).collect()));
+        Ok(self.samples.len() - 1)
+    }
+
+    /// Adds a .jed file as a sample with the given tags
+    pub fn add_jed<S: AsRef<str>>(&mut self, jed: &JEDECFile, tags: &[S]) -> Result<usize, XC2BitError> {
+        self.add_sample(&jed.f, tags)
+    }
+
+    /// Returns the number of samples in the corpus
+    pub fn num_samples(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns every tag used in the corpus, in sorted order
+    pub fn tags(&self) -> Vec<&str> {
+        let mut tags = BTreeSet::new();
+        for &(_, ref sample_tags) in &self.samples {
+            for tag in sample_tags {
+                tags.insert(tag.as_str());
+            }
+        }
+        tags.into_iter().collect()
+    }
+
+    /// Solves for the fuses corresponding to a single tag
+    pub fn solve_tag(&self, tag: &str) -> XC2TagSolution {
+        let num_fuses = self.num_fuses.unwrap_or(0);
+
+        let (positive, negative): (Vec<_>, Vec<_>) = self.samples.iter().enumerate()
+            .partition(|&(_, &(_, ref tags))| tags.contains(tag));
+
+        // Fuses with a consistent value across all samples with the tag that also vary somewhere in the corpus
+        let mut fuses = Vec::new();
+        if !positive.is_empty() {
+            let first = &(positive[0].1).0;
+            for i in 0..num_fuses {
+                let val = first[i];
+                if positive.iter().all(|&(_, &(ref f, _))| f[i] == val) &&
+                    negative.iter().any(|&(_, &(ref f, _))| f[i] != val) {
+
+                    fuses.push((i, val));
+                }
+            }
+        }
+
+        let mut certain = BTreeSet::new();
+        let mut conflicts = Vec::new();
+        for &(sample_i, &(ref f, _)) in &negative {
+            let mut mismatches = fuses.iter().filter(|&&(i, val)| f[i] != val);
+            match (mismatches.next(), mismatches.next()) {
+                (None, _) => conflicts.push(sample_i),
+                (Some(&(i, _)), None) => {
+                    certain.insert(i);
+                },
+                _ => {},
+            }
+        }
+
+        XC2TagSolution {
+            tag: tag.to_owned(),
+            num_samples: positive.len(),
+            fuses,
+            certain: certain.into_iter().collect(),
+            conflicts,
+        }
+    }
+
+    /// Solves for the fuses corresponding to every tag in the corpus
+    pub fn solve(&self) -> Vec<XC2TagSolution> {
+        self.tags().into_iter().map(|tag| self.solve_tag(tag)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solve_corpus() {
+        let mut corpus = XC2FuseCorpus::new();
+        // Fuse 0 is "slew=fast" (active low), fuses 2 and 3 are a 2-bit field, fuse 4 is noise
+        corpus.add_sample(&[true, false, false, false, true], &["slew=slow", "mode=0"]).unwrap();
+        corpus.add_sample(&[false, false, false, true, true], &["slew=fast", "mode=1"]).unwrap();
+        corpus.add_sample(&[false, false, true, false, false], &["slew=fast", "mode=2"]).unwrap();
+        corpus.add_sample(&[true, false, true, true, true], &["slew=slow", "mode=3"]).unwrap();
+        assert!(corpus.add_sample(&[true], &["bad"]).is_err());
+
+        let fast = corpus.solve_tag("slew=fast");
+        assert_eq!(fast.num_samples, 2);
+        assert_eq!(fast.fuses, vec![(0, false)]);
+        assert!(fast.is_determined());
+
+        // Every other sample differs from mode 1 in at least two fuses, so none of them is known to be necessary
+        let mode1 = corpus.solve_tag("mode=1");
+        assert_eq!(mode1.fuses, vec![(0, false), (2, false), (3, true), (4, true)]);
+        assert_eq!(mode1.certain, Vec::<usize>::new());
+        assert_eq!(mode1.undetermined(), vec![0, 2, 3, 4]);
+        assert!(!mode1.is_determined());
+
+        // A tag that the fuses can't explain
+        corpus.add_sample(&[true, false, false, false, true], &["mode=0", "slew=slow", "pin 1 used"]).unwrap();
+        let unexplained = corpus.solve_tag("pin 1 used");
+        assert_eq!(unexplained.conflicts, vec![0]);
+
+        assert_eq!(corpus.solve().len(), 7);
+    }
+}
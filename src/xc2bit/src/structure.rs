@@ -27,6 +27,31 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::*;
 
+/// What a connection made by `get_device_structure` is
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum XC2ConnectionKind {
+    /// A connection that is always there
+    Fixed,
+    /// A connection from a ZIA choice into an input of an AND term, which exists if the ZIA row selects the choice
+    /// and the macrocell or IOB feeds the signal of `path` into the ZIA
+    ZIAChoice {
+        /// Index of the choice within the ZIA row
+        choice: u32,
+        path: XC2ZIAFeedbackPath,
+    },
+}
+
+/// Which signal a ZIA choice carries from its macrocell or IOB
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum XC2ZIAFeedbackPath {
+    /// The output of the XOR gate
+    Xor,
+    /// The input from the pin, including the dedicated input pin
+    Pin,
+    /// The output of the register
+    Register,
+}
+
 /// Returns the function block and macrocell index of the global clock signal GCKn for the given device
 pub fn get_gck(device: XC2Device, idx: usize) -> Option<(u32, u32)> {
     if idx >= 3 {
@@ -126,7 +151,7 @@ pub fn get_dge(device: XC2Device) -> Option<(u32, u32)> {
 /// * `node_callback`: node unique name, node type, function block index, index within function block
 /// * `wire_callback`: wire unique name
 /// * `connection_callback`: value returned from `node_callback`, value returned from `wire_callback`,
-///    port name, index within port, extra data
+///    port name, index within port, kind of connection
 ///
 /// This interface was designed specifically for the place-and-route tool to build up a model of the device. However,
 /// it is sufficiently generic to be useful for other programs as well. The interface is designed around callbacks
@@ -135,13 +160,14 @@ pub fn get_dge(device: XC2Device) -> Option<(u32, u32)> {
 /// identified with strings (rather than numbers) as a compromise between performance and convenience to the caller.
 ///
 /// Note that mux sites are not represented here. They just appear as multiple drivers onto the same wire.
+/// `XC2RoutingGraph` represents them explicitly, together with the setting that selects each input.
 ///
-/// FIXME: Explain what the extra data is for (it's a hack fo the ZIA)
+/// All connections are `XC2ConnectionKind::Fixed` except for the ZIA connections into the AND terms.
 pub fn get_device_structure<N, W, C>(device: XC2Device,
     mut node_callback: N, mut wire_callback: W, mut connection_callback: C)
     where N: FnMut(&str, &str, u32, u32) -> usize,
           W: FnMut(&str) -> usize,
-          C: FnMut(usize, usize, &'static str, u32, (u32, u32)) -> () {
+          C: FnMut(usize, usize, &'static str, u32, XC2ConnectionKind) -> () {
 
     // Global buffers and the output wires
     // Cannot create the input wires until after IO stuff is created
@@ -149,20 +175,20 @@ pub fn get_device_structure<N, W, C>(device: XC2Device,
     let gck = (0..3).map(|i| {
         let w = wire_callback(&format!("gck_{}", i));
         let n = node_callback(&format!("bufg_gck_{}", i), "BUFG", 0, i);
-        connection_callback(n, w, "O", 0, (0, 0));
+        connection_callback(n, w, "O", 0, XC2ConnectionKind::Fixed);
         (w, n)
     }).collect::<Vec<_>>();
     // GTS
     let gts = (0..4).map(|i| {
         let w = wire_callback(&format!("gts_{}", i));
         let n = node_callback(&format!("bufg_gts_{}", i), "BUFGTS", 0, i);
-        connection_callback(n, w, "O", 0, (0, 0));
+        connection_callback(n, w, "O", 0, XC2ConnectionKind::Fixed);
         (w, n)
     }).collect::<Vec<_>>();
     // GSR
     let gsr_wire = wire_callback("gsr");
     let gsr_node = node_callback("bufg_gsr", "BUFGSR", 0, 0);
-    connection_callback(gsr_node, gsr_wire, "O", 0, (0, 0));
+    connection_callback(gsr_node, gsr_wire, "O", 0, XC2ConnectionKind::Fixed);
 
     // Function blocks
     let fb_things = (0..device.num_fbs() as u32).map(|fb| {
@@ -186,7 +212,7 @@ pub fn get_device_structure<N, W, C>(device: XC2Device,
         // AND gates
         let and_nodes = (0..ANDTERMS_PER_FB).map(|i| {
             let n = node_callback(&format!("fb{}_andgate{}", fb, i), "ANDTERM", fb, i as u32);
-            connection_callback(n, pterm_wires[i], "OUT", 0, (0, 0));
+            connection_callback(n, pterm_wires[i], "OUT", 0, XC2ConnectionKind::Fixed);
 
             n
         }).collect::<Vec<_>>();
@@ -194,22 +220,22 @@ pub fn get_device_structure<N, W, C>(device: XC2Device,
         // OR gates
         for i in 0..MCS_PER_FB {
             let n = node_callback(&format!("fb{}_orgate{}", fb, i), "ORTERM", fb, i as u32);
-            connection_callback(n, orterm_wires[i], "OUT", 0, (0, 0));
+            connection_callback(n, orterm_wires[i], "OUT", 0, XC2ConnectionKind::Fixed);
 
             // Inputs
             for j in 0..ANDTERMS_PER_FB {
-                connection_callback(n, pterm_wires[j], "IN", j as u32, (0, 0));
+                connection_callback(n, pterm_wires[j], "IN", j as u32, XC2ConnectionKind::Fixed);
             }
         }
 
         // XOR gates
         let xor_nodes = (0..MCS_PER_FB).map(|i| {
             let n = node_callback(&format!("fb{}_xorgate{}", fb, i), "MACROCELL_XOR", fb, i as u32);
-            connection_callback(n, xorterm_wires[i], "OUT", 0, (0, 0));
+            connection_callback(n, xorterm_wires[i], "OUT", 0, XC2ConnectionKind::Fixed);
 
             // Inputs
-            connection_callback(n, orterm_wires[i], "IN_ORTERM", 0, (0, 0));
-            connection_callback(n, pterm_wires[get_ptc(i as u32) as usize], "IN_PTC", 0, (0, 0));
+            connection_callback(n, orterm_wires[i], "IN_ORTERM", 0, XC2ConnectionKind::Fixed);
+            connection_callback(n, pterm_wires[get_ptc(i as u32) as usize], "IN_PTC", 0, XC2ConnectionKind::Fixed);
 
             n
         }).collect::<Vec<_>>();
@@ -219,39 +245,39 @@ pub fn get_device_structure<N, W, C>(device: XC2Device,
             let n = node_callback(&format!("fb{}_reg{}", fb, i), "REG", fb, i as u32);
 
             // Output
-            connection_callback(n, regout_wires[i], "Q", 0, (0, 0));
+            connection_callback(n, regout_wires[i], "Q", 0, XC2ConnectionKind::Fixed);
 
             // D/T input
-            connection_callback(n, xorterm_wires[i], "D/T", 0, (0, 0));
+            connection_callback(n, xorterm_wires[i], "D/T", 0, XC2ConnectionKind::Fixed);
 
             // CE input
-            connection_callback(n, pterm_wires[get_ptc(i as u32) as usize], "CE", 0, (0, 0));
+            connection_callback(n, pterm_wires[get_ptc(i as u32) as usize], "CE", 0, XC2ConnectionKind::Fixed);
 
             // Clock sources
             // GCK
             for j in 0..3 {
-                connection_callback(n, gck[j].0, "CLK", j as u32, (0, 0));
+                connection_callback(n, gck[j].0, "CLK", j as u32, XC2ConnectionKind::Fixed);
             }
             // CTC
-            connection_callback(n, pterm_wires[CTC as usize], "CLK", 3, (0, 0));
+            connection_callback(n, pterm_wires[CTC as usize], "CLK", 3, XC2ConnectionKind::Fixed);
             // PTC
-            connection_callback(n, pterm_wires[get_ptc(i as u32) as usize], "CLK", 4, (0, 0));
+            connection_callback(n, pterm_wires[get_ptc(i as u32) as usize], "CLK", 4, XC2ConnectionKind::Fixed);
 
             // Set
             // GSR
-            connection_callback(n, gsr_wire, "S", 0, (0, 0));
+            connection_callback(n, gsr_wire, "S", 0, XC2ConnectionKind::Fixed);
             // CTS
-            connection_callback(n, pterm_wires[CTS as usize], "S", 1, (0, 0));
+            connection_callback(n, pterm_wires[CTS as usize], "S", 1, XC2ConnectionKind::Fixed);
             // PTA
-            connection_callback(n, pterm_wires[get_pta(i as u32) as usize], "S", 2, (0, 0));
+            connection_callback(n, pterm_wires[get_pta(i as u32) as usize], "S", 2, XC2ConnectionKind::Fixed);
 
             // Reset
             // GSR
-            connection_callback(n, gsr_wire, "R", 0, (0, 0));
+            connection_callback(n, gsr_wire, "R", 0, XC2ConnectionKind::Fixed);
             // CTR
-            connection_callback(n, pterm_wires[CTR as usize], "R", 1, (0, 0));
+            connection_callback(n, pterm_wires[CTR as usize], "R", 1, XC2ConnectionKind::Fixed);
             // PTA
-            connection_callback(n, pterm_wires[get_pta(i as u32) as usize], "R", 2, (0, 0));
+            connection_callback(n, pterm_wires[get_pta(i as u32) as usize], "R", 2, XC2ConnectionKind::Fixed);
 
             n
         }).collect::<Vec<_>>();
@@ -269,12 +295,12 @@ pub fn get_device_structure<N, W, C>(device: XC2Device,
         let from_w = wire_callback(&format!("from_iob_{}", iob_idx));
 
         // To the ZIA
-        connection_callback(fb_things[fb as usize].0[i as usize], from_w, "D/T", 1, (0, 0));
+        connection_callback(fb_things[fb as usize].0[i as usize], from_w, "D/T", 1, XC2ConnectionKind::Fixed);
 
         // From the XOR
-        connection_callback(fb_things[fb as usize].1[i as usize], to_w, "OUT", 1, (0, 0));
+        connection_callback(fb_things[fb as usize].1[i as usize], to_w, "OUT", 1, XC2ConnectionKind::Fixed);
         // From the register
-        connection_callback(fb_things[fb as usize].0[i as usize], to_w, "Q", 1, (0, 0));
+        connection_callback(fb_things[fb as usize].0[i as usize], to_w, "Q", 1, XC2ConnectionKind::Fixed);
 
         (to_w, from_w)
     }).collect::<Vec<_>>();
@@ -285,23 +311,24 @@ pub fn get_device_structure<N, W, C>(device: XC2Device,
         let n = node_callback(&format!("iob_{}", iob_idx), "IOBUFE", 0, iob_idx as u32);
 
         // The input to the IOB (from the macrocell, to the outside world)
-        connection_callback(n, to_from_iob_wires[iob_idx].0, "I", 0, (0, 0));
+        connection_callback(n, to_from_iob_wires[iob_idx].0, "I", 0, XC2ConnectionKind::Fixed);
 
         // The output from the IOB (from the outside world, into the circuitry)
-        connection_callback(n, to_from_iob_wires[iob_idx].1, "O", 0, (0, 0));
+        connection_callback(n, to_from_iob_wires[iob_idx].1, "O", 0, XC2ConnectionKind::Fixed);
 
         // The output enables
         let (iob_fb, iob_mc) = iob_num_to_fb_mc_num(device, iob_idx as u32).unwrap();
         // GTS
         for i in 0..4 {
-            connection_callback(n, gts[i].0, "E", i as u32, (0, 0));
+            connection_callback(n, gts[i].0, "E", i as u32, XC2ConnectionKind::Fixed);
         }
         // Open-drain mode
-        connection_callback(n, to_from_iob_wires[iob_idx].0, "E", 4, (0, 0));
+        connection_callback(n, to_from_iob_wires[iob_idx].0, "E", 4, XC2ConnectionKind::Fixed);
         // CTE
-        connection_callback(n, fb_things[iob_fb as usize].2[CTE as usize], "E", 5, (0, 0));
+        connection_callback(n, fb_things[iob_fb as usize].2[CTE as usize], "E", 5, XC2ConnectionKind::Fixed);
         // PTB
-        connection_callback(n, fb_things[iob_fb as usize].2[get_ptb(iob_mc) as usize], "E", 6, (0, 0));
+        connection_callback(n, fb_things[iob_fb as usize].2[get_ptb(iob_mc) as usize], "E", 6,
+            XC2ConnectionKind::Fixed);
     }
 
     // Input-only pad
@@ -316,7 +343,7 @@ pub fn get_device_structure<N, W, C>(device: XC2Device,
             let n = node_callback("ipad", "IBUF", 0, 0);
 
             // The output from the IOB (from the outside world, into the circuitry)
-            connection_callback(n, w, "O", 0, (0, 0));
+            connection_callback(n, w, "O", 0, XC2ConnectionKind::Fixed);
         },
         _ => {
             from_ipad_w = None;
@@ -328,19 +355,19 @@ pub fn get_device_structure<N, W, C>(device: XC2Device,
     for i in 0..3 {
         let (fb, mc) = get_gck(device, i as usize).unwrap();
         let iob_idx = fb_mc_num_to_iob_num(device, fb, mc).unwrap();
-        connection_callback(gck[i].1, to_from_iob_wires[iob_idx as usize].1, "I", 0, (0, 0));
+        connection_callback(gck[i].1, to_from_iob_wires[iob_idx as usize].1, "I", 0, XC2ConnectionKind::Fixed);
     }
     // GTS
     for i in 0..4 {
         let (fb, mc) = get_gts(device, i as usize).unwrap();
         let iob_idx = fb_mc_num_to_iob_num(device, fb, mc).unwrap();
-        connection_callback(gts[i].1, to_from_iob_wires[iob_idx as usize].1, "I", 0, (0, 0));
+        connection_callback(gts[i].1, to_from_iob_wires[iob_idx as usize].1, "I", 0, XC2ConnectionKind::Fixed);
     }
     // GSR
     {
         let (fb, mc) = get_gsr(device);
         let iob_idx = fb_mc_num_to_iob_num(device, fb, mc).unwrap();
-        connection_callback(gsr_node, to_from_iob_wires[iob_idx as usize].1, "I", 0, (0, 0));
+        connection_callback(gsr_node, to_from_iob_wires[iob_idx as usize].1, "I", 0, XC2ConnectionKind::Fixed);
     }
 
     // The ZIA
@@ -353,27 +380,32 @@ pub fn get_device_structure<N, W, C>(device: XC2Device,
                             // From the XOR gate
                             connection_callback(fb_things[and_fb as usize].3[and_i as usize],
                                 fb_things[zia_fb as usize].5[zia_mc as usize], "IN", zia_row_i,
-                                (zia_choice_i as u32, 0));
+                                XC2ConnectionKind::ZIAChoice{choice: zia_choice_i as u32,
+                                    path: XC2ZIAFeedbackPath::Xor});
                             // From the register
                             connection_callback(fb_things[and_fb as usize].3[and_i as usize],
                                 fb_things[zia_fb as usize].4[zia_mc as usize], "IN", zia_row_i,
-                                (zia_choice_i as u32, 1));
+                                XC2ConnectionKind::ZIAChoice{choice: zia_choice_i as u32,
+                                    path: XC2ZIAFeedbackPath::Register});
                         },
                         &XC2ZIAInput::IBuf{ibuf: zia_iob} => {
                             let (iob_fb, iob_mc) = iob_num_to_fb_mc_num(device, zia_iob as u32).unwrap();
                             // From the pad
                             connection_callback(fb_things[and_fb as usize].3[and_i as usize],
                                 to_from_iob_wires[zia_iob as usize].1, "IN", zia_row_i,
-                                (zia_choice_i as u32, 0));
+                                XC2ConnectionKind::ZIAChoice{choice: zia_choice_i as u32,
+                                    path: XC2ZIAFeedbackPath::Pin});
                             // From the register
                             connection_callback(fb_things[and_fb as usize].3[and_i as usize],
                                 fb_things[iob_fb as usize].4[iob_mc as usize], "IN", zia_row_i,
-                                (zia_choice_i as u32, 1));
+                                XC2ConnectionKind::ZIAChoice{choice: zia_choice_i as u32,
+                                    path: XC2ZIAFeedbackPath::Register});
                         },
                         &XC2ZIAInput::DedicatedInput => {
                             connection_callback(fb_things[and_fb as usize].3[and_i as usize],
                                 from_ipad_w.unwrap(), "IN", zia_row_i,
-                                (zia_choice_i as u32, 0));
+                                XC2ConnectionKind::ZIAChoice{choice: zia_choice_i as u32,
+                                    path: XC2ZIAFeedbackPath::Pin});
                         },
                         // These cannot be in the choices table; they are special cases
                         _ => unreachable!(),
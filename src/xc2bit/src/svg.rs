@@ -0,0 +1,173 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Renders a `FuseArray` as an annotated SVG image of the physical fuse layout
+
+use std::io;
+use std::io::Write;
+
+use crate::*;
+
+/// Size of each fuse in the rendered image, in SVG user units
+const FUSE_SIZE: usize = 4;
+/// Height of the legend below the fuse grid
+const LEGEND_HEIGHT: usize = 40;
+
+/// Returns the (fuse = 1, fuse = 0) colours used for fuses with the given function
+fn region_colours(function: Option<&XC2FuseFunction>) -> (&'static str, &'static str) {
+    match function {
+        Some(&XC2FuseFunction::Zia{..}) => ("#a6c8ff", "#1f5fbf"),
+        Some(&XC2FuseFunction::AndTerm{..}) => ("#b8e6b0", "#2e8b2e"),
+        Some(&XC2FuseFunction::OrTerm{..}) => ("#ffd39a", "#d97a00"),
+        Some(&XC2FuseFunction::Macrocell{..}) => ("#dcc2f0", "#7a3fa8"),
+        Some(&XC2FuseFunction::Iob{..}) | Some(&XC2FuseFunction::InPin{..}) => ("#a8e4e0", "#1d8a84"),
+        Some(&XC2FuseFunction::Global{..}) => ("#f5c0c0", "#b03030"),
+        None => ("#e8e8e8", "#909090"),
+    }
+}
+
+/// Escapes text for use inside an SVG element
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+impl FuseArray {
+    /// Writes the fuse array as an SVG image. Each fuse is drawn at its physical location and shaded according to
+    /// its function (ZIA, AND array, OR array, macrocell, IOB, global), with a darker shade for fuses that are 0.
+    /// Every fuse has a tooltip naming it. If `other` is given, fuses that differ between `self` and `other` are
+    /// highlighted in red.
+    pub fn write_svg<W: Write>(&self, device: XC2Device, other: Option<&FuseArray>, mut writer: W)
+        -> Result<(), io::Error> {
+
+        let (w, h) = self.dim();
+        if let Some(other) = other {
+            if other.dim() != (w, h) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "fuse arrays have different dimensions"));
+            }
+        }
+
+        let fuse_map = XC2FuseMap::new(device);
+
+        let img_w = w * FUSE_SIZE;
+        let img_h = h * FUSE_SIZE + LEGEND_HEIGHT;
+        write!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n")?;
+        write!(writer, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+            viewBox=\"0 0 {} {}\">\n", img_w, img_h, img_w, img_h)?;
+        write!(writer, "<title>{} fuse array</title>\n", device)?;
+        write!(writer, "<rect width=\"{}\" height=\"{}\" fill=\"#ffffff\"/>\n", img_w, img_h)?;
+
+        write!(writer, "<g shape-rendering=\"crispEdges\">\n")?;
+        for y in 0..h {
+            for x in 0..w {
+                let fuse = fuse_map.crbit_fuse(x, y);
+                let val = self.get(x, y);
+                let other_val = other.map(|other| other.get(x, y));
+                let differs = other_val == Some(!val);
+
+                let (colour_1, colour_0) = region_colours(fuse.map(|fuse| &fuse.function));
+                let fill = if differs {
+                    "#ff0000"
+                } else if val {
+                    colour_1
+                } else {
+                    colour_0
+                };
+
+                let mut tooltip = format!("{}|{}: ", x, y);
+                if let Some(fuse) = fuse {
+                    tooltip.push_str(&format!("{}", fuse.function));
+                    if let Some(jed) = fuse.jed {
+                        tooltip.push_str(&format!(" (JED {})", jed));
+                    }
+                } else {
+                    tooltip.push_str("unknown");
+                }
+                if differs {
+                    tooltip.push_str(&format!(" = {}→{}", val as u8, other_val.unwrap() as u8));
+                } else {
+                    tooltip.push_str(&format!(" = {}", val as u8));
+                }
+
+                write!(writer, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"><title>{}</title></rect>\n",
+                    x * FUSE_SIZE, y * FUSE_SIZE, FUSE_SIZE, FUSE_SIZE, fill, xml_escape(&tooltip))?;
+            }
+        }
+        write!(writer, "</g>\n")?;
+
+        // Legend
+        let legend: &[(&str, Option<XC2FuseFunction>)] = &[
+            ("ZIA", Some(XC2FuseFunction::Zia{fb: 0, row: 0, bit: 0})),
+            ("AND array", Some(XC2FuseFunction::AndTerm{fb: 0, term: 0, input: 0, complement: false})),
+            ("OR array", Some(XC2FuseFunction::OrTerm{fb: 0, mc: 0, term: 0})),
+            ("macrocell", Some(XC2FuseFunction::Macrocell{fb: 0, mc: 0, field: "", bit: None})),
+            ("IOB", Some(XC2FuseFunction::Iob{iob: 0, field: "", bit: None})),
+            ("global", Some(XC2FuseFunction::Global{field: "", bit: None})),
+            ("unknown", None),
+        ];
+        let legend_y = h * FUSE_SIZE + 12;
+        for (i, &(name, ref function)) in legend.iter().enumerate() {
+            let (colour_1, colour_0) = region_colours(function.as_ref());
+            let legend_x = 8 + i * 120;
+            write!(writer, "<rect x=\"{}\" y=\"{}\" width=\"12\" height=\"12\" fill=\"{}\"/>\n",
+                legend_x, legend_y, colour_1)?;
+            write!(writer, "<rect x=\"{}\" y=\"{}\" width=\"12\" height=\"12\" fill=\"{}\"/>\n",
+                legend_x + 12, legend_y, colour_0)?;
+            write!(writer, "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"12\">{}</text>\n",
+                legend_x + 30, legend_y + 11, name)?;
+        }
+        if other.is_some() {
+            let legend_x = 8 + legend.len() * 120;
+            write!(writer, "<rect x=\"{}\" y=\"{}\" width=\"24\" height=\"12\" fill=\"#ff0000\"/>\n",
+                legend_x, legend_y)?;
+            write!(writer, "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"12\">differs</text>\n",
+                legend_x + 30, legend_y + 11)?;
+        }
+
+        write!(writer, "</svg>\n")?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn svg_highlights_differences() {
+        let bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c32a-4-vq44").unwrap());
+        let fuse_array = bitstream.to_crbit();
+        let mut other_fuse_array = bitstream.to_crbit();
+        let val = other_fuse_array.get(10, 3);
+        other_fuse_array.set(10, 3, !val);
+
+        let mut svg = Vec::new();
+        fuse_array.write_svg(XC2Device::XC2C32A, Some(&other_fuse_array), &mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert_eq!(svg.matches("fill=\"#ff0000\"><title>").count(), 1);
+        assert!(svg.contains(&format!("<title>10|3: FB1 AND term 0 ZIA input 3 complement (JED 327) = {}→{}</title>",
+            val as u8, !val as u8)));
+    }
+}
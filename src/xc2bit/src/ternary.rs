@@ -0,0 +1,359 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Fuse arrays containing "unknown/don't care" fuses in addition to 0s and 1s
+
+use std::io;
+use std::io::Write;
+use std::ops::{Index, IndexMut};
+
+use crate::*;
+use crate::crbit::{CrbitHeader, parse_crbit, write_crbit_header};
+use crate::fusemap_logical::{total_logical_fuse_count};
+use crate::fusemap_physical::{fuse_array_dims};
+
+/// The state of a single fuse in a `TernaryFuseArray`
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum TernaryFuse {
+    Zero,
+    One,
+    /// Unknown or don't care
+    X,
+}
+
+impl TernaryFuse {
+    /// Returns whether this fuse is compatible with the given definite value (i.e. it is either X or equal to it)
+    pub fn matches(self, val: bool) -> bool {
+        match self {
+            TernaryFuse::Zero => !val,
+            TernaryFuse::One => val,
+            TernaryFuse::X => true,
+        }
+    }
+
+    /// Returns the definite value of this fuse, or `None` if it is X
+    pub fn value(self) -> Option<bool> {
+        match self {
+            TernaryFuse::Zero => Some(false),
+            TernaryFuse::One => Some(true),
+            TernaryFuse::X => None,
+        }
+    }
+
+    fn to_char(self) -> char {
+        match self {
+            TernaryFuse::Zero => '0',
+            TernaryFuse::One => '1',
+            TernaryFuse::X => 'X',
+        }
+    }
+}
+
+impl From<bool> for TernaryFuse {
+    fn from(val: bool) -> Self {
+        if val {TernaryFuse::One} else {TernaryFuse::Zero}
+    }
+}
+
+/// Struct representing a 2-dimensional fuse array where each fuse can also be X (unknown/don't care). This uses the
+/// same coordinate system as `FuseArray`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct TernaryFuseArray {
+    /// Internal 1-dimensional storage
+    v: Vec<TernaryFuse>,
+    /// Width of the array
+    w: usize,
+    /// Possibly contains a device name
+    pub dev_name_str: Option<String>,
+}
+
+impl TernaryFuseArray {
+    /// Get a fuse value at the particular xy coordinate
+    pub fn get(&self, x: usize, y: usize) -> TernaryFuse {
+        self.v[y * self.w + x]
+    }
+
+    /// Set the fuse value at the particular xy coordinate
+    pub fn set(&mut self, x: usize, y: usize, val: TernaryFuse) {
+        self.v[y * self.w + x] = val;
+    }
+
+    /// Returns the dimensions of this array as (width, height)
+    pub fn dim(&self) -> (usize, usize) {
+        (self.w, self.v.len() / self.w)
+    }
+
+    /// Constructs a new `TernaryFuseArray` object with the given dimensions and filled with Xs
+    pub fn from_dim(w: usize, h: usize) -> Self {
+        TernaryFuseArray {
+            w,
+            v: vec![TernaryFuse::X; w*h],
+            dev_name_str: None,
+        }
+    }
+
+    /// Constructs a new `TernaryFuseArray` with all fuses set to the definite values in `fuse_array`
+    pub fn from_fuse_array(fuse_array: &FuseArray) -> Self {
+        let (w, h) = fuse_array.dim();
+        let mut ret = Self::from_dim(w, h);
+        for y in 0..h {
+            for x in 0..w {
+                ret.set(x, y, fuse_array.get(x, y).into());
+            }
+        }
+        ret.dev_name_str = fuse_array.dev_name_str.clone();
+
+        ret
+    }
+
+    /// Constructs a new `TernaryFuseArray` from `fuse_array`, but with every fuse that is false in `mask` set to X.
+    /// Combined with `modelled_crbit_mask`, this can be used to ignore fuses that are not modelled by this crate.
+    pub fn from_fuse_array_masked(fuse_array: &FuseArray, mask: &FuseArray) -> Self {
+        assert_eq!(fuse_array.dim(), mask.dim());
+
+        let mut ret = Self::from_fuse_array(fuse_array);
+        let (w, h) = ret.dim();
+        for y in 0..h {
+            for x in 0..w {
+                if !mask.get(x, y) {
+                    ret.set(x, y, TernaryFuse::X);
+                }
+            }
+        }
+
+        ret
+    }
+
+    /// Converts this array into a `FuseArray`. Returns `None` if any fuse is X.
+    pub fn to_fuse_array(&self) -> Option<FuseArray> {
+        let (w, h) = self.dim();
+        let mut ret = FuseArray::from_dim(w, h);
+        for y in 0..h {
+            for x in 0..w {
+                ret.set(x, y, self.get(x, y).value()?);
+            }
+        }
+        ret.dev_name_str = self.dev_name_str.clone();
+
+        Some(ret)
+    }
+
+    /// Compares a fuse array against this array, ignoring all fuses that are X. Returns the coordinates of all fuses
+    /// that do not match.
+    pub fn masked_compare(&self, fuse_array: &FuseArray) -> Vec<(usize, usize)> {
+        assert_eq!(self.dim(), fuse_array.dim());
+
+        let (w, h) = self.dim();
+        let mut ret = Vec::new();
+        for y in 0..h {
+            for x in 0..w {
+                if !self.get(x, y).matches(fuse_array.get(x, y)) {
+                    ret.push((x, y));
+                }
+            }
+        }
+
+        ret
+    }
+
+    /// Combines the known fuses of two arrays. A fuse that is X in one array takes the value from the other array.
+    /// Returns the coordinates of the first fuse where the two arrays contain conflicting definite values.
+    pub fn merge(&self, other: &TernaryFuseArray) -> Result<Self, (usize, usize)> {
+        assert_eq!(self.dim(), other.dim());
+
+        let (w, h) = self.dim();
+        let mut ret = self.clone();
+        for y in 0..h {
+            for x in 0..w {
+                match (self.get(x, y), other.get(x, y)) {
+                    (TernaryFuse::X, b) => ret.set(x, y, b),
+                    (_, TernaryFuse::X) => {},
+                    (a, b) if a == b => {},
+                    _ => return Err((x, y)),
+                }
+            }
+        }
+
+        Ok(ret)
+    }
+
+    /// Keeps only the fuses on which both arrays agree. All other fuses are set to X.
+    pub fn intersect(&self, other: &TernaryFuseArray) -> Self {
+        assert_eq!(self.dim(), other.dim());
+
+        let (w, h) = self.dim();
+        let mut ret = self.clone();
+        for y in 0..h {
+            for x in 0..w {
+                if self.get(x, y) != other.get(x, y) {
+                    ret.set(x, y, TernaryFuse::X);
+                }
+            }
+        }
+
+        ret
+    }
+
+    /// Processes the given data and converts it into a `TernaryFuseArray` struct. This accepts the same format as
+    /// `FuseArray::from_file_contents` with the addition of 'X' (or 'x') for unknown fuses. The `CHECKSUM` header is
+    /// not checked.
+    pub fn from_file_contents(in_bytes: &[u8]) -> Result<Self, CrbitParseError> {
+        let (header, v, w) = parse_crbit(in_bytes, |c| match c {
+            '0' => Some(TernaryFuse::Zero),
+            '1' => Some(TernaryFuse::One),
+            'X' | 'x' => Some(TernaryFuse::X),
+            _ => None,
+        })?;
+
+        Ok(TernaryFuseArray {
+            v,
+            w,
+            dev_name_str: header.dev_name_str,
+        })
+    }
+
+    /// Writes the fuse array to the extended crbit format, which is the same as the normal crbit format except that
+    /// unknown fuses are written as 'X'.
+    pub fn write_to_writer<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        write_crbit_header(&mut writer, "ternary crbit native bitstream", &CrbitHeader {
+            dev_name_str: self.dev_name_str.clone(),
+            ..Default::default()
+        })?;
+
+        let (w, h) = self.dim();
+        for y in 0..h {
+            for x in 0..w {
+                write!(writer, "{}", self.get(x, y).to_char())?;
+            }
+            write!(writer, "\n")?;
+        }
+        write!(writer, "\n")?;
+
+        Ok(())
+    }
+}
+
+impl Index<(usize, usize)> for TernaryFuseArray {
+    type Output = TernaryFuse;
+
+    fn index(&self, coords: (usize, usize)) -> &TernaryFuse {
+        &self.v[coords.1 * self.w + coords.0]
+    }
+}
+
+impl IndexMut<(usize, usize)> for TernaryFuseArray {
+    fn index_mut(&mut self, coords: (usize, usize)) -> &mut TernaryFuse {
+        &mut self.v[coords.1 * self.w + coords.0]
+    }
+}
+
+/// Returns a mask of the crbit fuses that are modelled by `XC2BitstreamBits` for the given device. Fuses that are
+/// modelled are true and all other (reserved or unknown) fuses are false.
+pub fn modelled_crbit_mask(device: XC2Device) -> FuseArray {
+    let (w, h) = fuse_array_dims(device);
+    let mut ret = FuseArray::from_dim(w, h);
+
+    for fuse in XC2FuseMap::new(device).fuses() {
+        if let Some(crbit) = fuse.crbit {
+            ret[crbit] = true;
+        }
+    }
+
+    ret
+}
+
+/// Returns a mask of the .jed fuses that are modelled by `XC2BitstreamBits` for the given device. Fuses that are
+/// modelled are true and all other (reserved or unknown) fuses are false.
+pub fn modelled_jed_mask(device: XC2Device) -> Vec<bool> {
+    let mut ret = vec![false; total_logical_fuse_count(device)];
+
+    for fuse in XC2FuseMap::new(device).fuses() {
+        if let Some(jed) = fuse.jed {
+            ret[jed] = true;
+        }
+    }
+
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ternary_crbit_roundtrip() {
+        let data = b"// DEVICE XC2C32A-4-VQ44\n01X\nx10\n";
+        let fuse_array = TernaryFuseArray::from_file_contents(data).unwrap();
+        assert_eq!(fuse_array.dim(), (3, 2));
+        assert_eq!(fuse_array.get(2, 0), TernaryFuse::X);
+        assert_eq!(fuse_array.get(0, 1), TernaryFuse::X);
+        assert_eq!(fuse_array.get(1, 1), TernaryFuse::One);
+
+        let mut out = Vec::new();
+        fuse_array.write_to_writer(&mut out).unwrap();
+        assert_eq!(TernaryFuseArray::from_file_contents(&out).unwrap(), fuse_array);
+
+        assert!(TernaryFuseArray::from_file_contents(b"01X\n0\n").is_err());
+    }
+
+    #[test]
+    fn ternary_merge_intersect() {
+        let a = TernaryFuseArray::from_file_contents(b"01XX\n").unwrap();
+        let b = TernaryFuseArray::from_file_contents(b"0X1X\n").unwrap();
+        let c = TernaryFuseArray::from_file_contents(b"10XX\n").unwrap();
+
+        assert_eq!(a.merge(&b).unwrap(), TernaryFuseArray::from_file_contents(b"011X\n").unwrap());
+        assert_eq!(a.merge(&c), Err((0, 0)));
+        assert_eq!(a.intersect(&b), TernaryFuseArray::from_file_contents(b"0XXX\n").unwrap());
+
+        let mut definite = FuseArray::from_dim(4, 1);
+        definite.set(1, 0, true);
+        definite.set(3, 0, true);
+        assert_eq!(a.masked_compare(&definite), vec![]);
+        assert_eq!(c.masked_compare(&definite), vec![(0, 0), (1, 0)]);
+    }
+
+    #[test]
+    fn ternary_modelled_mask() {
+        let bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c64a-5-vq44").unwrap());
+        let fuse_array = bitstream.to_crbit();
+        let mask = modelled_crbit_mask(XC2Device::XC2C64A);
+        let expected = TernaryFuseArray::from_fuse_array_masked(&fuse_array, &mask);
+
+        // Flipping an unmodelled fuse must not be reported
+        let mut readback = bitstream.to_crbit();
+        let (w, h) = readback.dim();
+        let unmodelled = (0..h).flat_map(|y| (0..w).map(move |x| (x, y))).find(|&(x, y)| !mask.get(x, y)).unwrap();
+        readback[unmodelled] = !readback[unmodelled];
+        assert_eq!(expected.masked_compare(&readback), vec![]);
+
+        // But a modelled one must
+        let modelled = XC2FuseMap::new(XC2Device::XC2C64A).fuses()[0].crbit.unwrap();
+        readback[modelled] = !readback[modelled];
+        assert_eq!(expected.masked_compare(&readback), vec![modelled]);
+
+        assert!(modelled_jed_mask(XC2Device::XC2C64A).iter().all(|&x| x));
+    }
+}
@@ -23,7 +23,7 @@ OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 */
 
-use std::collections::BTreeMap;
+use alloc::collections::BTreeMap;
 
 pub fn b2s(b: bool) -> &'static str {
     if b {"1"} else {"0"}
@@ -57,7 +57,7 @@ impl LinebreakSet {
 pub struct LinebreakSetIter<'a> {
     last_break_val: usize,
     last_break_rep: usize,
-    set_iter: ::std::collections::btree_map::Iter<'a, usize, usize>,
+    set_iter: alloc::collections::btree_map::Iter<'a, usize, usize>,
 }
 
 impl<'a> Iterator for LinebreakSetIter<'a> {
@@ -0,0 +1,287 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Resource utilization summary of a bitstream, similar to the summary printed by a fitter
+
+use std::fmt;
+
+use crate::*;
+use crate::equations::{mc_iob};
+
+/// Resources used in a single function block
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+pub struct XC2FBUtilization {
+    /// Macrocells whose register or combinatorial output is used, out of `MCS_PER_FB`
+    pub macrocells: usize,
+    /// Product terms that have any inputs or feed an OR term, out of `ANDTERMS_PER_FB`
+    pub product_terms: usize,
+    /// ZIA rows that are used by any product term, out of `INPUTS_PER_ANDTERM`
+    pub zia_rows: usize,
+}
+
+/// Resources used in an entire bitstream
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+pub struct XC2Utilization {
+    pub device: XC2Device,
+    /// Utilization of each function block
+    pub fbs: Vec<XC2FBUtilization>,
+    /// Total number of I/O pins, including the input-only pin on 32-macrocell devices
+    pub pins: usize,
+    /// Pins used only as inputs
+    pub input_pins: usize,
+    /// Pins used only as outputs
+    pub output_pins: usize,
+    /// Pins used as both inputs and outputs
+    pub bidir_pins: usize,
+    pub gck_enable: [bool; 3],
+    pub gsr_enable: bool,
+    pub gts_enable: [bool; 4],
+    /// Clock divider settings, on devices that have one
+    pub clock_div: Option<XC2ClockDiv>,
+}
+
+impl XC2Utilization {
+    /// Total number of macrocells used
+    pub fn macrocells(&self) -> usize {
+        self.fbs.iter().map(|x| x.macrocells).sum()
+    }
+
+    /// Total number of product terms used
+    pub fn product_terms(&self) -> usize {
+        self.fbs.iter().map(|x| x.product_terms).sum()
+    }
+
+    /// Total number of ZIA rows used
+    pub fn zia_rows(&self) -> usize {
+        self.fbs.iter().map(|x| x.zia_rows).sum()
+    }
+}
+
+/// Internal helper that formats "used/total (percent%)"
+fn fmt_used(f: &mut fmt::Formatter, name: &str, used: usize, total: usize) -> fmt::Result {
+    write!(f, "{:<16}{:>5}/{:<5} ({}%)\n", name, used, total, used * 100 / total)
+}
+
+impl fmt::Display for XC2Utilization {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let num_fbs = self.fbs.len();
+
+        write!(f, "device: {}\n", self.device)?;
+        fmt_used(f, "macrocells", self.macrocells(), num_fbs * MCS_PER_FB)?;
+        fmt_used(f, "product terms", self.product_terms(), num_fbs * ANDTERMS_PER_FB)?;
+        fmt_used(f, "ZIA rows", self.zia_rows(), num_fbs * INPUTS_PER_ANDTERM)?;
+        fmt_used(f, "I/O pins", self.input_pins + self.output_pins + self.bidir_pins, self.pins)?;
+        write!(f, "  inputs: {}, outputs: {}, bidirectional: {}\n",
+            self.input_pins, self.output_pins, self.bidir_pins)?;
+
+        write!(f, "global nets:")?;
+        let mut any_global = false;
+        for i in 0..3 {
+            if self.gck_enable[i] {
+                write!(f, " GCK{}", i)?;
+                any_global = true;
+            }
+        }
+        if self.gsr_enable {
+            write!(f, " GSR")?;
+            any_global = true;
+        }
+        for i in 0..4 {
+            if self.gts_enable[i] {
+                write!(f, " GTS{}", i)?;
+                any_global = true;
+            }
+        }
+        if !any_global {
+            write!(f, " none")?;
+        }
+        write!(f, "\n")?;
+
+        if let Some(clock_div) = self.clock_div {
+            if clock_div.enabled {
+                write!(f, "clock divider: {:?}{}\n", clock_div.div_ratio,
+                    if clock_div.delay {", delayed"} else {""})?;
+            } else {
+                write!(f, "clock divider: disabled\n")?;
+            }
+        }
+
+        write!(f, "\n")?;
+        write!(f, "FB    macrocells  product terms  ZIA rows\n")?;
+        for (i, fb) in self.fbs.iter().enumerate() {
+            write!(f, "FB{:<4}{:>5}/{:<6}{:>6}/{:<8}{:>4}/{}\n", i + 1,
+                fb.macrocells, MCS_PER_FB, fb.product_terms, ANDTERMS_PER_FB, fb.zia_rows, INPUTS_PER_ANDTERM)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl XC2BitstreamBits {
+    /// Computes how many of the resources of the device are used by this bitstream
+    pub fn utilization(&self) -> XC2Utilization {
+        let device = self.device_type();
+        let global_nets = self.get_global_nets();
+
+        // Which pins are used as inputs (indexed by IOB), and whether the input-only pin is used
+        let mut iob_is_input = vec![false; device.num_iobs()];
+        let mut input_only_used = false;
+        for fb in self.get_fb() {
+            for row in 0..INPUTS_PER_ANDTERM {
+                let used = (0..ANDTERMS_PER_FB).any(|i| fb.get_andterm(i).get(row) || fb.get_andterm(i).get_b(row));
+                if !used {
+                    continue;
+                }
+                match *fb.get_zia(row) {
+                    XC2ZIAInput::IBuf{ibuf} => iob_is_input[ibuf as usize] = true,
+                    XC2ZIAInput::DedicatedInput => input_only_used = true,
+                    _ => {},
+                }
+            }
+        }
+        let mut mark_global_pin = |pin: Option<(u32, u32)>, enabled: bool| {
+            if let Some((fb, mc)) = pin {
+                if let Some(iob) = fb_mc_num_to_iob_num(device, fb, mc) {
+                    if enabled {
+                        iob_is_input[iob as usize] = true;
+                    }
+                }
+            }
+        };
+        for i in 0..3 {
+            mark_global_pin(get_gck(device, i), global_nets.gck_enable[i]);
+        }
+        mark_global_pin(Some(get_gsr(device)), global_nets.gsr_enable);
+        for i in 0..4 {
+            mark_global_pin(get_gts(device, i), global_nets.gts_enable[i]);
+        }
+
+        let mut fbs = Vec::new();
+        let mut iob_is_output = vec![false; device.num_iobs()];
+        for (fb_i, fb) in self.get_fb().iter().enumerate() {
+            let mut macrocells = 0;
+            for mc_i in 0..MCS_PER_FB {
+                let mc = &fb.mcs[mc_i];
+                let mut used = mc.fb_mode != XC2MCFeedbackMode::Disabled;
+                if let Some((zia_mode, _, obuf_mode)) = mc_iob(self, fb_i as u32, mc_i as u32) {
+                    let iob = fb_mc_num_to_iob_num(device, fb_i as u32, mc_i as u32).unwrap() as usize;
+                    if obuf_mode != XC2IOBOBufMode::Disabled {
+                        iob_is_output[iob] = true;
+                        used = true;
+                    }
+                    if zia_mode == XC2IOBZIAMode::REG {
+                        used = true;
+                    }
+                    if mc.ff_in_ibuf && used {
+                        iob_is_input[iob] = true;
+                    }
+                }
+                if used {
+                    macrocells += 1;
+                }
+            }
+
+            let product_terms = (0..ANDTERMS_PER_FB).filter(|&i| {
+                let term = fb.get_andterm(i);
+                (0..INPUTS_PER_ANDTERM).any(|j| term.get(j) || term.get_b(j)) ||
+                    fb.or_terms.iter().any(|x| x.get(i))
+            }).count();
+
+            let zia_rows = (0..INPUTS_PER_ANDTERM).filter(|&j| {
+                (0..ANDTERMS_PER_FB).any(|i| fb.get_andterm(i).get(j) || fb.get_andterm(i).get_b(j))
+            }).count();
+
+            fbs.push(XC2FBUtilization {
+                macrocells,
+                product_terms,
+                zia_rows,
+            });
+        }
+
+        let mut pins = device.num_iobs();
+        let mut input_pins = 0;
+        let mut output_pins = 0;
+        let mut bidir_pins = 0;
+        for i in 0..device.num_iobs() {
+            match (iob_is_input[i], iob_is_output[i]) {
+                (true, false) => input_pins += 1,
+                (false, true) => output_pins += 1,
+                (true, true) => bidir_pins += 1,
+                (false, false) => {},
+            }
+        }
+        if device == XC2Device::XC2C32 || device == XC2Device::XC2C32A {
+            pins += 1;
+            if input_only_used {
+                input_pins += 1;
+            }
+        }
+
+        XC2Utilization {
+            device,
+            fbs,
+            pins,
+            input_pins,
+            output_pins,
+            bidir_pins,
+            gck_enable: global_nets.gck_enable,
+            gsr_enable: global_nets.gsr_enable,
+            gts_enable: global_nets.gts_enable,
+            clock_div: self.get_clock_div().cloned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utilization() {
+        let mut bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c32a-4-vq44").unwrap());
+        {
+            let fb = &mut bitstream.bits.get_fb_mut()[0];
+            *fb.get_mut_zia(0) = XC2ZIAInput::IBuf{ibuf: 5};
+            *fb.get_mut_zia(1) = XC2ZIAInput::DedicatedInput;
+            fb.get_mut_andterm(20).set(0, true);
+            fb.get_mut_andterm(20).set_b(1, true);
+            fb.or_terms[2].set(20, true);
+            fb.or_terms[2].set(21, true);
+        }
+        bitstream.bits.get_mut_small_iob(2).unwrap().obuf_mode = XC2IOBOBufMode::PushPull;
+        bitstream.bits.get_mut_small_iob(5).unwrap().obuf_mode = XC2IOBOBufMode::TriStatePTB;
+        bitstream.bits.get_global_nets_mut().gck_enable[0] = true;
+
+        let utilization = bitstream.bits.utilization();
+        assert_eq!(utilization.macrocells(), 2);
+        assert_eq!(utilization.fbs[0], XC2FBUtilization {macrocells: 2, product_terms: 2, zia_rows: 2});
+        assert_eq!(utilization.pins, 33);
+        // INPUT_ONLY and GCK0
+        assert_eq!(utilization.input_pins, 2);
+        assert_eq!(utilization.output_pins, 1);
+        assert_eq!(utilization.bidir_pins, 1);
+        assert!(format!("{}", utilization).contains("global nets: GCK0\n"));
+    }
+}
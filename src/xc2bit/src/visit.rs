@@ -0,0 +1,401 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Typed iterators and a visitor trait over all of the resources in a bitstream
+//!
+//! These hide the differences between the device variants of `XC2BitstreamBits`, the split between small and large
+//! IOBs, and macrocells that do not have an IOB attached, so that analyses do not have to hand-write the same nested
+//! loops and `match` statements.
+
+use crate::*;
+
+/// Reference to an IOB, which can be either the small or the large variant depending on the device
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum XC2IOBRef<'a> {
+    Small(&'a XC2MCSmallIOB),
+    Large(&'a XC2MCLargeIOB),
+}
+
+impl<'a> XC2IOBRef<'a> {
+    /// Selects the source used to drive the ZIA
+    pub fn zia_mode(&self) -> XC2IOBZIAMode {
+        match self {
+            &XC2IOBRef::Small(iob) => iob.zia_mode,
+            &XC2IOBRef::Large(iob) => iob.zia_mode,
+        }
+    }
+
+    /// Whether the output buffer is driven by the register rather than the XOR gate
+    pub fn obuf_uses_ff(&self) -> bool {
+        match self {
+            &XC2IOBRef::Small(iob) => iob.obuf_uses_ff,
+            &XC2IOBRef::Large(iob) => iob.obuf_uses_ff,
+        }
+    }
+
+    /// Selects the output mode of the IOB
+    pub fn obuf_mode(&self) -> XC2IOBOBufMode {
+        match self {
+            &XC2IOBRef::Small(iob) => iob.obuf_mode,
+            &XC2IOBRef::Large(iob) => iob.obuf_mode,
+        }
+    }
+
+    /// Whether the "termination" (bus hold or pull-up, depending on global settings) is enabled
+    pub fn termination_enabled(&self) -> bool {
+        match self {
+            &XC2IOBRef::Small(iob) => iob.termination_enabled,
+            &XC2IOBRef::Large(iob) => iob.termination_enabled,
+        }
+    }
+
+    /// Whether the output slew rate is fast
+    pub fn slew_is_fast(&self) -> bool {
+        match self {
+            &XC2IOBRef::Small(iob) => iob.slew_is_fast,
+            &XC2IOBRef::Large(iob) => iob.slew_is_fast,
+        }
+    }
+
+    /// Whether the input buffer uses the Schmitt trigger. For large IOBs, this also includes the VREF modes.
+    pub fn schmitt_trigger(&self) -> bool {
+        match self {
+            &XC2IOBRef::Small(iob) => iob.schmitt_trigger,
+            &XC2IOBRef::Large(iob) => iob.ibuf_mode != XC2IOBIbufMode::NoVrefNoSt,
+        }
+    }
+
+    /// Input buffer mode, only present on large IOBs
+    pub fn ibuf_mode(&self) -> Option<XC2IOBIbufMode> {
+        match self {
+            &XC2IOBRef::Small(_) => None,
+            &XC2IOBRef::Large(iob) => Some(iob.ibuf_mode),
+        }
+    }
+
+    /// Whether the DataGate feature is used, only present on large IOBs
+    pub fn uses_data_gate(&self) -> Option<bool> {
+        match self {
+            &XC2IOBRef::Small(_) => None,
+            &XC2IOBRef::Large(iob) => Some(iob.uses_data_gate),
+        }
+    }
+}
+
+/// A function block together with its index
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct XC2FBRef<'a> {
+    pub fb: u32,
+    pub fb_bits: &'a XC2BitstreamFB,
+}
+
+/// The input selected for one row of the ZIA feeding a function block
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct XC2ZIASelection {
+    pub fb: u32,
+    pub row: u32,
+    pub input: XC2ZIAInput,
+}
+
+/// One AND term in the PLA of a function block
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct XC2AndTermRef<'a> {
+    pub fb: u32,
+    pub term: u32,
+    pub and_term: &'a XC2PLAAndTerm,
+}
+
+impl<'a> XC2AndTermRef<'a> {
+    /// Returns the ZIA rows used by this AND term, as `(row, complement)` pairs
+    pub fn inputs(&self) -> impl Iterator<Item = (u32, bool)> + 'a {
+        let and_term = self.and_term;
+        (0..INPUTS_PER_ANDTERM).flat_map(move |i| {
+            let true_lit = if and_term.get(i) { Some((i as u32, false)) } else { None };
+            let comp_lit = if and_term.get_b(i) { Some((i as u32, true)) } else { None };
+            true_lit.into_iter().chain(comp_lit)
+        })
+    }
+
+    /// Returns `true` if this AND term does not use any inputs (and therefore is constant 1)
+    pub fn is_empty(&self) -> bool {
+        self.inputs().next().is_none()
+    }
+}
+
+/// One OR term in the PLA of a function block. The OR term index is the same as the macrocell it drives.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct XC2OrTermRef<'a> {
+    pub fb: u32,
+    pub mc: u32,
+    pub or_term: &'a XC2PLAOrTerm,
+}
+
+impl<'a> XC2OrTermRef<'a> {
+    /// Returns the indices of the AND terms used by this OR term
+    pub fn and_terms(&self) -> impl Iterator<Item = u32> + 'a {
+        let or_term = self.or_term;
+        (0..ANDTERMS_PER_FB).filter(move |&i| or_term.get(i)).map(|i| i as u32)
+    }
+}
+
+/// A macrocell together with its location and the IOB attached to it. `iob` is `None` for buried macrocells.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct XC2MacrocellRef<'a> {
+    pub fb: u32,
+    pub mc: u32,
+    pub macrocell: &'a XC2Macrocell,
+    pub iob: Option<(u32, XC2IOBRef<'a>)>,
+}
+
+/// An IOB together with its index and the macrocell it is attached to
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct XC2IOBLoc<'a> {
+    pub iob: u32,
+    pub fb: u32,
+    pub mc: u32,
+    pub iob_bits: XC2IOBRef<'a>,
+}
+
+/// Callbacks for walking over all resources in a bitstream using `XC2BitstreamBits::visit`. All methods default to
+/// doing nothing, so only the resources of interest need to be handled.
+///
+/// Resources are visited in the following order: global nets, clock divider (if present), then for each function
+/// block the function block itself, its ZIA rows, AND terms, OR terms, and macrocells, then all IOBs, and finally the
+/// input-only pin (if present).
+pub trait XC2ResourceVisitor {
+    fn visit_global_nets(&mut self, _global_nets: &XC2GlobalNets) {}
+    fn visit_clock_div(&mut self, _clock_div: &XC2ClockDiv) {}
+    fn visit_fb(&mut self, _fb: XC2FBRef) {}
+    fn visit_zia(&mut self, _zia: XC2ZIASelection) {}
+    fn visit_and_term(&mut self, _and_term: XC2AndTermRef) {}
+    fn visit_or_term(&mut self, _or_term: XC2OrTermRef) {}
+    fn visit_macrocell(&mut self, _mc: XC2MacrocellRef) {}
+    fn visit_iob(&mut self, _iob: XC2IOBLoc) {}
+    fn visit_input_only(&mut self, _inpin: &XC2ExtraIBuf) {}
+}
+
+impl XC2BitstreamBits {
+    /// Returns the IOB with the given index regardless of whether this device uses small or large IOBs
+    pub fn get_iob(&self, i: u32) -> Option<XC2IOBRef<'_>> {
+        if i as usize >= self.device_type().num_iobs() {
+            return None;
+        }
+        if let Some(iob) = self.get_small_iob(i as usize) {
+            Some(XC2IOBRef::Small(iob))
+        } else {
+            self.get_large_iob(i as usize).map(XC2IOBRef::Large)
+        }
+    }
+
+    /// Iterates over all function blocks
+    pub fn fbs(&self) -> impl Iterator<Item = XC2FBRef<'_>> {
+        self.get_fb().iter().enumerate().map(|(fb, fb_bits)| XC2FBRef {
+            fb: fb as u32,
+            fb_bits,
+        })
+    }
+
+    /// Iterates over the ZIA row selections of all function blocks
+    pub fn zia_selections(&self) -> impl Iterator<Item = XC2ZIASelection> + '_ {
+        self.fbs().flat_map(|fb| (0..INPUTS_PER_ANDTERM).map(move |row| XC2ZIASelection {
+            fb: fb.fb,
+            row: row as u32,
+            input: *fb.fb_bits.get_zia(row),
+        }))
+    }
+
+    /// Iterates over the AND terms of all function blocks
+    pub fn and_terms(&self) -> impl Iterator<Item = XC2AndTermRef<'_>> {
+        self.fbs().flat_map(|fb| (0..ANDTERMS_PER_FB).map(move |term| XC2AndTermRef {
+            fb: fb.fb,
+            term: term as u32,
+            and_term: fb.fb_bits.get_andterm(term),
+        }))
+    }
+
+    /// Iterates over the OR terms of all function blocks
+    pub fn or_terms(&self) -> impl Iterator<Item = XC2OrTermRef<'_>> {
+        self.fbs().flat_map(|fb| fb.fb_bits.or_terms.iter().enumerate().map(move |(mc, or_term)| XC2OrTermRef {
+            fb: fb.fb,
+            mc: mc as u32,
+            or_term,
+        }))
+    }
+
+    /// Iterates over all macrocells, including buried macrocells that have no IOB
+    pub fn macrocells(&self) -> impl Iterator<Item = XC2MacrocellRef<'_>> {
+        self.fbs().flat_map(move |fb| self.fb_macrocells(fb))
+    }
+
+    /// Iterates over the macrocells of one function block, including buried macrocells that have no IOB
+    fn fb_macrocells<'a>(&'a self, fb: XC2FBRef<'a>) -> impl Iterator<Item = XC2MacrocellRef<'a>> {
+        let device = self.device_type();
+        fb.fb_bits.mcs.iter().enumerate().map(move |(mc, macrocell)| {
+            let mc = mc as u32;
+            let iob = fb_mc_num_to_iob_num(device, fb.fb, mc)
+                .and_then(|iob| self.get_iob(iob).map(|iob_bits| (iob, iob_bits)));
+            XC2MacrocellRef {
+                fb: fb.fb,
+                mc,
+                macrocell,
+                iob,
+            }
+        })
+    }
+
+    /// Iterates over all IOBs. This does not include the input-only pin on the XC2C32(A).
+    pub fn iobs(&self) -> impl Iterator<Item = XC2IOBLoc<'_>> {
+        let device = self.device_type();
+        (0..device.num_iobs() as u32).map(move |iob| {
+            let (fb, mc) = iob_num_to_fb_mc_num(device, iob).unwrap();
+            XC2IOBLoc {
+                iob,
+                fb,
+                mc,
+                iob_bits: self.get_iob(iob).unwrap(),
+            }
+        })
+    }
+
+    /// Walks over every resource in the bitstream, calling the corresponding method of `visitor` for each
+    pub fn visit<V: XC2ResourceVisitor>(&self, visitor: &mut V) {
+        visitor.visit_global_nets(self.get_global_nets());
+        if let Some(clock_div) = self.get_clock_div() {
+            visitor.visit_clock_div(clock_div);
+        }
+
+        for fb in self.fbs() {
+            visitor.visit_fb(fb);
+            for row in 0..INPUTS_PER_ANDTERM {
+                visitor.visit_zia(XC2ZIASelection {
+                    fb: fb.fb,
+                    row: row as u32,
+                    input: *fb.fb_bits.get_zia(row),
+                });
+            }
+            for term in 0..ANDTERMS_PER_FB {
+                visitor.visit_and_term(XC2AndTermRef {
+                    fb: fb.fb,
+                    term: term as u32,
+                    and_term: fb.fb_bits.get_andterm(term),
+                });
+            }
+            for (mc, or_term) in fb.fb_bits.or_terms.iter().enumerate() {
+                visitor.visit_or_term(XC2OrTermRef {
+                    fb: fb.fb,
+                    mc: mc as u32,
+                    or_term,
+                });
+            }
+            for mc in self.fb_macrocells(fb) {
+                visitor.visit_macrocell(mc);
+            }
+        }
+
+        for iob in self.iobs() {
+            visitor.visit_iob(iob);
+        }
+        if let Some(inpin) = self.get_inpin() {
+            visitor.visit_input_only(inpin);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Counter {
+        last_fb: Option<u32>,
+        fbs: usize,
+        zia: usize,
+        and_terms: usize,
+        or_terms: usize,
+        buried: usize,
+        iobs: usize,
+        inpin: usize,
+    }
+
+    impl XC2ResourceVisitor for Counter {
+        fn visit_fb(&mut self, fb: XC2FBRef) {
+            self.last_fb = Some(fb.fb);
+            self.fbs += 1;
+        }
+        fn visit_zia(&mut self, _zia: XC2ZIASelection) { self.zia += 1; }
+        fn visit_and_term(&mut self, _and_term: XC2AndTermRef) { self.and_terms += 1; }
+        fn visit_or_term(&mut self, _or_term: XC2OrTermRef) { self.or_terms += 1; }
+        fn visit_macrocell(&mut self, mc: XC2MacrocellRef) {
+            // Macrocells are visited together with the function block that contains them
+            assert_eq!(self.last_fb, Some(mc.fb));
+            if mc.iob.is_none() {
+                self.buried += 1;
+            }
+        }
+        fn visit_iob(&mut self, _iob: XC2IOBLoc) { self.iobs += 1; }
+        fn visit_input_only(&mut self, _inpin: &XC2ExtraIBuf) { self.inpin += 1; }
+    }
+
+    #[test]
+    fn visit_counts() {
+        for &(part, fbs, iobs, inpin) in &[("xc2c32a-4-vq44", 2, 32, 1), ("xc2c128-6-vq100", 8, 100, 0)] {
+            let bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str(part).unwrap());
+            let mut counter = Counter::default();
+            bitstream.bits.visit(&mut counter);
+
+            assert_eq!(counter.fbs, fbs);
+            assert_eq!(counter.zia, fbs * INPUTS_PER_ANDTERM);
+            assert_eq!(counter.and_terms, fbs * ANDTERMS_PER_FB);
+            assert_eq!(counter.or_terms, fbs * MCS_PER_FB);
+            assert_eq!(counter.buried, fbs * MCS_PER_FB - iobs);
+            assert_eq!(counter.iobs, iobs);
+            assert_eq!(counter.inpin, inpin);
+
+            for iob in bitstream.bits.iobs() {
+                let mc = bitstream.bits.macrocells().find(|mc| mc.fb == iob.fb && mc.mc == iob.mc).unwrap();
+                assert_eq!(mc.iob, Some((iob.iob, iob.iob_bits)));
+            }
+        }
+    }
+
+    #[test]
+    fn and_term_inputs() {
+        let mut bitstream = XC2Bitstream::blank_bitstream(XC2DeviceSpeedPackage::from_str("xc2c32a-4-vq44").unwrap());
+        {
+            let and_term = bitstream.bits.get_fb_mut()[1].get_mut_andterm(7);
+            and_term.set(3, true);
+            and_term.set_b(20, true);
+        }
+        bitstream.bits.get_fb_mut()[1].or_terms[2].set(7, true);
+
+        let used = bitstream.bits.and_terms().filter(|t| !t.is_empty()).collect::<Vec<_>>();
+        assert_eq!(used.len(), 1);
+        assert_eq!((used[0].fb, used[0].term), (1, 7));
+        assert_eq!(used[0].inputs().collect::<Vec<_>>(), vec![(3, false), (20, true)]);
+
+        let or_term = bitstream.bits.or_terms().find(|t| t.fb == 1 && t.mc == 2).unwrap();
+        assert_eq!(or_term.and_terms().collect::<Vec<_>>(), vec![7]);
+    }
+}
@@ -25,6 +25,8 @@ OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 //! Contains functions pertaining to the ZIA
 
+use alloc::vec::Vec;
+
 use crate::*;
 
 /// Represents one output of the ZIA. The ZIA is divided into rows, and each row can independently select a choice
@@ -9832,6 +9834,19 @@ impl XC2ZIAInput {
     }
 }
 
+/// Returns the bits (as written to the .jed file) that select the given choice in a row of the ZIA, or `None` if the
+/// row cannot select it
+pub fn zia_encode_choice(device: XC2Device, row: u32, choice: XC2ZIAInput) -> Option<Vec<bool>> {
+    match device {
+        XC2Device::XC2C32 | XC2Device::XC2C32A => XC2ZIAInput::encode_32_zia_choice(row, choice).map(|x| x.to_vec()),
+        XC2Device::XC2C64 | XC2Device::XC2C64A => XC2ZIAInput::encode_64_zia_choice(row, choice).map(|x| x.to_vec()),
+        XC2Device::XC2C128 => XC2ZIAInput::encode_128_zia_choice(row, choice).map(|x| x.to_vec()),
+        XC2Device::XC2C256 => XC2ZIAInput::encode_256_zia_choice(row, choice).map(|x| x.to_vec()),
+        XC2Device::XC2C384 => XC2ZIAInput::encode_384_zia_choice(row, choice).map(|x| x.to_vec()),
+        XC2Device::XC2C512 => XC2ZIAInput::encode_512_zia_choice(row, choice).map(|x| x.to_vec()),
+    }
+}
+
 /// Returns the width in bits of one row of the ZIA
 pub fn zia_get_row_width(device: XC2Device) -> usize {
     match device {
@@ -9843,3 +9858,163 @@ pub fn zia_get_row_width(device: XC2Device) -> usize {
         XC2Device::XC2C512 => 88,
     }
 }
+
+// Generated by build.rs from ZIA_MAP_32 and ZIA_MAP_64
+include!(concat!(env!("OUT_DIR"), "/zia_inverse.rs"));
+
+static ZIA_ALL_ROWS: [u32; INPUTS_PER_ANDTERM] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
+    20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39];
+
+/// Returns the inverse of the ZIA map, that is the rows (in increasing order) that can select each input. Only inputs
+/// that appear in the ZIA map are included, so the constants `Zero` and `One` are not.
+///
+/// The ZIA maps of the XC2C128 and larger parts are not known yet (`ZIA_MAP_128` and up are placeholders), so this
+/// returns `None` for them.
+pub fn zia_inverse_table(device: XC2Device) -> Option<&'static [(XC2ZIAInput, &'static [u32])]> {
+    match device {
+        XC2Device::XC2C32 | XC2Device::XC2C32A => Some(&ZIA_INVERSE_32),
+        XC2Device::XC2C64 | XC2Device::XC2C64A => Some(&ZIA_INVERSE_64),
+        _ => None,
+    }
+}
+
+/// Returns the rows of the ZIA that can select the given input, in increasing order. The constants `Zero` and `One`
+/// can be output by every row. Returns `None` if the ZIA map of the device is not known (see `zia_inverse_table`).
+pub fn zia_rows_for_input(device: XC2Device, input: XC2ZIAInput) -> Option<&'static [u32]> {
+    let table = zia_inverse_table(device)?;
+    match input {
+        XC2ZIAInput::Zero | XC2ZIAInput::One => Some(&ZIA_ALL_ROWS),
+        _ => Some(table.iter().find(|x| x.0 == input).map(|x| x.1).unwrap_or(&[])),
+    }
+}
+
+/// Tries to find a row of the ZIA for every one of the given inputs so that all of them can enter one function block at
+/// the same time. An input that is listed more than once only uses one row.
+///
+/// On success, returns the row for each entry of `inputs`. Otherwise, returns a subset of the inputs that cannot all be
+/// routed because together they can only be selected by fewer rows than there are inputs in the subset. Removing any
+/// one of them makes the rest of the subset routable.
+///
+/// Returns `None` if the ZIA map of the device is not known (see `zia_inverse_table`).
+pub fn zia_assign_rows(device: XC2Device, inputs: &[XC2ZIAInput])
+    -> Option<Result<Vec<u32>, Vec<XC2ZIAInput>>> {
+    // Bipartite matching between the distinct inputs and the rows, using augmenting paths
+    fn try_augment(u: usize, candidates: &[&[u32]], row_owner: &mut [Option<usize>], visited: &mut [bool]) -> bool {
+        for &row in candidates[u] {
+            let row = row as usize;
+            if visited[row] {
+                continue;
+            }
+            visited[row] = true;
+            if row_owner[row].is_none() || try_augment(row_owner[row].unwrap(), candidates, row_owner, visited) {
+                row_owner[row] = Some(u);
+                return true;
+            }
+        }
+        false
+    }
+
+    zia_inverse_table(device)?;
+
+    let mut distinct = Vec::new();
+    for &input in inputs {
+        if !distinct.contains(&input) {
+            distinct.push(input);
+        }
+    }
+    let candidates = distinct.iter().map(|&input| zia_rows_for_input(device, input))
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut row_owner = [None; INPUTS_PER_ANDTERM];
+    for u in 0..distinct.len() {
+        let mut visited = [false; INPUTS_PER_ANDTERM];
+        if !try_augment(u, &candidates, &mut row_owner, &mut visited) {
+            // Every row reachable from the failed input is taken by another input that could only be moved onto
+            // another one of these rows. Those inputs together with the failed one have fewer rows than inputs.
+            let mut blocking = alloc::vec![distinct[u]];
+            for row in 0..INPUTS_PER_ANDTERM {
+                if visited[row] {
+                    blocking.push(distinct[row_owner[row].unwrap()]);
+                }
+            }
+            return Some(Err(blocking));
+        }
+    }
+
+    let mut input_row = alloc::vec![0; distinct.len()];
+    for (row, owner) in row_owner.iter().enumerate() {
+        if let &Some(u) = owner {
+            input_row[u] = row as u32;
+        }
+    }
+    Some(Ok(inputs.iter().map(|input| input_row[distinct.iter().position(|x| x == input).unwrap()]).collect()))
+}
+
+// These tests need `zia_inverse_table`
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zia_inverse_consistent() {
+        for &device in &[XC2Device::XC2C32A, XC2Device::XC2C64A] {
+            let inverse = zia_inverse_table(device).unwrap();
+            for &(input, rows) in inverse {
+                let expected = (0..INPUTS_PER_ANDTERM as u32)
+                    .filter(|&row| zia_table_get_row(device, row as usize).contains(&input)).collect::<Vec<_>>();
+                assert_eq!(rows, &expected[..]);
+                for &row in rows {
+                    assert!(zia_encode_choice(device, row, input).is_some());
+                }
+            }
+
+            // Every input of the ZIA map is in the table, and every macrocell can get into the ZIA
+            for row in 0..INPUTS_PER_ANDTERM {
+                for input in zia_table_get_row(device, row) {
+                    assert!(inverse.iter().any(|x| x.0 == *input));
+                }
+            }
+            for fb in 0..device.num_fbs() {
+                for mc in 0..MCS_PER_FB {
+                    assert!(zia_rows_for_input(device, XC2ZIAInput::Macrocell{fb: fb as u8, mc: mc as u8})
+                        .unwrap().len() > 0);
+                }
+            }
+        }
+
+        assert_eq!(zia_inverse_table(XC2Device::XC2C128), None);
+        assert_eq!(zia_rows_for_input(XC2Device::XC2C256, XC2ZIAInput::One), None);
+    }
+
+    #[test]
+    fn zia_assign() {
+        let device = XC2Device::XC2C32A;
+
+        // All inputs that row 0 can select, plus a duplicate
+        let mut inputs = ZIA_MAP_32[0].to_vec();
+        inputs.push(inputs[0]);
+        let rows = zia_assign_rows(device, &inputs).unwrap().unwrap();
+        assert_eq!(rows[0], rows[inputs.len() - 1]);
+        for (i, &input) in inputs.iter().enumerate() {
+            assert!(zia_rows_for_input(device, input).unwrap().contains(&rows[i]));
+            for j in 0..i {
+                assert!(inputs[j] == input || rows[j] != rows[i]);
+            }
+        }
+
+        // Every input of the device can't enter at once, and whatever is reported as blocking really is
+        let all = zia_inverse_table(device).unwrap().iter().map(|x| x.0).collect::<Vec<_>>();
+        let blocking = zia_assign_rows(device, &all).unwrap().unwrap_err();
+        let mut blocking_rows = blocking.iter().flat_map(|&input| zia_rows_for_input(device, input).unwrap())
+            .collect::<Vec<_>>();
+        blocking_rows.sort();
+        blocking_rows.dedup();
+        assert!(blocking_rows.len() < blocking.len());
+        assert!(zia_assign_rows(device, &blocking[1..]).unwrap().is_ok());
+
+        // Rows can't be assigned without a ZIA map, even when nothing needs a row
+        assert_eq!(zia_assign_rows(XC2Device::XC2C128, &[XC2ZIAInput::One]), None);
+        assert_eq!(zia_assign_rows(XC2Device::XC2C512, &[]), None);
+    }
+}
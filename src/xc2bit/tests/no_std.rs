@@ -23,29 +23,19 @@ OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 */
 
-//! Testing tool that converts a .jed to the internal .crbit format
-
-use std::fs::File;
-use std::io::Read;
-
-use xc2bit::*;
-
-fn main() {
-    let args = ::std::env::args().collect::<Vec<_>>();
-
-    if args.len() != 2 {
-        println!("Usage: {} file.crbit", args[0]);
-        ::std::process::exit(1);
-    }
-
-    // Read the entire file
-    let mut f = File::open(&args[1]).expect("failed to open file");
-    let mut data = Vec::new();
-    f.read_to_end(&mut data).expect("failed to read data");
-
-    let fuse_array = FuseArray::from_file_contents(&data).expect("failed to read crbit");
-
-    let bitstream = XC2Bitstream::from_crbit(&fuse_array).expect("failed to process crbit");
-
-    bitstream.to_jed(&mut ::std::io::stdout()).expect("failed to write jed");
+//! Builds the crate in tests/no_std, which uses xc2bit without std
+
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn builds_without_std() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/no_std");
+    // The dependencies are a subset of the ones of this crate, so they have already been fetched
+    let status = Command::new(env!("CARGO"))
+        .args(["build", "--offline"])
+        .arg("--manifest-path").arg(dir.join("Cargo.toml"))
+        .env("CARGO_TARGET_DIR", Path::new(env!("CARGO_TARGET_TMPDIR")).join("no_std"))
+        .status().expect("failed to run cargo");
+    assert!(status.success(), "xc2bit does not build without std");
 }
@@ -23,32 +23,34 @@ OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
 OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 */
 
-//! Testing tool that converts a .jed to the internal .crbit format
+//! Uses the parts of xc2bit that are available without std. This crate is `no_std` and provides its own panic
+//! handler, so building it fails with a duplicate `panic_impl` lang item if xc2bit or one of its dependencies pulls in
+//! std.
 
-use std::fs::File;
-use std::io::Read;
+#![no_std]
 
-use jedec::*;
-use xc2bit::*;
+extern crate alloc;
 
-fn main() {
-    let args = ::std::env::args().collect::<Vec<_>>();
+use alloc::vec::Vec;
 
-    if args.len() != 2 {
-        println!("Usage: {} file.jed", args[0]);
-        ::std::process::exit(1);
-    }
+use jedec::JEDECFile;
+use xc2bit::{FuseArray, XC2Bitstream};
 
-    // Read the entire file
-    let mut f = File::open(&args[1]).expect("failed to open file");
-    let mut data = Vec::new();
-    f.read_to_end(&mut data).expect("failed to read data");
+/// Converts the contents of a .jed file into the contents of a .crbit file and back
+pub fn jed_to_crbit_to_jed(jed: &[u8]) -> Option<Vec<u8>> {
+    let jed = JEDECFile::from_bytes(jed).ok()?;
+    let bitstream = XC2Bitstream::from_jed(&jed).ok()?;
 
-    let jed = JEDECFile::from_bytes(&data).expect("failed to read jed");
-    let bitstream_result = XC2Bitstream::from_jed(&jed);
-    let bitstream = bitstream_result.expect("failed to process jed");
+    let mut crbit = Vec::new();
+    bitstream.to_crbit().write_to_writer(&mut crbit).ok()?;
 
-    let fuse_array = bitstream.to_crbit();
+    let bitstream = XC2Bitstream::from_crbit(&FuseArray::from_file_contents(&crbit).ok()?).ok()?;
+    let mut jed = Vec::new();
+    bitstream.to_jed(&mut jed).ok()?;
+    Some(jed)
+}
 
-    fuse_array.write_to_writer(&mut ::std::io::stdout()).expect("failed to print crbit");
+#[panic_handler]
+fn panic(_info: &core::panic::PanicInfo) -> ! {
+    loop {}
 }
@@ -0,0 +1,95 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+#ifndef XC2PAR_H
+#define XC2PAR_H
+
+/* Generated from src/lib.rs by cbindgen. Do not edit. */
+
+#include <stddef.h>
+#include <stdint.h>
+#include "xc2bit.h"
+
+// Success
+#define XC2PAR_OK 0
+
+// A pointer was NULL, or an argument was out of range
+#define XC2PAR_ERROR_INVALID_ARGUMENT 1
+
+// The netlist could not be read
+#define XC2PAR_ERROR_NETLIST 2
+
+// The design could not be placed and routed
+#define XC2PAR_ERROR_PAR_FAILED 3
+
+// A bug in the library
+#define XC2PAR_ERROR_INTERNAL 4
+
+// Output format: .jed
+#define XC2PAR_OUTPUT_JED 0
+
+// Output format: .crbit
+#define XC2PAR_OUTPUT_CRBIT 1
+
+// Options for `xc2par_complete_flow`, which can be set to the defaults with `xc2par_options_init`
+typedef struct xc2par_options {
+    // Maximum number of iterations of the placer
+    uint32_t max_iter;
+    // Seed of the random number generator
+    uint32_t rng_seed[4];
+    // One of the `XC2PAR_OUTPUT_*` formats
+    uint32_t output_format;
+    // 0 = warnings, 1 = info, 2 = debug
+    uint32_t log_level;
+} xc2par_options;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Fills in the default options
+void xc2par_options_init(struct xc2par_options *options);
+
+// Places and routes a Yosys JSON netlist for the given part, such as "XC2C32A-4-VQ44". The bitstream is written into
+// a newly allocated buffer, which must be freed with `xc2bit_buffer_free`. Passing NULL for the options uses the
+// defaults. If `out_log` is not NULL, the log messages are returned in another buffer, even if the flow fails.
+int32_t xc2par_complete_flow(const char *part,
+                             const uint8_t *netlist,
+                             size_t netlist_len,
+                             const struct xc2par_options *options,
+                             uint8_t **out_data,
+                             size_t *out_len,
+                             uint8_t **out_log,
+                             size_t *out_log_len);
+
+// Returns a description of the most recent error on the calling thread. The string stays valid until the next call
+// into this library on the same thread.
+const char *xc2par_last_error_message(void);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* XC2PAR_H */
@@ -0,0 +1,149 @@
+# Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+# All rights reserved.
+#
+# Redistribution and use in source and binary forms, with or without
+# modification, are permitted provided that the following conditions are met:
+#
+# 1. Redistributions of source code must retain the above copyright notice,
+#    this list of conditions and the following disclaimer.
+# 2. Redistributions in binary form must reproduce the above copyright notice,
+#    this list of conditions and the following disclaimer in the documentation
+#    and/or other materials provided with the distribution.
+#
+# THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+# ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+# WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+# DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+# FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+# DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+# SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+# CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+# OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+# OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+"""Python bindings for the xc2par fitter, built on its C interface (include/xc2par.h) using ctypes.
+
+Install the package with `pip install src/xc2par-capi`, which builds the shared library and puts it into the package.
+The shared library is found using the XC2PAR_LIBRARY environment variable, then in the package, then in the cargo
+target directory of src/xc2par-capi, and then on the system library path. The result of `fit` can be loaded with the
+xc2bit module.
+
+    >>> import xc2par
+    >>> jed, log = xc2par.fit(open("design.json").read(), "XC2C32A-6-VQ44", log_level=1)
+"""
+
+import ctypes
+import ctypes.util
+import os
+import sys
+
+XC2PAR_OK = 0
+XC2PAR_ERROR_INVALID_ARGUMENT = 1
+XC2PAR_ERROR_NETLIST = 2
+XC2PAR_ERROR_PAR_FAILED = 3
+XC2PAR_ERROR_INTERNAL = 4
+
+OUTPUT_FORMATS = ["jed", "crbit"]
+
+
+def _load_library():
+    path = os.environ.get("XC2PAR_LIBRARY")
+    if path:
+        return ctypes.CDLL(path)
+    if sys.platform == "darwin":
+        name = "libxc2par_capi.dylib"
+    elif sys.platform == "win32":
+        name = "xc2par_capi.dll"
+    else:
+        name = "libxc2par_capi.so"
+    here = os.path.dirname(os.path.abspath(__file__))
+    path = os.path.join(here, name)
+    if os.path.exists(path):
+        return ctypes.CDLL(path)
+    for target_dir in [os.path.join(here, "..", "..", "target"), os.path.join(here, "..", "..", "..", "..", "target")]:
+        for profile in ["release", "debug"]:
+            path = os.path.join(target_dir, profile, name)
+            if os.path.exists(path):
+                return ctypes.CDLL(path)
+    path = ctypes.util.find_library("xc2par_capi")
+    if path:
+        return ctypes.CDLL(path)
+    raise OSError("could not find the xc2par library; set XC2PAR_LIBRARY to its path")
+
+
+class _Options(ctypes.Structure):
+    # struct xc2par_options
+    _fields_ = [
+        ("max_iter", ctypes.c_uint32),
+        ("rng_seed", ctypes.c_uint32 * 4),
+        ("output_format", ctypes.c_uint32),
+        ("log_level", ctypes.c_uint32),
+    ]
+
+
+_lib = _load_library()
+_buffer = ctypes.POINTER(ctypes.c_uint8)
+
+_lib.xc2par_options_init.argtypes = [ctypes.POINTER(_Options)]
+_lib.xc2par_options_init.restype = None
+_lib.xc2par_complete_flow.argtypes = [ctypes.c_char_p, ctypes.c_char_p, ctypes.c_size_t, ctypes.POINTER(_Options),
+    ctypes.POINTER(_buffer), ctypes.POINTER(ctypes.c_size_t), ctypes.POINTER(_buffer), ctypes.POINTER(ctypes.c_size_t)]
+_lib.xc2par_complete_flow.restype = ctypes.c_int
+_lib.xc2par_last_error_message.argtypes = []
+_lib.xc2par_last_error_message.restype = ctypes.c_char_p
+_lib.xc2bit_buffer_free.argtypes = [_buffer, ctypes.c_size_t]
+_lib.xc2bit_buffer_free.restype = None
+
+
+class XC2ParError(Exception):
+    """An error reported by xc2par. `code` is one of the XC2PAR_ERROR_* values, and `log` holds the log messages of
+    the failed run."""
+
+    def __init__(self, code, message, log):
+        super(XC2ParError, self).__init__(message)
+        self.code = code
+        self.log = log
+
+
+def _take_buffer(data, length):
+    if not data:
+        return b""
+    try:
+        return ctypes.string_at(data, length.value)
+    finally:
+        _lib.xc2bit_buffer_free(data, length)
+
+
+def fit(netlist, part, max_iter=None, rng_seed=None, output_format="jed", log_level=0):
+    """Places and routes a Yosys JSON netlist (a str or bytes) for a part such as "XC2C32A-4-VQ44", using the same
+    settings as `XC2ParOptions`. The log messages are captured at the given level, which is 0 for warnings, 1 for info
+    and 2 for debug messages.
+
+    Returns the contents of the output file as bytes and the log messages as a str."""
+
+    if isinstance(netlist, str):
+        netlist = netlist.encode("utf-8")
+    if output_format not in OUTPUT_FORMATS:
+        raise ValueError("output_format must be one of {}".format(", ".join(OUTPUT_FORMATS)))
+
+    options = _Options()
+    _lib.xc2par_options_init(ctypes.byref(options))
+    if max_iter is not None:
+        options.max_iter = max_iter
+    if rng_seed is not None:
+        if len(rng_seed) != 4:
+            raise ValueError("rng_seed must have 4 elements")
+        options.rng_seed[:] = rng_seed
+    options.output_format = OUTPUT_FORMATS.index(output_format)
+    options.log_level = log_level
+
+    data = _buffer()
+    data_len = ctypes.c_size_t()
+    log = _buffer()
+    log_len = ctypes.c_size_t()
+    code = _lib.xc2par_complete_flow(part.encode("ascii"), netlist, len(netlist), ctypes.byref(options),
+        ctypes.byref(data), ctypes.byref(data_len), ctypes.byref(log), ctypes.byref(log_len))
+    log = _take_buffer(log, log_len).decode("utf-8", "replace")
+    if code != XC2PAR_OK:
+        raise XC2ParError(code, _lib.xc2par_last_error_message().decode("utf-8", "replace"), log)
+    return _take_buffer(data, data_len), log
@@ -0,0 +1,62 @@
+# Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+# All rights reserved.
+#
+# Redistribution and use in source and binary forms, with or without
+# modification, are permitted provided that the following conditions are met:
+#
+# 1. Redistributions of source code must retain the above copyright notice,
+#    this list of conditions and the following disclaimer.
+# 2. Redistributions in binary form must reproduce the above copyright notice,
+#    this list of conditions and the following disclaimer in the documentation
+#    and/or other materials provided with the distribution.
+#
+# THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+# ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+# WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+# DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+# FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+# DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+# SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+# CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+# OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+# OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+"""Builds the xc2par Python package. The shared library of this crate is built with cargo in release mode and put into
+the package next to __init__.py, where the package looks for it first. cargo must be installed, and CARGO_TARGET_DIR is
+honored if it is set."""
+
+import os
+import shutil
+import subprocess
+import sys
+
+from setuptools import setup
+from setuptools.command.build_py import build_py
+from setuptools.dist import Distribution
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+
+if sys.platform == "darwin":
+    LIBRARY = "libxc2par_capi.dylib"
+elif sys.platform == "win32":
+    LIBRARY = "xc2par_capi.dll"
+else:
+    LIBRARY = "libxc2par_capi.so"
+
+
+class BuildPyWithLibrary(build_py):
+    def run(self):
+        super().run()
+        subprocess.check_call(["cargo", "build", "--release", "--lib"], cwd=HERE)
+        target_dir = os.environ.get("CARGO_TARGET_DIR", os.path.join(HERE, "target"))
+        self.mkpath(os.path.join(self.build_lib, "xc2par"))
+        shutil.copy(os.path.join(target_dir, "release", LIBRARY), os.path.join(self.build_lib, "xc2par", LIBRARY))
+
+
+class BinaryDistribution(Distribution):
+    # The package contains a shared library, so wheels are specific to the platform
+    def has_ext_modules(self):
+        return True
+
+
+setup(cmdclass={"build_py": BuildPyWithLibrary}, distclass=BinaryDistribution)
@@ -0,0 +1,217 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! # xc2par-capi: C interface to xc2par
+//!
+//! These functions are declared in `include/xc2par.h`, which is generated from this file by cbindgen. The libraries
+//! built from this crate also contain the C interface of xc2bit (see `xc2bit.h`), so programs linking against them
+//! should not also link against the xc2bit-capi libraries. Every function that can fail returns one of the `XC2PAR_*`
+//! error codes, and a description of the most recent error on the calling thread can be retrieved with
+//! `xc2par_last_error_message`.
+//!
+//! Pointers passed to these functions must either be NULL or point to valid memory of the given length.
+
+// The safety requirements are the same for every function and are listed above
+#![allow(clippy::missing_safety_doc)]
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::io;
+use std::io::Write;
+use std::os::raw::c_char;
+use std::panic;
+use std::slice;
+use std::sync::{Arc, Mutex};
+
+use slog::{o, Drain};
+
+use xc2bit::*;
+// Only referenced so that the functions of the C interface of xc2bit are linked into this library
+extern crate xc2bit_capi;
+
+use xc2par::*;
+
+/// Success
+pub const XC2PAR_OK: i32 = 0;
+/// A pointer was NULL, or an argument was out of range
+pub const XC2PAR_ERROR_INVALID_ARGUMENT: i32 = 1;
+/// The netlist could not be read
+pub const XC2PAR_ERROR_NETLIST: i32 = 2;
+/// The design could not be placed and routed
+pub const XC2PAR_ERROR_PAR_FAILED: i32 = 3;
+/// A bug in the library
+pub const XC2PAR_ERROR_INTERNAL: i32 = 4;
+
+/// Output format: .jed
+pub const XC2PAR_OUTPUT_JED: u32 = 0;
+/// Output format: .crbit
+pub const XC2PAR_OUTPUT_CRBIT: u32 = 1;
+
+/// Options for `xc2par_complete_flow`, which can be set to the defaults with `xc2par_options_init`
+#[repr(C)]
+pub struct XC2ParCOptions {
+    /// Maximum number of iterations of the placer
+    max_iter: u32,
+    /// Seed of the random number generator
+    rng_seed: [u32; 4],
+    /// One of the `XC2PAR_OUTPUT_*` formats
+    output_format: u32,
+    /// 0 = warnings, 1 = info, 2 = debug
+    log_level: u32,
+}
+
+/// Collects the log messages of a run, in the same format that the xc2par binary prints them
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+unsafe fn output_buffer(data: Vec<u8>, out_data: *mut *mut u8, out_len: *mut usize) {
+    let data = data.into_boxed_slice();
+    *out_len = data.len();
+    *out_data = Box::into_raw(data) as *mut u8;
+}
+
+/// An error code together with its description
+struct CError(i32, String);
+
+fn invalid(msg: &str) -> CError {
+    CError(XC2PAR_ERROR_INVALID_ARGUMENT, msg.to_owned())
+}
+
+impl From<PARFlowError> for CError {
+    fn from(err: PARFlowError) -> Self {
+        let code = match err {
+            PARFlowError::SerdeError(_) | PARFlowError::FrontendError(_) | PARFlowError::IntermedToInputError(_) =>
+                XC2PAR_ERROR_NETLIST,
+            PARFlowError::PARIterationsExceeded | PARFlowError::PARSanityCheckFailed(_) => XC2PAR_ERROR_PAR_FAILED,
+            PARFlowError::OutputWriteError(_) => XC2PAR_ERROR_INTERNAL,
+        };
+        CError(code, format!("{}", err))
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+/// Runs the body of an exported function, turning errors and panics into error codes
+fn guard<F>(f: F) -> i32 where F: FnOnce() -> Result<(), CError> {
+    let CError(code, msg) = match panic::catch_unwind(panic::AssertUnwindSafe(f)) {
+        Ok(Ok(())) => return XC2PAR_OK,
+        Ok(Err(err)) => err,
+        Err(_) => CError(XC2PAR_ERROR_INTERNAL, String::from("internal error")),
+    };
+    let msg = CString::new(msg.replace('\0', " ")).unwrap();
+    LAST_ERROR.with(|x| *x.borrow_mut() = msg);
+    code
+}
+
+/// Fills in the default options
+#[no_mangle]
+pub unsafe extern "C" fn xc2par_options_init(options: *mut XC2ParCOptions) {
+    if let Some(options) = options.as_mut() {
+        let defaults = XC2ParOptions::new();
+        options.max_iter = defaults.max_iter;
+        options.rng_seed = defaults.rng_seed;
+        options.output_format = XC2PAR_OUTPUT_JED;
+        options.log_level = 0;
+    }
+}
+
+/// Places and routes a Yosys JSON netlist for the given part, such as "XC2C32A-4-VQ44". The bitstream is written into
+/// a newly allocated buffer, which must be freed with `xc2bit_buffer_free`. Passing NULL for the options uses the
+/// defaults. If `out_log` is not NULL, the log messages are returned in another buffer, even if the flow fails.
+#[no_mangle]
+pub unsafe extern "C" fn xc2par_complete_flow(part: *const c_char, netlist: *const u8, netlist_len: usize,
+    options: *const XC2ParCOptions, out_data: *mut *mut u8, out_len: *mut usize, out_log: *mut *mut u8,
+    out_log_len: *mut usize) -> i32 {
+
+    guard(|| {
+        if part.is_null() || netlist.is_null() || out_data.is_null() || out_len.is_null() ||
+            (!out_log.is_null() && out_log_len.is_null()) {
+
+            return Err(invalid("NULL pointer passed"));
+        }
+        let part = CStr::from_ptr(part).to_str().map_err(|_| invalid("part is not valid UTF-8"))?;
+        let part = XC2DeviceSpeedPackage::from_str(part)
+            .ok_or_else(|| invalid(&format!("part \"{}\" is invalid/unsupported", part)))?;
+        let netlist = slice::from_raw_parts(netlist, netlist_len);
+
+        let mut par_options = XC2ParOptions::new();
+        let mut log_level = slog::Level::Warning;
+        if let Some(options) = options.as_ref() {
+            par_options.max_iter(options.max_iter);
+            par_options.with_prng_seed(options.rng_seed);
+            par_options.output_format(match options.output_format {
+                XC2PAR_OUTPUT_JED => ParOutputFormat::Jed,
+                XC2PAR_OUTPUT_CRBIT => ParOutputFormat::Crbit,
+                _ => return Err(invalid("unknown output format")),
+            });
+            log_level = match options.log_level {
+                0 => slog::Level::Warning,
+                1 => slog::Level::Info,
+                _ => slog::Level::Debug,
+            };
+        }
+
+        let log_buffer = LogBuffer::default();
+        let log = if out_log.is_null() {
+            None
+        } else {
+            let decorator = slog_term::PlainSyncDecorator::new(log_buffer.clone());
+            let drain = slog_term::FullFormat::new(decorator).build()
+                .filter(move |record| record.level().is_at_least(log_level)).fuse();
+            Some(slog::Logger::root(drain, o!()))
+        };
+
+        let mut data = Vec::new();
+        let result = xc2par::xc2par_complete_flow(&par_options, part, netlist, &mut data, log);
+
+        if !out_log.is_null() {
+            let log = log_buffer.0.lock().unwrap().clone();
+            output_buffer(log, out_log, out_log_len);
+        }
+        result?;
+        output_buffer(data, out_data, out_len);
+        Ok(())
+    })
+}
+
+/// Returns a description of the most recent error on the calling thread. The string stays valid until the next call
+/// into this library on the same thread.
+#[no_mangle]
+pub extern "C" fn xc2par_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|x| x.borrow().as_ptr())
+}
@@ -0,0 +1,98 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+/* Test program for the C interface, run by tests/capi.rs */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "xc2par.h"
+
+#define CHECK(x) do { \
+        if (!(x)) { \
+            fprintf(stderr, "%s:%d: check failed: %s (last error: %s)\n", __FILE__, __LINE__, #x, \
+                xc2par_last_error_message()); \
+            exit(1); \
+        } \
+    } while (0)
+
+static uint8_t *read_file(const char *path, size_t *len) {
+    FILE *f = fopen(path, "rb");
+    CHECK(f != NULL);
+    CHECK(fseek(f, 0, SEEK_END) == 0);
+    long size = ftell(f);
+    CHECK(size >= 0);
+    CHECK(fseek(f, 0, SEEK_SET) == 0);
+    uint8_t *data = malloc(size);
+    CHECK(data != NULL);
+    CHECK(fread(data, 1, size, f) == (size_t)size);
+    fclose(f);
+    *len = size;
+    return data;
+}
+
+int main(int argc, char **argv) {
+    CHECK(argc == 2);
+
+    size_t netlist_len;
+    uint8_t *netlist = read_file(argv[1], &netlist_len);
+
+    /* The default options produce a .jed that xc2bit can read */
+    uint8_t *out;
+    size_t out_len;
+    CHECK(xc2par_complete_flow("XC2C32A-4-VQ44", netlist, netlist_len, NULL, &out, &out_len, NULL, NULL) == XC2PAR_OK);
+    xc2bit_bitstream *bitstream = NULL;
+    CHECK(xc2bit_bitstream_from_jed(out, out_len, &bitstream) == XC2BIT_OK);
+    xc2bit_bitstream_free(bitstream);
+    xc2bit_buffer_free(out, out_len);
+
+    xc2par_options options;
+    xc2par_options_init(&options);
+    options.output_format = XC2PAR_OUTPUT_CRBIT;
+    options.log_level = 1;
+    uint8_t *log;
+    size_t log_len;
+    CHECK(xc2par_complete_flow("XC2C32A-4-VQ44", netlist, netlist_len, &options, &out, &out_len, &log, &log_len) ==
+        XC2PAR_OK);
+    CHECK(log_len > 0);
+    xc2bit_buffer_free(log, log_len);
+    CHECK(xc2bit_bitstream_from_crbit(out, out_len, &bitstream) == XC2BIT_OK);
+    xc2bit_bitstream_free(bitstream);
+    xc2bit_buffer_free(out, out_len);
+
+    /* Errors */
+    options.output_format = 2;
+    CHECK(xc2par_complete_flow("XC2C32A-4-VQ44", netlist, netlist_len, &options, &out, &out_len, NULL, NULL) ==
+        XC2PAR_ERROR_INVALID_ARGUMENT);
+    CHECK(xc2par_complete_flow("XC2C33", netlist, netlist_len, NULL, &out, &out_len, NULL, NULL) ==
+        XC2PAR_ERROR_INVALID_ARGUMENT);
+    CHECK(xc2par_complete_flow("XC2C32A-4-VQ44", (const uint8_t *)"{", 1, NULL, &out, &out_len, NULL, NULL) ==
+        XC2PAR_ERROR_NETLIST);
+    CHECK(strlen(xc2par_last_error_message()) > 0);
+
+    free(netlist);
+    return 0;
+}
@@ -0,0 +1,55 @@
+/*
+Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+All rights reserved.
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice,
+   this list of conditions and the following disclaimer.
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+*/
+
+//! Runs the tests of the Python bindings in python/ against the shared library
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn python_bindings() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // The shared library built for this test is next to it
+    let deps_dir = std::env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let lib = fs::read_dir(&deps_dir).unwrap()
+        .map(|x| x.unwrap().path())
+        .filter(|x| {
+            let name = x.file_name().unwrap().to_str().unwrap();
+            name.starts_with("libxc2par_capi") && (name.ends_with(".so") || name.ends_with(".dylib"))
+        })
+        .max_by_key(|x| fs::metadata(x).unwrap().modified().unwrap())
+        .expect("shared library not found");
+
+    let python = std::env::var("PYTHON").unwrap_or_else(|_| String::from("python3"));
+    let status = Command::new(&python)
+        .arg(dir.join("tests/python/test_xc2par.py"))
+        .arg(dir.join("../../tests/xc2par/frontend-reftests/blinky-newencoding.json"))
+        .env("PYTHONPATH", dir.join("python"))
+        .env("PYTHONDONTWRITEBYTECODE", "1")
+        .env("XC2PAR_LIBRARY", &lib)
+        .status().expect("failed to run Python");
+    assert!(status.success(), "Python tests failed");
+}
@@ -0,0 +1,60 @@
+# Copyright (c) 2016-2017, Robert Ou <rqou@robertou.com> and contributors
+# All rights reserved.
+#
+# Redistribution and use in source and binary forms, with or without
+# modification, are permitted provided that the following conditions are met:
+#
+# 1. Redistributions of source code must retain the above copyright notice,
+#    this list of conditions and the following disclaimer.
+# 2. Redistributions in binary form must reproduce the above copyright notice,
+#    this list of conditions and the following disclaimer in the documentation
+#    and/or other materials provided with the distribution.
+#
+# THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND
+# ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED
+# WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+# DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+# FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+# DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+# SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+# CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+# OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+# OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+"""Tests for the Python bindings, run by tests/python.rs. The argument is the path of a Yosys JSON netlist."""
+
+import sys
+import unittest
+
+import xc2par
+
+NETLIST_PATH = None
+
+
+class FitTests(unittest.TestCase):
+    def setUp(self):
+        with open(NETLIST_PATH) as f:
+            self.netlist = f.read()
+
+    def test_fit(self):
+        jed, log = xc2par.fit(self.netlist, "XC2C32A-4-VQ44")
+        self.assertIn(b"QF", jed)
+        crbit, log = xc2par.fit(self.netlist, "XC2C32A-4-VQ44", output_format="crbit", rng_seed=[1, 2, 3, 4],
+            log_level=1)
+        self.assertNotEqual(crbit, jed)
+        self.assertNotEqual(log, "")
+
+    def test_errors(self):
+        with self.assertRaises(ValueError):
+            xc2par.fit(self.netlist, "XC2C32A-4-VQ44", output_format="bit")
+        with self.assertRaises(xc2par.XC2ParError) as cm:
+            xc2par.fit(self.netlist, "XC2C33")
+        self.assertEqual(cm.exception.code, xc2par.XC2PAR_ERROR_INVALID_ARGUMENT)
+        with self.assertRaises(xc2par.XC2ParError) as cm:
+            xc2par.fit("{", "XC2C32A-4-VQ44")
+        self.assertEqual(cm.exception.code, xc2par.XC2PAR_ERROR_NETLIST)
+
+
+if __name__ == "__main__":
+    NETLIST_PATH = sys.argv.pop(1)
+    unittest.main()